@@ -0,0 +1,51 @@
+//! Benchmarks of building large expressions
+//!
+//! Run with `cargo bench`. The IN and projection cases build a single
+//! expression with many operands, so they are dominated by the formatting of
+//! the expression string.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dynamodb_expression::*;
+
+fn in_condition(operands: i64) -> Builder {
+    Builder::new().with_filter(r#in(
+        name("foo"),
+        (0..operands)
+            .map(|i| value(i) as Box<dyn OperandBuilder>)
+            .collect(),
+    ))
+}
+
+fn projection(names: usize) -> Builder {
+    Builder::new().with_projection(names_list(
+        name("foo"),
+        (0..names)
+            .map(|i| name(format!("bar{}.baz[{}]", i, i)))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+fn build(c: &mut Criterion) {
+    let input = in_condition(100);
+    c.bench_function("build in 100", |b| {
+        b.iter(|| black_box(input.clone()).build())
+    });
+
+    let input = in_condition(1000);
+    c.bench_function("build in 1000", |b| {
+        b.iter(|| black_box(input.clone()).build())
+    });
+
+    let input = projection(100);
+    c.bench_function("build projection 100", |b| {
+        b.iter(|| black_box(input.clone()).build())
+    });
+
+    let input = projection(1000);
+    c.bench_function("build projection 1000", |b| {
+        b.iter(|| black_box(input.clone()).build())
+    });
+}
+
+criterion_group!(benches, build);
+criterion_main!(benches);
@@ -0,0 +1,355 @@
+//! Expansion of the DynamoAttributes derive
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::{meta::ParseNestedMeta, Attribute, Data, DeriveInput, Fields, LitStr};
+
+// the attribute of a single struct field
+struct FieldAttribute {
+    ident: syn::Ident,
+    attribute: String,
+    projected: bool,
+}
+
+// expands to the DynamoAttributes impl and the attribute name constants of
+// the argument struct
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let attributes = field_attributes(input)?;
+
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let constants = attributes.iter().map(|field| {
+        let constant = format_ident!(
+            "{}",
+            field
+                .ident
+                .to_string()
+                .trim_start_matches("r#")
+                .to_uppercase()
+        );
+        let attribute = &field.attribute;
+        let doc = format!("The attribute name of the field `{}`", field.ident);
+        quote! {
+            #[doc = #doc]
+            #vis const #constant: &'static str = #attribute;
+        }
+    });
+
+    let all = attributes.iter().map(|field| &field.attribute);
+    let projected = attributes
+        .iter()
+        .filter(|field| field.projected)
+        .map(|field| &field.attribute);
+
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#constants)*
+        }
+
+        impl #impl_generics ::dynamodb_expression::DynamoAttributes for #ident #ty_generics #where_clause {
+            const ATTRIBUTES: &'static [&'static str] = &[#(#all),*];
+            const PROJECTED: &'static [&'static str] = &[#(#projected),*];
+        }
+    })
+}
+
+// returns the attributes of the fields of the argument struct which are
+// serialized, in declaration order
+fn field_attributes(input: &DeriveInput) -> syn::Result<Vec<FieldAttribute>> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(not_named_struct(input)),
+        },
+        _ => return Err(not_named_struct(input)),
+    };
+
+    let rename_all = container_rename_rule(&input.attrs)?;
+
+    let mut attributes = Vec::new();
+    for field in fields {
+        let Some(ident) = field.ident.clone() else {
+            continue;
+        };
+
+        let serde = serde_field(&field.attrs)?;
+        if serde.skip {
+            continue;
+        }
+
+        let field_name = ident.to_string();
+        let field_name = field_name.trim_start_matches("r#");
+        let attribute = match serde.rename {
+            Some(rename) => rename,
+            None => rename_all.apply(field_name),
+        };
+
+        attributes.push(FieldAttribute {
+            ident,
+            attribute,
+            projected: !projection_skip(&field.attrs)?,
+        });
+    }
+
+    Ok(attributes)
+}
+
+fn not_named_struct(input: &DeriveInput) -> syn::Error {
+    syn::Error::new_spanned(
+        &input.ident,
+        "DynamoAttributes can only be derived for structs with named fields",
+    )
+}
+
+// the serde attributes of a field that change its attribute name
+#[derive(Default)]
+struct SerdeField {
+    rename: Option<String>,
+    skip: bool,
+}
+
+fn serde_field(attrs: &[Attribute]) -> syn::Result<SerdeField> {
+    let mut field = SerdeField::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                if let Some(rename) = serialize_name(&meta)? {
+                    field.rename = Some(rename);
+                }
+            } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                field.skip = true;
+            } else {
+                skip_meta(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(field)
+}
+
+fn container_rename_rule(attrs: &[Attribute]) -> syn::Result<RenameRule> {
+    let mut rule = RenameRule::None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                if let Some(name) = serialize_name(&meta)? {
+                    rule = RenameRule::from_name(&name)
+                        .ok_or_else(|| meta.error(format!("unknown rename rule {:?}", name)))?;
+                }
+            } else {
+                skip_meta(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(rule)
+}
+
+// returns the serialized name of either rename = "..." or
+// rename(serialize = "...")
+fn serialize_name(meta: &ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(syn::Token![=]) {
+        let name: LitStr = meta.value()?.parse()?;
+        return Ok(Some(name.value()));
+    }
+
+    let mut name = None;
+    meta.parse_nested_meta(|nested| {
+        if nested.path.is_ident("serialize") {
+            let value: LitStr = nested.value()?.parse()?;
+            name = Some(value.value());
+        } else {
+            skip_meta(&nested)?;
+        }
+        Ok(())
+    })?;
+
+    Ok(name)
+}
+
+// consumes the value of a serde attribute that doesn't affect attribute names
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<TokenTree>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.input.parse::<proc_macro2::Group>()?;
+    }
+    Ok(())
+}
+
+fn projection_skip(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut skip = false;
+
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("projection"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported projection attribute, expected `skip`"))
+            }
+        })?;
+    }
+
+    Ok(skip)
+}
+
+/// The serde rename_all rules, applied to snake_case field names.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum RenameRule {
+    None,
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "lowercase" => Self::LowerCase,
+            "UPPERCASE" => Self::UpperCase,
+            "PascalCase" => Self::PascalCase,
+            "camelCase" => Self::CamelCase,
+            "snake_case" => Self::SnakeCase,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnakeCase,
+            "kebab-case" => Self::KebabCase,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebabCase,
+            _ => return None,
+        })
+    }
+
+    fn apply(self, field: &str) -> String {
+        match self {
+            Self::None | Self::LowerCase | Self::SnakeCase => field.to_owned(),
+            Self::UpperCase | Self::ScreamingSnakeCase => field.to_ascii_uppercase(),
+            Self::PascalCase => field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                        None => String::new(),
+                    }
+                })
+                .collect(),
+            Self::CamelCase => {
+                let pascal = Self::PascalCase.apply(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            Self::KebabCase => field.replace('_', "-"),
+            Self::ScreamingKebabCase => field.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(input: DeriveInput) -> Vec<(String, bool)> {
+        field_attributes(&input)
+            .unwrap()
+            .into_iter()
+            .map(|field| (field.attribute, field.projected))
+            .collect()
+    }
+
+    #[test]
+    fn serialized_fields() {
+        let input: DeriveInput = syn::parse_quote! {
+            #[serde(rename_all = "camelCase", deny_unknown_fields)]
+            struct Item {
+                #[serde(rename = "PK")]
+                id: String,
+                created_at: u64,
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                #[projection(skip)]
+                large_blob: Option<Vec<u8>>,
+                #[serde(skip)]
+                cache: u32,
+                #[serde(rename(serialize = "t", deserialize = "type"))]
+                r#type: String,
+            }
+        };
+
+        assert_eq!(
+            attributes(input),
+            vec![
+                ("PK".to_owned(), true),
+                ("createdAt".to_owned(), true),
+                ("largeBlob".to_owned(), false),
+                ("t".to_owned(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_input() {
+        for input in [
+            syn::parse_quote!(
+                struct Tuple(u32);
+            ),
+            syn::parse_quote!(
+                enum Item {
+                    A,
+                }
+            ),
+        ] {
+            assert_eq!(
+                expand(&input).unwrap_err().to_string(),
+                "DynamoAttributes can only be derived for structs with named fields"
+            );
+        }
+
+        let input: DeriveInput = syn::parse_quote! {
+            struct Item {
+                #[projection(hide)]
+                id: String,
+            }
+        };
+        assert_eq!(
+            expand(&input).unwrap_err().to_string(),
+            "unsupported projection attribute, expected `skip`"
+        );
+    }
+
+    #[test]
+    fn rename_rules() {
+        for (rule, expected) in [
+            ("lowercase", "created_at"),
+            ("UPPERCASE", "CREATED_AT"),
+            ("PascalCase", "CreatedAt"),
+            ("camelCase", "createdAt"),
+            ("snake_case", "created_at"),
+            ("SCREAMING_SNAKE_CASE", "CREATED_AT"),
+            ("kebab-case", "created-at"),
+            ("SCREAMING-KEBAB-CASE", "CREATED-AT"),
+        ] {
+            assert_eq!(
+                RenameRule::from_name(rule).map(|rule| rule.apply("created_at")),
+                Some(expected.to_owned()),
+                "{}",
+                rule
+            );
+        }
+        assert_eq!(RenameRule::from_name("Title Case"), None);
+    }
+}
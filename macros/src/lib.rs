@@ -0,0 +1,32 @@
+//! Procedural macros for [dynamodb_expression](https://github.com/Luminoth/dynamodb_expression)
+//!
+//! The macros are re-exported by dynamodb_expression when its macros feature is
+//! enabled, and should be used from there.
+
+mod attributes;
+mod name;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, LitStr};
+
+/// Parses and validates a document path at compile time, and expands to the
+/// equivalent dynamodb_expression::Path converted into a NameBuilder.
+#[proc_macro]
+pub fn name(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as LitStr);
+
+    name::expand(&literal)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements dynamodb_expression::DynamoAttributes for a struct with named
+/// fields, and adds an attribute name constant for each of its fields.
+#[proc_macro_derive(DynamoAttributes, attributes(projection, serde))]
+pub fn derive_dynamo_attributes(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    attributes::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,145 @@
+//! Parsing of document paths for the name! macro
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::LitStr;
+
+/// The maximum number of nested levels in a document path, see
+/// dynamodb_expression::MAX_PATH_DEPTH.
+const MAX_PATH_DEPTH: usize = 32;
+
+#[derive(PartialEq, Eq, Debug)]
+enum PathElement {
+    Field(String),
+    Index(usize),
+}
+
+// expands to the Path of the argument document path converted into a
+// NameBuilder
+pub(crate) fn expand(literal: &LitStr) -> syn::Result<TokenStream> {
+    let elements =
+        parse_path(&literal.value()).map_err(|message| syn::Error::new(literal.span(), message))?;
+
+    let mut expanded = quote!(::dynamodb_expression::path);
+    for (idx, element) in elements.iter().enumerate() {
+        expanded.extend(match element {
+            PathElement::Field(field) if idx == 0 => quote!((#field)),
+            PathElement::Field(field) => quote!(.field(#field)),
+            PathElement::Index(index) => quote!(.index(#index)),
+        });
+    }
+    expanded.extend(quote!(.into_name()));
+
+    Ok(expanded)
+}
+
+// parses a document path with the rules of NameParseMode::Strict
+fn parse_path(path: &str) -> Result<Vec<PathElement>, String> {
+    if path.is_empty() {
+        return Err("document path is empty".to_owned());
+    }
+
+    let mut elements = Vec::new();
+    for segment in path.split('.') {
+        let (word, mut substr) = match segment.find('[') {
+            Some(j) => segment.split_at(j),
+            None => (segment, ""),
+        };
+
+        if word.is_empty() {
+            return Err(format!("missing attribute name in {:?}", path));
+        }
+
+        if word.contains(']') {
+            return Err(invalid_segment(segment, "unmatched \"]\""));
+        }
+        elements.push(PathElement::Field(word.to_owned()));
+
+        while !substr.is_empty() {
+            // each index must be of the form [digits]
+            if !substr.starts_with('[') {
+                return Err(invalid_segment(
+                    segment,
+                    &format!("unexpected {:?} after list index", substr),
+                ));
+            }
+
+            let end = match substr.find(']') {
+                Some(end) => end,
+                None => return Err(invalid_segment(segment, "unclosed list index")),
+            };
+
+            let index = &substr[1..end];
+            match index.parse::<usize>() {
+                Ok(parsed) if index.chars().all(|ch| ch.is_ascii_digit()) => {
+                    elements.push(PathElement::Index(parsed))
+                }
+                _ => {
+                    return Err(invalid_segment(
+                        segment,
+                        &format!("invalid list index {:?}", index),
+                    ))
+                }
+            }
+
+            substr = &substr[end + 1..];
+        }
+    }
+
+    if elements.len() > MAX_PATH_DEPTH {
+        return Err(format!(
+            "document path is nested {} levels deep, the maximum is {}",
+            elements.len(),
+            MAX_PATH_DEPTH
+        ));
+    }
+
+    Ok(elements)
+}
+
+fn invalid_segment(segment: &str, reason: &str) -> String {
+    format!("{} in {:?}", reason, segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_valid_path() {
+        assert_eq!(
+            parse_path("foo.bar[0][12].baz"),
+            Ok(vec![
+                PathElement::Field("foo".to_owned()),
+                PathElement::Field("bar".to_owned()),
+                PathElement::Index(0),
+                PathElement::Index(12),
+                PathElement::Field("baz".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_invalid_path() {
+        for (input, expected) in [
+            ("", "document path is empty"),
+            ("foo..bar", r#"missing attribute name in "foo..bar""#),
+            ("[0]", r#"missing attribute name in "[0]""#),
+            ("foo]", r#"unmatched "]" in "foo]""#),
+            ("foo[abc]", r#"invalid list index "abc" in "foo[abc]""#),
+            ("foo[+1]", r#"invalid list index "+1" in "foo[+1]""#),
+            ("foo[1][", r#"unclosed list index in "foo[1][""#),
+            (
+                "foo[1]bar",
+                r#"unexpected "bar" after list index in "foo[1]bar""#,
+            ),
+        ] {
+            assert_eq!(parse_path(input), Err(expected.to_owned()), "{}", input);
+        }
+
+        assert_eq!(
+            parse_path(&(vec!["foo"; 32].join(".") + "[0]")),
+            Err("document path is nested 33 levels deep, the maximum is 32".to_owned())
+        );
+    }
+}
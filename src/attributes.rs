@@ -0,0 +1,137 @@
+//! Attribute names of item models
+//!
+//! DynamoAttributes ties the attribute names used in expressions to the struct
+//! that models an item, so renaming a field doesn't silently leave stale names
+//! behind in expressions.
+
+use crate::{name_literal, names_list, NameBuilder, ProjectionBuilder};
+
+/// Represents a struct modeling a DynamoDB item, with the names of the
+/// attributes it is serialized to.
+///
+/// DynamoAttributes is usually derived with the macros feature enabled, in
+/// which case the attribute names honor `#[serde(rename)]`,
+/// `#[serde(rename_all)]` and `#[serde(skip)]`, fields marked with
+/// `#[projection(skip)]` are left out of PROJECTED, and an attribute name
+/// constant is added to the struct for each of its fields.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// struct Item;
+///
+/// impl DynamoAttributes for Item {
+///     const ATTRIBUTES: &'static [&'static str] = &["pk", "name", "blob"];
+///     const PROJECTED: &'static [&'static str] = &["pk", "name"];
+/// }
+///
+/// let expr = Builder::new()
+///     .with_projection(Item::projection())
+///     .build()
+///     .unwrap();
+/// assert_eq!(expr.projection().unwrap(), "#0, #1");
+/// assert_eq!(expr.names().as_ref().unwrap()["#1"], "name");
+/// ```
+pub trait DynamoAttributes {
+    /// The attribute names of the item, in field declaration order.
+    const ATTRIBUTES: &'static [&'static str];
+
+    /// The attribute names included in projection(), all of ATTRIBUTES by
+    /// default.
+    const PROJECTED: &'static [&'static str] = Self::ATTRIBUTES;
+
+    /// Returns a NameBuilder for each of the ATTRIBUTES.
+    ///
+    /// The attribute names are taken literally, as by name_literal().
+    #[allow(clippy::vec_box)]
+    fn fields() -> Vec<Box<NameBuilder>> {
+        Self::ATTRIBUTES
+            .iter()
+            .map(|attribute| name_literal(*attribute))
+            .collect()
+    }
+
+    /// Returns a ProjectionBuilder over the PROJECTED attributes.
+    ///
+    /// If there are no PROJECTED attributes, the ProjectionBuilder is empty and
+    /// building an expression with it returns the typed error
+    /// UnsetParameterError.
+    fn projection() -> ProjectionBuilder {
+        let mut names = Self::PROJECTED
+            .iter()
+            .map(|attribute| name_literal(*attribute));
+
+        match names.next() {
+            Some(first) => names_list(first, names.collect::<Vec<_>>()),
+            None => ProjectionBuilder::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "macros")]
+    use std::collections::HashMap;
+
+    use crate::*;
+
+    struct Item;
+
+    impl DynamoAttributes for Item {
+        const ATTRIBUTES: &'static [&'static str] = &["pk", "a.b"];
+    }
+
+    #[test]
+    fn default_projection() -> anyhow::Result<()> {
+        assert_eq!(Item::PROJECTED, &["pk", "a.b"]);
+        assert_eq!(
+            Item::fields(),
+            vec![name_literal("pk"), name_literal("a.b")]
+        );
+        assert_eq!(
+            Item::projection(),
+            names_list(name_literal("pk"), vec![name_literal("a.b")])
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn derive() -> anyhow::Result<()> {
+        #[allow(dead_code)]
+        #[derive(DynamoAttributes)]
+        #[serde(rename_all = "camelCase")]
+        struct Order {
+            #[serde(rename = "PK")]
+            id: String,
+            created_at: u64,
+            #[projection(skip)]
+            line_items: Vec<String>,
+            #[serde(skip)]
+            cached: bool,
+        }
+
+        assert_eq!(Order::ID, "PK");
+        assert_eq!(Order::CREATED_AT, "createdAt");
+        assert_eq!(Order::LINE_ITEMS, "lineItems");
+        assert_eq!(Order::ATTRIBUTES, &["PK", "createdAt", "lineItems"]);
+        assert_eq!(Order::PROJECTED, &["PK", "createdAt"]);
+
+        let expr = Builder::new()
+            .with_projection(Order::projection())
+            .build()?;
+        assert_eq!(expr.projection(), Some("#0, #1"));
+        assert_eq!(
+            expr.names(),
+            &Some(HashMap::from([
+                ("#0".to_owned(), "PK".to_owned()),
+                ("#1".to_owned(), "createdAt".to_owned()),
+            ]))
+        );
+
+        Ok(())
+    }
+}
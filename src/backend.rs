@@ -0,0 +1,407 @@
+//! A value representation independent of the aws-sdk-dynamodb version
+//!
+//! The AttributeValue of aws-sdk-dynamodb is part of the public API of the
+//! crate, so code converting values between the crate and another version of
+//! the SDK, or another SDK, goes through DynamoValue instead. Implementing
+//! AttributeValueLike for a value type lets it be used as an operand with
+//! value_like(), and read from an Expression with Expression::values_as().
+
+use std::collections::{BTreeMap, HashMap};
+
+use aws_sdk_dynamodb::primitives::Blob;
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{error::ExpressionError, value, Expression, ToAttributeValue, ValueBuilder};
+
+/// Represents a DynamoDB value without depending on the types of an SDK.
+///
+/// The variants match the DynamoDB data types, with binary data as bytes and
+/// the entries of maps sorted by key.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DynamoValue {
+    /// S represents a String
+    S(String),
+
+    /// N represents a Number, in its string form
+    N(String),
+
+    /// B represents Binary data
+    B(Vec<u8>),
+
+    /// Bool represents a Boolean
+    Bool(bool),
+
+    /// Null represents the Null value
+    Null,
+
+    /// Ss represents a String Set
+    Ss(Vec<String>),
+
+    /// Ns represents a Number Set
+    Ns(Vec<String>),
+
+    /// Bs represents a Binary Set
+    Bs(Vec<Vec<u8>>),
+
+    /// L represents a List
+    L(Vec<DynamoValue>),
+
+    /// M represents a Map
+    M(BTreeMap<String, DynamoValue>),
+}
+
+/// Converts a value type of an SDK from and to a DynamoValue.
+///
+/// Values that have no DynamoValue, such as the values of types unknown to
+/// an SDK, return an error rather than being replaced. The crate only
+/// implements it for the AttributeValue of the aws-sdk-dynamodb 1.x version
+/// it depends on. Implementing it for the value type of another SDK, or of
+/// another version of aws-sdk-dynamodb, through a local newtype if needed,
+/// makes that type usable with the crate.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use dynamodb_expression::{error::ExpressionError, *};
+///
+/// // the value type of another SDK
+/// #[derive(Clone, PartialEq, Debug)]
+/// enum OtherValue {
+///     Text(String),
+///     Other,
+/// }
+///
+/// impl AttributeValueLike for OtherValue {
+///     fn into_dynamo_value(self) -> Result<DynamoValue, ExpressionError> {
+///         match self {
+///             OtherValue::Text(s) => Ok(DynamoValue::S(s)),
+///             OtherValue::Other => Err(ExpressionError::InvalidParameterError(
+///                 "intoDynamoValue".to_owned(),
+///                 "OtherValue::Other".to_owned(),
+///             )),
+///         }
+///     }
+///
+///     fn from_dynamo_value(value: DynamoValue) -> Self {
+///         match value {
+///             DynamoValue::S(s) => OtherValue::Text(s),
+///             _ => OtherValue::Other,
+///         }
+///     }
+/// }
+///
+/// let artist = value_like(OtherValue::Text("ace".to_owned())).unwrap();
+/// let expr = Builder::new()
+///     .with_condition(name("artist").equal(artist))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(
+///     expr.values_as::<OtherValue>().unwrap().unwrap()[":0"],
+///     OtherValue::Text("ace".to_owned())
+/// );
+/// ```
+pub trait AttributeValueLike: Sized {
+    /// Returns the DynamoValue of the value, or the typed error
+    /// InvalidParameterError if the value has none.
+    fn into_dynamo_value(self) -> Result<DynamoValue, ExpressionError>;
+
+    /// Returns the value of the argument DynamoValue.
+    fn from_dynamo_value(value: DynamoValue) -> Self;
+}
+
+impl AttributeValueLike for DynamoValue {
+    fn into_dynamo_value(self) -> Result<DynamoValue, ExpressionError> {
+        Ok(self)
+    }
+
+    fn from_dynamo_value(value: DynamoValue) -> Self {
+        value
+    }
+}
+
+impl AttributeValueLike for AttributeValue {
+    // DynamoDB only accepts Null(true), and values of types unknown to the
+    // SDK, which only appear in responses, can't be represented
+    fn into_dynamo_value(self) -> Result<DynamoValue, ExpressionError> {
+        Ok(match self {
+            AttributeValue::S(s) => DynamoValue::S(s),
+            AttributeValue::N(n) => DynamoValue::N(n),
+            AttributeValue::B(b) => DynamoValue::B(b.into_inner()),
+            AttributeValue::Bool(b) => DynamoValue::Bool(b),
+            AttributeValue::Null(true) => DynamoValue::Null,
+            AttributeValue::Null(false) => {
+                return Err(invalid_attribute_value("AttributeValue::Null(false)"))
+            }
+            AttributeValue::Ss(ss) => DynamoValue::Ss(ss),
+            AttributeValue::Ns(ns) => DynamoValue::Ns(ns),
+            AttributeValue::Bs(bs) => {
+                DynamoValue::Bs(bs.into_iter().map(Blob::into_inner).collect())
+            }
+            AttributeValue::L(l) => DynamoValue::L(
+                l.into_iter()
+                    .map(Self::into_dynamo_value)
+                    .collect::<Result<_, _>>()?,
+            ),
+            AttributeValue::M(m) => DynamoValue::M(
+                m.into_iter()
+                    .map(|(k, v)| Ok((k, v.into_dynamo_value()?)))
+                    .collect::<Result<_, ExpressionError>>()?,
+            ),
+            _ => return Err(invalid_attribute_value("AttributeValue::Unknown")),
+        })
+    }
+
+    fn from_dynamo_value(value: DynamoValue) -> Self {
+        match value {
+            DynamoValue::S(s) => AttributeValue::S(s),
+            DynamoValue::N(n) => AttributeValue::N(n),
+            DynamoValue::B(b) => AttributeValue::B(Blob::new(b)),
+            DynamoValue::Bool(b) => AttributeValue::Bool(b),
+            DynamoValue::Null => AttributeValue::Null(true),
+            DynamoValue::Ss(ss) => AttributeValue::Ss(ss),
+            DynamoValue::Ns(ns) => AttributeValue::Ns(ns),
+            DynamoValue::Bs(bs) => AttributeValue::Bs(bs.into_iter().map(Blob::new).collect()),
+            DynamoValue::L(l) => {
+                AttributeValue::L(l.into_iter().map(Self::from_dynamo_value).collect())
+            }
+            DynamoValue::M(m) => AttributeValue::M(
+                m.into_iter()
+                    .map(|(k, v)| (k, Self::from_dynamo_value(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl ToAttributeValue for DynamoValue {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::from_dynamo_value(self.clone())
+    }
+}
+
+pub(crate) fn invalid_attribute_value(parameter_type: &str) -> ExpressionError {
+    ExpressionError::InvalidParameterError("intoDynamoValue".to_owned(), parameter_type.to_owned())
+}
+
+/// Returns a ValueBuilder for the argument value of any type implementing
+/// AttributeValueLike, see AttributeValueLike.
+///
+/// Values that have no DynamoValue return the typed error
+/// InvalidParameterError.
+pub fn value_like(
+    value_like: impl AttributeValueLike,
+) -> Result<Box<ValueBuilder<DynamoValue>>, ExpressionError> {
+    Ok(value(value_like.into_dynamo_value()?))
+}
+
+impl Expression {
+    /// Returns the ExpressionAttributeValues of the Expression converted to
+    /// the argument AttributeValueLike type, see AttributeValueLike.
+    ///
+    /// Values that have no DynamoValue, such as AttributeValue::Null(false),
+    /// return the typed error InvalidParameterError.
+    pub fn values_as<T: AttributeValueLike>(
+        &self,
+    ) -> Result<Option<HashMap<String, T>>, ExpressionError> {
+        self.values()
+            .as_ref()
+            .map(|values| {
+                values
+                    .iter()
+                    .map(|(alias, value)| {
+                        Ok((
+                            alias.clone(),
+                            T::from_dynamo_value(value.clone().into_dynamo_value()?),
+                        ))
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use aws_sdk_dynamodb::config::http::HttpResponse;
+    use aws_sdk_dynamodb::config::{BehaviorVersion, Credentials, Region};
+    use aws_sdk_dynamodb::primitives::Blob;
+    use aws_sdk_dynamodb::types::AttributeValue;
+    use aws_smithy_runtime_api::client::http::{
+        HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpConnector,
+    };
+    use aws_smithy_runtime_api::client::orchestrator::HttpRequest;
+    use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+    use aws_smithy_runtime_api::shared::IntoShared;
+    use aws_smithy_types::body::SdkBody;
+
+    use crate::{error::ExpressionError, *};
+
+    // answers every request with the argument JSON body
+    #[derive(Clone, Debug)]
+    struct StaticResponse(&'static str);
+
+    impl HttpConnector for StaticResponse {
+        fn call(&self, _request: HttpRequest) -> HttpConnectorFuture {
+            HttpConnectorFuture::ready(Ok(HttpResponse::new(
+                200.try_into().unwrap(),
+                SdkBody::from(self.0),
+            )))
+        }
+    }
+
+    impl HttpClient for StaticResponse {
+        fn http_connector(
+            &self,
+            _settings: &HttpConnectorSettings,
+            _components: &RuntimeComponents,
+        ) -> SharedHttpConnector {
+            self.clone().into_shared()
+        }
+    }
+
+    // returns the value of the "a" attribute of the item in the argument
+    // GetItem response body, as decoded by the SDK
+    fn decoded_value(body: &'static str) -> anyhow::Result<AttributeValue> {
+        let config = aws_sdk_dynamodb::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::for_tests())
+            .http_client(StaticResponse(body))
+            .build();
+        let output = tokio_test::block_on(
+            aws_sdk_dynamodb::Client::from_conf(config)
+                .get_item()
+                .table_name("Music")
+                .send(),
+        )?;
+
+        let mut item = output.item.unwrap();
+
+        Ok(item.remove("a").unwrap())
+    }
+
+    #[test]
+    fn dynamo_value_round_trip() -> anyhow::Result<()> {
+        let input = AttributeValue::M(
+            [
+                ("s", AttributeValue::S("a".to_owned())),
+                ("n", AttributeValue::N("1".to_owned())),
+                ("b", AttributeValue::B(Blob::new("b"))),
+                ("bool", AttributeValue::Bool(true)),
+                ("null", AttributeValue::Null(true)),
+                ("ss", AttributeValue::Ss(vec!["a".to_owned()])),
+                ("ns", AttributeValue::Ns(vec!["1".to_owned()])),
+                ("bs", AttributeValue::Bs(vec![Blob::new("b")])),
+                (
+                    "l",
+                    AttributeValue::L(vec![AttributeValue::S("a".to_owned())]),
+                ),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v))
+            .collect(),
+        );
+
+        let dynamo_value = input.clone().into_dynamo_value()?;
+        assert_eq!(
+            dynamo_value,
+            DynamoValue::M(BTreeMap::from([
+                ("s".to_owned(), DynamoValue::S("a".to_owned())),
+                ("n".to_owned(), DynamoValue::N("1".to_owned())),
+                ("b".to_owned(), DynamoValue::B(b"b".to_vec())),
+                ("bool".to_owned(), DynamoValue::Bool(true)),
+                ("null".to_owned(), DynamoValue::Null),
+                ("ss".to_owned(), DynamoValue::Ss(vec!["a".to_owned()])),
+                ("ns".to_owned(), DynamoValue::Ns(vec!["1".to_owned()])),
+                ("bs".to_owned(), DynamoValue::Bs(vec![b"b".to_vec()])),
+                (
+                    "l".to_owned(),
+                    DynamoValue::L(vec![DynamoValue::S("a".to_owned())])
+                ),
+            ]))
+        );
+        assert_eq!(AttributeValue::from_dynamo_value(dynamo_value), input);
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_attribute_values() -> anyhow::Result<()> {
+        let null_error = || {
+            ExpressionError::InvalidParameterError(
+                "intoDynamoValue".to_owned(),
+                "AttributeValue::Null(false)".to_owned(),
+            )
+        };
+        let unknown_error = || {
+            ExpressionError::InvalidParameterError(
+                "intoDynamoValue".to_owned(),
+                "AttributeValue::Unknown".to_owned(),
+            )
+        };
+
+        assert_eq!(
+            AttributeValue::Null(false).into_dynamo_value(),
+            Err(null_error())
+        );
+        assert_eq!(
+            AttributeValue::L(vec![AttributeValue::Null(false)]).into_dynamo_value(),
+            Err(null_error())
+        );
+        assert_eq!(
+            value_like(AttributeValue::Null(false)).err(),
+            Some(null_error())
+        );
+        assert_eq!(
+            Builder::new()
+                .with_update(set(name("a"), value(AttributeValue::Null(false))))
+                .build()?
+                .values_as::<DynamoValue>(),
+            Err(null_error())
+        );
+
+        let unknown = decoded_value(r#"{"Item": {"a": {"FUTURE": "x"}}}"#)?;
+        assert!(unknown.is_unknown());
+        assert_eq!(unknown.clone().into_dynamo_value(), Err(unknown_error()));
+        assert_eq!(
+            AttributeValue::M([("a".to_owned(), unknown)].into()).into_dynamo_value(),
+            Err(unknown_error())
+        );
+        assert_eq!(
+            decoded_value(r#"{"Item": {"a": {"S": "x"}}}"#)?.into_dynamo_value(),
+            Ok(DynamoValue::S("x".to_owned()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn value_like_operand() -> anyhow::Result<()> {
+        let expr = Builder::new()
+            .with_update(set(name("a"), value_like(DynamoValue::N("5".to_owned()))?))
+            .build()?;
+
+        assert_eq!(
+            expr.values().as_ref().unwrap()[":0"],
+            AttributeValue::N("5".to_owned())
+        );
+        assert_eq!(
+            expr.values_as::<DynamoValue>()?.unwrap()[":0"],
+            DynamoValue::N("5".to_owned())
+        );
+        assert_eq!(
+            Builder::new()
+                .with_projection(names_list(name("a"), vec![]))
+                .build()?
+                .values_as::<DynamoValue>()?,
+            None
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,9 @@
+//! Shims keeping older versions of the API working
+//!
+//! Each submodule re-exports the current API, replacing the items whose
+//! signatures have changed since that version with versions that keep the old
+//! signatures. Downstream code can switch its imports to the submodule of the
+//! version it was written against and migrate to the current API one call
+//! site at a time.
+
+pub mod v0;
@@ -0,0 +1,246 @@
+//! The 0.1 API, where building returned anyhow::Result
+//!
+//! Builders from this module can be converted to and from the current Builder,
+//! so both APIs can be mixed while migrating.
+//!
+//! # Example
+//!
+//! ```
+//! use dynamodb_expression::compat::v0::*;
+//!
+//! fn build_filter(artist: &str) -> anyhow::Result<Expression> {
+//!     let filt = name("Artist").equal(value(artist.to_owned()));
+//!     Builder::new().with_filter(filt).build()
+//! }
+//! ```
+
+pub use crate::*;
+
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::error::ExpressionError;
+
+/// The 0.1 Builder, returning anyhow::Result from build().
+///
+/// Errors are returned the way 0.1 returned them, without the builder path of
+/// the current API. The errors that 0.1 returned as plain messages are
+/// returned as plain messages again, so only InvalidParameterError,
+/// UnsetParameterError and the errors added since can be downcast to
+/// ExpressionError.
+#[derive(Default, Debug, Clone)]
+pub struct Builder(crate::Builder);
+
+impl Builder {
+    /// Returns an empty Builder struct.
+    pub fn new() -> Self {
+        Self(crate::Builder::new())
+    }
+
+    /// Adds the argument ConditionBuilder as a Condition Expression.
+    pub fn with_condition(self, condition_builder: ConditionBuilder) -> Builder {
+        Self(self.0.with_condition(condition_builder))
+    }
+
+    /// Adds the argument ProjectionBuilder as a Projection Expression.
+    pub fn with_projection(self, projection_builder: ProjectionBuilder) -> Builder {
+        Self(self.0.with_projection(projection_builder))
+    }
+
+    /// Adds the argument KeyConditionBuilder as a Key Condition Expression.
+    pub fn with_key_condition(self, key_condition_builder: KeyConditionBuilder) -> Builder {
+        Self(self.0.with_key_condition(key_condition_builder))
+    }
+
+    /// Adds the argument ConditionBuilder as a Filter Expression.
+    pub fn with_filter(self, filter: ConditionBuilder) -> Builder {
+        Self(self.0.with_filter(filter))
+    }
+
+    /// Adds the argument UpdateBuilder as an Update Expression.
+    pub fn with_update(self, update_builder: UpdateBuilder) -> Builder {
+        Self(self.0.with_update(update_builder))
+    }
+
+    /// Builds an Expression struct representing multiple types of DynamoDB
+    /// Expressions.
+    pub fn build(self) -> anyhow::Result<Expression> {
+        self.0.build().map(Expression).map_err(into_v0_error)
+    }
+
+    /// Returns the current Builder wrapped by this Builder.
+    pub fn into_inner(self) -> crate::Builder {
+        self.0
+    }
+}
+
+impl From<crate::Builder> for Builder {
+    fn from(builder: crate::Builder) -> Self {
+        Self(builder)
+    }
+}
+
+impl From<Builder> for crate::Builder {
+    fn from(builder: Builder) -> Self {
+        builder.0
+    }
+}
+
+/// The 0.1 Expression, whose getters return `Option<&String>`.
+#[derive(Default, Debug, PartialEq, Clone)]
+pub struct Expression(crate::Expression);
+
+impl Expression {
+    /// Returns the string corresponding to the Condition Expression.
+    pub fn condition(&self) -> Option<&String> {
+        self.expression(ExpressionType::Condition)
+    }
+
+    /// Returns the string corresponding to the Filter Expression.
+    pub fn filter(&self) -> Option<&String> {
+        self.expression(ExpressionType::Filter)
+    }
+
+    /// Returns the string corresponding to the Projection Expression.
+    pub fn projection(&self) -> Option<&String> {
+        self.expression(ExpressionType::Projection)
+    }
+
+    /// Returns the string corresponding to the Key Condition Expression.
+    pub fn key_condition(&self) -> Option<&String> {
+        self.expression(ExpressionType::KeyCondition)
+    }
+
+    /// Returns the string corresponding to the Update Expression.
+    pub fn update(&self) -> Option<&String> {
+        self.expression(ExpressionType::Update)
+    }
+
+    /// Returns the ExpressionAttributeNames.
+    pub fn names(&self) -> &Option<HashMap<String, String>> {
+        self.0.names()
+    }
+
+    /// Returns the ExpressionAttributeValues.
+    pub fn values(&self) -> &Option<HashMap<String, AttributeValue>> {
+        self.0.values()
+    }
+
+    /// Returns the current Expression wrapped by this Expression.
+    pub fn into_inner(self) -> crate::Expression {
+        self.0
+    }
+
+    fn expression(&self, expression_type: ExpressionType) -> Option<&String> {
+        self.0.expressions.get(&expression_type)
+    }
+}
+
+impl From<crate::Expression> for Expression {
+    fn from(expression: crate::Expression) -> Self {
+        Self(expression)
+    }
+}
+
+impl From<Expression> for crate::Expression {
+    fn from(expression: Expression) -> Self {
+        expression.0
+    }
+}
+
+fn into_v0_error(err: ExpressionError) -> anyhow::Error {
+    match err {
+        ExpressionError::InPath { source, .. } | ExpressionError::CreatedAt { source, .. } => {
+            into_v0_error(*source)
+        }
+        ExpressionError::InvalidKeyCondition { .. } => {
+            anyhow::anyhow!("buildKeyCondition error: invalid key condition constructed")
+        }
+        err @ (ExpressionError::InvalidParameterError(..)
+        | ExpressionError::UnsetParameterError(..)
+        | ExpressionError::LimitExceededError(..)
+        | ExpressionError::BetweenBoundsOutOfOrder(..)) => anyhow::Error::new(err),
+        err => anyhow::anyhow!(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compat::v0::*;
+
+    #[test]
+    fn build() -> anyhow::Result<()> {
+        let input = Builder::new().with_condition(name("foo").equal(value(5)));
+
+        assert_eq!(
+            input.build()?.into_inner(),
+            crate::Builder::new()
+                .with_condition(name("foo").equal(value(5)))
+                .build()?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn typed_error() -> anyhow::Result<()> {
+        let input =
+            Builder::new().with_filter(name("foo").equal(value(5)).and(name("").equal(value(6))));
+
+        assert_eq!(
+            input
+                .build()
+                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
+                .unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "BuildOperand".to_owned(),
+                "NameBuilder".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn string_error() -> anyhow::Result<()> {
+        let input = Builder::new().with_key_condition(
+            key("foo")
+                .less_than(value(5))
+                .and(key("bar").equal(value(6))),
+        );
+
+        assert_eq!(
+            input.build().unwrap_err().to_string(),
+            "buildKeyCondition error: invalid key condition constructed"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn convert() -> anyhow::Result<()> {
+        let input: Builder = crate::Builder::new()
+            .with_filter(name("foo").equal(value(5)))
+            .into();
+        let input = crate::Builder::from(input.with_projection(names_list(name("bar"), vec![])));
+
+        assert_eq!(input.build()?.projection().unwrap(), "#0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn getters() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_filter(name("foo").equal(value(5)))
+            .build()?;
+
+        let filter: Option<&String> = input.filter();
+        assert_eq!(filter, Some(&"#0 = :0".to_owned()));
+        assert_eq!(input.condition(), None);
+        assert_eq!(input.names().as_ref().unwrap().len(), 1);
+
+        Ok(())
+    }
+}
@@ -1,18 +1,25 @@
 //! Ported from [condition.go](https://github.com/aws/aws-sdk-go/blob/master/service/dynamodb/expression/condition.go)
 
-use anyhow::bail;
+use std::cmp::Ordering;
+
+use aws_sdk_dynamodb::types::AttributeValue;
 use derivative::*;
 
 use crate::{
-    error::ExpressionError, value, ExpressionNode, NameBuilder, OperandBuilder, SizeBuilder,
-    TreeBuilder,
+    error::ExpressionError, key, key_and, key_begins_with, key_between, key_equal,
+    key_greater_than, key_greater_than_equal, key_less_than, key_less_than_equal, record, value,
+    ExpressionLimit, ExpressionNode, FmtExpression, IntoOperand, KeyBuilder, KeyConditionBuilder,
+    NameBuilder, OperandBuilder, Recorder, SizeBuilder, ToAttributeValue, TreeBuilder,
 };
 
 /// Specifies the types of the struct conditionBuilder,
 /// representing the different types of Conditions (i.e. And, Or, Between, ...)
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Derivative)]
+///
+/// It is returned by ConditionBuilder::kind() to inspect built conditions.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Derivative, strum::Display)]
 #[derivative(Default)]
-enum ConditionMode {
+#[strum(serialize_all = "snake_case")]
+pub enum ConditionMode {
     /// Unset catches errors for unset ConditionBuilder structs
     #[derivative(Default)]
     Unset,
@@ -62,7 +69,7 @@ enum ConditionMode {
     /// BeginsWith represents the Begins With Condition
     BeginsWith,
 
-    // Contains represents the Contains Condition
+    /// Contains represents the Contains Condition
     Contains,
 }
 
@@ -72,7 +79,7 @@ enum ConditionMode {
 /// the DynamoDB type that is being checked and ensure compile time checks.
 ///
 /// [More Information](http://docs.aws.amazon.com/amazondynamodb/latest/developerguide/Expressions.OperatorsAndFunctions.html#Expressions.OperatorsAndFunctions.Functions)
-#[derive(Copy, Clone, PartialEq, Eq, Debug, strum::AsRefStr)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, strum::AsRefStr, strum::EnumString)]
 pub enum DynamoDbAttributeType {
     /// String represents the DynamoDB String type
     #[strum(serialize = "S")]
@@ -124,14 +131,115 @@ pub enum DynamoDbAttributeType {
 /// [More Information](http://docs.aws.amazon.com/amazondynamodb/latest/developerguide/Expressions.ConditionExpressions.html)
 ///
 /// [More Information on Filter Expressions](http://docs.aws.amazon.com/amazondynamodb/latest/developerguide/Query.html#Query.FilterExpression)
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq)]
 pub struct ConditionBuilder {
-    operand_list: Vec<Box<dyn OperandBuilder>>,
-    condition_list: Vec<ConditionBuilder>,
-    mode: ConditionMode,
+    pub(crate) operand_list: Vec<Box<dyn OperandBuilder>>,
+    pub(crate) condition_list: Vec<ConditionBuilder>,
+    pub(crate) mode: ConditionMode,
+}
+
+impl std::fmt::Debug for ConditionBuilder {
+    // renders the logical structure, e.g. And(Equal(..), Between(..))
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut tuple = f.debug_tuple(&format!("{:?}", self.mode));
+        for condition in self.condition_list.iter() {
+            tuple.field(condition);
+        }
+        for operand in self.operand_list.iter() {
+            tuple.field(operand);
+        }
+        tuple.finish()
+    }
 }
 
 impl ConditionBuilder {
+    /// Returns the kind of the ConditionBuilder, such as ConditionMode::And
+    /// or ConditionMode::Equal.
+    ///
+    /// Together with operands() and children(), kind() gives a read-only view
+    /// of the condition tree, so that code receiving a ConditionBuilder can
+    /// check what it contains before the request is sent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // returns whether the condition requires tenant_id to equal a value,
+    /// // either directly or through one side of an AND
+    /// fn is_tenant_scoped(condition: &ConditionBuilder) -> bool {
+    ///     match condition.kind() {
+    ///         ConditionMode::And => condition.children().iter().any(is_tenant_scoped),
+    ///         ConditionMode::Equal => condition.operands().iter().any(|operand| {
+    ///             operand
+    ///                 .as_any()
+    ///                 .downcast_ref::<NameBuilder>()
+    ///                 .and_then(|name| name.attribute_path().ok())
+    ///                 .is_some_and(|path| path == ["tenant_id"])
+    ///         }),
+    ///         _ => false,
+    ///     }
+    /// }
+    ///
+    /// let scoped = name("tenant_id").equal(value("acme")).and(name("open").is_true());
+    /// let unscoped = name("tenant_id").equal(value("acme")).or(name("open").is_true());
+    ///
+    /// assert!(is_tenant_scoped(&scoped));
+    /// assert!(!is_tenant_scoped(&unscoped));
+    /// ```
+    pub fn kind(&self) -> ConditionMode {
+        self.mode
+    }
+
+    /// Returns the operands of the ConditionBuilder, such as the name and the
+    /// value of an Equal condition.
+    ///
+    /// The logical conditions And, Or and Not have no operands, see
+    /// children(). An operand can be inspected by downcasting it to its
+    /// builder type, such as NameBuilder, through as_any().
+    pub fn operands(&self) -> &[Box<dyn OperandBuilder>] {
+        &self.operand_list
+    }
+
+    /// Returns the conditions combined by the ConditionBuilder if it is an
+    /// And, Or or Not condition, and an empty slice otherwise.
+    pub fn children(&self) -> &[ConditionBuilder] {
+        &self.condition_list
+    }
+
+    /// Returns the number of conditions the ConditionBuilder requires or
+    /// allows: the number of children of an And or an Or condition, 0 for an
+    /// unset ConditionBuilder, and 1 for any other condition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let filters = [("color", "red"), ("size", "small")];
+    /// let condition: ConditionBuilder = filters
+    ///     .iter()
+    ///     .map(|(field, wanted)| name(*field).equal(value(*wanted)))
+    ///     .collect();
+    ///
+    /// assert_eq!(condition.len(), 2);
+    /// assert!(ConditionBuilder::default().is_empty());
+    /// ```
+    pub fn len(&self) -> usize {
+        match self.mode {
+            ConditionMode::Unset => 0,
+            ConditionMode::And | ConditionMode::Or => self.condition_list.len(),
+            _ => 1,
+        }
+    }
+
+    /// Returns true if the ConditionBuilder is unset, such as
+    /// ConditionBuilder::default(), for which build() returns the typed error
+    /// UnsetParameterError.
+    pub fn is_empty(&self) -> bool {
+        self.mode == ConditionMode::Unset
+    }
+
     /// Returns a ConditionBuilder representing the logical AND clause of the argument ConditionBuilders.
     ///
     /// The resulting ConditionBuilder can be used as a
@@ -210,161 +318,354 @@ impl ConditionBuilder {
         not(self)
     }
 
-    fn build_child_nodes(&self) -> anyhow::Result<Vec<ExpressionNode>> {
-        let mut child_nodes = Vec::new();
-
-        for condition in self.condition_list.iter() {
-            let node = condition.build_tree()?;
-            child_nodes.push(node);
-        }
-
-        for ope in self.operand_list.iter() {
-            let operand = ope.build_operand()?;
+    // builds the node of the condition from the already built nodes of its
+    // conditions
+    fn build_node(
+        &self,
+        mut child_nodes: Vec<ExpressionNode>,
+    ) -> Result<ExpressionNode, ExpressionError> {
+        for (i, ope) in self.operand_list.iter().enumerate() {
+            let operand = ope.build_operand().map_err(|e| {
+                e.in_path(format!("operand[{}]", i))
+                    .in_path(self.mode.to_string())
+            })?;
             child_nodes.push(operand.expression_node);
         }
 
-        Ok(child_nodes)
+        let ret = ExpressionNode::from_children(child_nodes);
+
+        match self.mode {
+            ConditionMode::Equal
+            | ConditionMode::NotEqual
+            | ConditionMode::LessThan
+            | ConditionMode::LessThanEqual
+            | ConditionMode::GreaterThan
+            | ConditionMode::GreaterThanEqual => {
+                Ok(ConditionBuilder::compare_build_condition(self.mode, ret)?)
+            }
+            ConditionMode::And | ConditionMode::Or => {
+                Ok(ConditionBuilder::compound_build_condition(self, ret)?)
+            }
+            ConditionMode::Not => Ok(ConditionBuilder::not_build_condition(ret)),
+            ConditionMode::Between => ConditionBuilder::between_build_condition(ret),
+            ConditionMode::In => Ok(ConditionBuilder::in_build_condition(self, ret)),
+            ConditionMode::AttrExists => Ok(ConditionBuilder::attr_exists_build_condition(ret)),
+            ConditionMode::AttrNotExists => {
+                Ok(ConditionBuilder::attr_not_exists_build_condition(ret))
+            }
+            ConditionMode::AttrType => Ok(ConditionBuilder::attr_type_build_condition(ret)),
+            ConditionMode::BeginsWith => Ok(ConditionBuilder::begins_with_build_condition(ret)),
+            ConditionMode::Contains => Ok(ConditionBuilder::contains_build_condition(ret)),
+            ConditionMode::Unset => Err(ExpressionError::UnsetParameterError(
+                "buildTree".to_owned(),
+                "ConditionBuilder".to_owned(),
+            )),
+        }
     }
 
     fn compare_build_condition(
         mode: ConditionMode,
         mut node: ExpressionNode,
-    ) -> anyhow::Result<ExpressionNode> {
-        match mode {
-            ConditionMode::Equal => "$c = $c".clone_into(&mut node.fmt_expression),
-            ConditionMode::NotEqual => "$c <> $c".clone_into(&mut node.fmt_expression),
-            ConditionMode::LessThan => "$c < $c".clone_into(&mut node.fmt_expression),
-            ConditionMode::LessThanEqual => "$c <= $c".clone_into(&mut node.fmt_expression),
-            ConditionMode::GreaterThan => "$c > $c".clone_into(&mut node.fmt_expression),
-            ConditionMode::GreaterThanEqual => "$c >= $c".clone_into(&mut node.fmt_expression),
-            _ => bail!(
-                "build compare condition error: unsupported mode: {:?}",
-                mode
-            ),
-        }
+    ) -> Result<ExpressionNode, ExpressionError> {
+        let operator = match mode {
+            ConditionMode::Equal => " = ",
+            ConditionMode::NotEqual => " <> ",
+            ConditionMode::LessThan => " < ",
+            ConditionMode::LessThanEqual => " <= ",
+            ConditionMode::GreaterThan => " > ",
+            ConditionMode::GreaterThanEqual => " >= ",
+            _ => {
+                return Err(ExpressionError::UnsupportedMode(
+                    "build compare condition".to_owned(),
+                    format!("{:?}", mode),
+                ))
+            }
+        };
+        node.fmt_expression = FmtExpression::new().child().literal(operator).child();
+
         Ok(node)
     }
 
     fn compound_build_condition(
         condition_builder: &ConditionBuilder,
         mut node: ExpressionNode,
-    ) -> anyhow::Result<ExpressionNode> {
-        // create a string with escaped characters to substitute them with proper
-        // aliases during runtime
+    ) -> Result<ExpressionNode, ExpressionError> {
+        // create the segments to substitute with proper aliases during runtime
         let mode = match condition_builder.mode {
-            ConditionMode::And => " AND ",
-            ConditionMode::Or => " OR ",
-            _ => bail!(
-                "build compound condition error: unsupported mode: {:?}",
-                condition_builder.mode
-            ),
+            ConditionMode::And => ") AND (",
+            ConditionMode::Or => ") OR (",
+            _ => {
+                return Err(ExpressionError::UnsupportedMode(
+                    "build compound condition".to_owned(),
+                    format!("{:?}", condition_builder.mode),
+                ))
+            }
         };
 
-        node.fmt_expression = format!(
-            "($c){}",
-            format!("{}{}", mode, "($c)").repeat(condition_builder.condition_list.len() - 1)
-        );
+        node.fmt_expression = FmtExpression::new()
+            .children(condition_builder.condition_list.len(), mode)
+            .wrap("(", ")");
 
         Ok(node)
     }
 
     fn not_build_condition(mut node: ExpressionNode) -> ExpressionNode {
-        // create a string with escaped characters to substitute them with proper
-        // aliases during runtime
-        "NOT ($c)".clone_into(&mut node.fmt_expression);
+        // create the segments to substitute with proper aliases during runtime
+        node.fmt_expression = FmtExpression::new().child().wrap("NOT (", ")");
 
         node
     }
 
-    fn between_build_condition(mut node: ExpressionNode) -> ExpressionNode {
-        // Create a string with special characters that can be substituted later: $c
-        "$c BETWEEN $c AND $c".clone_into(&mut node.fmt_expression);
+    fn between_build_condition(
+        mut node: ExpressionNode,
+    ) -> Result<ExpressionNode, ExpressionError> {
+        check_between_bounds(&node)?;
 
-        node
+        // Create the segments that can be substituted later
+        node.fmt_expression = FmtExpression::new()
+            .child()
+            .literal(" BETWEEN ")
+            .child()
+            .literal(" AND ")
+            .child();
+
+        Ok(node)
     }
 
     fn in_build_condition(
         condition_builder: &ConditionBuilder,
         mut node: ExpressionNode,
     ) -> ExpressionNode {
-        // Create a string with special characters that can be substituted later: $c
-        node.fmt_expression = format!(
-            "$c IN ($c{})",
-            ", $c".repeat(condition_builder.operand_list.len() - 2)
-        );
+        // Create the segments that can be substituted later
+        node.fmt_expression = FmtExpression::new()
+            .child()
+            .literal(" IN (")
+            .children(condition_builder.operand_list.len() - 1, ", ")
+            .literal(")");
 
         node
     }
 
     fn attr_exists_build_condition(mut node: ExpressionNode) -> ExpressionNode {
-        // Create a string with special characters that can be substituted later: $c
-        "attribute_exists ($c)".clone_into(&mut node.fmt_expression);
+        // Create the segments that can be substituted later
+        node.fmt_expression = FmtExpression::new().child().wrap("attribute_exists (", ")");
 
         node
     }
 
     fn attr_not_exists_build_condition(mut node: ExpressionNode) -> ExpressionNode {
-        // Create a string with special characters that can be substituted later: $c
-        "attribute_not_exists ($c)".clone_into(&mut node.fmt_expression);
+        // Create the segments that can be substituted later
+        node.fmt_expression = FmtExpression::new()
+            .child()
+            .wrap("attribute_not_exists (", ")");
 
         node
     }
 
     fn attr_type_build_condition(mut node: ExpressionNode) -> ExpressionNode {
-        // Create a string with special characters that can be substituted later: $c
-        "attribute_type ($c, $c)".clone_into(&mut node.fmt_expression);
+        // Create the segments that can be substituted later
+        node.fmt_expression = FmtExpression::new()
+            .children(2, ", ")
+            .wrap("attribute_type (", ")");
 
         node
     }
 
     fn begins_with_build_condition(mut node: ExpressionNode) -> ExpressionNode {
-        // Create a string with special characters that can be substituted later: $c
-        "begins_with ($c, $c)".clone_into(&mut node.fmt_expression);
+        // Create the segments that can be substituted later
+        node.fmt_expression = FmtExpression::new()
+            .children(2, ", ")
+            .wrap("begins_with (", ")");
 
         node
     }
 
     fn contains_build_condition(mut node: ExpressionNode) -> ExpressionNode {
-        // Create a string with special characters that can be substituted later: $c
-        "contains ($c, $c)".clone_into(&mut node.fmt_expression);
+        // Create the segments that can be substituted later
+        node.fmt_expression = FmtExpression::new()
+            .children(2, ", ")
+            .wrap("contains (", ")");
 
         node
     }
 }
 
+/// Checks that the lower bound of a BETWEEN node is not greater than its upper
+/// bound, if both bounds are comparable values.
+pub(crate) fn check_between_bounds(node: &ExpressionNode) -> Result<(), ExpressionError> {
+    let (lower, upper) = match node.children.as_slice() {
+        [_, lower, upper] => (lower.value(), upper.value()),
+        _ => return Ok(()),
+    };
+
+    let ordering = match (lower, upper) {
+        (Some(AttributeValue::N(lower)), Some(AttributeValue::N(upper))) => {
+            compare_numbers(lower, upper)
+        }
+        (Some(AttributeValue::S(lower)), Some(AttributeValue::S(upper))) => Some(lower.cmp(upper)),
+        (Some(AttributeValue::B(lower)), Some(AttributeValue::B(upper))) => {
+            Some(lower.as_ref().cmp(upper.as_ref()))
+        }
+        _ => None,
+    };
+
+    if ordering == Some(Ordering::Greater) {
+        return Err(ExpressionError::BetweenBoundsOutOfOrder(
+            "betweenBuildCondition".to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn compare_numbers(left: &str, right: &str) -> Option<Ordering> {
+    // integers are compared exactly, anything else as a float
+    if let (Ok(left), Ok(right)) = (left.parse::<i128>(), right.parse::<i128>()) {
+        return Some(left.cmp(&right));
+    }
+
+    left.parse::<f64>()
+        .ok()?
+        .partial_cmp(&right.parse::<f64>().ok()?)
+}
+
 impl TreeBuilder for ConditionBuilder {
-    fn build_tree(&self) -> anyhow::Result<ExpressionNode> {
-        let child_nodes = self.build_child_nodes()?;
-        let ret = ExpressionNode::from_children(child_nodes);
+    fn into_condition(self: Box<Self>) -> Option<ConditionBuilder> {
+        Some(*self)
+    }
 
-        match self.mode {
-            ConditionMode::Equal
-            | ConditionMode::NotEqual
-            | ConditionMode::LessThan
-            | ConditionMode::LessThanEqual
-            | ConditionMode::GreaterThan
-            | ConditionMode::GreaterThanEqual => {
-                Ok(ConditionBuilder::compare_build_condition(self.mode, ret)?)
+    fn build_tree(&self) -> Result<ExpressionNode, ExpressionError> {
+        // nested conditions are built with an explicit stack instead of
+        // recursion, so long chains such as a.or(b).or(c)... can't overflow
+        // the call stack. Every entry holds a condition and the nodes of the
+        // conditions of it that are already built.
+        let mut stack = vec![(self, Vec::with_capacity(self.condition_list.len()))];
+
+        loop {
+            let (condition, built) = match stack.last() {
+                Some((condition, child_nodes)) => (*condition, child_nodes.len()),
+                None => {
+                    return Err(ExpressionError::UnsetParameterError(
+                        "buildTree".to_owned(),
+                        "ConditionBuilder".to_owned(),
+                    ))
+                }
+            };
+
+            if let Some(next) = condition.condition_list.get(built) {
+                stack.push((next, Vec::with_capacity(next.condition_list.len())));
+                continue;
             }
-            ConditionMode::And | ConditionMode::Or => {
-                Ok(ConditionBuilder::compound_build_condition(self, ret)?)
-            }
-            ConditionMode::Not => Ok(ConditionBuilder::not_build_condition(ret)),
-            ConditionMode::Between => Ok(ConditionBuilder::between_build_condition(ret)),
-            ConditionMode::In => Ok(ConditionBuilder::in_build_condition(self, ret)),
-            ConditionMode::AttrExists => Ok(ConditionBuilder::attr_exists_build_condition(ret)),
-            ConditionMode::AttrNotExists => {
-                Ok(ConditionBuilder::attr_not_exists_build_condition(ret))
+
+            let node = match stack.pop() {
+                Some((condition, child_nodes)) => condition.build_node(child_nodes),
+                None => continue,
+            };
+
+            match (node, stack.last_mut()) {
+                (Ok(node), Some((_, child_nodes))) => child_nodes.push(node),
+                (Ok(node), None) => return Ok(node),
+                (Err(e), _) => {
+                    // every parent adds the position of the failed condition
+                    return Err(stack.iter().rev().fold(e, |e, (parent, child_nodes)| {
+                        e.in_path(format!("{}[{}]", parent.mode, child_nodes.len()))
+                    }));
+                }
             }
-            ConditionMode::AttrType => Ok(ConditionBuilder::attr_type_build_condition(ret)),
-            ConditionMode::BeginsWith => Ok(ConditionBuilder::begins_with_build_condition(ret)),
-            ConditionMode::Contains => Ok(ConditionBuilder::contains_build_condition(ret)),
-            ConditionMode::Unset => bail!(ExpressionError::UnsetParameterError(
-                "buildTree".to_owned(),
-                "ConditionBuilder".to_owned(),
-            )),
         }
     }
+
+    fn record(&self, recorder: &mut Recorder) -> String {
+        let operands = self
+            .operand_list
+            .iter()
+            .map(|operand| operand.record(recorder))
+            .collect::<Vec<_>>();
+        let mut conditions = self
+            .condition_list
+            .iter()
+            .map(|condition| condition.record(recorder))
+            .collect::<Vec<_>>();
+
+        let function = match self.mode {
+            ConditionMode::Equal => "equal",
+            ConditionMode::NotEqual => "not_equal",
+            ConditionMode::LessThan => "less_than",
+            ConditionMode::LessThanEqual => "less_than_equal",
+            ConditionMode::GreaterThan => "greater_than",
+            ConditionMode::GreaterThanEqual => "greater_than_equal",
+            ConditionMode::Between => "between",
+            ConditionMode::AttrExists => "attribute_exists",
+            ConditionMode::AttrNotExists => "attribute_not_exists",
+            ConditionMode::Not => "not",
+            ConditionMode::And if conditions.len() > 2 => {
+                // there is no variadic AND, nesting it is equivalent
+                let first = conditions.remove(0);
+                return conditions
+                    .into_iter()
+                    .fold(first, |left, right| format!("and({}, {})", left, right));
+            }
+            ConditionMode::And => "and",
+            ConditionMode::Or if conditions.len() > 2 => {
+                return format!(
+                    "or_all_with(vec![{}], OrFanGuard::new({})).unwrap()",
+                    conditions.join(", "),
+                    conditions.len()
+                );
+            }
+            ConditionMode::Or => "or",
+            ConditionMode::In => {
+                let (left, right) = operands.split_first().unzip();
+                return format!(
+                    "r#in({}, vec![{}])",
+                    left.map(String::as_str).unwrap_or_default(),
+                    right.unwrap_or_default().join(", ")
+                );
+            }
+            ConditionMode::AttrType => {
+                let attr_type = self
+                    .operand_list
+                    .get(1)
+                    .and_then(|operand| record::string_operand(operand.as_ref()))
+                    .and_then(|attr_type| attr_type.parse::<DynamoDbAttributeType>().ok());
+                return match (operands.first(), attr_type) {
+                    (Some(name), Some(attr_type)) => format!(
+                        "attribute_type({}, DynamoDbAttributeType::{:?})",
+                        name, attr_type
+                    ),
+                    _ => "ConditionBuilder::default()".to_owned(),
+                };
+            }
+            ConditionMode::BeginsWith | ConditionMode::Contains => {
+                let function = if self.mode == ConditionMode::BeginsWith {
+                    "begins_with"
+                } else {
+                    "contains"
+                };
+                // string operands are recorded as plain strings, any other
+                // operand of begins_with as-is
+                let operand = match self
+                    .operand_list
+                    .get(1)
+                    .and_then(|operand| record::string_operand(operand.as_ref()))
+                {
+                    Some(operand) => recorder.redact_string(&operand),
+                    None if self.mode == ConditionMode::BeginsWith => {
+                        operands.get(1).cloned().unwrap_or_default()
+                    }
+                    None => recorder.redact_string(""),
+                };
+                return match operands.first() {
+                    Some(name) => format!("{}({}, {})", function, name, operand),
+                    None => "ConditionBuilder::default()".to_owned(),
+                };
+            }
+            ConditionMode::Unset => return "ConditionBuilder::default()".to_owned(),
+        };
+
+        conditions.extend(operands);
+        format!("{}({})", function, conditions.join(", "))
+    }
 }
 
 /// Returns a ConditionBuilder representing the equality clause of the two argument OperandBuilders.
@@ -580,6 +881,48 @@ pub fn and(left: ConditionBuilder, right: ConditionBuilder) -> ConditionBuilder
     }
 }
 
+/// Collects ConditionBuilders into the logical AND clause of all of them.
+///
+/// A single condition is returned as-is. Collecting no condition returns an
+/// unset ConditionBuilder, for which build() returns the typed error
+/// UnsetParameterError.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let required = ["Name", "Email"];
+/// let condition: ConditionBuilder = required.iter().map(|n| name(*n).attribute_exists()).collect();
+///
+/// let expr = Builder::new().with_condition(condition).build().unwrap();
+/// assert_eq!(
+///     expr.condition().unwrap(),
+///     "(attribute_exists (#0)) AND (attribute_exists (#1))"
+/// );
+/// ```
+impl FromIterator<ConditionBuilder> for ConditionBuilder {
+    fn from_iter<T: IntoIterator<Item = ConditionBuilder>>(iter: T) -> Self {
+        let mut condition = ConditionBuilder::default();
+        condition.extend(iter);
+        condition
+    }
+}
+
+/// Adds ConditionBuilders to the logical AND clause of the ConditionBuilder,
+/// see FromIterator.
+impl Extend<ConditionBuilder> for ConditionBuilder {
+    fn extend<T: IntoIterator<Item = ConditionBuilder>>(&mut self, iter: T) {
+        for condition in iter {
+            match self.mode {
+                ConditionMode::Unset => *self = condition,
+                ConditionMode::And => self.condition_list.push(condition),
+                _ => *self = and(std::mem::take(self), condition),
+            }
+        }
+    }
+}
+
 /// Returns a ConditionBuilder representing the logical OR clause of the argument ConditionBuilders.
 ///
 /// The resulting ConditionBuilder can be used as a
@@ -611,6 +954,165 @@ pub fn or(left: ConditionBuilder, right: ConditionBuilder) -> ConditionBuilder {
     }
 }
 
+/// The number of branches or_all() accepts by default.
+pub const DEFAULT_MAX_OR_BRANCHES: usize = 20;
+
+/// Specifies how or_all_with() guards against large OR conditions.
+///
+/// Filters with many OR branches grow the expression quickly and are
+/// evaluated after the items are read, so every branch costs read capacity
+/// without reducing it. Such filters usually mean the access pattern should be
+/// served by a key design or an index instead.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct OrFanGuard {
+    max_branches: usize,
+    convert_to_in: bool,
+}
+
+impl Default for OrFanGuard {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_OR_BRANCHES)
+    }
+}
+
+impl OrFanGuard {
+    /// Returns an OrFanGuard rejecting OR conditions with more than
+    /// max_branches branches.
+    pub fn new(max_branches: usize) -> Self {
+        Self {
+            max_branches,
+            convert_to_in: false,
+        }
+    }
+
+    /// Converts OR conditions whose branches all compare the same operand for
+    /// equality into a single IN condition instead of rejecting them.
+    pub fn convert_to_in(mut self) -> Self {
+        self.convert_to_in = true;
+        self
+    }
+
+    /// Returns the maximum number of branches.
+    pub fn max_branches(&self) -> usize {
+        self.max_branches
+    }
+}
+
+/// Returns a ConditionBuilder representing the logical OR clause of all of
+/// the argument ConditionBuilders.
+///
+/// If more than DEFAULT_MAX_OR_BRANCHES conditions are given, or_all()
+/// returns the typed error TooManyOrBranches, since filters this large should
+/// be replaced by a different query design. Use or_all_with() to configure the
+/// limit. If no conditions are given, or_all() returns the typed error
+/// UnsetParameterError.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // condition represents the condition where the item attribute "Color" is
+/// // "red" OR the item attribute "Size" is "small"
+/// let condition = or_all(vec![
+///     name("Color").equal(value("red")),
+///     name("Size").equal(value("small")),
+/// ])
+/// .unwrap();
+/// ```
+pub fn or_all(
+    conditions: impl IntoIterator<Item = ConditionBuilder>,
+) -> Result<ConditionBuilder, ExpressionError> {
+    or_all_with(conditions, OrFanGuard::default())
+}
+
+/// Returns a ConditionBuilder representing the logical OR clause of all of
+/// the argument ConditionBuilders, guarded by the argument OrFanGuard.
+///
+/// If the guard converts equality fans and every condition compares the same
+/// operand for equality, the conditions are combined into a single IN
+/// condition, as long as the values fit into the DynamoDB limit on IN
+/// operands. Otherwise, more conditions than the guard allows return the typed
+/// error TooManyOrBranches.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let colors = ["red", "green", "blue"];
+/// let conditions = colors.iter().map(|color| name("Color").equal(value(*color)));
+///
+/// let condition = or_all_with(conditions.clone(), OrFanGuard::new(2));
+/// assert!(condition.is_err());
+///
+/// let condition = or_all_with(conditions, OrFanGuard::new(2).convert_to_in()).unwrap();
+/// let expr = Builder::new().with_filter(condition).build().unwrap();
+/// assert_eq!(expr.filter().unwrap(), "#0 IN (:0, :1, :2)");
+/// ```
+pub fn or_all_with(
+    conditions: impl IntoIterator<Item = ConditionBuilder>,
+    guard: OrFanGuard,
+) -> Result<ConditionBuilder, ExpressionError> {
+    let mut conditions = conditions.into_iter().collect::<Vec<_>>();
+
+    match conditions.len() {
+        0 => {
+            return Err(ExpressionError::UnsetParameterError(
+                "orAll".to_owned(),
+                "ConditionBuilder".to_owned(),
+            ))
+        }
+        1 => return Ok(conditions.remove(0)),
+        _ => (),
+    }
+
+    if guard.convert_to_in
+        && conditions.len() <= crate::MAX_IN_OPERANDS
+        && is_equality_fan(&conditions)
+    {
+        let mut left = None;
+        let mut right = Vec::new();
+        for condition in conditions {
+            let mut operands = condition.operand_list.into_iter();
+            left = operands.next();
+            right.extend(operands);
+        }
+
+        if let Some(left) = left {
+            return Ok(r#in(left, right));
+        }
+
+        return Err(ExpressionError::UnsetParameterError(
+            "orAll".to_owned(),
+            "ConditionBuilder".to_owned(),
+        ));
+    }
+
+    if conditions.len() > guard.max_branches {
+        return Err(ExpressionError::TooManyOrBranches(
+            "orAll".to_owned(),
+            conditions.len(),
+            guard.max_branches,
+        ));
+    }
+
+    Ok(ConditionBuilder {
+        operand_list: Vec::new(),
+        condition_list: conditions,
+        mode: ConditionMode::Or,
+    })
+}
+
+// every condition compares the same operand for equality
+fn is_equality_fan(conditions: &[ConditionBuilder]) -> bool {
+    conditions.iter().all(|condition| {
+        condition.mode == ConditionMode::Equal
+            && condition.operand_list.len() == 2
+            && *condition.operand_list[0] == *conditions[0].operand_list[0]
+    })
+}
+
 /// Returns a ConditionBuilder representing the logical NOT clause of the argument ConditionBuilder.
 ///
 /// The resulting ConditionBuilder can be used as a
@@ -707,6 +1209,114 @@ pub fn r#in(
     }
 }
 
+/// Returns a ConditionBuilder representing the result of the IN function
+/// in DynamoDB Condition Expressions, checking the left operand against the
+/// argument values.
+///
+/// Every value is wrapped in a ValueBuilder, so the values can be given as a
+/// plain list. If no values are given, in_values() returns the typed error
+/// UnsetParameterError. If more values are given than DynamoDB accepts as IN
+/// operands, in_values() returns the typed error LimitExceededError.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // condition represents the condition where the value of the item
+/// // attribute "Color" is checked against the list of colors "red",
+/// // "green", and "blue".
+/// let condition = in_values(name("Color"), ["red", "green", "blue"]).unwrap();
+///
+/// let expr = Builder::new().with_condition(condition).build().unwrap();
+/// assert_eq!(expr.condition().unwrap(), "#0 IN (:0, :1, :2)");
+/// ```
+pub fn in_values<T: ToAttributeValue>(
+    left: Box<dyn OperandBuilder>,
+    values: impl IntoIterator<Item = T>,
+) -> Result<ConditionBuilder, ExpressionError> {
+    let right = values
+        .into_iter()
+        .map(|v| value(v) as Box<dyn OperandBuilder>)
+        .collect::<Vec<_>>();
+
+    if right.is_empty() {
+        return Err(ExpressionError::UnsetParameterError(
+            "inValues".to_owned(),
+            "values".to_owned(),
+        ));
+    }
+
+    if right.len() > crate::MAX_IN_OPERANDS {
+        return Err(ExpressionError::LimitExceededError(
+            "inValues".to_owned(),
+            ExpressionLimit::InOperands,
+            right.len(),
+        ));
+    }
+
+    Ok(r#in(left, right))
+}
+
+/// Returns a ConditionBuilder checking the left operand against the argument
+/// values, split into IN functions of at most MAX_IN_OPERANDS values OR-ed
+/// together.
+///
+/// This allows checking against more values than DynamoDB accepts in a single
+/// IN function. If the values fit into a single IN function, the result is
+/// the same as in_values(). If no values are given, in_values_chunked()
+/// returns the typed error UnsetParameterError. Large lists still count
+/// towards the limits on the size of an expression, which
+/// Builder::build_validated() checks.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let ids = (0..150).map(|id| format!("id-{}", id));
+/// let condition = in_values_chunked(name("Id"), ids).unwrap();
+///
+/// let expr = Builder::new().with_filter(condition).build().unwrap();
+/// let filter = expr.filter().unwrap();
+/// assert!(filter.starts_with("(#0 IN (:0, :1, "));
+/// assert!(filter.contains(":99)) OR (#0 IN (:100, :101, "));
+/// ```
+pub fn in_values_chunked<T: ToAttributeValue>(
+    left: Box<dyn OperandBuilder>,
+    values: impl IntoIterator<Item = T>,
+) -> Result<ConditionBuilder, ExpressionError> {
+    let mut right = values
+        .into_iter()
+        .map(|v| value(v) as Box<dyn OperandBuilder>)
+        .collect::<Vec<_>>();
+
+    if right.is_empty() {
+        return Err(ExpressionError::UnsetParameterError(
+            "inValuesChunked".to_owned(),
+            "values".to_owned(),
+        ));
+    }
+
+    let mut conditions = Vec::with_capacity(right.len().div_ceil(crate::MAX_IN_OPERANDS));
+    while right.len() > crate::MAX_IN_OPERANDS {
+        let rest = right.split_off(crate::MAX_IN_OPERANDS);
+        conditions.push(r#in(left.clone(), right));
+        right = rest;
+    }
+    conditions.push(r#in(left, right));
+
+    if conditions.len() == 1 {
+        return Ok(conditions.remove(0));
+    }
+
+    Ok(ConditionBuilder {
+        operand_list: Vec::new(),
+        condition_list: conditions,
+        mode: ConditionMode::Or,
+    })
+}
+
 /// Returns a ConditionBuilder representing the result of the
 /// attribute_exists function in DynamoDB Condition Expressions.
 ///
@@ -803,6 +1413,9 @@ pub fn attribute_type(
 /// The resulting ConditionBuilder can be used as a part of other Condition Expressions or as
 /// an argument to the WithCondition() method for the Builder struct.
 ///
+/// The prefix is either a string, or any OperandBuilder such as a value
+/// built at runtime or the path of another item attribute.
+///
 /// # Example
 ///
 /// ```
@@ -817,10 +1430,23 @@ pub fn attribute_type(
 /// // Used to make an Builder
 /// let builder = Builder::new().with_condition(another_condition);
 /// ```
-pub fn begins_with(name: Box<NameBuilder>, prefix: impl Into<String>) -> ConditionBuilder {
-    let v = value(prefix.into());
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // condition represents the boolean condition of whether the item
+/// // attribute "Path" starts with the value of the item attribute
+/// // "ParentPath"
+/// let condition = begins_with(name("Path"), name("ParentPath"));
+///
+/// let expr = Builder::new().with_condition(condition).build().unwrap();
+/// assert_eq!(expr.condition().unwrap(), "begins_with (#0, #1)");
+/// ```
+pub fn begins_with(name: Box<NameBuilder>, prefix: impl IntoOperand) -> ConditionBuilder {
     ConditionBuilder {
-        operand_list: vec![name, v],
+        operand_list: vec![name, prefix.into_operand()],
         condition_list: Vec::new(),
         mode: ConditionMode::BeginsWith,
     }
@@ -847,14 +1473,41 @@ pub fn begins_with(name: Box<NameBuilder>, prefix: impl Into<String>) -> Conditi
 /// let builder = Builder::new().with_condition(another_condition);
 /// ```
 pub fn contains(name: Box<NameBuilder>, substr: impl Into<String>) -> ConditionBuilder {
-    let v = value(substr.into());
+    contains_operand(name, value(substr.into()))
+}
+
+// contains() of any operand, such as a number element of a list attribute
+pub(crate) fn contains_operand(
+    name: Box<NameBuilder>,
+    operand: Box<dyn OperandBuilder>,
+) -> ConditionBuilder {
     ConditionBuilder {
-        operand_list: vec![name, v],
+        operand_list: vec![name, operand],
         condition_list: Vec::new(),
         mode: ConditionMode::Contains,
     }
 }
 
+// combines the argument conditions into a single logical clause
+fn compound_condition(
+    function_name: &str,
+    mode: ConditionMode,
+    mut conditions: Vec<ConditionBuilder>,
+) -> Result<ConditionBuilder, ExpressionError> {
+    match conditions.len() {
+        0 => Err(ExpressionError::UnsetParameterError(
+            function_name.to_owned(),
+            "values".to_owned(),
+        )),
+        1 => Ok(conditions.remove(0)),
+        _ => Ok(ConditionBuilder {
+            operand_list: Vec::new(),
+            condition_list: conditions,
+            mode,
+        }),
+    }
+}
+
 /// Trait for building a ConditionBuilder representing the equality clause of the two argument OperandBuilders.
 pub trait EqualBuilder: OperandBuilder {
     /// Returns a ConditionBuilder representing the equality clause of the two argument OperandBuilders.
@@ -1270,13 +1923,13 @@ pub trait BetweenBuilder: OperandBuilder {
     /// ```
     fn between(
         self: Box<Self>,
-        upper: Box<dyn OperandBuilder>,
         lower: Box<dyn OperandBuilder>,
+        upper: Box<dyn OperandBuilder>,
     ) -> ConditionBuilder
     where
         Self: Sized + 'static,
     {
-        between(self, upper, lower)
+        between(self, lower, upper)
     }
 }
 
@@ -1342,6 +1995,64 @@ pub trait InBuilder: OperandBuilder {
     {
         r#in(self, right)
     }
+
+    /// Returns a ConditionBuilder representing the result of the IN function
+    /// in DynamoDB Condition Expressions, checking the operand against the
+    /// argument values.
+    ///
+    /// The values must not be empty and must fit into the DynamoDB limit on
+    /// IN operands, otherwise a typed error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the condition where the value of the item
+    /// // attribute "Color" is checked against the list of colors "red",
+    /// // "green", and "blue".
+    /// let condition = name("Color").in_values(["red", "green", "blue"]).unwrap();
+    ///
+    /// // Used in another Condition Expression
+    /// let another_condition = not(condition);
+    /// // Used to make an Builder
+    /// let builder = Builder::new().with_condition(another_condition);
+    /// ```
+    fn in_values<T: ToAttributeValue>(
+        self: Box<Self>,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<ConditionBuilder, ExpressionError>
+    where
+        Self: Sized + 'static,
+    {
+        in_values(self, values)
+    }
+
+    /// Returns a ConditionBuilder checking the operand against the argument
+    /// values, split into IN functions of at most MAX_IN_OPERANDS values OR-ed
+    /// together.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the condition where the value of the item
+    /// // attribute "Id" is any of 250 ids, checked by 3 IN functions
+    /// let condition = name("Id").in_values_chunked(0..250).unwrap();
+    ///
+    /// // Used to make an Builder
+    /// let builder = Builder::new().with_filter(condition);
+    /// ```
+    fn in_values_chunked<T: ToAttributeValue>(
+        self: Box<Self>,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<ConditionBuilder, ExpressionError>
+    where
+        Self: Sized + 'static,
+    {
+        in_values_chunked(self, values)
+    }
 }
 
 impl NameBuilder {
@@ -1442,7 +2153,7 @@ impl NameBuilder {
     /// // Used to make an Builder
     /// let builder = Builder::new().with_condition(another_condition);
     /// ```
-    pub fn begins_with(self: Box<NameBuilder>, prefix: impl Into<String>) -> ConditionBuilder {
+    pub fn begins_with(self: Box<NameBuilder>, prefix: impl IntoOperand) -> ConditionBuilder {
         begins_with(self, prefix)
     }
 
@@ -1469,6 +2180,346 @@ impl NameBuilder {
     pub fn contains(self: Box<NameBuilder>, substr: impl Into<String>) -> ConditionBuilder {
         contains(self, substr)
     }
+
+    /// Returns a ConditionBuilder representing the logical NOT clause of the
+    /// contains function in DynamoDB Condition Expressions.
+    ///
+    /// An item without the attribute does not contain the substring either,
+    /// so it matches this condition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item
+    /// // attribute "Tags" does not have the value "archived"
+    /// let condition = name("Tags").not_contains("archived");
+    ///
+    /// let expr = Builder::new().with_condition(condition).build().unwrap();
+    /// assert_eq!(expr.condition().unwrap(), "NOT (contains (#0, :0))");
+    /// ```
+    pub fn not_contains(self: Box<NameBuilder>, substr: impl Into<String>) -> ConditionBuilder {
+        not(contains(self, substr))
+    }
+
+    /// Returns a ConditionBuilder representing the logical NOT clause of the
+    /// begins_with function in DynamoDB Condition Expressions.
+    ///
+    /// An item without the attribute does not begin with the prefix either,
+    /// so it matches this condition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item
+    /// // attribute "CodeName" does not start with the substring "Ben"
+    /// let condition = name("CodeName").not_begins_with("Ben");
+    ///
+    /// let expr = Builder::new().with_condition(condition).build().unwrap();
+    /// assert_eq!(expr.condition().unwrap(), "NOT (begins_with (#0, :0))");
+    /// ```
+    pub fn not_begins_with(self: Box<NameBuilder>, prefix: impl IntoOperand) -> ConditionBuilder {
+        not(begins_with(self, prefix))
+    }
+
+    /// Returns a ConditionBuilder representing the logical OR clause of the
+    /// item attribute being equal to each of the argument values.
+    ///
+    /// This matches the same items as in_values(), and can be used where the
+    /// IN comparator is not wanted. If no values are given, any_of() returns
+    /// the typed error UnsetParameterError.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the condition where the item attribute "Status"
+    /// // is "A", "B" or "C"
+    /// let condition = name("Status").any_of(["A", "B", "C"]).unwrap();
+    ///
+    /// let expr = Builder::new().with_filter(condition).build().unwrap();
+    /// assert_eq!(
+    ///     expr.filter().unwrap(),
+    ///     "(#0 = :0) OR (#0 = :1) OR (#0 = :2)"
+    /// );
+    /// ```
+    pub fn any_of<T: ToAttributeValue>(
+        self: Box<NameBuilder>,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<ConditionBuilder, ExpressionError> {
+        let conditions = values
+            .into_iter()
+            .map(|v| equal(self.clone(), value(v)))
+            .collect();
+
+        compound_condition("anyOf", ConditionMode::Or, conditions)
+    }
+
+    /// Returns a ConditionBuilder representing the logical AND clause of the
+    /// contains function of the item attribute for each of the argument
+    /// needles.
+    ///
+    /// The resulting condition is true if the attribute contains every needle,
+    /// such as a string set having all of the argument strings. If no needles
+    /// are given, all_of() returns the typed error UnsetParameterError.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the condition where the item attribute "Tags"
+    /// // has both values "new" and "sale"
+    /// let condition = name("Tags").all_of(["new", "sale"]).unwrap();
+    ///
+    /// let expr = Builder::new().with_filter(condition).build().unwrap();
+    /// assert_eq!(
+    ///     expr.filter().unwrap(),
+    ///     "(contains (#0, :0)) AND (contains (#0, :1))"
+    /// );
+    /// ```
+    pub fn all_of(
+        self: Box<NameBuilder>,
+        needles: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<ConditionBuilder, ExpressionError> {
+        let conditions = needles
+            .into_iter()
+            .map(|needle| contains(self.clone(), needle))
+            .collect();
+
+        compound_condition("allOf", ConditionMode::And, conditions)
+    }
+
+    /// Returns a ConditionBuilder that is true only if the item does not have
+    /// the attribute at all.
+    ///
+    /// This is the same as attribute_not_exists(). An attribute that is present
+    /// with the DynamoDB Null type is not missing, so an item with
+    /// `"Nickname": {"NULL": true}` does not match this condition. Use
+    /// is_missing_or_null() to match both.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item
+    /// // has no attribute "Nickname"
+    /// let condition = name("Nickname").is_missing();
+    ///
+    /// // Used to make an Builder
+    /// let builder = Builder::new().with_condition(condition);
+    /// ```
+    pub fn is_missing(self: Box<NameBuilder>) -> ConditionBuilder {
+        attribute_not_exists(self)
+    }
+
+    /// Returns a ConditionBuilder that is true only if the item has the
+    /// attribute and its value is of the DynamoDB Null type.
+    ///
+    /// This is the same as attribute_type() with DynamoDbAttributeType::Null.
+    /// A missing attribute is not null, so an item without the attribute does
+    /// not match this condition. Use is_missing_or_null() to match both.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item
+    /// // attribute "Nickname" is explicitly set to null
+    /// let condition = name("Nickname").is_null();
+    ///
+    /// // Used to make an Builder
+    /// let builder = Builder::new().with_condition(condition);
+    /// ```
+    pub fn is_null(self: Box<NameBuilder>) -> ConditionBuilder {
+        attribute_type(self, DynamoDbAttributeType::Null)
+    }
+
+    /// Returns a ConditionBuilder that is true if the item does not have the
+    /// attribute, or has it with a value of the DynamoDB Null type.
+    ///
+    /// The resulting condition is
+    /// `attribute_not_exists(a) OR attribute_type(a, NULL)`, matching the
+    /// items that is_missing() and is_null() each match on their own.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item
+    /// // attribute "Nickname" has no usable value
+    /// let condition = name("Nickname").is_missing_or_null();
+    ///
+    /// // Used to make an Builder
+    /// let builder = Builder::new().with_condition(condition);
+    /// ```
+    pub fn is_missing_or_null(self: Box<NameBuilder>) -> ConditionBuilder {
+        self.clone().is_missing().or(self.is_null())
+    }
+
+    /// Returns a ConditionBuilder that is true only if the item has the
+    /// attribute with a value that is not of the DynamoDB Null type.
+    ///
+    /// The resulting condition is
+    /// `attribute_exists(a) AND NOT (attribute_type(a, NULL))`, matching the
+    /// items that is_missing_or_null() does not match.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item
+    /// // attribute "Nickname" has a usable value
+    /// let condition = name("Nickname").is_not_null();
+    ///
+    /// let expr = Builder::new().with_condition(condition).build().unwrap();
+    /// assert_eq!(
+    ///     expr.condition().unwrap(),
+    ///     "(attribute_exists (#0)) AND (NOT (attribute_type (#0, :0)))"
+    /// );
+    /// ```
+    pub fn is_not_null(self: Box<NameBuilder>) -> ConditionBuilder {
+        self.clone().attribute_exists().and(self.is_null().not())
+    }
+
+    /// Returns a ConditionBuilder that is true if the item does not have the
+    /// attribute, or has it with a value of the argument DynamoDbAttributeType.
+    ///
+    /// The resulting condition is
+    /// `attribute_not_exists(a) OR attribute_type(a, attr_type)`, which checks
+    /// optional attributes without rejecting the items that do not set them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item
+    /// // attribute "Tags" is either missing or a string set
+    /// let condition = name("Tags").is_type_or_missing(DynamoDbAttributeType::StringSet);
+    ///
+    /// let expr = Builder::new().with_condition(condition).build().unwrap();
+    /// assert_eq!(
+    ///     expr.condition().unwrap(),
+    ///     "(attribute_not_exists (#0)) OR (attribute_type (#0, :0))"
+    /// );
+    /// ```
+    pub fn is_type_or_missing(
+        self: Box<NameBuilder>,
+        attr_type: DynamoDbAttributeType,
+    ) -> ConditionBuilder {
+        self.clone()
+            .is_missing()
+            .or(attribute_type(self, attr_type))
+    }
+
+    /// Returns a ConditionBuilder representing the item attribute being equal
+    /// to the boolean true.
+    ///
+    /// With Builder::with_value_dedup(), every boolean true in an Expression
+    /// shares the same value alias, so repeated is_true() conditions add a
+    /// single entry to the values map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item
+    /// // attribute "Active" is true
+    /// let condition = name("Active").is_true();
+    ///
+    /// // Used to make an Builder
+    /// let builder = Builder::new().with_condition(condition);
+    /// ```
+    pub fn is_true(self: Box<NameBuilder>) -> ConditionBuilder {
+        equal(self, value(true))
+    }
+
+    /// Returns a ConditionBuilder representing the item attribute being equal
+    /// to the boolean false.
+    ///
+    /// With Builder::with_value_dedup(), every boolean false in an Expression
+    /// shares the same value alias, so repeated is_false() conditions add a
+    /// single entry to the values map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item
+    /// // attribute "Deleted" is false
+    /// let condition = name("Deleted").is_false();
+    ///
+    /// // Used to make an Builder
+    /// let builder = Builder::new().with_condition(condition);
+    /// ```
+    pub fn is_false(self: Box<NameBuilder>) -> ConditionBuilder {
+        equal(self, value(false))
+    }
+
+    /// Returns a ConditionBuilder that is true if the item does not have the
+    /// attribute, or if the argument ConditionBuilder is true.
+    ///
+    /// The resulting condition is `attribute_not_exists(a) OR (condition)`,
+    /// the usual guard of a conditional write that creates an item or updates
+    /// it only if it is in an expected state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item has
+    /// // no attribute "Status", or its attribute "Status" is not "LOCKED"
+    /// let condition = name("Status").not_exists_or(name("Status").not_equal(value("LOCKED")));
+    ///
+    /// let expr = Builder::new().with_condition(condition).build().unwrap();
+    /// assert_eq!(
+    ///     expr.condition().unwrap(),
+    ///     "(attribute_not_exists (#0)) OR (#0 <> :0)"
+    /// );
+    /// ```
+    pub fn not_exists_or(self: Box<NameBuilder>, condition: ConditionBuilder) -> ConditionBuilder {
+        attribute_not_exists(self).or(condition)
+    }
+
+    /// Returns a ConditionBuilder that is true if the item does not have the
+    /// attribute, or if the attribute is equal to the argument OperandBuilder.
+    ///
+    /// This is the same as not_exists_or() with an equality clause of the
+    /// attribute.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // condition represents the boolean condition of whether the item has
+    /// // no attribute "Status", or its attribute "Status" is "ACTIVE"
+    /// let condition = name("Status").not_exists_or_equal(value("ACTIVE"));
+    ///
+    /// let expr = Builder::new().with_condition(condition).build().unwrap();
+    /// assert_eq!(
+    ///     expr.condition().unwrap(),
+    ///     "(attribute_not_exists (#0)) OR (#0 = :0)"
+    /// );
+    /// ```
+    pub fn not_exists_or_equal(
+        self: Box<NameBuilder>,
+        right: Box<dyn OperandBuilder>,
+    ) -> ConditionBuilder {
+        self.clone().not_exists_or(equal(self, right))
+    }
 }
 
 impl EqualBuilder for NameBuilder {}
@@ -1489,6 +2540,129 @@ impl GreaterThanEqualBuilder for SizeBuilder {}
 impl BetweenBuilder for SizeBuilder {}
 impl InBuilder for SizeBuilder {}
 
+/// Converts a ConditionBuilder that only uses the comparisons supported by
+/// Key Condition Expressions into a KeyConditionBuilder.
+///
+/// The condition must be a comparison (=, <, <=, >, >=), a BETWEEN or a
+/// begins_with of a top-level attribute and values, or the AND of two such
+/// conditions, the first of which is an equality. Other conditions return the
+/// typed error InvalidKeyCondition, describing why the condition can't be
+/// used as a key condition.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let condition = name("pk")
+///     .equal(value("USER#1"))
+///     .and(begins_with(name("sk"), "ORDER#"));
+///
+/// let key_condition = KeyConditionBuilder::try_from(condition).unwrap();
+/// let expr = Builder::new().with_key_condition(key_condition).build().unwrap();
+/// assert_eq!(expr.key_condition().unwrap(), "(#0 = :0) AND (begins_with (#1, :1))");
+///
+/// let condition = name("pk").equal(value("USER#1")).or(name("pk").equal(value("USER#2")));
+/// assert!(KeyConditionBuilder::try_from(condition).is_err());
+/// ```
+impl TryFrom<ConditionBuilder> for KeyConditionBuilder {
+    type Error = ExpressionError;
+
+    fn try_from(condition: ConditionBuilder) -> Result<Self, Self::Error> {
+        if condition.mode != ConditionMode::And {
+            return key_comparison(&condition);
+        }
+
+        match condition.condition_list.as_slice() {
+            [left, right]
+                if left.mode != ConditionMode::And && right.mode != ConditionMode::And =>
+            {
+                if left.mode != ConditionMode::Equal {
+                    return Err(not_a_key_condition(
+                        "first key condition must be an equality condition".to_owned(),
+                    ));
+                }
+                Ok(key_and(key_comparison(left)?, key_comparison(right)?))
+            }
+            _ => Err(not_a_key_condition(
+                "more than two key conditions are not supported".to_owned(),
+            )),
+        }
+    }
+}
+
+// converts a single comparison of a key attribute
+fn key_comparison(condition: &ConditionBuilder) -> Result<KeyConditionBuilder, ExpressionError> {
+    let (left, right) = match condition.operand_list.split_first() {
+        Some((left, right)) => (key_operand(left.as_ref())?, right),
+        None => {
+            return Err(not_a_key_condition(format!(
+                "{} is not supported in key conditions",
+                condition.mode
+            )))
+        }
+    };
+    let mut values = right
+        .iter()
+        .map(|operand| key_value(operand.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter();
+
+    let key_condition = match (condition.mode, values.next(), values.next(), values.next()) {
+        (ConditionMode::Equal, Some(v), None, None) => key_equal(left, value(v)),
+        (ConditionMode::LessThan, Some(v), None, None) => key_less_than(left, value(v)),
+        (ConditionMode::LessThanEqual, Some(v), None, None) => key_less_than_equal(left, value(v)),
+        (ConditionMode::GreaterThan, Some(v), None, None) => key_greater_than(left, value(v)),
+        (ConditionMode::GreaterThanEqual, Some(v), None, None) => {
+            key_greater_than_equal(left, value(v))
+        }
+        (ConditionMode::Between, Some(lower), Some(upper), None) => {
+            key_between(left, value(lower), value(upper))
+        }
+        (ConditionMode::BeginsWith, Some(AttributeValue::S(prefix)), None, None) => {
+            key_begins_with(left, prefix)
+        }
+        (mode, ..) => {
+            return Err(not_a_key_condition(format!(
+                "{} is not supported in key conditions",
+                mode
+            )))
+        }
+    };
+
+    Ok(key_condition)
+}
+
+// returns the key attribute an operand names
+fn key_operand(operand: &dyn OperandBuilder) -> Result<Box<KeyBuilder>, ExpressionError> {
+    let node = operand.build_operand()?.expression_node;
+    match node.names.as_slice() {
+        [name] if node.values.is_empty() && node.fmt_expression == FmtExpression::new().name() => {
+            Ok(key(name.clone()))
+        }
+        _ => Err(not_a_key_condition(
+            "the first operand of a key condition must be a top-level attribute".to_owned(),
+        )),
+    }
+}
+
+// returns the value an operand holds
+fn key_value(operand: &dyn OperandBuilder) -> Result<AttributeValue, ExpressionError> {
+    let node = operand.build_operand()?.expression_node;
+    match node.values.as_slice() {
+        [value] if node.names.is_empty() && node.fmt_expression == FmtExpression::new().value() => {
+            Ok(value.clone())
+        }
+        _ => Err(not_a_key_condition(
+            "key attributes can only be compared to values".to_owned(),
+        )),
+    }
+}
+
+fn not_a_key_condition(reason: String) -> ExpressionError {
+    ExpressionError::InvalidKeyCondition { reason }
+}
+
 #[cfg(test)]
 mod tests {
     use aws_sdk_dynamodb::types::AttributeValue;
@@ -1824,14 +2998,13 @@ mod tests {
         let input = name("").size().equal(value(5));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("equal")
         );
 
         Ok(())
@@ -1842,14 +3015,13 @@ mod tests {
         let input = name("").size().not_equal(value(5));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("not_equal")
         );
 
         Ok(())
@@ -1860,14 +3032,13 @@ mod tests {
         let input = name("").size().less_than(value(5));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("less_than")
         );
 
         Ok(())
@@ -1878,14 +3049,13 @@ mod tests {
         let input = name("").size().less_than_equal(value(5));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("less_than_equal")
         );
 
         Ok(())
@@ -1896,14 +3066,13 @@ mod tests {
         let input = name("").size().greater_than(value(5));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("greater_than")
         );
 
         Ok(())
@@ -1914,14 +3083,13 @@ mod tests {
         let input = name("").size().greater_than_equal(value(5));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("greater_than_equal")
         );
 
         Ok(())
@@ -1932,10 +3100,7 @@ mod tests {
         let input = ConditionBuilder::default();
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "buildTree".to_owned(),
                 "ConditionBuilder".to_owned()
@@ -1983,6 +3148,90 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn clone_condition() -> anyhow::Result<()> {
+        let common = name("foo").equal(value(5));
+        let input = common.clone().and(name("bar").equal(value("baz")));
+
+        assert_eq!(common.clone().build_tree()?, common.build_tree()?);
+        assert_eq!(
+            input.clone().build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    common.build_tree()?,
+                    ExpressionNode::from_children_expression(
+                        vec![
+                            ExpressionNode::from_names(vec!["bar".to_owned()], "$n"),
+                            ExpressionNode::from_values(
+                                vec![AttributeValue::S("baz".to_owned())],
+                                "$v"
+                            )
+                        ],
+                        "$c = $c"
+                    ),
+                ],
+                "($c) AND ($c)"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn debug_condition() -> anyhow::Result<()> {
+        let input = name("foo")
+            .equal(value(5))
+            .and(name("bar").between(value(1), name("baz").size()));
+
+        assert_eq!(
+            format!("{:?}", input),
+            "And(\
+                Equal(NameBuilder { name: \"foo\", mode: Strict }, ValueBuilder { value: 5 }), \
+                Between(\
+                    NameBuilder { name: \"bar\", mode: Strict }, \
+                    ValueBuilder { value: 1 }, \
+                    SizeBuilder { name_builder: NameBuilder { name: \"baz\", mode: Strict } }\
+                )\
+            )"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn eq_condition() -> anyhow::Result<()> {
+        let input = name("foo")
+            .equal(value(5))
+            .and(name("bar").begins_with("baz"));
+
+        assert_eq!(
+            input,
+            name("foo")
+                .equal(value(5))
+                .and(name("bar").begins_with("baz"))
+        );
+        assert_ne!(
+            input,
+            name("foo")
+                .equal(value(6))
+                .and(name("bar").begins_with("baz"))
+        );
+        assert_ne!(
+            input,
+            name("foo")
+                .equal(value("5"))
+                .and(name("bar").begins_with("baz"))
+        );
+        assert_ne!(
+            input,
+            name("foo")
+                .equal(value(5))
+                .or(name("bar").begins_with("baz"))
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn basic_method_or() -> anyhow::Result<()> {
         let input = name("foo")
@@ -2023,6 +3272,60 @@ mod tests {
 
     // TODO: variadic tests require a macro with variadic arguments
 
+    #[test]
+    fn condition_len() -> anyhow::Result<()> {
+        let leaf = || name("foo").equal(value(5));
+
+        assert_eq!(ConditionBuilder::default().len(), 0);
+        assert!(ConditionBuilder::default().is_empty());
+        assert_eq!(leaf().len(), 1);
+        assert!(!leaf().is_empty());
+        assert_eq!(not(leaf().and(leaf())).len(), 1);
+        assert_eq!(leaf().or(leaf()).len(), 2);
+        assert_eq!(leaf().and(leaf()).and(leaf()).len(), 2);
+        assert_eq!(
+            vec![leaf(), leaf(), leaf()]
+                .into_iter()
+                .collect::<ConditionBuilder>()
+                .len(),
+            3
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_and() -> anyhow::Result<()> {
+        let conditions = || (0..3).map(|i| name(format!("foo{}", i)).equal(value(i)));
+
+        let input = conditions().collect::<ConditionBuilder>();
+        assert_eq!(
+            Builder::new().with_condition(input).build()?.condition(),
+            Some("(#0 = :0) AND (#1 = :1) AND (#2 = :2)")
+        );
+
+        let mut input = name("bar").attribute_exists();
+        input.extend(conditions().take(2));
+        assert_eq!(
+            Builder::new().with_condition(input).build()?.condition(),
+            Some("(attribute_exists (#0)) AND (#1 = :0) AND (#2 = :1)")
+        );
+
+        assert_eq!(
+            conditions()
+                .take(1)
+                .collect::<ConditionBuilder>()
+                .build_tree()?,
+            name("foo0").equal(value(0)).build_tree()?
+        );
+        assert_eq!(
+            std::iter::empty().collect::<ConditionBuilder>().mode,
+            ConditionMode::Unset
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn invalid_operand_error_and() -> anyhow::Result<()> {
         let input = name("")
@@ -2031,14 +3334,14 @@ mod tests {
             .and(name("[5]").between(value(3), value(9)));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("greater_than_equal")
+            .in_path("and[0]")
         );
 
         Ok(())
@@ -2052,14 +3355,14 @@ mod tests {
             .or(name("[5]").between(value(3), value(9)));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("greater_than_equal")
+            .in_path("or[0]")
         );
 
         Ok(())
@@ -2116,14 +3419,15 @@ mod tests {
             .not();
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("greater_than_equal")
+            .in_path("or[0]")
+            .in_path("not[0]")
         );
 
         Ok(())
@@ -2187,18 +3491,70 @@ mod tests {
     }
 
     #[test]
-    fn invalid_operand_error_between() -> anyhow::Result<()> {
-        let input = name("[5]").between(value(3), name("foo..bar"));
+    fn between_bounds_out_of_order() -> anyhow::Result<()> {
+        let out_of_order =
+            error::ExpressionError::BetweenBoundsOutOfOrder("betweenBuildCondition".to_owned());
 
         assert_eq!(
-            input
+            name("foo")
+                .between(value(10), value(5))
+                .build_tree()
+                .unwrap_err(),
+            out_of_order
+        );
+        assert_eq!(
+            name("foo")
+                .between(value(1.5), value(-2.25))
+                .build_tree()
+                .unwrap_err(),
+            out_of_order
+        );
+        assert_eq!(
+            name("foo")
+                .between(value("b"), value("a"))
                 .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
                 .unwrap_err(),
+            out_of_order
+        );
+        assert_eq!(
+            name("foo")
+                .between(value(2), value(10))
+                .or(name("bar").between(value(10), value(2)))
+                .build_tree()
+                .unwrap_err(),
+            out_of_order.in_path("or[1]")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn between_bounds_unchecked() -> anyhow::Result<()> {
+        // equal bounds are valid, strings compare by bytes, and names or mixed
+        // types can't be compared
+        name("foo").between(value(5), value(5)).build_tree()?;
+        name("foo").between(value("10"), value("9")).build_tree()?;
+        name("foo").between(name("bar"), value(5)).build_tree()?;
+        name("foo").between(value("b"), value(1)).build_tree()?;
+        name("foo")
+            .between(value(i64::MAX), value(i64::MAX))
+            .build_tree()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_operand_error_between() -> anyhow::Result<()> {
+        let input = name("[5]").between(value(3), name("foo..bar"));
+
+        assert_eq!(
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("between")
         );
 
         Ok(())
@@ -2266,14 +3622,13 @@ mod tests {
         let input = name("[5]").r#in(vec![value(3), name("foo..bar")]);
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("in")
         );
 
         Ok(())
@@ -2314,14 +3669,13 @@ mod tests {
         let input = attribute_exists(name(""));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("attr_exists")
         );
 
         Ok(())
@@ -2332,14 +3686,13 @@ mod tests {
         let input = attribute_not_exists(name(""));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("attr_not_exists")
         );
 
         Ok(())
@@ -2494,14 +3847,112 @@ mod tests {
         let input = name("").attribute_type(DynamoDbAttributeType::Map);
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("attr_type")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_missing() -> anyhow::Result<()> {
+        let input = name("foo").is_missing();
+
+        assert_eq!(
+            input.build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![ExpressionNode::from_names(vec!["foo".to_owned()], "$n")],
+                "attribute_not_exists ($c)"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_true() -> anyhow::Result<()> {
+        let input = name("foo").is_true();
+
+        assert_eq!(
+            input.build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                    ExpressionNode::from_values(vec![AttributeValue::Bool(true)], "$v")
+                ],
+                "$c = $c"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_false() -> anyhow::Result<()> {
+        let input = name("foo").is_false();
+
+        assert_eq!(
+            input.build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                    ExpressionNode::from_values(vec![AttributeValue::Bool(false)], "$v")
+                ],
+                "$c = $c"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_null() -> anyhow::Result<()> {
+        let input = name("foo").is_null();
+
+        assert_eq!(
+            input.build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                    ExpressionNode::from_values(vec![AttributeValue::S("NULL".to_owned())], "$v")
+                ],
+                "attribute_type ($c, $c)"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_missing_or_null() -> anyhow::Result<()> {
+        let input = name("foo").is_missing_or_null();
+
+        assert_eq!(
+            input.build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    ExpressionNode::from_children_expression(
+                        vec![ExpressionNode::from_names(vec!["foo".to_owned()], "$n")],
+                        "attribute_not_exists ($c)"
+                    ),
+                    ExpressionNode::from_children_expression(
+                        vec![
+                            ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                            ExpressionNode::from_values(
+                                vec![AttributeValue::S("NULL".to_owned())],
+                                "$v"
+                            )
+                        ],
+                        "attribute_type ($c, $c)"
+                    ),
+                ],
+                "($c) OR ($c)"
+            )
         );
 
         Ok(())
@@ -2530,14 +3981,13 @@ mod tests {
         let input = name("").begins_with("bar");
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("begins_with")
         );
 
         Ok(())
@@ -2566,14 +4016,13 @@ mod tests {
         let input = name("").contains("bar");
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("contains")
         );
 
         Ok(())
@@ -2649,4 +4098,459 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn or_all() -> anyhow::Result<()> {
+        let input = condition::or_all(vec![
+            name("foo").equal(value(5)),
+            name("bar").less_than(value(6)),
+            name("baz").attribute_exists(),
+        ])?;
+
+        assert_eq!(input.build_tree()?.fmt_expression, "($c) OR ($c) OR ($c)");
+
+        let input = condition::or_all(vec![name("foo").equal(value(5))])?;
+        assert_eq!(input, name("foo").equal(value(5)));
+
+        assert_eq!(
+            condition::or_all(vec![]).unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "orAll".to_owned(),
+                "ConditionBuilder".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn or_all_guard() -> anyhow::Result<()> {
+        let input = (0..DEFAULT_MAX_OR_BRANCHES as i64 + 1)
+            .map(|id| name("foo").equal(value(id)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            condition::or_all(input.clone()).unwrap_err(),
+            error::ExpressionError::TooManyOrBranches(
+                "orAll".to_owned(),
+                DEFAULT_MAX_OR_BRANCHES + 1,
+                DEFAULT_MAX_OR_BRANCHES
+            )
+        );
+
+        let converted = or_all_with(input, OrFanGuard::default().convert_to_in())?;
+        assert_eq!(
+            converted,
+            r#in(
+                name("foo"),
+                (0..DEFAULT_MAX_OR_BRANCHES as i64 + 1)
+                    .map(|id| value(id) as Box<dyn OperandBuilder>)
+                    .collect()
+            )
+        );
+
+        // different operands can not be converted
+        let input = vec![
+            name("foo").equal(value(5)),
+            name("bar").equal(value(6)),
+            name("foo").equal(value(7)),
+        ];
+        assert_eq!(
+            or_all_with(input, OrFanGuard::new(2).convert_to_in()).unwrap_err(),
+            error::ExpressionError::TooManyOrBranches("orAll".to_owned(), 3, 2)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn deep_chain() -> anyhow::Result<()> {
+        let mut input = name("foo").equal(value(0));
+        for id in 1..3000 {
+            input = input.or(name("foo").equal(value(id)));
+        }
+
+        let expr = Builder::new().with_filter(input).build()?;
+        let filter = expr.filter().unwrap_or_default();
+        assert!(
+            filter.starts_with("((((") && filter.contains("((#0 = :0) OR (#0 = :1)) OR (#0 = :2)")
+        );
+        assert!(filter.ends_with(") OR (#0 = :2999)"));
+
+        let mut input = name("").equal(value(0));
+        for id in 1..3000 {
+            input = input.and(name("foo").equal(value(id)));
+        }
+
+        let err = input.build_tree().unwrap_err();
+        assert!(err
+            .path()
+            .unwrap_or_default()
+            .starts_with("and[0] > and[0] > and[0]"));
+        assert!(err
+            .path()
+            .unwrap_or_default()
+            .ends_with("and[0] > equal > operand[0]"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_values() -> anyhow::Result<()> {
+        let input = name("foo").in_values(["a", "b"])?;
+        assert_eq!(input, name("foo").r#in(vec![value("a"), value("b")]));
+
+        let input = size(name("foo")).in_values(vec![1, 2, 3])?;
+        assert_eq!(
+            input,
+            size(name("foo")).r#in(vec![value(1), value(2), value(3)])
+        );
+
+        assert_eq!(
+            name("foo").in_values(Vec::<i64>::new()).unwrap_err(),
+            error::ExpressionError::UnsetParameterError("inValues".to_owned(), "values".to_owned())
+        );
+        assert_eq!(
+            name("foo")
+                .in_values(0..=MAX_IN_OPERANDS as i64)
+                .unwrap_err(),
+            error::ExpressionError::LimitExceededError(
+                "inValues".to_owned(),
+                ExpressionLimit::InOperands,
+                MAX_IN_OPERANDS + 1
+            )
+        );
+        assert!(name("foo").in_values(1..=MAX_IN_OPERANDS as i64).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_values_chunked() -> anyhow::Result<()> {
+        let input = name("foo").in_values_chunked(0..MAX_IN_OPERANDS as i64)?;
+        assert_eq!(input, name("foo").in_values(0..MAX_IN_OPERANDS as i64)?);
+
+        let input = name("foo").in_values_chunked(0..2 * MAX_IN_OPERANDS as i64 + 1)?;
+        assert_eq!(
+            input,
+            or_all_with(
+                vec![
+                    name("foo").in_values(0..MAX_IN_OPERANDS as i64)?,
+                    name("foo").in_values(MAX_IN_OPERANDS as i64..2 * MAX_IN_OPERANDS as i64)?,
+                    name("foo").in_values([2 * MAX_IN_OPERANDS as i64])?,
+                ],
+                OrFanGuard::new(3)
+            )?
+        );
+        assert_eq!(input.build_tree()?.fmt_expression, "($c) OR ($c) OR ($c)");
+
+        assert_eq!(
+            name("foo")
+                .in_values_chunked(Vec::<i64>::new())
+                .unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "inValuesChunked".to_owned(),
+                "values".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn begins_with_operand() -> anyhow::Result<()> {
+        let prefix = String::from("ba");
+        assert_eq!(
+            name("foo").begins_with(&prefix),
+            name("foo").begins_with("ba")
+        );
+        assert_eq!(
+            name("foo").begins_with(prefix.as_str()),
+            begins_with(name("foo"), value("ba".to_owned()))
+        );
+
+        let input = name("foo").begins_with(name("bar"));
+        let node = input.build_tree()?;
+        assert_eq!(node.fmt_expression, "begins_with ($c, $c)");
+        assert_eq!(
+            node.children[1],
+            name("bar").build_operand()?.expression_node
+        );
+
+        let expr = Builder::new().with_condition(input).build()?;
+        assert_eq!(expr.condition().unwrap(), "begins_with (#0, #1)");
+        assert_eq!(expr.values(), &None);
+
+        let input = Builder::new().with_condition(
+            name("foo")
+                .begins_with(name("bar"))
+                .and(name("baz").begins_with("qux")),
+        );
+        assert_eq!(
+            input.record().script(),
+            "use dynamodb_expression::*;\n\
+             \n\
+             let builder = Builder::new()\n    \
+                 .with_condition(and(begins_with(name(\"foo\"), name(\"bar\")), begins_with(name(\"baz\"), \"s0\")));\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn negated_functions() -> anyhow::Result<()> {
+        assert_eq!(
+            name("foo").not_contains("bar"),
+            not(contains(name("foo"), "bar"))
+        );
+        assert_eq!(
+            name("foo").not_begins_with(name("bar")),
+            not(begins_with(name("foo"), name("bar")))
+        );
+
+        assert_eq!(
+            name("foo").not_exists_or_equal(value(5)),
+            or(
+                attribute_not_exists(name("foo")),
+                equal(name("foo"), value(5))
+            )
+        );
+        assert_eq!(
+            name("foo").not_exists_or(name("bar").less_than(value(5))),
+            or(
+                attribute_not_exists(name("foo")),
+                less_than(name("bar"), value(5))
+            )
+        );
+
+        let input = name("foo").not_exists_or_equal(value(5));
+        assert_eq!(
+            input.build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    ExpressionNode::from_children_expression(
+                        vec![ExpressionNode::from_names(vec!["foo".to_owned()], "$n")],
+                        "attribute_not_exists ($c)"
+                    ),
+                    ExpressionNode::from_children_expression(
+                        vec![
+                            ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                            ExpressionNode::from_values(
+                                vec![AttributeValue::N("5".to_owned())],
+                                "$v"
+                            )
+                        ],
+                        "$c = $c"
+                    ),
+                ],
+                "($c) OR ($c)"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_not_null() -> anyhow::Result<()> {
+        let input = name("foo").is_not_null();
+
+        assert_eq!(
+            input.build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    ExpressionNode::from_children_expression(
+                        vec![ExpressionNode::from_names(vec!["foo".to_owned()], "$n")],
+                        "attribute_exists ($c)"
+                    ),
+                    ExpressionNode::from_children_expression(
+                        vec![ExpressionNode::from_children_expression(
+                            vec![
+                                ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                                ExpressionNode::from_values(
+                                    vec![AttributeValue::S("NULL".to_owned())],
+                                    "$v"
+                                )
+                            ],
+                            "attribute_type ($c, $c)"
+                        )],
+                        "NOT ($c)"
+                    ),
+                ],
+                "($c) AND ($c)"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_type_or_missing() -> anyhow::Result<()> {
+        let input = name("foo").is_type_or_missing(DynamoDbAttributeType::Map);
+
+        assert_eq!(
+            input,
+            attribute_not_exists(name("foo"))
+                .or(attribute_type(name("foo"), DynamoDbAttributeType::Map))
+        );
+        assert_eq!(
+            name("foo").is_type_or_missing(DynamoDbAttributeType::Null),
+            name("foo").is_missing_or_null()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn any_of() -> anyhow::Result<()> {
+        let input = name("foo").any_of([1, 2, 3])?;
+        assert_eq!(input.build_tree()?.fmt_expression, "($c) OR ($c) OR ($c)");
+        assert_eq!(
+            input,
+            condition::or_all(vec![
+                name("foo").equal(value(1)),
+                name("foo").equal(value(2)),
+                name("foo").equal(value(3)),
+            ])?
+        );
+
+        assert_eq!(name("foo").any_of(["a"])?, name("foo").equal(value("a")));
+        assert_eq!(
+            name("foo").any_of(Vec::<i64>::new()).unwrap_err(),
+            error::ExpressionError::UnsetParameterError("anyOf".to_owned(), "values".to_owned())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn all_of() -> anyhow::Result<()> {
+        let input = name("foo").all_of(["a", "b", "c"])?;
+        assert_eq!(input.build_tree()?.fmt_expression, "($c) AND ($c) AND ($c)");
+        assert_eq!(
+            input.condition_list,
+            vec![
+                name("foo").contains("a"),
+                name("foo").contains("b"),
+                name("foo").contains("c"),
+            ]
+        );
+
+        assert_eq!(name("foo").all_of(["a"])?, name("foo").contains("a"));
+        assert_eq!(
+            name("foo").all_of(Vec::<String>::new()).unwrap_err(),
+            error::ExpressionError::UnsetParameterError("allOf".to_owned(), "values".to_owned())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_condition_try_from() -> anyhow::Result<()> {
+        assert_eq!(
+            KeyConditionBuilder::try_from(
+                name("pk")
+                    .equal(value("a"))
+                    .and(name("sk").between(value(1), value(5)))
+            )?
+            .build_tree()?,
+            key("pk")
+                .equal(value("a"))
+                .and(key("sk").between(value(1), value(5)))
+                .build_tree()?
+        );
+        assert_eq!(
+            KeyConditionBuilder::try_from(name("pk").greater_than_equal(value(5)))?.build_tree()?,
+            key("pk").greater_than_equal(value(5)).build_tree()?
+        );
+
+        for (input, reason) in [
+            (
+                name("pk").equal(value("a")).or(name("sk").equal(value(1))),
+                "or is not supported in key conditions",
+            ),
+            (
+                name("sk")
+                    .less_than(value(1))
+                    .and(name("pk").equal(value("a"))),
+                "first key condition must be an equality condition",
+            ),
+            (
+                name("pk")
+                    .equal(value("a"))
+                    .and(name("sk").equal(value(1)))
+                    .and(name("c").equal(value(2))),
+                "more than two key conditions are not supported",
+            ),
+            (
+                name("pk").all_of(["a", "b", "c"])?,
+                "more than two key conditions are not supported",
+            ),
+            (
+                name("pk").attribute_exists(),
+                "attr_exists is not supported in key conditions",
+            ),
+            (
+                name("pk").contains("a"),
+                "contains is not supported in key conditions",
+            ),
+            (
+                name("a.b").equal(value(1)),
+                "the first operand of a key condition must be a top-level attribute",
+            ),
+            (
+                name("pk").equal(name("sk")),
+                "key attributes can only be compared to values",
+            ),
+            (
+                begins_with(name("pk"), "a")
+                    .and(name("sk").equal(value(1)))
+                    .not(),
+                "not is not supported in key conditions",
+            ),
+        ] {
+            assert_eq!(
+                KeyConditionBuilder::try_from(input).unwrap_err(),
+                error::ExpressionError::InvalidKeyCondition {
+                    reason: reason.to_owned()
+                }
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn inspect_condition() -> anyhow::Result<()> {
+        let input = name("foo")
+            .equal(value(5))
+            .and(not(name("bar").between(value(1), value(2))));
+
+        assert_eq!(input.kind(), ConditionMode::And);
+        assert!(input.operands().is_empty());
+        assert_eq!(input.children().len(), 2);
+
+        let left = &input.children()[0];
+        assert_eq!(left.kind(), ConditionMode::Equal);
+        assert!(left.children().is_empty());
+        assert_eq!(
+            left.operands()[0]
+                .as_any()
+                .downcast_ref::<NameBuilder>()
+                .map(NameBuilder::attribute_path)
+                .transpose()?,
+            Some(vec!["foo".to_owned()])
+        );
+        assert_eq!(
+            left.operands()[1].build_operand()?.expression_node.values,
+            [AttributeValue::N("5".to_owned())]
+        );
+
+        let right = &input.children()[1];
+        assert_eq!(right.kind(), ConditionMode::Not);
+        assert_eq!(right.children()[0].kind(), ConditionMode::Between);
+        assert_eq!(right.children()[0].operands().len(), 3);
+        assert_eq!(ConditionBuilder::default().kind(), ConditionMode::Unset);
+
+        Ok(())
+    }
 }
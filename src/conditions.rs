@@ -0,0 +1,116 @@
+//! Conditions of writes that depend on whether the item exists
+//!
+//! DynamoDB has no separate create and update operations: a PutItem replaces
+//! any existing item and an UpdateItem creates a missing one. The conditions
+//! of this module restrict a write to one of these cases, by checking the key
+//! attributes of the item, which every existing item has.
+
+use crate::{
+    attribute_exists, attribute_not_exists, error::ExpressionError, name_literal, ConditionBuilder,
+    NameBuilder,
+};
+
+/// Returns a ConditionBuilder for a write that only succeeds if no item with
+/// the same key exists, with `attribute_not_exists (#k)` for each of the
+/// argument key attribute names.
+///
+/// This is the condition of a "create only" PutItem. The names are taken
+/// literally, as key attributes are top-level attributes. If no name is
+/// given, ensure_not_exists() returns the typed error UnsetParameterError.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let condition = conditions::ensure_not_exists(["pk", "sk"]).unwrap();
+///
+/// let expr = Builder::new().with_condition(condition).build().unwrap();
+/// assert_eq!(
+///     expr.condition().unwrap(),
+///     "(attribute_not_exists (#0)) AND (attribute_not_exists (#1))"
+/// );
+/// ```
+pub fn ensure_not_exists(
+    key: impl IntoIterator<Item = impl Into<String>>,
+) -> Result<ConditionBuilder, ExpressionError> {
+    key_condition(key, attribute_not_exists, "ensureNotExists")
+}
+
+/// Returns a ConditionBuilder for a write that only succeeds if an item with
+/// the same key exists, with `attribute_exists (#k)` for each of the argument
+/// key attribute names.
+///
+/// This is the condition of an "update only" UpdateItem, which would
+/// otherwise create the item. If no name is given, ensure_exists() returns
+/// the typed error UnsetParameterError.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let condition = conditions::ensure_exists(["pk"]).unwrap();
+///
+/// let expr = Builder::new().with_condition(condition).build().unwrap();
+/// assert_eq!(expr.condition().unwrap(), "attribute_exists (#0)");
+/// assert_eq!(expr.names().as_ref().unwrap()["#0"], "pk");
+/// ```
+pub fn ensure_exists(
+    key: impl IntoIterator<Item = impl Into<String>>,
+) -> Result<ConditionBuilder, ExpressionError> {
+    key_condition(key, attribute_exists, "ensureExists")
+}
+
+fn key_condition(
+    key: impl IntoIterator<Item = impl Into<String>>,
+    condition: fn(Box<NameBuilder>) -> ConditionBuilder,
+    function_name: &str,
+) -> Result<ConditionBuilder, ExpressionError> {
+    key.into_iter()
+        .map(|key_name| condition(name_literal(key_name)))
+        .reduce(ConditionBuilder::and)
+        .ok_or_else(|| {
+            ExpressionError::UnsetParameterError(function_name.to_owned(), "key".to_owned())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn ensure_item() -> anyhow::Result<()> {
+        assert_eq!(
+            conditions::ensure_not_exists(["pk", "sk"])?.build_tree()?,
+            upsert::if_missing([name("pk"), name("sk")])?.build_tree()?
+        );
+        assert_eq!(
+            conditions::ensure_exists(vec!["pk".to_owned(), "sk".to_owned()])?.build_tree()?,
+            name("pk")
+                .attribute_exists()
+                .and(name("sk").attribute_exists())
+                .build_tree()?
+        );
+        assert_eq!(
+            conditions::ensure_exists(["a.b"])?.build_tree()?,
+            name_literal("a.b").attribute_exists().build_tree()?
+        );
+        assert_eq!(
+            conditions::ensure_not_exists(Vec::<String>::new()).unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "ensureNotExists".to_owned(),
+                "key".to_owned()
+            )
+        );
+        assert_eq!(
+            conditions::ensure_exists(Vec::<String>::new()).unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "ensureExists".to_owned(),
+                "key".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+}
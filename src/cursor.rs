@@ -0,0 +1,371 @@
+//! Cursor pagination helpers
+//!
+//! A Cursor wraps the LastEvaluatedKey returned by a Query or Scan so that it
+//! can be handed to a client as an opaque string and later turned back into an
+//! ExclusiveStartKey.
+
+use std::collections::{BTreeSet, HashMap};
+
+use aws_sdk_dynamodb::primitives::Blob;
+use aws_sdk_dynamodb::types::AttributeValue;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde_json::{Map, Value};
+
+use crate::{error::ExpressionError, ExpressionNode, KeyConditionBuilder, TreeBuilder};
+
+/// Represents a pagination cursor built from the LastEvaluatedKey of a Query or
+/// Scan.
+///
+/// Cursors encode to an opaque, URL safe string with encode() and decode back
+/// with decode(). Before a decoded Cursor is used as an ExclusiveStartKey it
+/// should be checked with validate(), which verifies that the key attributes
+/// in the Cursor are exactly the ones the Query expects. This catches the
+/// classic bug of reusing a cursor that was produced by a different table or
+/// index.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use dynamodb_expression::*;
+///
+/// let last_evaluated_key = HashMap::from([
+///     ("Artist".to_owned(), AttributeValue::S("No One You Know".to_owned())),
+///     ("SongTitle".to_owned(), AttributeValue::S("Call Me Today".to_owned())),
+/// ]);
+///
+/// // hand the encoded cursor to the client
+/// let encoded = Cursor::new(last_evaluated_key).encode().unwrap();
+///
+/// // and validate it when the client passes it back for the next page
+/// let key_cond = key("Artist").equal(value("No One You Know"));
+/// let cursor = Cursor::decode(&encoded).unwrap();
+/// cursor.validate(&key_cond, ["Artist", "SongTitle"]).unwrap();
+///
+/// let exclusive_start_key = cursor.into_exclusive_start_key();
+/// ```
+#[derive(Default, Debug, PartialEq, Clone)]
+pub struct Cursor {
+    key: HashMap<String, AttributeValue>,
+}
+
+impl Cursor {
+    /// Returns a Cursor wrapping the given LastEvaluatedKey.
+    pub fn new(last_evaluated_key: HashMap<String, AttributeValue>) -> Self {
+        Self {
+            key: last_evaluated_key,
+        }
+    }
+
+    /// Returns a Cursor wrapping the given LastEvaluatedKey, or None if there
+    /// are no more pages.
+    pub fn from_last_evaluated_key(
+        last_evaluated_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Option<Self> {
+        last_evaluated_key
+            .filter(|key| !key.is_empty())
+            .map(Cursor::new)
+    }
+
+    /// Returns the key attributes held by the Cursor.
+    pub fn key(&self) -> &HashMap<String, AttributeValue> {
+        &self.key
+    }
+
+    /// Consumes the Cursor, returning the key to use as the ExclusiveStartKey.
+    pub fn into_exclusive_start_key(self) -> HashMap<String, AttributeValue> {
+        self.key
+    }
+
+    /// Encodes the Cursor as an opaque, URL safe string.
+    pub fn encode(&self) -> Result<String, ExpressionError> {
+        let mut object = Map::new();
+        for (name, value) in self.key.iter() {
+            object.insert(name.clone(), to_json(value)?);
+        }
+
+        let json = serde_json::to_vec(&Value::Object(object)).map_err(|_| {
+            ExpressionError::InvalidParameterError("encodeCursor".to_owned(), "Cursor".to_owned())
+        })?;
+        Ok(URL_SAFE_NO_PAD.encode(json))
+    }
+
+    /// Decodes a Cursor previously encoded with encode().
+    pub fn decode(encoded: impl AsRef<str>) -> Result<Self, ExpressionError> {
+        let json = URL_SAFE_NO_PAD
+            .decode(encoded.as_ref())
+            .map_err(|_| invalid_cursor())?;
+
+        let object = match serde_json::from_slice(&json).map_err(|_| invalid_cursor())? {
+            Value::Object(object) => object,
+            _ => return Err(invalid_cursor()),
+        };
+
+        let mut key = HashMap::new();
+        for (name, value) in object.iter() {
+            key.insert(name.clone(), from_json(value)?);
+        }
+
+        Ok(Self::new(key))
+    }
+
+    /// Verifies that the Cursor holds exactly the expected key attributes.
+    ///
+    /// The expected key attributes are every key referenced by the argument
+    /// KeyConditionBuilder together with the argument table keys. For a Query
+    /// against an index the table keys must include both the primary key of
+    /// the table and the keys of the index, since DynamoDB returns all of them
+    /// in the LastEvaluatedKey.
+    pub fn validate(
+        &self,
+        key_condition: &KeyConditionBuilder,
+        table_keys: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<(), ExpressionError> {
+        let mut expected = table_keys
+            .into_iter()
+            .map(Into::into)
+            .collect::<BTreeSet<_>>();
+        collect_names(&key_condition.build_tree()?, &mut expected);
+
+        self.validate_key_names(expected)
+    }
+
+    /// Verifies that the Cursor holds exactly the argument key attributes.
+    pub fn validate_key_names(
+        &self,
+        key_names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<(), ExpressionError> {
+        let expected = key_names
+            .into_iter()
+            .map(Into::into)
+            .collect::<BTreeSet<_>>();
+        let actual = self.key.keys().cloned().collect::<BTreeSet<_>>();
+
+        if expected != actual {
+            return Err(ExpressionError::InvalidParameterError(
+                "validateCursor".to_owned(),
+                "Cursor".to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_cursor() -> ExpressionError {
+    ExpressionError::InvalidParameterError("decodeCursor".to_owned(), "Cursor".to_owned())
+}
+
+fn collect_names(node: &ExpressionNode, names: &mut BTreeSet<String>) {
+    names.extend(node.names.iter().cloned());
+    for child in node.children.iter() {
+        collect_names(child, names);
+    }
+}
+
+fn to_json(value: &AttributeValue) -> Result<Value, ExpressionError> {
+    let (tag, value) = match value {
+        AttributeValue::S(s) => ("S", Value::from(s.clone())),
+        AttributeValue::N(n) => ("N", Value::from(n.clone())),
+        AttributeValue::B(b) => ("B", Value::from(URL_SAFE_NO_PAD.encode(b.as_ref()))),
+        AttributeValue::Ss(ss) => ("SS", Value::from(ss.clone())),
+        AttributeValue::Ns(ns) => ("NS", Value::from(ns.clone())),
+        AttributeValue::Bs(bs) => (
+            "BS",
+            Value::from(
+                bs.iter()
+                    .map(|b| URL_SAFE_NO_PAD.encode(b.as_ref()))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        AttributeValue::Bool(b) => ("BOOL", Value::from(*b)),
+        AttributeValue::Null(n) => ("NULL", Value::from(*n)),
+        AttributeValue::L(l) => (
+            "L",
+            Value::Array(
+                l.iter()
+                    .map(to_json)
+                    .collect::<Result<_, ExpressionError>>()?,
+            ),
+        ),
+        AttributeValue::M(m) => {
+            let mut object = Map::new();
+            for (name, value) in m.iter() {
+                object.insert(name.clone(), to_json(value)?);
+            }
+            ("M", Value::Object(object))
+        }
+        _ => {
+            return Err(ExpressionError::InvalidParameterError(
+                "encodeCursor".to_owned(),
+                "AttributeValue".to_owned(),
+            ))
+        }
+    };
+
+    let mut object = Map::new();
+    object.insert(tag.to_owned(), value);
+    Ok(Value::Object(object))
+}
+
+fn from_json(value: &Value) -> Result<AttributeValue, ExpressionError> {
+    // every value is an object with exactly one type tag
+    let (tag, value) = match value.as_object() {
+        Some(object) if object.len() == 1 => object.iter().next().ok_or_else(invalid_cursor)?,
+        _ => return Err(invalid_cursor()),
+    };
+
+    let string = |value: &Value| value.as_str().map(str::to_owned).ok_or_else(invalid_cursor);
+    let strings = |value: &Value| -> Result<Vec<String>, ExpressionError> {
+        match value.as_array() {
+            Some(array) => array.iter().map(string).collect(),
+            None => Err(invalid_cursor()),
+        }
+    };
+    let blob = |value: &Value| -> Result<Blob, ExpressionError> {
+        let encoded = string(value)?;
+        let decoded = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|_| invalid_cursor())?;
+        Ok(Blob::new(decoded))
+    };
+
+    Ok(match tag.as_str() {
+        "S" => AttributeValue::S(string(value)?),
+        "N" => AttributeValue::N(string(value)?),
+        "B" => AttributeValue::B(blob(value)?),
+        "SS" => AttributeValue::Ss(strings(value)?),
+        "NS" => AttributeValue::Ns(strings(value)?),
+        "BS" => AttributeValue::Bs(match value.as_array() {
+            Some(array) => array
+                .iter()
+                .map(blob)
+                .collect::<Result<_, ExpressionError>>()?,
+            None => return Err(invalid_cursor()),
+        }),
+        "BOOL" => AttributeValue::Bool(value.as_bool().ok_or_else(invalid_cursor)?),
+        "NULL" => AttributeValue::Null(value.as_bool().ok_or_else(invalid_cursor)?),
+        "L" => AttributeValue::L(match value.as_array() {
+            Some(array) => array
+                .iter()
+                .map(from_json)
+                .collect::<Result<_, ExpressionError>>()?,
+            None => return Err(invalid_cursor()),
+        }),
+        "M" => {
+            let mut map = HashMap::new();
+            for (name, value) in value.as_object().ok_or_else(invalid_cursor)?.iter() {
+                map.insert(name.clone(), from_json(value)?);
+            }
+            AttributeValue::M(map)
+        }
+        _ => return Err(invalid_cursor()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::primitives::Blob;
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    use crate::*;
+
+    fn last_evaluated_key() -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            ("pk".to_owned(), AttributeValue::S("foo".to_owned())),
+            ("sk".to_owned(), AttributeValue::N("5".to_owned())),
+        ])
+    }
+
+    #[test]
+    fn round_trip() -> anyhow::Result<()> {
+        let input = Cursor::new(HashMap::from([
+            ("s".to_owned(), AttributeValue::S("foo".to_owned())),
+            ("n".to_owned(), AttributeValue::N("5".to_owned())),
+            ("b".to_owned(), AttributeValue::B(Blob::new(vec![0, 1, 2]))),
+            (
+                "ss".to_owned(),
+                AttributeValue::Ss(vec!["a".to_owned(), "b".to_owned()]),
+            ),
+            (
+                "bs".to_owned(),
+                AttributeValue::Bs(vec![Blob::new(vec![3])]),
+            ),
+            ("bool".to_owned(), AttributeValue::Bool(true)),
+            ("null".to_owned(), AttributeValue::Null(true)),
+            (
+                "l".to_owned(),
+                AttributeValue::L(vec![AttributeValue::N("1".to_owned())]),
+            ),
+            (
+                "m".to_owned(),
+                AttributeValue::M(HashMap::from([(
+                    "nested".to_owned(),
+                    AttributeValue::S("bar".to_owned()),
+                )])),
+            ),
+        ]));
+
+        assert_eq!(Cursor::decode(input.encode()?)?, input);
+
+        Ok(())
+    }
+
+    #[test]
+    fn no_more_pages() -> anyhow::Result<()> {
+        assert_eq!(Cursor::from_last_evaluated_key(None), None);
+        assert_eq!(Cursor::from_last_evaluated_key(Some(HashMap::new())), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decode_garbage() -> anyhow::Result<()> {
+        for input in ["!!!", "bm90IGpzb24", "W10"] {
+            assert_eq!(
+                Cursor::decode(input).unwrap_err(),
+                error::ExpressionError::InvalidParameterError(
+                    "decodeCursor".to_owned(),
+                    "Cursor".to_owned()
+                )
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_matching_keys() -> anyhow::Result<()> {
+        let input = Cursor::new(last_evaluated_key());
+
+        input.validate(&key("pk").equal(value("foo")), ["sk"])?;
+        input.validate(
+            &key("pk")
+                .equal(value("foo"))
+                .and(key("sk").greater_than(value(1))),
+            Vec::<String>::new(),
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_different_index() -> anyhow::Result<()> {
+        let input = Cursor::new(last_evaluated_key());
+
+        assert_eq!(
+            input
+                .validate(&key("gsi_pk").equal(value("foo")), ["pk", "sk"])
+                .unwrap_err(),
+            error::ExpressionError::InvalidParameterError(
+                "validateCursor".to_owned(),
+                "Cursor".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+//! UpdateBuilders generated from the difference between two items
+
+use std::collections::{BTreeSet, HashMap};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{path, value, Path, UpdateBuilder, MAX_PATH_DEPTH};
+
+/// Returns an UpdateBuilder that turns the old item into the new item.
+///
+/// Attributes that were added or whose value changed are SET, and attributes
+/// that are missing from the new item are REMOVEd. Changed map attributes are
+/// SET as a whole, use diff_nested() to update only the changed entries. The
+/// elements of string, number and binary sets are compared regardless of
+/// their order. The operations are added in attribute name order, and if the
+/// items are the same the returned UpdateBuilder is empty.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use dynamodb_expression::*;
+///
+/// let old = HashMap::from([
+///     ("name".to_owned(), AttributeValue::S("ace".to_owned())),
+///     ("age".to_owned(), AttributeValue::N("30".to_owned())),
+/// ]);
+/// let new = HashMap::from([
+///     ("name".to_owned(), AttributeValue::S("ace".to_owned())),
+///     ("level".to_owned(), AttributeValue::N("2".to_owned())),
+/// ]);
+///
+/// let expr = Builder::new().with_update(diff(&old, &new)).build().unwrap();
+/// assert_eq!(expr.update().unwrap(), "SET #0 = :0\nREMOVE #1\n");
+/// assert_eq!(expr.names().as_ref().unwrap()["#1"], "age");
+/// ```
+pub fn diff(
+    old: &HashMap<String, AttributeValue>,
+    new: &HashMap<String, AttributeValue>,
+) -> UpdateBuilder {
+    diff_maps(UpdateBuilder::default(), None, old, new, false)
+}
+
+/// Returns an UpdateBuilder that turns the old item into the new item, as
+/// diff() does, descending into map attributes present in both items.
+///
+/// Only the changed entries of such maps are SET or REMOVEd, using document
+/// paths, so concurrent updates to other entries of the same map are kept.
+/// Maps nested MAX_PATH_DEPTH levels deep are SET as a whole.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use dynamodb_expression::*;
+///
+/// let settings = |theme: &str| {
+///     AttributeValue::M(HashMap::from([
+///         ("theme".to_owned(), AttributeValue::S(theme.to_owned())),
+///         ("lang".to_owned(), AttributeValue::S("en".to_owned())),
+///     ]))
+/// };
+/// let old = HashMap::from([("settings".to_owned(), settings("light"))]);
+/// let new = HashMap::from([("settings".to_owned(), settings("dark"))]);
+///
+/// let expr = Builder::new()
+///     .with_update(diff_nested(&old, &new))
+///     .build()
+///     .unwrap();
+/// assert_eq!(expr.update().unwrap(), "SET #0.#1 = :0\n");
+/// ```
+pub fn diff_nested(
+    old: &HashMap<String, AttributeValue>,
+    new: &HashMap<String, AttributeValue>,
+) -> UpdateBuilder {
+    diff_maps(UpdateBuilder::default(), None, old, new, true)
+}
+
+fn diff_maps(
+    mut update: UpdateBuilder,
+    parent: Option<&Path>,
+    old: &HashMap<String, AttributeValue>,
+    new: &HashMap<String, AttributeValue>,
+    nested: bool,
+) -> UpdateBuilder {
+    let attributes = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+
+    for attribute in attributes {
+        let attribute_path = match parent {
+            Some(parent) => parent.clone().field(attribute),
+            None => path(attribute),
+        };
+
+        update = match (old.get(attribute), new.get(attribute)) {
+            (Some(_), None) => update.remove(attribute_path.into_name()),
+            (None, Some(new_value)) => {
+                update.set(attribute_path.into_name(), value(new_value.clone()))
+            }
+            (Some(AttributeValue::M(old_map)), Some(AttributeValue::M(new_map)))
+                if nested && attribute_path.elements().len() < MAX_PATH_DEPTH =>
+            {
+                diff_maps(update, Some(&attribute_path), old_map, new_map, nested)
+            }
+            (Some(old_value), Some(new_value)) if !same_value(old_value, new_value) => {
+                update.set(attribute_path.into_name(), value(new_value.clone()))
+            }
+            _ => update,
+        };
+    }
+
+    update
+}
+
+// compares attribute values, ignoring the order of set elements
+fn same_value(a: &AttributeValue, b: &AttributeValue) -> bool {
+    fn same_set<T: Ord>(a: &[T], b: &[T]) -> bool {
+        a.len() == b.len() && a.iter().collect::<BTreeSet<_>>() == b.iter().collect::<BTreeSet<_>>()
+    }
+
+    match (a, b) {
+        (AttributeValue::Ss(a), AttributeValue::Ss(b))
+        | (AttributeValue::Ns(a), AttributeValue::Ns(b)) => same_set(a, b),
+        (AttributeValue::Bs(a), AttributeValue::Bs(b)) => same_set(
+            &a.iter().map(|b| b.as_ref()).collect::<Vec<_>>(),
+            &b.iter().map(|b| b.as_ref()).collect::<Vec<_>>(),
+        ),
+        (AttributeValue::L(a), AttributeValue::L(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_value(a, b))
+        }
+        (AttributeValue::M(a), AttributeValue::M(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, a)| b.get(k).is_some_and(|b| same_value(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    use crate::*;
+
+    fn s(s: &str) -> AttributeValue {
+        AttributeValue::S(s.to_owned())
+    }
+
+    fn item(attributes: &[(&str, AttributeValue)]) -> HashMap<String, AttributeValue> {
+        attributes
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn diff_items() -> anyhow::Result<()> {
+        let old = item(&[
+            ("a", s("1")),
+            ("b", s("2")),
+            (
+                "c",
+                AttributeValue::Ss(vec!["x".to_owned(), "y".to_owned()]),
+            ),
+            ("d.e", AttributeValue::M(item(&[("f", s("1"))]))),
+        ]);
+        let new = item(&[
+            ("a", s("1")),
+            (
+                "c",
+                AttributeValue::Ss(vec!["y".to_owned(), "x".to_owned()]),
+            ),
+            ("d.e", AttributeValue::M(item(&[("f", s("2"))]))),
+            ("g", s("3")),
+        ]);
+
+        assert_eq!(
+            diff(&old, &new),
+            remove(name_literal("b"))
+                .set(
+                    name_literal("d.e"),
+                    value(AttributeValue::M(item(&[("f", s("2"))])))
+                )
+                .set(name_literal("g"), value(s("3")))
+        );
+        assert_eq!(diff(&old, &old), UpdateBuilder::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_nested_items() -> anyhow::Result<()> {
+        let old = item(&[(
+            "m",
+            AttributeValue::M(item(&[
+                ("a", s("1")),
+                ("b", s("2")),
+                ("n", AttributeValue::M(item(&[("c", s("3"))]))),
+            ])),
+        )]);
+        let new = item(&[(
+            "m",
+            AttributeValue::M(item(&[
+                ("a", s("1")),
+                ("n", AttributeValue::M(item(&[("c", s("4"))]))),
+                ("x.y", s("5")),
+            ])),
+        )]);
+
+        assert_eq!(
+            diff_nested(&old, &new),
+            remove(path("m").field("b").into_name())
+                .set(path("m").field("n").field("c").into_name(), value(s("4")))
+                .set(path("m").field("x.y").into_name(), value(s("5")))
+        );
+
+        let expr = Builder::new()
+            .with_update(diff_nested(&old, &new))
+            .build()?;
+        assert_eq!(
+            expr.update(),
+            Some("SET #0.#1.#2 = :0, #0.#3 = :1\nREMOVE #0.#4\n")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_nested_depth() -> anyhow::Result<()> {
+        let deep = |leaf: &str| {
+            (0..MAX_PATH_DEPTH).fold(s(leaf), |inner, _| AttributeValue::M(item(&[("m", inner)])))
+        };
+        let old = item(&[("m", deep("a"))]);
+        let new = item(&[("m", deep("b"))]);
+
+        let input = diff_nested(&old, &new);
+        assert!(Builder::new().with_update(input).build().is_ok());
+
+        Ok(())
+    }
+}
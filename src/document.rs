@@ -0,0 +1,137 @@
+//! Values from aws-smithy-types Documents
+//!
+//! Smithy-generated clients represent loosely-typed JSON-like data as a
+//! Document. The conversions of this module turn a Document into a
+//! DynamoValue, so that it can be stored or compared with an operand.
+
+use aws_smithy_types::{Document, Number};
+
+use crate::{error::ExpressionError, value, DynamoValue, ValueBuilder};
+
+impl TryFrom<Document> for DynamoValue {
+    type Error = ExpressionError;
+
+    /// Converts the Document into a DynamoValue: objects become maps, arrays
+    /// become lists, and the other variants the matching scalar types.
+    ///
+    /// Documents with a NaN or infinite number, which DynamoDB can't store,
+    /// return the typed error InvalidParameterError.
+    fn try_from(document: Document) -> Result<Self, Self::Error> {
+        Ok(match document {
+            Document::Object(object) => DynamoValue::M(
+                object
+                    .into_iter()
+                    .map(|(k, v)| Ok((k, v.try_into()?)))
+                    .collect::<Result<_, ExpressionError>>()?,
+            ),
+            Document::Array(array) => DynamoValue::L(
+                array
+                    .into_iter()
+                    .map(DynamoValue::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Document::Number(Number::PosInt(n)) => DynamoValue::N(n.to_string()),
+            Document::Number(Number::NegInt(n)) => DynamoValue::N(n.to_string()),
+            Document::Number(Number::Float(n)) => {
+                if !n.is_finite() {
+                    return Err(ExpressionError::InvalidParameterError(
+                        "documentValue".to_owned(),
+                        "document".to_owned(),
+                    ));
+                }
+                DynamoValue::N(n.to_string())
+            }
+            Document::String(s) => DynamoValue::S(s),
+            Document::Bool(b) => DynamoValue::Bool(b),
+            Document::Null => DynamoValue::Null,
+        })
+    }
+}
+
+/// Returns a ValueBuilder for the value of the argument Document, see
+/// DynamoValue::try_from().
+///
+/// # Example
+///
+/// ```
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use aws_smithy_types::{Document, Number};
+/// use dynamodb_expression::*;
+///
+/// let document = Document::Array(vec![
+///     Document::String("ace".to_owned()),
+///     Document::Number(Number::PosInt(1)),
+/// ]);
+///
+/// let expr = Builder::new()
+///     .with_update(set(name("tags"), document_value(document).unwrap()))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(
+///     expr.values().as_ref().unwrap()[":0"],
+///     AttributeValue::L(vec![
+///         AttributeValue::S("ace".to_owned()),
+///         AttributeValue::N("1".to_owned()),
+///     ])
+/// );
+/// ```
+pub fn document_value(
+    document: Document,
+) -> Result<Box<ValueBuilder<DynamoValue>>, ExpressionError> {
+    Ok(value(DynamoValue::try_from(document)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use aws_smithy_types::{Document, Number};
+
+    use crate::{error::ExpressionError, *};
+
+    #[test]
+    fn document_conversion() -> anyhow::Result<()> {
+        let input = Document::Object(HashMap::from([
+            ("s".to_owned(), Document::String("a".to_owned())),
+            ("pos".to_owned(), Document::Number(Number::PosInt(1))),
+            ("neg".to_owned(), Document::Number(Number::NegInt(-1))),
+            ("float".to_owned(), Document::Number(Number::Float(1.5))),
+            ("bool".to_owned(), Document::Bool(true)),
+            ("null".to_owned(), Document::Null),
+            (
+                "array".to_owned(),
+                Document::Array(vec![Document::String("b".to_owned())]),
+            ),
+        ]));
+
+        assert_eq!(
+            DynamoValue::try_from(input)?,
+            DynamoValue::M(BTreeMap::from([
+                ("s".to_owned(), DynamoValue::S("a".to_owned())),
+                ("pos".to_owned(), DynamoValue::N("1".to_owned())),
+                ("neg".to_owned(), DynamoValue::N("-1".to_owned())),
+                ("float".to_owned(), DynamoValue::N("1.5".to_owned())),
+                ("bool".to_owned(), DynamoValue::Bool(true)),
+                ("null".to_owned(), DynamoValue::Null),
+                (
+                    "array".to_owned(),
+                    DynamoValue::L(vec![DynamoValue::S("b".to_owned())])
+                ),
+            ]))
+        );
+
+        assert_eq!(
+            document_value(Document::Array(vec![Document::Number(Number::Float(
+                f64::NAN
+            ))]))
+            .unwrap_err(),
+            ExpressionError::InvalidParameterError(
+                "documentValue".to_owned(),
+                "document".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 //! Ported from [error.go](https://github.com/aws/aws-sdk-go/blob/master/service/dynamodb/expression/error.go)
 
+use std::panic::Location;
+
 /// Represents a DynamoDB Expression Error
-#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[derive(thiserror::Error, Debug)]
 pub enum ExpressionError {
     /// Returned if invalid parameters are encountered.
     ///
@@ -38,12 +40,402 @@ pub enum ExpressionError {
     /// ```
     #[error("{0} error: unset parameter: {1}")]
     UnsetParameterError(/*functionName*/ String, /*parameterType*/ String),
+
+    /// Returned if an expression exceeds a documented DynamoDB service limit.
+    ///
+    /// This error is returned by Builder::build_validated(), and by functions
+    /// checking a limit on their arguments such as in_values(). The error
+    /// message includes the limit that was exceeded along with the actual and
+    /// maximum allowed values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // err is of type LimitExceededError
+    /// let err = Builder::new()
+    ///     .with_condition(name("a".repeat(256)).attribute_exists())
+    ///     .build_validated()
+    ///     .err();
+    /// ```
+    #[error("{0} error: limit exceeded: {1} ({2} > {max})", max = .1.max())]
+    LimitExceededError(
+        /*functionName*/ String,
+        /*limit*/ crate::ExpressionLimit,
+        /*actual*/ usize,
+    ),
+
+    /// Returned if a value bound by an expression exceeds a documented
+    /// DynamoDB size limit.
+    ///
+    /// This error is only returned by Builder::build_validated(). Values
+    /// compared to a key in a Key Condition Expression are checked against the
+    /// key size limits, every other value against the item size limit. The
+    /// error message includes the attribute the value is compared to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // err is of type ValueSizeExceeded
+    /// let err = Builder::new()
+    ///     .with_key_condition(key("pk").equal(value("a".repeat(4096))))
+    ///     .build_validated()
+    ///     .err();
+    /// ```
+    #[error("{0} error: value of {1} exceeds {2} ({3} > {max})", max = .2.max())]
+    ValueSizeExceeded(
+        /*functionName*/ String,
+        /*attribute*/ String,
+        /*limit*/ crate::ExpressionLimit,
+        /*actual*/ usize,
+    ),
+
+    /// Returned if an expression format string references more names, values
+    /// or children than its node holds.
+    #[error("{0} error: exprNode []{1} out of range")]
+    OperandIndexOutOfRange(/*functionName*/ String, /*operandType*/ String),
+
+    /// Returned if a builder is asked to build with a mode it does not support.
+    #[error("{0} error: unsupported mode: {1}")]
+    UnsupportedMode(/*functionName*/ String, /*mode*/ String),
+
+    /// Returned if an update clause is built without any operations.
+    #[error("buildChildNodes error: operationBuilder list is empty")]
+    EmptyOperationList,
+
+    /// Returned if a KeyConditionBuilder was constructed in a way DynamoDB
+    /// does not support, such as AND-ing more than two key conditions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // err is of type InvalidKeyCondition
+    /// let key_cond = key("foo").less_than(value(5)).and(key("bar").equal(value(6)));
+    /// let err = Builder::new().with_key_condition(key_cond).build().err();
+    /// ```
+    #[error("buildKeyCondition error: {reason}")]
+    InvalidKeyCondition { reason: String },
+
+    /// Returned if the lower bound of a BETWEEN clause is greater than its
+    /// upper bound.
+    ///
+    /// DynamoDB accepts such a clause, but it never matches any item. Bounds
+    /// are only compared when both are values of the same Number, String or
+    /// Binary type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // err is of type BetweenBoundsOutOfOrder
+    /// let err = Builder::new()
+    ///     .with_condition(name("foo").between(value(10), value(5)))
+    ///     .build()
+    ///     .err();
+    /// ```
+    #[error("{0} error: BETWEEN lower bound is greater than upper bound")]
+    BetweenBoundsOutOfOrder(/*functionName*/ String),
+
+    /// Returned if or_all() is given more conditions than its guard allows.
+    ///
+    /// Large OR conditions grow the expression quickly and cost read capacity
+    /// for every item they are evaluated against. The error message includes
+    /// the number of conditions and the maximum allowed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // err is of type TooManyOrBranches
+    /// let conditions = (0..50i64).map(|id| name("Id").equal(value(id)));
+    /// let err = or_all(conditions).err();
+    /// ```
+    #[error(
+        "{0} error: OR of {1} conditions exceeds the maximum of {2}: \
+         consider an IN condition or a key design serving this access pattern"
+    )]
+    TooManyOrBranches(
+        /*functionName*/ String,
+        /*branches*/ usize,
+        /*max*/ usize,
+    ),
+
+    /// Returned if a Projection Expression projects the same document path
+    /// twice, or a path nested in another projected path, which DynamoDB
+    /// rejects as overlapping paths. ProjectionBuilder::dedup() removes them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // err is of type OverlappingPaths
+    /// let err = Builder::new()
+    ///     .with_projection(names_list(name("a"), vec![name("a.b")]))
+    ///     .build()
+    ///     .err();
+    /// ```
+    #[error("{0} error: overlapping document paths: {1} and {2}")]
+    OverlappingPaths(
+        /*functionName*/ String,
+        /*first*/ String,
+        /*second*/ String,
+    ),
+
+    /// Returned if an item is missing one of the key attributes of the key
+    /// schema it is used with.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use dynamodb_expression::*;
+    ///
+    /// // err is of type MissingKeyAttribute
+    /// let err = key_condition_from_item(&HashMap::new(), &KeySchema::new("pk")).err();
+    /// ```
+    #[error("{0} error: missing key attribute: {1}")]
+    MissingKeyAttribute(/*functionName*/ String, /*attribute*/ String),
+
+    /// Returned if a Key Condition Expression or a Filter Expression doesn't
+    /// match the KeySchema given to Builder::with_key_schema().
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // err is of type KeySchemaViolation
+    /// let err = Builder::new()
+    ///     .with_key_schema(KeySchema::new("pk"))
+    ///     .with_key_condition(key("sk").equal(value(5)))
+    ///     .build()
+    ///     .err();
+    /// ```
+    #[error("{0} error: {1}")]
+    KeySchemaViolation(/*functionName*/ String, /*reason*/ String),
+
+    /// Wraps an error returned by a nested builder with the path leading to it.
+    ///
+    /// The path lists the builders from the expression down to the one that
+    /// failed, such as "filter > and[1] > between > operand[2]".
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let filter = name("foo")
+    ///     .equal(value(5))
+    ///     .and(name("bar").between(value(1), name("")));
+    /// let err = Builder::new().with_filter(filter).build().unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     err.path().as_deref(),
+    ///     Some("filter > and[1] > between > operand[2]")
+    /// );
+    /// ```
+    #[error("{source} (at {})", .path.join(" > "))]
+    InPath {
+        path: Vec<String>,
+        source: Box<ExpressionError>,
+    },
+
+    /// Wraps an error returned by a builder with the source location where
+    /// the builder was created, such as the call to name() with an empty name.
+    ///
+    /// Locations are diagnostics only: two errors that only differ in their
+    /// locations compare equal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let err = Builder::new()
+    ///     .with_filter(name("").equal(value(5)))
+    ///     .build()
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(err.location().unwrap().file(), file!());
+    /// ```
+    #[error("{source} (created at {location})")]
+    CreatedAt {
+        location: &'static Location<'static>,
+        source: Box<ExpressionError>,
+    },
+}
+
+impl PartialEq for ExpressionError {
+    fn eq(&self, other: &Self) -> bool {
+        use ExpressionError::*;
+
+        match (self, other) {
+            (CreatedAt { source, .. }, other) => **source == *other,
+            (this, CreatedAt { source, .. }) => *this == **source,
+            (InvalidParameterError(a0, a1), InvalidParameterError(b0, b1)) => a0 == b0 && a1 == b1,
+            (UnsetParameterError(a0, a1), UnsetParameterError(b0, b1)) => a0 == b0 && a1 == b1,
+            (LimitExceededError(a0, a1, a2), LimitExceededError(b0, b1, b2)) => {
+                a0 == b0 && a1 == b1 && a2 == b2
+            }
+            (ValueSizeExceeded(a0, a1, a2, a3), ValueSizeExceeded(b0, b1, b2, b3)) => {
+                a0 == b0 && a1 == b1 && a2 == b2 && a3 == b3
+            }
+            (OperandIndexOutOfRange(a0, a1), OperandIndexOutOfRange(b0, b1)) => {
+                a0 == b0 && a1 == b1
+            }
+            (UnsupportedMode(a0, a1), UnsupportedMode(b0, b1)) => a0 == b0 && a1 == b1,
+            (EmptyOperationList, EmptyOperationList) => true,
+            (InvalidKeyCondition { reason: a }, InvalidKeyCondition { reason: b }) => a == b,
+            (BetweenBoundsOutOfOrder(a), BetweenBoundsOutOfOrder(b)) => a == b,
+            (OverlappingPaths(a0, a1, a2), OverlappingPaths(b0, b1, b2)) => {
+                a0 == b0 && a1 == b1 && a2 == b2
+            }
+            (MissingKeyAttribute(a0, a1), MissingKeyAttribute(b0, b1)) => a0 == b0 && a1 == b1,
+            (KeySchemaViolation(a0, a1), KeySchemaViolation(b0, b1)) => a0 == b0 && a1 == b1,
+            (TooManyOrBranches(a0, a1, a2), TooManyOrBranches(b0, b1, b2)) => {
+                a0 == b0 && a1 == b1 && a2 == b2
+            }
+            (
+                InPath {
+                    path: a_path,
+                    source: a_source,
+                },
+                InPath {
+                    path: b_path,
+                    source: b_source,
+                },
+            ) => a_path == b_path && a_source == b_source,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ExpressionError {}
+
+impl ExpressionError {
+    /// Returns the path to the builder that returned the error, if known.
+    pub fn path(&self) -> Option<String> {
+        match self {
+            ExpressionError::InPath { path, .. } => Some(path.join(" > ")),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying error, without any path or location information.
+    pub fn root_cause(&self) -> &ExpressionError {
+        match self {
+            ExpressionError::InPath { source, .. } | ExpressionError::CreatedAt { source, .. } => {
+                source.root_cause()
+            }
+            _ => self,
+        }
+    }
+
+    /// Returns the source location where the builder that returned the error
+    /// was created, if known.
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        match self {
+            ExpressionError::CreatedAt { location, .. } => Some(location),
+            ExpressionError::InPath { source, .. } => source.location(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn created_at(self, location: Option<&'static Location<'static>>) -> Self {
+        match location {
+            Some(location) => ExpressionError::CreatedAt {
+                location,
+                source: Box::new(self),
+            },
+            None => self,
+        }
+    }
+
+    pub(crate) fn in_path(self, segment: impl Into<String>) -> Self {
+        match self {
+            ExpressionError::InPath { mut path, source } => {
+                path.insert(0, segment.into());
+                ExpressionError::InPath { path, source }
+            }
+            _ => ExpressionError::InPath {
+                path: vec![segment.into()],
+                source: Box::new(self),
+            },
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::error::ExpressionError;
 
+    #[test]
+    fn invalid_key_condition_error() -> anyhow::Result<()> {
+        let input = ExpressionError::InvalidKeyCondition {
+            reason: "reason".to_owned(),
+        };
+
+        assert_eq!(format!("{}", input), "buildKeyCondition error: reason");
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_path_error() -> anyhow::Result<()> {
+        let input = ExpressionError::UnsetParameterError("func".to_owned(), "param".to_owned())
+            .in_path("operand[0]")
+            .in_path("equal")
+            .in_path("filter");
+
+        assert_eq!(
+            format!("{}", input),
+            "func error: unset parameter: param (at filter > equal > operand[0])"
+        );
+        assert_eq!(input.path().as_deref(), Some("filter > equal > operand[0]"));
+        assert_eq!(
+            input.root_cause(),
+            &ExpressionError::UnsetParameterError("func".to_owned(), "param".to_owned())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn created_at_error() -> anyhow::Result<()> {
+        let location = std::panic::Location::caller();
+        let input = ExpressionError::UnsetParameterError("func".to_owned(), "param".to_owned())
+            .created_at(Some(location))
+            .in_path("operand[0]");
+
+        assert_eq!(
+            format!("{}", input),
+            format!(
+                "func error: unset parameter: param (created at {}) (at operand[0])",
+                location
+            )
+        );
+        assert_eq!(input.location(), Some(location));
+        assert_eq!(
+            input,
+            ExpressionError::UnsetParameterError("func".to_owned(), "param".to_owned())
+                .in_path("operand[0]")
+        );
+        assert_eq!(
+            input.root_cause(),
+            &ExpressionError::UnsetParameterError("func".to_owned(), "param".to_owned())
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn invalid_error() -> anyhow::Result<()> {
         let input = ExpressionError::InvalidParameterError("func".to_owned(), "param".to_owned());
@@ -61,4 +453,20 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn limit_error() -> anyhow::Result<()> {
+        let input = ExpressionError::LimitExceededError(
+            "func".to_owned(),
+            crate::ExpressionLimit::InOperands,
+            101,
+        );
+
+        assert_eq!(
+            format!("{}", input),
+            "func error: limit exceeded: InOperands (101 > 100)"
+        );
+
+        Ok(())
+    }
 }
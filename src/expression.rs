@@ -1,21 +1,41 @@
 //! Ported from [expression.go](https://github.com/aws/aws-sdk-go/blob/master/service/dynamodb/expression/expression.go)
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use anyhow::bail;
 use aws_sdk_dynamodb::types::AttributeValue;
 
-use crate::{ConditionBuilder, KeyConditionBuilder, ProjectionBuilder, UpdateBuilder};
+use crate::{
+    error::ExpressionError, limits, redact::ValueRedactor, reserved, schema, ClauseOrder,
+    ConditionBuilder, FilterSource, GuardedUpdate, KeyConditionBuilder, KeyConditionSource,
+    ProjectionBuilder, ProjectionSource, Recorder, RedactionPolicy, UpdateBuilder, UpdateSource,
+};
 
 /// Specifies the type of Expression. Declaring this type is used
 /// to eliminate magic strings
-#[derive(Copy, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Debug)]
-pub(crate) enum ExpressionType {
+#[derive(Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Debug, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum ExpressionType {
+    /// Projection represents a Projection Expression
     Projection,
+
+    /// KeyCondition represents a Key Condition Expression
     KeyCondition,
+
+    /// Condition represents a Condition Expression
     Condition,
+
+    /// Filter represents a Filter Expression
     Filter,
+
+    /// Update represents an Update Expression
     Update,
+
+    /// NamedCondition represents a Condition Expression added with
+    /// Builder::with_named_condition()
+    #[strum(to_string = "named_condition[{0}]")]
+    NamedCondition(String),
 }
 
 /// Represents the struct that builds the Expression struct.
@@ -40,16 +60,25 @@ pub(crate) enum ExpressionType {
 /// let expr = builder.build().unwrap();
 ///
 /// let query = client.query()
-///     .key_condition_expression(expr.key_condition().cloned().unwrap())
-///     .projection_expression(expr.projection().cloned().unwrap())
+///     .key_condition_expression(expr.key_condition().unwrap())
+///     .projection_expression(expr.projection().unwrap())
 ///     .set_expression_attribute_names(expr.names().clone())
 ///     .set_expression_attribute_values(expr.values().clone())
 ///     .table_name("SomeTable".to_owned());
 /// # })
 /// ```
-#[derive(Default)]
+#[derive(Default, Debug, Clone)]
 pub struct Builder {
     expressions: HashMap<ExpressionType, Box<dyn TreeBuilder>>,
+    names_prefix: String,
+    values_prefix: String,
+    dedup_values: bool,
+    bare_names: bool,
+    go_sdk_aliasing: bool,
+    hash_seed: Option<u64>,
+    key_schema: Option<schema::KeySchema>,
+    index_name: Option<String>,
+    redaction_policy: Option<Arc<RedactionPolicy>>,
 }
 
 impl Builder {
@@ -73,9 +102,223 @@ impl Builder {
     pub fn new() -> Self {
         Self {
             expressions: HashMap::new(),
+            ..Default::default()
         }
     }
 
+    /// Returns a Builder with the argument expressions, each of which may be
+    /// unset.
+    ///
+    /// This is the same as calling the with_*_if() methods for each of them,
+    /// for code that has the expressions of a request as separate options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::from_parts(
+    ///     Some(key("pk").equal(value("a"))),
+    ///     None,
+    ///     Some(name("open").equal(value(true))),
+    ///     None,
+    ///     None,
+    /// )
+    /// .build()
+    /// .unwrap();
+    ///
+    /// assert_eq!(expr.key_condition().unwrap(), "#0 = :0");
+    /// assert_eq!(expr.filter().unwrap(), "#1 = :1");
+    /// ```
+    pub fn from_parts(
+        key_condition: Option<KeyConditionBuilder>,
+        condition: Option<ConditionBuilder>,
+        filter: Option<ConditionBuilder>,
+        projection: Option<ProjectionBuilder>,
+        update: Option<UpdateBuilder>,
+    ) -> Self {
+        Builder::new()
+            .with_key_condition_if(key_condition)
+            .with_condition_if(condition)
+            .with_filter_if(filter)
+            .with_projection_if(projection)
+            .with_update_if(update)
+    }
+
+    /// Sets the prefixes of the aliases generated by the Builder.
+    ///
+    /// By default, names are aliased as `#0`, `#1`, ... and values as `:0`,
+    /// `:1`, ... which collides with hand-written placeholders using the same
+    /// scheme. With prefixes, the generated aliases become `#n0` and `:v0`
+    /// instead, so the names() and values() maps can be merged with maps from
+    /// other sources. Prefixes may only contain ASCII letters, digits and
+    /// underscores, otherwise build() returns the typed error
+    /// InvalidParameterError.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let cond = name("someKey").equal(value("someValue"));
+    /// let expr = Builder::new()
+    ///     .with_condition(cond)
+    ///     .with_alias_prefix("n", "v")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(expr.condition().unwrap(), "#n0 = :v0");
+    /// ```
+    pub fn with_alias_prefix(
+        mut self,
+        names: impl Into<String>,
+        values: impl Into<String>,
+    ) -> Builder {
+        self.names_prefix = names.into();
+        self.values_prefix = values.into();
+
+        self
+    }
+
+    /// Sets whether identical values share one alias.
+    ///
+    /// By default, every value gets its own alias even if the same value is
+    /// used several times, such as a tenant id used in both the Filter and the
+    /// Condition Expression. With deduplication, values that are equal share
+    /// the alias of their first use, keeping ExpressionAttributeValues small.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::new()
+    ///     .with_condition(name("tenant").equal(value("acme")))
+    ///     .with_filter(name("owner").equal(value("acme")))
+    ///     .with_value_dedup(true)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(expr.condition().unwrap(), "#0 = :0");
+    /// assert_eq!(expr.filter().unwrap(), "#1 = :0");
+    /// assert_eq!(expr.values().as_ref().unwrap().len(), 1);
+    /// ```
+    pub fn with_value_dedup(mut self, dedup_values: bool) -> Builder {
+        self.dedup_values = dedup_values;
+
+        self
+    }
+
+    /// Sets whether attribute names are written directly in the expressions
+    /// when they don't need an alias.
+    ///
+    /// By default, every attribute name is aliased. With bare names, a name
+    /// made of an ASCII letter followed by ASCII letters, digits and
+    /// underscores is written as-is unless reserved::is_reserved() returns
+    /// true for it, which keeps the expressions short and readable when
+    /// debugging. The other names are still aliased.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::new()
+    ///     .with_condition(name("price").less_than(value(10)).and(name("status").equal(value("open"))))
+    ///     .with_bare_names(true)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(expr.condition().unwrap(), "(price < :0) AND (#0 = :1)");
+    /// assert_eq!(expr.names().as_ref().unwrap().len(), 1);
+    /// ```
+    pub fn with_bare_names(mut self, bare_names: bool) -> Builder {
+        self.bare_names = bare_names;
+
+        self
+    }
+
+    /// Sets whether the aliases are assigned the way the Go SDK assigns them.
+    ///
+    /// By default, the aliases are assigned in the order Projection, Key
+    /// Condition, Condition, Filter and Update Expression. The Go SDK assigns
+    /// them in the alphabetical order of the expression types, and orders
+    /// the clauses of the Update Expression as ClauseOrder::GoSdk does. With
+    /// Go SDK aliasing, the Expression matches the output of the Go SDK byte
+    /// for byte, for cross-language golden tests.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::new()
+    ///     .with_key_condition(key("pk").equal(value("a")))
+    ///     .with_filter(name("open").is_true().and(name("pk").is_true()))
+    ///     .with_go_sdk_aliasing(true)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(expr.filter().unwrap(), "(#0 = :0) AND (#1 = :1)");
+    /// assert_eq!(expr.key_condition().unwrap(), "#1 = :2");
+    /// ```
+    pub fn with_go_sdk_aliasing(mut self, go_sdk_aliasing: bool) -> Builder {
+        self.go_sdk_aliasing = go_sdk_aliasing;
+
+        self
+    }
+
+    /// Sets the aliases to be derived from a hash of the names and values
+    /// they stand for, seeded with the argument seed.
+    ///
+    /// By default, the aliases are numbered in the order the names and values
+    /// are used, so changing one part of a Builder can renumber the aliases
+    /// of the others, and invalidate expressions cached by their strings.
+    /// Hashed aliases only depend on the seed and on the content they stand
+    /// for, such as `#3fa2c01b`, so identical expressions always produce
+    /// identical strings and maps. Equal values share one alias, as with
+    /// with_value_dedup(). In the unlikely case of two hashes colliding, the
+    /// second alias gets a numbered suffix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let price = name("price").less_than(value(10));
+    ///
+    /// let alone = Builder::new()
+    ///     .with_filter(price.clone())
+    ///     .with_hashed_aliases(7)
+    ///     .build()
+    ///     .unwrap();
+    /// let with_projection = Builder::new()
+    ///     .with_projection(names_list(name("id"), vec![]))
+    ///     .with_filter(price)
+    ///     .with_hashed_aliases(7)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(alone.filter(), with_projection.filter());
+    /// ```
+    pub fn with_hashed_aliases(mut self, seed: u64) -> Builder {
+        self.hash_seed = Some(seed);
+
+        self
+    }
+
+    /// Sets the RedactionPolicy of the built Expression, which
+    /// Expression::to_debug_string() and the tracing feature apply to the
+    /// values, see RedactionPolicy.
+    ///
+    /// The policy doesn't change the expressions, names or values sent to
+    /// DynamoDB.
+    pub fn with_redaction_policy(mut self, policy: RedactionPolicy) -> Builder {
+        self.redaction_policy = Some(Arc::new(policy));
+
+        self
+    }
+
     /// Adds the argument ConditionBuilder as a Condition
     /// Expression to the argument Builder.
     ///
@@ -83,6 +326,9 @@ impl Builder {
     /// ConditionBuilder representing a Condition Expression, with_condition()
     /// overwrites the existing ConditionBuilder.
     ///
+    /// Any type implementing FilterSource can be passed in place of a
+    /// ConditionBuilder.
+    ///
     /// # Example
     ///
     /// ```
@@ -97,9 +343,101 @@ impl Builder {
     /// // create an Expression struct
     /// let expr = builder.build().unwrap();
     /// ```
-    pub fn with_condition(mut self, condition_builder: ConditionBuilder) -> Builder {
-        self.expressions
-            .insert(ExpressionType::Condition, Box::new(condition_builder));
+    pub fn with_condition(mut self, condition_builder: impl FilterSource) -> Builder {
+        self.expressions.insert(
+            ExpressionType::Condition,
+            Box::new(condition_builder.into_filter()),
+        );
+
+        self
+    }
+
+    /// Adds the argument ConditionBuilder as a Condition Expression to the argument Builder
+    /// if it is Some, and leaves the Builder unchanged otherwise.
+    ///
+    /// This allows request parameters that are optional to be applied without
+    /// matching on each of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expected_version: Option<i64> = None;
+    /// let cond = expected_version.map(|version| name("version").equal(value(version)));
+    ///
+    /// let builder = Builder::new()
+    ///     .with_update(set(name("version"), value(1)))
+    ///     .with_condition_if(cond);
+    /// ```
+    pub fn with_condition_if(self, condition_builder: Option<ConditionBuilder>) -> Builder {
+        match condition_builder {
+            Some(condition_builder) => self.with_condition(condition_builder),
+            None => self,
+        }
+    }
+
+    /// Adds the argument ConditionBuilder to the Condition Expression of the
+    /// argument Builder.
+    ///
+    /// If the argument Builder already has a ConditionBuilder representing a
+    /// Condition Expression, and_condition() combines both with a logical AND
+    /// instead of overwriting it. Otherwise the argument ConditionBuilder
+    /// becomes the Condition Expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let mut builder = Builder::new().and_condition(attribute_exists(name("id")));
+    ///
+    /// let expected_version: Option<i64> = Some(3);
+    /// if let Some(version) = expected_version {
+    ///     builder = builder.and_condition(name("version").equal(value(version)));
+    /// }
+    ///
+    /// let expr = builder.build().unwrap();
+    /// assert_eq!(expr.condition().unwrap(), "(attribute_exists (#0)) AND (#1 = :0)");
+    /// ```
+    pub fn and_condition(self, condition_builder: impl FilterSource) -> Builder {
+        self.and_merge(ExpressionType::Condition, condition_builder.into_filter())
+    }
+
+    /// Adds the argument ConditionBuilder as a Condition Expression named
+    /// name to the argument Builder.
+    ///
+    /// Transactions need a separate Condition Expression for each item, but
+    /// share nothing else between them. Named conditions are built together
+    /// with the other DynamoDB Expressions of the Builder using the same
+    /// aliases, so the names() and values() maps of the resulting Expression
+    /// can be used for every item of the transaction. If the argument Builder
+    /// already has a condition with the same name, with_named_condition()
+    /// overwrites the existing ConditionBuilder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::new()
+    ///     .with_named_condition("debit", name("balance").greater_than_equal(value(100)))
+    ///     .with_named_condition("credit", name("status").equal(value("open")))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(expr.named_condition("credit").unwrap(), "#0 = :0");
+    /// assert_eq!(expr.named_condition("debit").unwrap(), "#1 >= :1");
+    /// ```
+    pub fn with_named_condition(
+        mut self,
+        name: impl Into<String>,
+        condition_builder: impl FilterSource,
+    ) -> Builder {
+        self.expressions.insert(
+            ExpressionType::NamedCondition(name.into()),
+            Box::new(condition_builder.into_filter()),
+        );
 
         self
     }
@@ -111,6 +449,9 @@ impl Builder {
     /// ProjectionBuilder representing a Projection Expression, with_projection()
     /// overwrites the existing ProjectionBuilder.
     ///
+    /// Any type implementing ProjectionSource can be passed in place of a
+    /// ProjectionBuilder.
+    ///
     /// # Example
     ///
     /// ```
@@ -125,13 +466,38 @@ impl Builder {
     /// // create an Expression struct
     /// let expr = builder.build().unwrap();
     /// ```
-    pub fn with_projection(mut self, projection_builder: ProjectionBuilder) -> Builder {
-        self.expressions
-            .insert(ExpressionType::Projection, Box::new(projection_builder));
+    pub fn with_projection(mut self, projection_builder: impl ProjectionSource) -> Builder {
+        self.expressions.insert(
+            ExpressionType::Projection,
+            Box::new(projection_builder.into_projection()),
+        );
 
         self
     }
 
+    /// Adds the argument ProjectionBuilder as a Projection Expression to the argument Builder
+    /// if it is Some, and leaves the Builder unchanged otherwise.
+    ///
+    /// This allows request parameters that are optional to be applied without
+    /// matching on each of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let summary_only = true;
+    /// let proj = summary_only.then(|| names_list(name("aName"), vec![name("anotherName")]));
+    ///
+    /// let builder = Builder::new().with_projection_if(proj);
+    /// ```
+    pub fn with_projection_if(self, projection_builder: Option<ProjectionBuilder>) -> Builder {
+        match projection_builder {
+            Some(projection_builder) => self.with_projection(projection_builder),
+            None => self,
+        }
+    }
+
     /// Adds the argument KeyConditionBuilder as a Key
     /// Condition Expression to the argument Builder.
     ///
@@ -139,6 +505,9 @@ impl Builder {
     /// has a KeyConditionBuilder representing a Key Condition Expression,
     /// with_key_condition() overwrites the existing KeyConditionBuilder.
     ///
+    /// Any type implementing KeyConditionSource can be passed in place of a
+    /// KeyConditionBuilder.
+    ///
     /// # Example
     ///
     /// ```
@@ -153,15 +522,110 @@ impl Builder {
     /// // create an Expression struct
     /// let expr = builder.build().unwrap();
     /// ```
-    pub fn with_key_condition(mut self, key_condition_builder: KeyConditionBuilder) -> Builder {
+    pub fn with_key_condition(mut self, key_condition_builder: impl KeyConditionSource) -> Builder {
         self.expressions.insert(
             ExpressionType::KeyCondition,
-            Box::new(key_condition_builder),
+            Box::new(key_condition_builder.into_key_condition()),
         );
 
         self
     }
 
+    /// Adds the argument KeyConditionBuilder as a Key Condition Expression to the argument Builder
+    /// if it is Some, and leaves the Builder unchanged otherwise.
+    ///
+    /// This allows request parameters that are optional to be applied without
+    /// matching on each of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let partition: Option<&str> = Some("somePartition");
+    /// let key_cond = partition.map(|partition| key("someKey").equal(value(partition)));
+    ///
+    /// let builder = Builder::new().with_key_condition_if(key_cond);
+    /// ```
+    pub fn with_key_condition_if(
+        self,
+        key_condition_builder: Option<KeyConditionBuilder>,
+    ) -> Builder {
+        match key_condition_builder {
+            Some(key_condition_builder) => self.with_key_condition(key_condition_builder),
+            None => self,
+        }
+    }
+
+    /// Sets the KeySchema the expressions of the Builder are checked against
+    /// when they are built.
+    ///
+    /// The Key Condition Expression must compare the partition key of the
+    /// table or of one of its indexes for equality, and may only add a
+    /// condition on the sort key of the same table or index. The Filter
+    /// Expression of such a query must not reference the key attributes of
+    /// the table or index it queries, which DynamoDB rejects. Expressions that don't match the
+    /// KeySchema return the typed error KeySchemaViolation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let schema = KeySchema::new("pk")
+    ///     .with_sort_key("sk")
+    ///     .with_index(SecondaryIndex::new("ByEmail", "email"));
+    ///
+    /// let builder = Builder::new()
+    ///     .with_key_schema(schema.clone())
+    ///     .with_key_condition(key("email").equal(value("ace@example.com")))
+    ///     .with_filter(name("sk").begins_with("ORDER#"));
+    /// assert!(builder.build().is_ok());
+    ///
+    /// let builder = Builder::new()
+    ///     .with_key_schema(schema)
+    ///     .with_key_condition(key("pk").equal(value("USER#1")))
+    ///     .with_filter(name("sk").begins_with("ORDER#"));
+    /// assert!(builder.build().is_err());
+    /// ```
+    pub fn with_key_schema(mut self, key_schema: schema::KeySchema) -> Builder {
+        self.key_schema = Some(key_schema);
+        self
+    }
+
+    /// Sets the name of the secondary index the expressions of the Builder
+    /// query, which is returned by Expression::index_name().
+    ///
+    /// If the Builder has a KeySchema, the index must be one of its indexes
+    /// and the Key Condition Expression is checked against the key of that
+    /// index only, so the index and the key condition can't disagree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let schema = KeySchema::new("pk").with_index(SecondaryIndex::new("ByEmail", "email"));
+    ///
+    /// let expr = Builder::new()
+    ///     .with_key_schema(schema.clone())
+    ///     .for_index("ByEmail")
+    ///     .with_key_condition(key("email").equal(value("ace@example.com")))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(expr.index_name(), Some("ByEmail"));
+    ///
+    /// let builder = Builder::new()
+    ///     .with_key_schema(schema)
+    ///     .for_index("ByEmail")
+    ///     .with_key_condition(key("pk").equal(value("USER#1")));
+    /// assert!(builder.build().is_err());
+    /// ```
+    pub fn for_index(mut self, index_name: impl Into<String>) -> Builder {
+        self.index_name = Some(index_name.into());
+        self
+    }
+
     /// Adds the argument ConditionBuilder as a Filter Expression
     /// to the argument Builder.
     ///
@@ -169,6 +633,9 @@ impl Builder {
     /// ConditionBuilder representing a Filter Expression, with_filter()
     /// overwrites the existing ConditionBuilder.
     ///
+    /// Any type implementing FilterSource can be passed in place of a
+    /// ConditionBuilder.
+    ///
     /// # Example
     ///
     /// ```
@@ -183,9 +650,84 @@ impl Builder {
     /// // create an Expression struct
     /// let expr = builder.build().unwrap();
     /// ```
-    pub fn with_filter(mut self, filter: ConditionBuilder) -> Builder {
+    pub fn with_filter(mut self, filter: impl FilterSource) -> Builder {
         self.expressions
-            .insert(ExpressionType::Filter, Box::new(filter));
+            .insert(ExpressionType::Filter, Box::new(filter.into_filter()));
+
+        self
+    }
+
+    /// Adds the argument ConditionBuilder as a Filter Expression to the argument Builder
+    /// if it is Some, and leaves the Builder unchanged otherwise.
+    ///
+    /// This allows request parameters that are optional to be applied without
+    /// matching on each of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let artist: Option<&str> = Some("No One You Know");
+    /// let filt = artist.map(|artist| name("Artist").equal(value(artist)));
+    ///
+    /// let builder = Builder::new().with_filter_if(filt);
+    /// ```
+    pub fn with_filter_if(self, filter: Option<ConditionBuilder>) -> Builder {
+        match filter {
+            Some(filter) => self.with_filter(filter),
+            None => self,
+        }
+    }
+
+    /// Adds the argument ConditionBuilder to the Filter Expression of the
+    /// argument Builder.
+    ///
+    /// If the argument Builder already has a ConditionBuilder representing a
+    /// Filter Expression, and_filter() combines both with a logical AND
+    /// instead of overwriting it. Otherwise the argument ConditionBuilder
+    /// becomes the Filter Expression. This allows a filter to be built up
+    /// incrementally from optional request parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let artist = Some("No One You Know");
+    /// let min_year: Option<i64> = None;
+    ///
+    /// let mut builder = Builder::new();
+    /// if let Some(artist) = artist {
+    ///     builder = builder.and_filter(name("Artist").equal(value(artist)));
+    /// }
+    /// if let Some(min_year) = min_year {
+    ///     builder = builder.and_filter(name("Year").greater_than_equal(value(min_year)));
+    /// }
+    ///
+    /// let expr = builder.build().unwrap();
+    /// assert_eq!(expr.filter().unwrap(), "#0 = :0");
+    /// ```
+    pub fn and_filter(self, filter: impl FilterSource) -> Builder {
+        self.and_merge(ExpressionType::Filter, filter.into_filter())
+    }
+
+    fn and_merge(
+        mut self,
+        expression_type: ExpressionType,
+        condition: ConditionBuilder,
+    ) -> Builder {
+        let condition = match self
+            .expressions
+            .remove(&expression_type)
+            .and_then(|existing| existing.into_condition())
+        {
+            Some(existing) => existing.and(condition),
+            None => condition,
+        };
+
+        self.expressions
+            .insert(expression_type, Box::new(condition));
 
         self
     }
@@ -197,6 +739,9 @@ impl Builder {
     /// representing a Update Expression, with_update() overwrites the existing
     /// UpdateBuilder.
     ///
+    /// Any type implementing UpdateSource can be passed in place of an
+    /// UpdateBuilder.
+    ///
     /// # Example
     ///
     /// ```
@@ -211,93 +756,538 @@ impl Builder {
     /// // create an Expression struct
     /// let expr = builder.build().unwrap();
     /// ```
-    pub fn with_update(mut self, update_builder: UpdateBuilder) -> Builder {
-        self.expressions
-            .insert(ExpressionType::Update, Box::new(update_builder));
+    pub fn with_update(mut self, update_builder: impl UpdateSource) -> Builder {
+        self.expressions.insert(
+            ExpressionType::Update,
+            Box::new(update_builder.into_update()),
+        );
 
         self
     }
 
-    /// Builds an Expression struct representing multiple types of DynamoDB
-    /// Expressions.
+    /// Adds the argument GuardedUpdate to the argument Builder.
     ///
-    /// Getter methods on the resulting Expression struct returns the
-    /// DynamoDB Expression strings as well as the maps that correspond to
-    /// ExpressionAttributeNames and ExpressionAttributeValues. Calling build() on an
-    /// empty Builder returns the typed error EmptyParameterError.
+    /// The condition of the GuardedUpdate is combined with the Condition
+    /// Expression of the Builder with a logical AND, as and_condition() does,
+    /// and its update is added to the operations of the Update Expression of
+    /// the Builder, if any.
     ///
     /// # Example
     ///
     /// ```
     /// use dynamodb_expression::*;
     ///
-    /// # tokio_test::block_on(async {
-    /// let shared_config = aws_config::from_env().load().await;
-    /// let client = aws_sdk_dynamodb::Client::new(&shared_config);
+    /// let expr = Builder::new()
+    ///     .with_condition(attribute_exists(name("id")))
+    ///     .with_update(set(name("name"), value("ace")))
+    ///     .with_guarded_update(versioning::optimistic_lock(name("version"), 1))
+    ///     .build()
+    ///     .unwrap();
     ///
-    /// // key_cond represents the Key Condition Expression
-    /// let key_cond = key("someKey").equal(value("someValue"));
-    /// // proj represents the Projection Expression
-    /// let proj = names_list(name("aName"), vec![name("anotherName"), name("oneOtherName")]);
+    /// assert_eq!(expr.condition().unwrap(), "(attribute_exists (#0)) AND (#1 = :0)");
+    /// assert_eq!(expr.update().unwrap(), "SET #2 = :1, #1 = #1 + :2\n");
+    /// ```
+    pub fn with_guarded_update(self, guarded: GuardedUpdate) -> Builder {
+        let (condition, update) = guarded.into_parts();
+        let mut builder = self.and_condition(condition);
+
+        let update = match builder
+            .expressions
+            .remove(&ExpressionType::Update)
+            .and_then(|existing| existing.into_update())
+        {
+            Some(existing) => existing.merge(update),
+            None => update,
+        };
+        builder
+            .expressions
+            .insert(ExpressionType::Update, Box::new(update));
+
+        builder
+    }
+
+    /// Adds the argument UpdateBuilder as a Update Expression to the argument Builder
+    /// if it is Some, and leaves the Builder unchanged otherwise.
     ///
-    /// // Add key_cond and proj to builder as a Key Condition and Projection
-    /// // respectively
-    /// let builder = Builder::new().with_key_condition(key_cond).with_projection(proj);
-    /// let expr = builder.build().unwrap();
+    /// This allows request parameters that are optional to be applied without
+    /// matching on each of them.
     ///
-    /// let query = client.query()
-    ///     .key_condition_expression(expr.key_condition().cloned().unwrap())
-    ///     .projection_expression(expr.projection().cloned().unwrap())
-    ///     .set_expression_attribute_names(expr.names().clone())
-    ///     .set_expression_attribute_values(expr.values().clone())
-    ///     .table_name("SomeTable".to_owned());
+    /// # Example
     ///
-    /// # })
     /// ```
-    pub fn build(self) -> anyhow::Result<Expression> {
-        let (alias_list, expressions) = self.build_child_trees()?;
-
-        let mut expression = Expression::new(expressions);
+    /// use dynamodb_expression::*;
+    ///
+    /// let new_name: Option<&str> = None;
+    /// let update = new_name.map(|new_name| set(name("someKey"), value(new_name)));
+    ///
+    /// let builder = Builder::new().with_update_if(update);
+    /// ```
+    pub fn with_update_if(self, update_builder: Option<UpdateBuilder>) -> Builder {
+        match update_builder {
+            Some(update_builder) => self.with_update(update_builder),
+            None => self,
+        }
+    }
+
+    /// Returns true if the Builder has an expression of the argument type.
+    ///
+    /// Code assembling a request generically can check which expressions are
+    /// set before building them, to decide which fields of the SDK input to
+    /// set. See also has_condition(), has_filter() and the other shorthands.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let builder = Builder::new()
+    ///     .with_filter(name("status").equal(value("open")))
+    ///     .with_named_condition("debit", name("balance").greater_than(value(0)));
+    ///
+    /// assert!(builder.has(&ExpressionType::Filter));
+    /// assert!(builder.has(&ExpressionType::NamedCondition("debit".to_owned())));
+    /// assert!(builder.has_filter());
+    /// assert!(!builder.has_update());
+    /// ```
+    pub fn has(&self, expression_type: &ExpressionType) -> bool {
+        self.expressions.contains_key(expression_type)
+    }
+
+    /// Returns true if the Builder has a Condition Expression.
+    pub fn has_condition(&self) -> bool {
+        self.has(&ExpressionType::Condition)
+    }
+
+    /// Returns true if the Builder has a Filter Expression.
+    pub fn has_filter(&self) -> bool {
+        self.has(&ExpressionType::Filter)
+    }
+
+    /// Returns true if the Builder has a Projection Expression.
+    pub fn has_projection(&self) -> bool {
+        self.has(&ExpressionType::Projection)
+    }
+
+    /// Returns true if the Builder has a Key Condition Expression.
+    pub fn has_key_condition(&self) -> bool {
+        self.has(&ExpressionType::KeyCondition)
+    }
+
+    /// Returns true if the Builder has an Update Expression.
+    pub fn has_update(&self) -> bool {
+        self.has(&ExpressionType::Update)
+    }
+
+    /// Returns the types of the expressions of the Builder, sorted.
+    pub fn expression_types(&self) -> Vec<&ExpressionType> {
+        let mut keys = self.expressions.keys().collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
+    /// Builds an Expression struct representing multiple types of DynamoDB
+    /// Expressions.
+    ///
+    /// Getter methods on the resulting Expression struct returns the
+    /// DynamoDB Expression strings as well as the maps that correspond to
+    /// ExpressionAttributeNames and ExpressionAttributeValues. Calling build() on an
+    /// empty Builder returns the typed error EmptyParameterError.
+    ///
+    /// With the tracing feature, build() and build_validated() emit
+    /// debug-level events with the type, the length and the aliased string of
+    /// each expression built, and the error of a failed build. The expressions
+    /// with their names and values substituted are only emitted at the trace
+    /// level.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let shared_config = aws_config::from_env().load().await;
+    /// let client = aws_sdk_dynamodb::Client::new(&shared_config);
+    ///
+    /// // key_cond represents the Key Condition Expression
+    /// let key_cond = key("someKey").equal(value("someValue"));
+    /// // proj represents the Projection Expression
+    /// let proj = names_list(name("aName"), vec![name("anotherName"), name("oneOtherName")]);
+    ///
+    /// // Add key_cond and proj to builder as a Key Condition and Projection
+    /// // respectively
+    /// let builder = Builder::new().with_key_condition(key_cond).with_projection(proj);
+    /// let expr = builder.build().unwrap();
+    ///
+    /// let query = client.query()
+    ///     .key_condition_expression(expr.key_condition().unwrap())
+    ///     .projection_expression(expr.projection().unwrap())
+    ///     .set_expression_attribute_names(expr.names().clone())
+    ///     .set_expression_attribute_values(expr.values().clone())
+    ///     .table_name("SomeTable".to_owned());
+    ///
+    /// # })
+    /// ```
+    pub fn build(self) -> Result<Expression, ExpressionError> {
+        self.build_expression(false)
+    }
+
+    /// Builds an Expression struct like build(), additionally checking the
+    /// result against the documented DynamoDB expression limits.
+    ///
+    /// The checked limits are the maximum expression string size, the maximum
+    /// attribute name length, the maximum number of IN operands, the maximum
+    /// document path depth and the maximum number of operators and functions
+    /// per expression. Exceeding any of them returns the typed error
+    /// LimitExceededError before the request is ever sent to DynamoDB.
+    ///
+    /// The sizes of the bound values are checked as well: values compared to
+    /// the partition key or the sort key of a Key Condition Expression must fit
+    /// into the key size limits, and any other value into the item size limit.
+    /// An oversized value returns the typed error ValueSizeExceeded naming the
+    /// attribute it is compared to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let cond = name("someKey").equal(value("someValue"));
+    /// let expr = Builder::new().with_condition(cond).build_validated().unwrap();
+    ///
+    /// let cond = name("a".repeat(256)).equal(value("someValue"));
+    /// let err = Builder::new().with_condition(cond).build_validated().unwrap_err();
+    /// assert_eq!(
+    ///     err,
+    ///     error::ExpressionError::LimitExceededError(
+    ///         "buildValidated".to_owned(),
+    ///         ExpressionLimit::AttributeNameLength,
+    ///         256,
+    ///     ),
+    /// );
+    /// ```
+    pub fn build_validated(self) -> Result<Expression, ExpressionError> {
+        self.build_expression(true)
+    }
+
+    /// Checks the Filter Expression of the Builder against the secondary
+    /// indexes of the table, returning a SchemaWarning for every problem found.
+    ///
+    /// A filter that references a key attribute of a sparse index without
+    /// checking attribute_exists() on it assumes the attribute is always
+    /// present, which only holds for the items of the index itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let index = SecondaryIndex::new("OpenOrders", "openedAt").sparse();
+    ///
+    /// let filt = name("openedAt").less_than(value(1700000000));
+    /// let builder = Builder::new().with_filter(filt);
+    /// assert_eq!(builder.check_sparse_indexes(&[index.clone()]).unwrap().len(), 1);
+    ///
+    /// let filt = name("openedAt")
+    ///     .attribute_exists()
+    ///     .and(name("openedAt").less_than(value(1700000000)));
+    /// let builder = Builder::new().with_filter(filt);
+    /// assert!(builder.check_sparse_indexes(&[index]).unwrap().is_empty());
+    /// ```
+    pub fn check_sparse_indexes(
+        &self,
+        indexes: &[schema::SecondaryIndex],
+    ) -> Result<Vec<schema::SchemaWarning>, ExpressionError> {
+        let filter = match self.expressions.get(&ExpressionType::Filter) {
+            Some(filter) => filter,
+            None => return Ok(vec![]),
+        };
+
+        let node = filter
+            .build_tree()
+            .map_err(|e| e.in_path(ExpressionType::Filter.to_string()))?;
 
-        if !alias_list.names.is_empty() {
-            let mut names = HashMap::new();
-            for (ind, val) in alias_list.names.iter().enumerate() {
-                names.insert(format!("#{}", ind), val.clone());
+        Ok(schema::check_sparse_indexes(&node, indexes))
+    }
+
+    fn build_expression(self, validate: bool) -> Result<Expression, ExpressionError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("build_expression", validate).entered();
+
+        let result = self.build_untraced_expression(validate);
+
+        #[cfg(feature = "tracing")]
+        crate::trace::record_build(&result);
+
+        result
+    }
+
+    fn build_untraced_expression(self, validate: bool) -> Result<Expression, ExpressionError> {
+        let (mut alias_list, expressions) = self.build_child_trees(validate)?;
+
+        let mut expression = Expression::new(expressions);
+        expression.index_name = self.index_name;
+        expression.redaction_policy = self.redaction_policy;
+
+        // the aliased names and values are moved into the maps, not copied
+        let alias_names = std::mem::take(&mut alias_list.names);
+        if !alias_names.is_empty() {
+            let mut names = HashMap::with_capacity(alias_names.len());
+            for (ind, val) in alias_names.into_iter().enumerate() {
+                if validate {
+                    limits::check_name(&val)?;
+                }
+                names.insert(alias_list.name_alias(ind), val);
             }
-            expression.names = Some(names);
+            expression.names = Arc::new(Some(names));
         }
 
-        if !alias_list.values.is_empty() {
-            let mut values = HashMap::new();
-            for (ind, val) in alias_list.values.iter().enumerate() {
-                values.insert(format!(":{}", ind), val.clone());
+        let alias_values = std::mem::take(&mut alias_list.values);
+        if !alias_values.is_empty() {
+            let mut values = HashMap::with_capacity(alias_values.len());
+            for (ind, val) in alias_values.into_iter().enumerate() {
+                values.insert(alias_list.value_alias(ind), val);
             }
-            expression.values = Some(values);
+            expression.values = Arc::new(Some(values));
         }
 
         Ok(expression)
     }
 
-    fn build_child_trees(&self) -> anyhow::Result<(AliasList, HashMap<ExpressionType, String>)> {
-        let mut alias_list = AliasList::default();
+    fn build_child_trees(
+        &self,
+        validate: bool,
+    ) -> Result<(AliasList, HashMap<ExpressionType, String>), ExpressionError> {
+        for prefix in [&self.names_prefix, &self.values_prefix] {
+            if !prefix
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            {
+                return Err(ExpressionError::InvalidParameterError(
+                    "buildChildTrees".to_owned(),
+                    "aliasPrefix".to_owned(),
+                ));
+            }
+        }
+
+        let mut alias_list = AliasList {
+            names_prefix: self.names_prefix.clone(),
+            values_prefix: self.values_prefix.clone(),
+            dedup_values: self.dedup_values,
+            bare_names: self.bare_names,
+            hash_seed: self.hash_seed,
+            ..Default::default()
+        };
         let mut formatted_expressions = HashMap::new();
         let mut keys = Vec::new();
 
         for expression_type in self.expressions.keys() {
-            keys.push(*expression_type);
+            keys.push(expression_type.clone());
         }
         keys.sort();
 
-        for key in keys.iter() {
-            let node = self.expressions[key].build_tree()?;
+        if let (Some(key_schema), Some(index_name)) = (&self.key_schema, &self.index_name) {
+            schema::check_index(key_schema, index_name)?;
+        }
+
+        // the key attributes queried by the key condition, checked against
+        // the filter which is built after it
+        let mut queried_keys = None;
+
+        let mut nodes = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let node = self
+                .build_child_tree(&key)
+                .map_err(|e| e.in_path(key.to_string()))?;
+            if let Some(key_schema) = &self.key_schema {
+                match key {
+                    ExpressionType::KeyCondition => {
+                        queried_keys = Some(
+                            schema::check_key_condition(
+                                key_schema,
+                                self.index_name.as_deref(),
+                                &node,
+                            )
+                            .map_err(|e| e.in_path(key.to_string()))?,
+                        );
+                    }
+                    ExpressionType::Filter => {
+                        if let Some(queried_keys) = &queried_keys {
+                            schema::check_filter(queried_keys, &node)
+                                .map_err(|e| e.in_path(key.to_string()))?;
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            if validate {
+                limits::check_node(&node)?;
+                limits::check_values(&node)?;
+                if key == ExpressionType::KeyCondition {
+                    limits::check_key_condition(&node)?;
+                }
+            }
+
+            nodes.push((key, node));
+        }
+
+        // the Go SDK assigns the aliases in the alphabetical order of the
+        // names of the expression types
+        if self.go_sdk_aliasing {
+            nodes.sort_by_key(|(key, _)| match key {
+                ExpressionType::Condition => 0,
+                ExpressionType::Filter => 1,
+                ExpressionType::KeyCondition => 2,
+                ExpressionType::Projection => 3,
+                ExpressionType::Update => 4,
+                ExpressionType::NamedCondition(_) => 5,
+            });
+        }
+
+        for (key, node) in nodes {
             let formatted_expression = node.build_expression_string(&mut alias_list)?;
-            formatted_expressions.insert(*key, formatted_expression);
+            if validate {
+                limits::check_expression(&formatted_expression)?;
+            }
+
+            formatted_expressions.insert(key, formatted_expression);
         }
 
         Ok((alias_list, formatted_expressions))
     }
+
+    fn build_child_tree(&self, key: &ExpressionType) -> Result<ExpressionNode, ExpressionError> {
+        let tree = &self.expressions[key];
+        if self.go_sdk_aliasing && *key == ExpressionType::Update {
+            if let Some(update) = tree.clone().into_update() {
+                return update.with_clause_order(ClauseOrder::GoSdk).build_tree();
+            }
+        }
+
+        tree.build_tree()
+    }
+
+    // returns the Builder method calls recreating the Builder, in build order
+    pub(crate) fn record_calls(&self, recorder: &mut Recorder) -> Vec<String> {
+        let mut calls = Vec::new();
+
+        if !self.names_prefix.is_empty() || !self.values_prefix.is_empty() {
+            calls.push(format!(
+                "with_alias_prefix({}, {})",
+                recorder.name(&self.names_prefix),
+                recorder.name(&self.values_prefix)
+            ));
+        }
+
+        if self.dedup_values {
+            calls.push("with_value_dedup(true)".to_owned());
+        }
+
+        if self.bare_names {
+            calls.push("with_bare_names(true)".to_owned());
+        }
+
+        if self.go_sdk_aliasing {
+            calls.push("with_go_sdk_aliasing(true)".to_owned());
+        }
+
+        if let Some(seed) = self.hash_seed {
+            calls.push(format!("with_hashed_aliases({})", seed));
+        }
+
+        if let Some(key_schema) = &self.key_schema {
+            calls.push(format!("with_key_schema({})", key_schema.record(recorder)));
+        }
+
+        if let Some(index_name) = &self.index_name {
+            calls.push(format!("for_index({})", recorder.name(index_name)));
+        }
+
+        let mut keys = self.expressions.keys().collect::<Vec<_>>();
+        keys.sort();
+
+        for key in keys {
+            let tree = self.expressions[key].record(recorder);
+            calls.push(match key {
+                ExpressionType::Projection => format!("with_projection({})", tree),
+                ExpressionType::KeyCondition => format!("with_key_condition({})", tree),
+                ExpressionType::Condition => format!("with_condition({})", tree),
+                ExpressionType::Filter => format!("with_filter({})", tree),
+                ExpressionType::Update => format!("with_update({})", tree),
+                ExpressionType::NamedCondition(name) => {
+                    format!("with_named_condition({}, {})", recorder.name(name), tree)
+                }
+            });
+        }
+
+        calls
+    }
+}
+
+/// Converts the ConditionBuilder into a Builder with it as the Condition
+/// Expression.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let expr = Expression::try_from(name("foo").attribute_exists()).unwrap();
+/// assert_eq!(expr.condition().unwrap(), "attribute_exists (#0)");
+/// ```
+impl From<ConditionBuilder> for Builder {
+    fn from(condition_builder: ConditionBuilder) -> Self {
+        Builder::new().with_condition(condition_builder)
+    }
+}
+
+/// Converts the KeyConditionBuilder into a Builder with it as the Key
+/// Condition Expression.
+impl From<KeyConditionBuilder> for Builder {
+    fn from(key_condition_builder: KeyConditionBuilder) -> Self {
+        Builder::new().with_key_condition(key_condition_builder)
+    }
+}
+
+/// Converts the ProjectionBuilder into a Builder with it as the Projection
+/// Expression.
+impl From<ProjectionBuilder> for Builder {
+    fn from(projection_builder: ProjectionBuilder) -> Self {
+        Builder::new().with_projection(projection_builder)
+    }
+}
+
+/// Converts the UpdateBuilder into a Builder with it as the Update
+/// Expression.
+impl From<UpdateBuilder> for Builder {
+    fn from(update_builder: UpdateBuilder) -> Self {
+        Builder::new().with_update(update_builder)
+    }
+}
+
+// builds an Expression from a single builder, through the Builder it converts
+// into
+macro_rules! impl_try_from_builder {
+    ($($builder:ty),+) => {
+        $(
+            impl TryFrom<$builder> for Expression {
+                type Error = ExpressionError;
+
+                fn try_from(builder: $builder) -> Result<Self, Self::Error> {
+                    Builder::from(builder).build()
+                }
+            }
+        )+
+    };
 }
 
+impl_try_from_builder!(
+    ConditionBuilder,
+    KeyConditionBuilder,
+    ProjectionBuilder,
+    UpdateBuilder
+);
+
 /// Represents a collection of DynamoDB Expressions.
 ///
 /// The getter methods of the Expression struct retrieves the formatted DynamoDB
@@ -323,28 +1313,107 @@ impl Builder {
 /// let expr = builder.build().unwrap();
 ///
 /// let query = client.query()
-///     .key_condition_expression(expr.key_condition().cloned().unwrap())
-///     .projection_expression(expr.projection().cloned().unwrap())
+///     .key_condition_expression(expr.key_condition().unwrap())
+///     .projection_expression(expr.projection().unwrap())
 ///     .set_expression_attribute_names(expr.names().clone())
 ///     .set_expression_attribute_values(expr.values().clone())
 ///     .table_name("SomeTable".to_owned());
 /// # })
 /// ```
+///
+/// The parts of an Expression are shared between its clones, so an Expression
+/// built once can be cloned cheaply for every request that uses it, such as
+/// the pages of a Query. A part is only copied when it is taken out of an
+/// Expression that shares it.
 #[derive(Default, Debug, PartialEq, Clone)]
 pub struct Expression {
-    expressions: HashMap<ExpressionType, String>,
-    names: Option<HashMap<String, String>>,
-    values: Option<HashMap<String, AttributeValue>>,
+    pub(crate) expressions: Arc<HashMap<ExpressionType, String>>,
+    names: Arc<Option<HashMap<String, String>>>,
+    values: Arc<Option<HashMap<String, AttributeValue>>>,
+    index_name: Option<String>,
+    redaction_policy: Option<Arc<RedactionPolicy>>,
 }
 
 impl Expression {
     fn new(expressions: HashMap<ExpressionType, String>) -> Self {
         Self {
-            expressions,
+            expressions: Arc::new(expressions),
             ..Default::default()
         }
     }
 
+    /// Returns an Expression made of the argument DynamoDB Expression strings,
+    /// ExpressionAttributeNames and ExpressionAttributeValues.
+    ///
+    /// The parts are used as-is, without checking that the aliases used by
+    /// the expressions are present in the maps. This allows tests and
+    /// adapters to create an Expression from known strings and maps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use aws_sdk_dynamodb::types::AttributeValue;
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Expression::from_parts(
+    ///     HashMap::from([(ExpressionType::Condition, "#0 = :0".to_owned())]),
+    ///     Some(HashMap::from([("#0".to_owned(), "foo".to_owned())])),
+    ///     Some(HashMap::from([(":0".to_owned(), AttributeValue::N("5".to_owned()))])),
+    /// );
+    ///
+    /// let built = Builder::new()
+    ///     .with_condition(name("foo").equal(value(5)))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(expr, built);
+    /// ```
+    pub fn from_parts(
+        expressions: HashMap<ExpressionType, String>,
+        names: Option<HashMap<String, String>>,
+        values: Option<HashMap<String, AttributeValue>>,
+    ) -> Self {
+        Self {
+            expressions: Arc::new(expressions),
+            names: Arc::new(names),
+            values: Arc::new(values),
+            index_name: None,
+            redaction_policy: None,
+        }
+    }
+
+    /// Deconstructs the Expression into its DynamoDB Expression strings,
+    /// ExpressionAttributeNames and ExpressionAttributeValues, without
+    /// copying them unless they are shared with a clone of the Expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::new()
+    ///     .with_condition(name("foo").equal(value(5)))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let (expressions, names, values) = expr.into_parts();
+    /// assert_eq!(expressions[&ExpressionType::Condition], "#0 = :0");
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn into_parts(
+        self,
+    ) -> (
+        HashMap<ExpressionType, String>,
+        Option<HashMap<String, String>>,
+        Option<HashMap<String, AttributeValue>>,
+    ) {
+        (
+            Arc::unwrap_or_clone(self.expressions),
+            Arc::unwrap_or_clone(self.names),
+            Arc::unwrap_or_clone(self.values),
+        )
+    }
+
     /// Returns the string corresponding to the Condition Expression
     /// of the argument Expression.
     ///
@@ -367,14 +1436,14 @@ impl Expression {
     /// let expression = builder.build().unwrap();
     ///
     /// let delete = client.delete_item()
-    ///     .condition_expression(expression.condition().cloned().unwrap())
+    ///     .condition_expression(expression.condition().unwrap())
     ///     .set_expression_attribute_names(expression.names().clone())
     ///     .set_expression_attribute_values(expression.values().clone())
     ///     .key("PartitionKey".to_owned(), aws_sdk_dynamodb::types::AttributeValue::S("SomeKey".to_owned()))
     ///     .table_name("SomeTable".to_owned());
     /// # })
     /// ```
-    pub fn condition(&self) -> Option<&String> {
+    pub fn condition(&self) -> Option<&str> {
         self.return_expression(ExpressionType::Condition)
     }
 
@@ -400,29 +1469,86 @@ impl Expression {
     /// let expression = builder.build().unwrap();
     ///
     /// let query = client.query()
-    ///     .key_condition_expression(expression.key_condition().cloned().unwrap())
-    ///     .filter_expression(expression.filter().cloned().unwrap())
+    ///     .key_condition_expression(expression.key_condition().unwrap())
+    ///     .filter_expression(expression.filter().unwrap())
     ///     .set_expression_attribute_names(expression.names().clone())
     ///     .set_expression_attribute_values(expression.values().clone())
     ///     .table_name("SomeTable".to_owned());
     /// # })
     /// ```
-    pub fn filter(&self) -> Option<&String> {
+    pub fn filter(&self) -> Option<&str> {
         self.return_expression(ExpressionType::Filter)
     }
 
-    pub fn projection(&self) -> Option<&String> {
+    /// Returns the string corresponding to the Condition Expression added
+    /// with the argument name by with_named_condition().
+    ///
+    /// If the Expression does not have a condition with that name this method
+    /// returns None. Every named condition uses the names() and values() maps
+    /// of the Expression, so that one Builder can produce the conditions of
+    /// all the items of a TransactWriteItems or of a BatchExecuteStatement.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::new()
+    ///     .with_named_condition("from", name("balance").greater_than_equal(value(100)))
+    ///     .with_named_condition("to", name("balance").attribute_exists())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(expr.condition_tags(), ["from", "to"]);
+    /// for tag in expr.condition_tags() {
+    ///     assert!(expr.named_condition(tag).unwrap().contains("#0"));
+    /// }
+    /// assert_eq!(expr.named_condition("other"), None);
+    /// ```
+    pub fn named_condition(&self, name: &str) -> Option<&str> {
+        self.return_expression(ExpressionType::NamedCondition(name.to_owned()))
+    }
+
+    /// Returns the tags of the Condition Expressions added with
+    /// Builder::with_named_condition(), in sorted order.
+    pub fn condition_tags(&self) -> Vec<&str> {
+        let mut tags = self
+            .expressions
+            .keys()
+            .filter_map(|expression_type| match expression_type {
+                ExpressionType::NamedCondition(tag) => Some(tag.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        tags.sort_unstable();
+        tags
+    }
+
+    pub fn projection(&self) -> Option<&str> {
         self.return_expression(ExpressionType::Projection)
     }
 
-    pub fn key_condition(&self) -> Option<&String> {
+    pub fn key_condition(&self) -> Option<&str> {
         self.return_expression(ExpressionType::KeyCondition)
     }
 
-    pub fn update(&self) -> Option<&String> {
+    pub fn update(&self) -> Option<&str> {
         self.return_expression(ExpressionType::Update)
     }
 
+    /// Returns the name of the secondary index set with Builder::for_index(),
+    /// to be used as the IndexName of a Query or a Scan.
+    pub fn index_name(&self) -> Option<&str> {
+        self.index_name.as_deref()
+    }
+
+    /// Returns the Expression with the argument RedactionPolicy, see
+    /// Builder::with_redaction_policy().
+    pub fn with_redaction_policy(mut self, policy: RedactionPolicy) -> Expression {
+        self.redaction_policy = Some(Arc::new(policy));
+        self
+    }
+
     pub fn names(&self) -> &Option<HashMap<String, String>> {
         &self.names
     }
@@ -431,209 +1557,1673 @@ impl Expression {
         &self.values
     }
 
-    fn return_expression(&self, expression_type: ExpressionType) -> Option<&String> {
-        self.expressions.get(&expression_type)
+    /// Returns a copy of the ExpressionAttributeNames of the argument
+    /// Expression, or an empty map if the Expression has no names.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let shared_config = aws_config::from_env().load().await;
+    /// let client = aws_sdk_dynamodb::Client::new(&shared_config);
+    ///
+    /// let key_cond = key("someKey").equal(value("someValue"));
+    /// let expr = Builder::new().with_key_condition(key_cond).build().unwrap();
+    ///
+    /// let query = client.query()
+    ///     .key_condition_expression(expr.key_condition().unwrap())
+    ///     .set_expression_attribute_names(Some(expr.names_map()))
+    ///     .set_expression_attribute_values(Some(expr.values_map()))
+    ///     .table_name("SomeTable".to_owned());
+    /// # })
+    /// ```
+    pub fn names_map(&self) -> HashMap<String, String> {
+        self.names.as_ref().clone().unwrap_or_default()
+    }
+
+    /// Returns a copy of the ExpressionAttributeValues of the argument
+    /// Expression, or an empty map if the Expression has no values.
+    pub fn values_map(&self) -> HashMap<String, AttributeValue> {
+        self.values.as_ref().clone().unwrap_or_default()
     }
-}
 
-#[derive(Default, Debug, Clone)]
-struct AliasList {
-    names: Vec<String>,
-    values: Vec<AttributeValue>,
-}
+    /// Returns the ExpressionAttributeNames of the argument Expression,
+    /// consuming it.
+    pub fn into_names(self) -> Option<HashMap<String, String>> {
+        Arc::unwrap_or_clone(self.names)
+    }
+
+    /// Returns the ExpressionAttributeValues of the argument Expression,
+    /// consuming it.
+    pub fn into_values(self) -> Option<HashMap<String, AttributeValue>> {
+        Arc::unwrap_or_clone(self.values)
+    }
+
+    /// Removes the string corresponding to the Condition Expression from the
+    /// argument Expression and returns it.
+    ///
+    /// The take_*() methods move the parts of the Expression out of it, so
+    /// they can be passed to the DynamoDB input builders without cloning. A
+    /// part shared with a clone of the Expression is copied instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let shared_config = aws_config::from_env().load().await;
+    /// let client = aws_sdk_dynamodb::Client::new(&shared_config);
+    ///
+    /// let cond = name("someKey").equal(value("someValue"));
+    /// let update = set(name("someName"), value("someValue"));
+    /// let mut expr = Builder::new()
+    ///     .with_condition(cond)
+    ///     .with_update(update)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let update = client.update_item()
+    ///     .set_condition_expression(expr.take_condition())
+    ///     .set_update_expression(expr.take_update())
+    ///     .set_expression_attribute_names(expr.take_names())
+    ///     .set_expression_attribute_values(expr.take_values())
+    ///     .table_name("SomeTable".to_owned());
+    /// # })
+    /// ```
+    pub fn take_condition(&mut self) -> Option<String> {
+        self.take_expression(ExpressionType::Condition)
+    }
+
+    /// Removes the string corresponding to the Filter Expression from the
+    /// argument Expression and returns it.
+    pub fn take_filter(&mut self) -> Option<String> {
+        self.take_expression(ExpressionType::Filter)
+    }
+
+    /// Removes the string corresponding to the Projection Expression from the
+    /// argument Expression and returns it.
+    pub fn take_projection(&mut self) -> Option<String> {
+        self.take_expression(ExpressionType::Projection)
+    }
+
+    /// Removes the string corresponding to the Key Condition Expression from
+    /// the argument Expression and returns it.
+    pub fn take_key_condition(&mut self) -> Option<String> {
+        self.take_expression(ExpressionType::KeyCondition)
+    }
+
+    /// Removes the string corresponding to the Update Expression from the
+    /// argument Expression and returns it.
+    pub fn take_update(&mut self) -> Option<String> {
+        self.take_expression(ExpressionType::Update)
+    }
+
+    /// Removes the ExpressionAttributeNames from the argument Expression and
+    /// returns them.
+    pub fn take_names(&mut self) -> Option<HashMap<String, String>> {
+        Arc::unwrap_or_clone(std::mem::take(&mut self.names))
+    }
+
+    /// Removes the ExpressionAttributeValues from the argument Expression and
+    /// returns them.
+    pub fn take_values(&mut self) -> Option<HashMap<String, AttributeValue>> {
+        Arc::unwrap_or_clone(std::mem::take(&mut self.values))
+    }
+
+    /// Returns the DynamoDB Expressions of the argument Expression with the
+    /// aliases replaced by the names and a printable form of the values they
+    /// stand for, one Expression per line.
+    ///
+    /// This is intended for logging and troubleshooting only. The returned
+    /// string is NOT a valid DynamoDB Expression: names are not escaped and
+    /// values are not quoted the way DynamoDB expects, so it must never be
+    /// sent to DynamoDB.
+    ///
+    /// The values are redacted as set with with_redaction_policy(), see
+    /// RedactionPolicy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let filt = name("Artist")
+    ///     .equal(value("No One You Know"))
+    ///     .and(name("Price").greater_than(value(5)));
+    /// let expr = Builder::new().with_filter(filt).build().unwrap();
+    ///
+    /// assert_eq!(
+    ///     expr.to_debug_string(),
+    ///     "filter: (Artist = \"No One You Know\") AND (Price > 5)"
+    /// );
+    /// ```
+    pub fn to_debug_string(&self) -> String {
+        let mut keys = self.expressions.keys().collect::<Vec<_>>();
+        keys.sort();
+
+        keys.into_iter()
+            .map(|key| {
+                format!(
+                    "{}: {}",
+                    key,
+                    self.substitute_aliases(self.expressions[key].trim_end())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns whether the argument Expression has the same DynamoDB
+    /// Expressions as this one, up to the numbering of the aliases.
+    ///
+    /// Every alias is compared by the name or the value it stands for, so
+    /// the result doesn't depend on the order the aliases were assigned in,
+    /// which may change between versions of the crate, nor on whether names
+    /// are written bare, see Builder::with_bare_names(). See also
+    /// assert_expr_eq!, which uses equivalent_to() in tests.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use aws_sdk_dynamodb::types::AttributeValue;
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::new()
+    ///     .with_condition(name("foo").equal(value(5)))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let expected = Expression::from_parts(
+    ///     [(ExpressionType::Condition, "#f = :five".to_owned())].into(),
+    ///     Some([("#f".to_owned(), "foo".to_owned())].into()),
+    ///     Some([(":five".to_owned(), AttributeValue::N("5".to_owned()))].into()),
+    /// );
+    ///
+    /// assert!(expr.equivalent_to(&expected));
+    /// assert_ne!(expr, expected);
+    /// ```
+    pub fn equivalent_to(&self, other: &Expression) -> bool {
+        self.expressions.len() == other.expressions.len()
+            && self.index_name == other.index_name
+            && self.expressions.iter().all(|(key, expression)| {
+                other.expressions.get(key).is_some_and(|other_expression| {
+                    self.resolve_names(expression) == other.resolve_names(other_expression)
+                })
+            })
+    }
+
+    // resolves the aliases of the expression, with the names merged into the
+    // text around them so that bare and aliased names compare equal
+    fn resolve_names<'a>(&'a self, expression: &'a str) -> Vec<NamesResolvedPart<'a>> {
+        let mut parts = Vec::new();
+        for part in self.resolve_aliases(expression) {
+            match (part, parts.last_mut()) {
+                (ResolvedPart::Value(value), _) => parts.push(NamesResolvedPart::Value(value)),
+                (
+                    ResolvedPart::Text(text) | ResolvedPart::Name(text),
+                    Some(NamesResolvedPart::Text(last)),
+                ) => last.push_str(text),
+                (ResolvedPart::Text(text) | ResolvedPart::Name(text), _) => {
+                    parts.push(NamesResolvedPart::Text(text.to_owned()))
+                }
+            }
+        }
+        parts
+    }
+
+    fn substitute_aliases(&self, expression: &str) -> String {
+        let mut redactor = ValueRedactor::default();
+
+        self.resolve_aliases(expression)
+            .map(|part| {
+                let redacted = redactor.redact(self.redaction_policy.as_deref(), &part);
+                match part {
+                    ResolvedPart::Text(text) | ResolvedPart::Name(text) => Cow::Borrowed(text),
+                    ResolvedPart::Value(value) => {
+                        Cow::Owned(redacted.unwrap_or_else(|| debug_value(value)))
+                    }
+                }
+            })
+            .collect()
+    }
+
+    // splits the expression into text and the names and values its aliases
+    // stand for. Unknown aliases are kept as text.
+    fn resolve_aliases<'a>(
+        &'a self,
+        expression: &'a str,
+    ) -> impl Iterator<Item = ResolvedPart<'a>> + 'a {
+        let mut rest = expression;
+
+        std::iter::from_fn(move || {
+            let mut chars = rest.char_indices();
+            let (_, first) = chars.next()?;
+
+            let end = if first == '#' || first == ':' {
+                chars
+                    .find(|(_, ch)| !ch.is_ascii_alphanumeric() && *ch != '_')
+                    .map_or(rest.len(), |(idx, _)| idx)
+            } else {
+                chars
+                    .find(|(_, ch)| *ch == '#' || *ch == ':')
+                    .map_or(rest.len(), |(idx, _)| idx)
+            };
+            let (part, remaining) = rest.split_at(end);
+            rest = remaining;
+
+            let resolved = match first {
+                '#' => self
+                    .names()
+                    .as_ref()
+                    .and_then(|names| names.get(part))
+                    .map(|name| ResolvedPart::Name(name)),
+                ':' => self
+                    .values()
+                    .as_ref()
+                    .and_then(|values| values.get(part))
+                    .map(ResolvedPart::Value),
+                _ => None,
+            };
+            Some(resolved.unwrap_or(ResolvedPart::Text(part)))
+        })
+    }
+
+    fn return_expression(&self, expression_type: ExpressionType) -> Option<&str> {
+        self.expressions.get(&expression_type).map(String::as_str)
+    }
+
+    fn take_expression(&mut self, expression_type: ExpressionType) -> Option<String> {
+        Arc::make_mut(&mut self.expressions).remove(&expression_type)
+    }
+}
+
+/// Asserts that two Expressions are equivalent, up to the numbering of their
+/// aliases, see Expression::equivalent_to().
+///
+/// On failure, the panic message shows both Expressions with their aliases
+/// substituted, as Expression::to_debug_string() does.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let left = Builder::new()
+///     .with_condition(name("a").equal(value(1)))
+///     .with_filter(name("b").equal(value(2)))
+///     .build()
+///     .unwrap();
+/// let right = Builder::new()
+///     .with_filter(name("b").equal(value(2)))
+///     .with_condition(name("a").equal(value(1)))
+///     .with_alias_prefix("n", "v")
+///     .build()
+///     .unwrap();
+///
+/// assert_expr_eq!(left, right);
+/// ```
+#[macro_export]
+#[allow(edition_2024_expr_fragment_specifier)]
+macro_rules! assert_expr_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !$crate::Expression::equivalent_to(left, right) {
+                    panic!(
+                        "assertion `left equivalent to right` failed\n  left: {}\n right: {}",
+                        left.to_debug_string(),
+                        right.to_debug_string()
+                    );
+                }
+            }
+        }
+    };
+}
+
+// a part of an expression string, see Expression::resolve_aliases()
+#[derive(PartialEq, Debug)]
+pub(crate) enum ResolvedPart<'a> {
+    Text(&'a str),
+    Name(&'a str),
+    Value(&'a AttributeValue),
+}
+
+// a part of an expression string, see Expression::resolve_names()
+#[derive(PartialEq, Debug)]
+enum NamesResolvedPart<'a> {
+    Text(String),
+    Value(&'a AttributeValue),
+}
+
+fn debug_value(value: &AttributeValue) -> String {
+    match value {
+        AttributeValue::S(s) => format!("{:?}", s),
+        AttributeValue::N(n) => n.clone(),
+        AttributeValue::Bool(b) => b.to_string(),
+        AttributeValue::Null(_) => "NULL".to_owned(),
+        AttributeValue::B(b) => format!("<{} bytes>", b.as_ref().len()),
+        AttributeValue::Ss(ss) => {
+            format!("<<{}>>", debug_list(ss.iter().map(|s| format!("{:?}", s))))
+        }
+        AttributeValue::Ns(ns) => format!("<<{}>>", debug_list(ns.iter().cloned())),
+        AttributeValue::Bs(bs) => format!(
+            "<<{}>>",
+            debug_list(bs.iter().map(|b| format!("<{} bytes>", b.as_ref().len())))
+        ),
+        AttributeValue::L(l) => format!("[{}]", debug_list(l.iter().map(debug_value))),
+        AttributeValue::M(m) => {
+            let mut entries = m
+                .iter()
+                .map(|(k, v)| format!("{:?}: {}", k, debug_value(v)))
+                .collect::<Vec<_>>();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        }
+        value => format!("{:?}", value),
+    }
+}
+
+fn debug_list(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
+
+#[derive(Default, Debug, Clone)]
+struct AliasList {
+    names: Vec<String>,
+    // position of every name in names, so repeated names are found in O(1)
+    name_index: HashMap<String, usize>,
+    values: Vec<AttributeValue>,
+    // positions in values by the hash of the value, so that deduplicated
+    // values are found without scanning every value
+    value_index: HashMap<u64, Vec<usize>>,
+    names_prefix: String,
+    values_prefix: String,
+    dedup_values: bool,
+    bare_names: bool,
+    // the seed of the hashed aliases, and the aliases of the names and values
+    hash_seed: Option<u64>,
+    hashed_names: Vec<String>,
+    hashed_values: Vec<String>,
+}
+
+impl AliasList {
+    fn alias_value(&mut self, dav: AttributeValue) -> String {
+        // hashed aliases are shared by equal values anyway
+        if self.dedup_values || self.hash_seed.is_some() {
+            let mut hasher = StableHasher::new(0);
+            hasher.write_value(&dav);
+            let positions = self.value_index.entry(hasher.finish()).or_default();
+
+            if let Some(&idx) = positions.iter().find(|&&idx| self.values[idx] == dav) {
+                return self.value_alias(idx);
+            }
+            positions.push(self.values.len());
+        }
+
+        if let Some(seed) = self.hash_seed {
+            let mut hasher = StableHasher::new(seed);
+            hasher.write_value(&dav);
+            let alias = self.hashed_alias(':', &self.values_prefix, hasher.finish());
+            self.hashed_values.push(alias);
+        }
+
+        self.values.push(dav);
+        self.value_alias(self.values.len() - 1)
+    }
+
+    fn alias_path(&mut self, nm: impl Into<String>) -> String {
+        let nm = nm.into();
+
+        if self.bare_names && reserved::is_bare_name(&nm) {
+            return nm;
+        }
+
+        if let Some(&idx) = self.name_index.get(&nm) {
+            return self.name_alias(idx);
+        }
+
+        if let Some(seed) = self.hash_seed {
+            let mut hasher = StableHasher::new(seed);
+            hasher.write_str(&nm);
+            let alias = self.hashed_alias('#', &self.names_prefix, hasher.finish());
+            self.hashed_names.push(alias);
+        }
+
+        let idx = self.names.len();
+        self.name_index.insert(nm.clone(), idx);
+        self.names.push(nm);
+        self.name_alias(idx)
+    }
+
+    fn name_alias(&self, idx: usize) -> String {
+        match self.hashed_names.get(idx) {
+            Some(alias) => alias.clone(),
+            None => format!("#{}{}", self.names_prefix, idx),
+        }
+    }
+
+    fn value_alias(&self, idx: usize) -> String {
+        match self.hashed_values.get(idx) {
+            Some(alias) => alias.clone(),
+            None => format!(":{}{}", self.values_prefix, idx),
+        }
+    }
+
+    // returns the alias of the argument hash, followed by a suffix in the
+    // unlikely case another name or value already has the same alias
+    fn hashed_alias(&self, sigil: char, prefix: &str, hash: u64) -> String {
+        let alias = format!("{}{}{:08x}", sigil, prefix, (hash ^ (hash >> 32)) as u32);
+        let taken = |alias: &String| {
+            self.hashed_names.contains(alias) || self.hashed_values.contains(alias)
+        };
+
+        if !taken(&alias) {
+            return alias;
+        }
+        (1..)
+            .map(|suffix| format!("{}_{}", alias, suffix))
+            .find(|alias| !taken(alias))
+            .unwrap_or(alias)
+    }
+}
+
+// the 64-bit FNV-1a hash, which unlike the hashers of the standard library is
+// guaranteed to stay the same across releases
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    pub(crate) fn new(seed: u64) -> Self {
+        let mut hasher = Self(0xcbf2_9ce4_8422_2325);
+        hasher.write(&seed.to_le_bytes());
+        hasher
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    // strings are prefixed by their length, so that the hashed parts can't
+    // run into each other
+    fn write_str(&mut self, s: &str) {
+        self.write(&(s.len() as u64).to_le_bytes());
+        self.write(s.as_bytes());
+    }
+
+    pub(crate) fn write_value(&mut self, value: &AttributeValue) {
+        match value {
+            AttributeValue::S(s) => {
+                self.write(b"S");
+                self.write_str(s);
+            }
+            AttributeValue::N(n) => {
+                self.write(b"N");
+                self.write_str(n);
+            }
+            AttributeValue::B(b) => {
+                self.write(b"B");
+                self.write(&(b.as_ref().len() as u64).to_le_bytes());
+                self.write(b.as_ref());
+            }
+            AttributeValue::Bool(b) => self.write(if *b { b"T" } else { b"F" }),
+            AttributeValue::Null(_) => self.write(b"0"),
+            AttributeValue::Ss(ss) => {
+                self.write(b"SS");
+                self.write(&(ss.len() as u64).to_le_bytes());
+                ss.iter().for_each(|s| self.write_str(s));
+            }
+            AttributeValue::Ns(ns) => {
+                self.write(b"NS");
+                self.write(&(ns.len() as u64).to_le_bytes());
+                ns.iter().for_each(|n| self.write_str(n));
+            }
+            AttributeValue::Bs(bs) => {
+                self.write(b"BS");
+                self.write(&(bs.len() as u64).to_le_bytes());
+                for b in bs {
+                    self.write(&(b.as_ref().len() as u64).to_le_bytes());
+                    self.write(b.as_ref());
+                }
+            }
+            AttributeValue::L(l) => {
+                self.write(b"L");
+                self.write(&(l.len() as u64).to_le_bytes());
+                l.iter().for_each(|v| self.write_value(v));
+            }
+            AttributeValue::M(m) => {
+                // the entries of maps are hashed in key order
+                let mut entries = m.iter().collect::<Vec<_>>();
+                entries.sort_unstable_by_key(|(k, _)| *k);
+
+                self.write(b"M");
+                self.write(&(entries.len() as u64).to_le_bytes());
+                for (k, v) in entries {
+                    self.write_str(k);
+                    self.write_value(v);
+                }
+            }
+            value => self.write_str(&format!("{:?}", value)),
+        }
+    }
+}
+
+pub(crate) trait TreeBuilder: TreeBuilderClone + std::fmt::Debug + Send {
+    fn build_tree(&self) -> Result<ExpressionNode, ExpressionError>;
+
+    // returns the calls constructing the builder, see Builder::record()
+    fn record(&self, recorder: &mut Recorder) -> String;
+
+    // allows condition and filter expressions to be merged after being added
+    fn into_condition(self: Box<Self>) -> Option<ConditionBuilder> {
+        None
+    }
+
+    // allows update expressions to be merged after being added
+    fn into_update(self: Box<Self>) -> Option<UpdateBuilder> {
+        None
+    }
+}
+
+// helper trait for cloning boxed TreeBuilders
+pub(crate) trait TreeBuilderClone {
+    fn clone_box(&self) -> Box<dyn TreeBuilder>;
+}
+
+impl<T> TreeBuilderClone for T
+where
+    T: TreeBuilder + Clone + 'static,
+{
+    fn clone_box(&self) -> Box<dyn TreeBuilder> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn TreeBuilder> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Represents a segment of the format of an ExpressionNode.
+///
+/// Name, Value and Child segments are replaced by the alias of the next name,
+/// the alias of the next value and the expression string of the next child of
+/// the node, while Literal segments are copied as-is.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum Segment {
+    Literal(Cow<'static, str>),
+    Name,
+    Value,
+    Child,
+}
+
+/// Represents the format of an ExpressionNode as a list of segments.
+#[derive(Default, Debug, PartialEq, Clone)]
+pub(crate) struct FmtExpression {
+    segments: Vec<Segment>,
+}
+
+impl FmtExpression {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn literal(mut self, literal: impl Into<Cow<'static, str>>) -> Self {
+        self.push_literal(literal);
+        self
+    }
+
+    pub(crate) fn name(mut self) -> Self {
+        self.segments.push(Segment::Name);
+        self
+    }
+
+    pub(crate) fn value(mut self) -> Self {
+        self.segments.push(Segment::Value);
+        self
+    }
+
+    pub(crate) fn child(mut self) -> Self {
+        self.segments.push(Segment::Child);
+        self
+    }
+
+    /// Appends count Child segments separated by the argument separator.
+    pub(crate) fn children(mut self, count: usize, separator: &'static str) -> Self {
+        for idx in 0..count {
+            if idx > 0 {
+                self.push_literal(separator);
+            }
+            self.segments.push(Segment::Child);
+        }
+        self
+    }
+
+    /// Appends a Literal segment, merging it into a preceding Literal segment
+    /// so equal formats always have equal segments.
+    pub(crate) fn push_literal(&mut self, literal: impl Into<Cow<'static, str>>) {
+        let literal = literal.into();
+        if literal.is_empty() {
+            return;
+        }
+
+        match self.segments.last_mut() {
+            Some(Segment::Literal(last)) => last.to_mut().push_str(&literal),
+            _ => self.segments.push(Segment::Literal(literal)),
+        }
+    }
+
+    /// Wraps the format in the argument prefix and suffix.
+    pub(crate) fn wrap(self, prefix: &'static str, suffix: &'static str) -> Self {
+        let mut wrapped = FmtExpression::new().literal(prefix);
+        for segment in self.segments {
+            match segment {
+                Segment::Literal(literal) => wrapped.push_literal(literal),
+                segment => wrapped.segments.push(segment),
+            }
+        }
+        wrapped.literal(suffix)
+    }
+
+    pub(crate) fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns true if the format starts with the argument literal.
+    pub(crate) fn starts_with(&self, prefix: &str) -> bool {
+        matches!(self.segments.first(), Some(Segment::Literal(literal)) if literal.starts_with(prefix))
+    }
+
+    /// Returns the length of the literal segments, used to size the expression
+    /// string.
+    fn literal_len(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.len(),
+                _ => 2,
+            })
+            .sum()
+    }
+}
+
+impl std::fmt::Display for FmtExpression {
+    // renders the format with the $n, $v and $c escapes of the Go SDK
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Literal(literal) => f.write_str(literal)?,
+                Segment::Name => f.write_str("$n")?,
+                Segment::Value => f.write_str("$v")?,
+                Segment::Child => f.write_str("$c")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// tests describe formats with the escapes of the Go SDK
+#[cfg(test)]
+impl From<&str> for FmtExpression {
+    fn from(fmt_expression: &str) -> Self {
+        let mut parsed = FmtExpression::new();
+        let mut rest = fmt_expression;
+        while let Some(idx) = rest.find('$') {
+            parsed.push_literal(rest[..idx].to_owned());
+            parsed = match rest.as_bytes().get(idx + 1) {
+                Some(b'n') => parsed.name(),
+                Some(b'v') => parsed.value(),
+                Some(b'c') => parsed.child(),
+                _ => panic!("invalid escape in {}", fmt_expression),
+            };
+            rest = &rest[idx + 2..];
+        }
+        parsed.literal(rest.to_owned())
+    }
+}
+
+#[cfg(test)]
+impl From<String> for FmtExpression {
+    fn from(fmt_expression: String) -> Self {
+        fmt_expression.as_str().into()
+    }
+}
+
+#[cfg(test)]
+impl PartialEq<&str> for FmtExpression {
+    fn eq(&self, other: &&str) -> bool {
+        let other: FmtExpression = (*other).into();
+        *self == other
+    }
+}
+
+#[derive(Default, Debug, PartialEq, Clone)]
+pub(crate) struct ExpressionNode {
+    pub(crate) names: Vec<String>,
+    pub(crate) values: Vec<AttributeValue>,
+    pub(crate) children: Vec<ExpressionNode>,
+    pub(crate) fmt_expression: FmtExpression,
+}
+
+impl ExpressionNode {
+    pub(crate) fn from_names(names: Vec<String>, fmt_exression: impl Into<FmtExpression>) -> Self {
+        Self {
+            names,
+            fmt_expression: fmt_exression.into(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn from_values(
+        values: Vec<AttributeValue>,
+        fmt_exression: impl Into<FmtExpression>,
+    ) -> Self {
+        Self {
+            values,
+            fmt_expression: fmt_exression.into(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn from_children(children: Vec<ExpressionNode>) -> Self {
+        Self {
+            children,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn from_children_expression(
+        children: Vec<ExpressionNode>,
+        fmt_expression: impl Into<FmtExpression>,
+    ) -> Self {
+        Self {
+            children,
+            fmt_expression: fmt_expression.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the value of the node if it represents a single value operand.
+    pub(crate) fn value(&self) -> Option<&AttributeValue> {
+        match self.values.as_slice() {
+            [value] if self.fmt_expression.segments() == [Segment::Value] => Some(value),
+            _ => None,
+        }
+    }
+
+    fn build_expression_string(
+        self,
+        alias_list: &mut AliasList,
+    ) -> Result<String, ExpressionError> {
+        let mut formatted_expression = String::with_capacity(self.fmt_expression.literal_len());
+        self.write_expression_string(alias_list, &mut formatted_expression)?;
+
+        Ok(formatted_expression)
+    }
+
+    // consumes the node, so its names and values are moved into the AliasList
+    // rather than copied
+    fn write_expression_string(
+        self,
+        alias_list: &mut AliasList,
+        formatted_expression: &mut String,
+    ) -> Result<(), ExpressionError> {
+        // children are written with an explicit stack instead of recursion, so
+        // deeply nested expressions can't overflow the call stack
+        let mut stack = vec![NodeSegments::new(self)];
+
+        while let Some(node) = stack.last_mut() {
+            let segment = match node.segments.next() {
+                Some(segment) => segment,
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
+
+            match segment {
+                Segment::Literal(literal) => formatted_expression.push_str(&literal),
+                Segment::Name => {
+                    let name = node.names.next().ok_or_else(|| {
+                        ExpressionError::OperandIndexOutOfRange(
+                            "substitutePath".to_owned(),
+                            "names".to_owned(),
+                        )
+                    })?;
+                    formatted_expression.push_str(&alias_list.alias_path(name));
+                }
+                Segment::Value => {
+                    let value = node.values.next().ok_or_else(|| {
+                        ExpressionError::OperandIndexOutOfRange(
+                            "substituteValue".to_owned(),
+                            "values".to_owned(),
+                        )
+                    })?;
+                    formatted_expression.push_str(&alias_list.alias_value(value));
+                }
+                Segment::Child => {
+                    let child = node.children.next().ok_or_else(|| {
+                        ExpressionError::OperandIndexOutOfRange(
+                            "substituteChild".to_owned(),
+                            "children".to_owned(),
+                        )
+                    })?;
+                    stack.push(NodeSegments::new(child));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Since each exprNode contains a slice of names, values, and children that
+// correspond to the segments, the segments consume the slices in order
+struct NodeSegments {
+    segments: std::vec::IntoIter<Segment>,
+    names: std::vec::IntoIter<String>,
+    values: std::vec::IntoIter<AttributeValue>,
+    children: std::vec::IntoIter<ExpressionNode>,
+}
+
+impl NodeSegments {
+    fn new(node: ExpressionNode) -> Self {
+        Self {
+            segments: node.fmt_expression.segments.into_iter(),
+            names: node.names.into_iter(),
+            values: node.values.into_iter(),
+            children: node.children.into_iter(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    use crate::*;
+
+    //https://stackoverflow.com/questions/27582739/how-do-i-create-a-hashmap-literal
+    #[allow(edition_2024_expr_fragment_specifier)]
+    macro_rules! hashmap(
+        { $($key:expr => $value:expr),+ } => {
+            {
+                let mut m = ::std::collections::HashMap::new();
+                $(
+                    m.insert($key, $value);
+                )+
+                m
+            }
+        };
+    );
+
+    #[test]
+    fn builder_conversions() -> anyhow::Result<()> {
+        assert_eq!(
+            Expression::try_from(name("foo").equal(value(5)))?,
+            Builder::new()
+                .with_condition(name("foo").equal(value(5)))
+                .build()?
+        );
+        assert_eq!(
+            Expression::try_from(key("foo").equal(value(5)))?.key_condition(),
+            Some("#0 = :0")
+        );
+        assert_eq!(
+            Expression::try_from(names_list(name("foo"), vec![]))?.projection(),
+            Some("#0")
+        );
+        assert_eq!(
+            Expression::try_from(remove(name("foo")))?.update(),
+            Some("REMOVE #0\n")
+        );
+        assert!(Expression::try_from(UpdateBuilder::default()).is_err());
+
+        let input = Builder::from_parts(
+            None,
+            Some(name("foo").attribute_exists()),
+            None,
+            Some(names_list(name("bar"), vec![])),
+            Some(set(name("baz"), value(1))),
+        );
+        assert_eq!(
+            input.expression_types(),
+            [
+                &ExpressionType::Projection,
+                &ExpressionType::Condition,
+                &ExpressionType::Update
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn expression_presence() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_update(set(name("foo"), value(5)))
+            .with_projection(names_list(name("foo"), vec![]))
+            .with_named_condition("bar", name("bar").attribute_exists());
+
+        assert!(input.has_update());
+        assert!(input.has_projection());
+        assert!(!input.has_condition());
+        assert!(!input.has_filter());
+        assert!(!input.has_key_condition());
+        assert!(input.has(&ExpressionType::NamedCondition("bar".to_owned())));
+        assert!(!input.has(&ExpressionType::NamedCondition("baz".to_owned())));
+        assert_eq!(
+            input.expression_types(),
+            [
+                &ExpressionType::Projection,
+                &ExpressionType::Update,
+                &ExpressionType::NamedCondition("bar".to_owned())
+            ]
+        );
+        assert!(Builder::new().expression_types().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn condition() -> anyhow::Result<()> {
+        let input = Builder::new().with_condition(name("foo").equal(value(5)));
+
+        assert_eq!(
+            input.build()?,
+            Expression {
+                expressions: hashmap!(ExpressionType::Condition => "#0 = :0".to_owned()).into(),
+                names: Some(hashmap!("#0".to_owned() => "foo".to_owned())).into(),
+                values: Some(hashmap!(":0".to_owned() => AttributeValue::N("5".to_owned()))).into(),
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn projection() -> anyhow::Result<()> {
+        let input =
+            Builder::new().with_projection(names_list(name("foo"), vec![name("bar"), name("baz")]));
+
+        assert_eq!(
+            input.build()?,
+            Expression {
+                expressions: hashmap!(ExpressionType::Projection => "#0, #1, #2".to_owned()).into(),
+                names: Some(
+                    hashmap!("#0".to_owned() => "foo".to_owned(), "#1".to_owned() => "bar".to_owned(), "#2".to_owned() => "baz".to_owned())
+                ).into(),
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_condition() -> anyhow::Result<()> {
+        let input = Builder::new().with_key_condition(key("foo").equal(value(5)));
+
+        assert_eq!(
+            input.build()?,
+            Expression {
+                expressions: hashmap!(ExpressionType::KeyCondition => "#0 = :0".to_owned()).into(),
+                names: Some(hashmap!("#0".to_owned() => "foo".to_owned())).into(),
+                values: Some(hashmap!(":0".to_owned() => AttributeValue::N("5".to_owned()))).into(),
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn filter() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(name("foo").equal(value(5)));
+
+        assert_eq!(
+            input.build()?,
+            Expression {
+                expressions: hashmap!(ExpressionType::Filter => "#0 = :0".to_owned()).into(),
+                names: Some(hashmap!("#0".to_owned() => "foo".to_owned())).into(),
+                values: Some(hashmap!(":0".to_owned() => AttributeValue::N("5".to_owned()))).into(),
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn update() -> anyhow::Result<()> {
+        let input = Builder::new().with_update(set(name("foo"), value(5)));
+
+        assert_eq!(
+            input.build()?,
+            Expression {
+                expressions: hashmap!(ExpressionType::Update => "SET #0 = :0\n".to_owned()).into(),
+                names: Some(hashmap!("#0".to_owned() => "foo".to_owned())).into(),
+                values: Some(hashmap!(":0".to_owned() => AttributeValue::N("5".to_owned()))).into(),
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    // TODO: not sure if it matters, but this test produces
+    // different results than the Go version, however the
+    // end dynamo outcome is the same for both
+    #[test]
+    fn compound() -> anyhow::Result<()> {
+        // the Go SDK assigns the aliases in another order, see go_sdk_aliasing()
+        let input = Builder::new()
+            .with_condition(name("foo").equal(value(5)))
+            .with_filter(name("bar").less_than(value(6)))
+            .with_projection(names_list(name("foo"), vec![name("bar"), name("baz")]))
+            .with_key_condition(key("foo").equal(value(5)))
+            .with_update(set(name("foo"), value(5)));
+
+        assert_eq!(
+            input.build()?,
+            Expression {
+                expressions: hashmap!(
+                ExpressionType::Condition => "#0 = :1".to_owned(),
+                ExpressionType::Filter => "#1 < :2".to_owned(),
+                ExpressionType::Projection => "#0, #1, #2".to_owned(),
+                ExpressionType::KeyCondition => "#0 = :0".to_owned(),
+                ExpressionType::Update => "SET #0 = :3\n".to_owned()
+                )
+                .into(),
+                names: Some(hashmap!(
+                "#0".to_owned() => "foo".to_owned(),
+                "#1".to_owned() => "bar".to_owned(),
+                "#2".to_owned() => "baz".to_owned()
+                ))
+                .into(),
+                values: Some(hashmap!(
+                    ":0".to_owned() => AttributeValue::N("5".to_owned()),
+                    ":1".to_owned() => AttributeValue::N("5".to_owned()),
+                    ":2".to_owned() => AttributeValue::N("6".to_owned()),
+                    ":3".to_owned() => AttributeValue::N("5".to_owned())
+                ))
+                .into(),
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn go_sdk_aliasing() -> anyhow::Result<()> {
+        // the output of the Go SDK for the Builder of compound()
+        let input = Builder::new()
+            .with_condition(name("foo").equal(value(5)))
+            .with_filter(name("bar").less_than(value(6)))
+            .with_projection(names_list(name("foo"), vec![name("bar"), name("baz")]))
+            .with_key_condition(key("foo").equal(value(5)))
+            .with_update(set(name("foo"), value(5)))
+            .with_go_sdk_aliasing(true);
+
+        assert_eq!(
+            input.build()?,
+            Expression::from_parts(
+                hashmap!(
+                    ExpressionType::Condition => "#0 = :0".to_owned(),
+                    ExpressionType::Filter => "#1 < :1".to_owned(),
+                    ExpressionType::KeyCondition => "#0 = :2".to_owned(),
+                    ExpressionType::Projection => "#0, #1, #2".to_owned(),
+                    ExpressionType::Update => "SET #0 = :3\n".to_owned()
+                ),
+                Some(hashmap!(
+                    "#0".to_owned() => "foo".to_owned(),
+                    "#1".to_owned() => "bar".to_owned(),
+                    "#2".to_owned() => "baz".to_owned()
+                )),
+                Some(hashmap!(
+                    ":0".to_owned() => AttributeValue::N("5".to_owned()),
+                    ":1".to_owned() => AttributeValue::N("6".to_owned()),
+                    ":2".to_owned() => AttributeValue::N("5".to_owned()),
+                    ":3".to_owned() => AttributeValue::N("5".to_owned())
+                )),
+            )
+        );
+
+        // the update clauses are sorted
+        let expr = Builder::new()
+            .with_condition(name("a").is_true().and(name("b").is_true()))
+            .with_update(
+                set(name("a"), value(false))
+                    .remove(name("b"))
+                    .add(name("c"), value(1)),
+            )
+            .with_go_sdk_aliasing(true)
+            .build()?;
+        assert_eq!(expr.condition(), Some("(#0 = :0) AND (#1 = :1)"));
+        assert_eq!(expr.update(), Some("ADD #2 :2\nREMOVE #1\nSET #0 = :3\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn hashed_aliases() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(name("foo").equal(value(5)).and(name("bar").equal(value(5))))
+            .with_hashed_aliases(0);
+
+        // equal values share one alias
+        let expr = input.clone().build()?;
+        assert_eq!(expr.names_map().len(), 2);
+        assert_eq!(expr.values_map().len(), 1);
+        assert_eq!(expr.to_debug_string(), "condition: (foo = 5) AND (bar = 5)");
+
+        // the aliases don't depend on the other expressions or on the order
+        // of use, but do depend on the seed
+        let other = Builder::new()
+            .with_update(set(name("bar"), value("x")))
+            .with_projection(names_list(name("bar"), vec![name("foo")]))
+            .with_condition(name("foo").equal(value(5)).and(name("bar").equal(value(5))))
+            .with_hashed_aliases(0)
+            .build()?;
+        assert_eq!(other.condition(), expr.condition());
+        let reseeded = input.with_hashed_aliases(1).build()?;
+        assert_ne!(reseeded.condition(), expr.condition());
+        assert!(reseeded.equivalent_to(&expr));
+
+        // values of different types and maps in different orders
+        let mut aliases = expression::AliasList {
+            hash_seed: Some(0),
+            ..Default::default()
+        };
+        let n = aliases.alias_value(AttributeValue::N("5".to_owned()));
+        let s = aliases.alias_value(AttributeValue::S("5".to_owned()));
+        assert_ne!(n, s);
+        let m = aliases.alias_value(AttributeValue::M(
+            [("a", "1"), ("b", "2")]
+                .into_iter()
+                .map(|(k, v)| (k.to_owned(), AttributeValue::S(v.to_owned())))
+                .collect(),
+        ));
+        assert_eq!(n.len(), 9);
+        assert_eq!(
+            expression::AliasList {
+                hash_seed: Some(0),
+                ..Default::default()
+            }
+            .alias_value(AttributeValue::M(
+                [("b", "2"), ("a", "1")]
+                    .into_iter()
+                    .map(|(k, v)| (k.to_owned(), AttributeValue::S(v.to_owned())))
+                    .collect(),
+            )),
+            m
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn hashed_alias_collision() -> anyhow::Result<()> {
+        let mut aliases = expression::AliasList {
+            hash_seed: Some(0),
+            ..Default::default()
+        };
+        let first = aliases.alias_path("foo");
+        // fakes a collision by forcing the alias of another name
+        aliases.hashed_names.push(aliases.hashed_alias('#', "", 1));
+        aliases.names.push("bar".to_owned());
+        assert_eq!(
+            aliases.hashed_alias('#', "", 1),
+            format!("{}_1", aliases.hashed_names[1])
+        );
+        assert_eq!(aliases.alias_path("foo"), first);
+
+        Ok(())
+    }
+
+    #[test]
+    fn shared_booleans() -> anyhow::Result<()> {
+        let input = || {
+            Builder::new().with_filter(
+                name("foo")
+                    .is_true()
+                    .and(name("bar").is_false())
+                    .and(name("baz").is_true())
+                    .and(name("qux").equal(value(false))),
+            )
+        };
+        let expression = "(((#0 = :0) AND (#1 = :1)) AND (#2 = :2)) AND (#3 = :3)";
+        let shared_expression = "(((#0 = :0) AND (#1 = :1)) AND (#2 = :0)) AND (#3 = :1)";
+        let names = Some(hashmap!(
+            "#0".to_owned() => "foo".to_owned(),
+            "#1".to_owned() => "bar".to_owned(),
+            "#2".to_owned() => "baz".to_owned(),
+            "#3".to_owned() => "qux".to_owned()
+        ));
+
+        // booleans get their own aliases by default
+        assert_eq!(
+            input().build()?,
+            Expression {
+                expressions: hashmap!(ExpressionType::Filter => expression.to_owned()).into(),
+                names: names.clone().into(),
+                values: Some(hashmap!(
+                    ":0".to_owned() => AttributeValue::Bool(true),
+                    ":1".to_owned() => AttributeValue::Bool(false),
+                    ":2".to_owned() => AttributeValue::Bool(true),
+                    ":3".to_owned() => AttributeValue::Bool(false)
+                ))
+                .into(),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            input().with_value_dedup(true).build()?,
+            Expression {
+                expressions: hashmap!(ExpressionType::Filter => shared_expression.to_owned())
+                    .into(),
+                names: names.into(),
+                values: Some(hashmap!(
+                    ":0".to_owned() => AttributeValue::Bool(true),
+                    ":1".to_owned() => AttributeValue::Bool(false)
+                ))
+                .into(),
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn and_filter() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .and_filter(name("foo").equal(value(5)))
+            .and_filter(name("bar").less_than(value(6)))
+            .and_condition(name("baz").attribute_exists());
+
+        assert_eq!(
+            input.build()?,
+            Expression {
+                expressions: hashmap!(
+                    ExpressionType::Condition => "attribute_exists (#0)".to_owned(),
+                    ExpressionType::Filter => "(#1 = :0) AND (#2 < :1)".to_owned()
+                )
+                .into(),
+                names: Some(hashmap!(
+                    "#0".to_owned() => "baz".to_owned(),
+                    "#1".to_owned() => "foo".to_owned(),
+                    "#2".to_owned() => "bar".to_owned()
+                ))
+                .into(),
+                values: Some(hashmap!(
+                    ":0".to_owned() => AttributeValue::N("5".to_owned()),
+                    ":1".to_owned() => AttributeValue::N("6".to_owned())
+                ))
+                .into(),
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn and_filter_after_with_filter() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_filter(name("foo").equal(value(5)))
+            .and_filter(name("bar").less_than(value(6)));
+
+        assert_eq!(input.build()?.filter().unwrap(), "(#0 = :0) AND (#1 < :1)");
+
+        let input = Builder::new()
+            .and_condition(name("foo").equal(value(5)))
+            .with_condition(name("bar").less_than(value(6)));
+
+        assert_eq!(input.build()?.condition().unwrap(), "#0 < :0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_if() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_key_condition_if(Some(key("foo").equal(value(5))))
+            .with_filter_if(None)
+            .with_projection_if(Some(names_list(name("bar"), vec![])))
+            .with_condition_if(None)
+            .with_update_if(None);
+
+        assert_eq!(
+            input.build()?,
+            Builder::new()
+                .with_key_condition(key("foo").equal(value(5)))
+                .with_projection(names_list(name("bar"), vec![]))
+                .build()?
+        );
+
+        let input = Builder::new()
+            .with_filter(name("foo").equal(value(5)))
+            .with_filter_if(None);
+
+        assert_eq!(input.build()?.filter().unwrap(), "#0 = :0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn value_dedup() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(name("tenant").equal(value("acme")))
+            .with_filter(
+                name("owner")
+                    .equal(value("acme"))
+                    .and(name("count").greater_than(value(5))),
+            )
+            .with_update(set(name("count"), value(5)))
+            .with_value_dedup(true);
+
+        assert_eq!(
+            input.build()?,
+            Expression {
+                expressions: hashmap!(
+                    ExpressionType::Condition => "#0 = :0".to_owned(),
+                    ExpressionType::Filter => "(#1 = :0) AND (#2 > :1)".to_owned(),
+                    ExpressionType::Update => "SET #2 = :1\n".to_owned()
+                )
+                .into(),
+                names: Some(hashmap!(
+                    "#0".to_owned() => "tenant".to_owned(),
+                    "#1".to_owned() => "owner".to_owned(),
+                    "#2".to_owned() => "count".to_owned()
+                ))
+                .into(),
+                values: Some(hashmap!(
+                    ":0".to_owned() => AttributeValue::S("acme".to_owned()),
+                    ":1".to_owned() => AttributeValue::N("5".to_owned())
+                ))
+                .into(),
+                ..Default::default()
+            },
+        );
 
-impl AliasList {
-    fn alias_value(&mut self, dav: AttributeValue) -> String {
-        self.values.push(dav);
-        format!(":{}", self.values.len() - 1)
-    }
+        let input = Builder::new().with_condition(
+            name("tenant")
+                .equal(value("acme"))
+                .and(name("owner").equal(value("acme"))),
+        );
 
-    fn alias_path(&mut self, nm: impl Into<String>) -> String {
-        let nm = nm.into();
+        assert_eq!(
+            input.build()?.condition().unwrap(),
+            "(#0 = :0) AND (#1 = :1)"
+        );
 
-        for (idx, name) in self.names.iter().enumerate() {
-            if nm == *name {
-                return format!("#{}", idx);
-            }
-        }
+        // values with the same hash are only shared if they are equal
+        let expr = Builder::new()
+            .with_update(
+                (0..1000)
+                    .flat_map(|i| [i, i])
+                    .enumerate()
+                    .fold(UpdateBuilder::default(), |update, (idx, i)| {
+                        update.set(name(format!("a{}", idx)), value(i))
+                    })
+                    .set(name("n"), value(AttributeValue::Null(true)))
+                    .set(name("m"), value(AttributeValue::Null(false))),
+            )
+            .with_value_dedup(true)
+            .build()?;
+        assert_eq!(expr.values().as_ref().unwrap().len(), 1002);
 
-        self.names.push(nm);
-        format!("#{}", self.names.len() - 1)
+        Ok(())
     }
-}
 
-pub(crate) trait TreeBuilder: Send {
-    fn build_tree(&self) -> anyhow::Result<ExpressionNode>;
-}
+    #[test]
+    fn debug_string() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(
+                name("foo.bar[0]")
+                    .equal(value("baz"))
+                    .or(name("tags").contains("a")),
+            )
+            .with_update(
+                set(name("tags"), value(vec!["a", "b"]))
+                    .set(name("active"), value(true))
+                    .remove(name("foo")),
+            )
+            .with_alias_prefix("n", "v");
 
-#[derive(Default, Debug, PartialEq, Clone)]
-pub(crate) struct ExpressionNode {
-    pub(crate) names: Vec<String>,
-    values: Vec<AttributeValue>,
-    pub(crate) children: Vec<ExpressionNode>,
-    pub(crate) fmt_expression: String,
-}
+        assert_eq!(
+            input.build()?.to_debug_string(),
+            "condition: (foo.bar[0] = \"baz\") OR (contains (tags, \"a\"))\n\
+             update: SET tags = <<\"a\", \"b\">>, active = true\nREMOVE foo"
+        );
 
-impl ExpressionNode {
-    pub(crate) fn from_names(names: Vec<String>, fmt_exression: impl Into<String>) -> Self {
-        Self {
-            names,
-            fmt_expression: fmt_exression.into(),
-            ..Default::default()
-        }
+        Ok(())
     }
 
-    pub(crate) fn from_values(
-        values: Vec<AttributeValue>,
-        fmt_exression: impl Into<String>,
-    ) -> Self {
-        Self {
-            values,
-            fmt_expression: fmt_exression.into(),
-            ..Default::default()
-        }
-    }
+    #[test]
+    fn from_into_parts() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_filter(name("foo").equal(value(5)))
+            .with_projection(names_list(name("bar"), vec![]))
+            .build()?;
 
-    pub(crate) fn from_children(children: Vec<ExpressionNode>) -> Self {
-        Self {
-            children,
-            ..Default::default()
-        }
-    }
+        let (expressions, names, values) = input.clone().into_parts();
+        assert_eq!(
+            expressions,
+            hashmap!(
+                ExpressionType::Projection => "#0".to_owned(),
+                ExpressionType::Filter => "#1 = :0".to_owned()
+            )
+        );
+        assert_eq!(Expression::from_parts(expressions, names, values), input);
 
-    pub(crate) fn from_children_expression(
-        children: Vec<ExpressionNode>,
-        fmt_expression: impl Into<String>,
-    ) -> Self {
-        Self {
-            children,
-            fmt_expression: fmt_expression.into(),
-            ..Default::default()
-        }
-    }
+        let input = Expression::from_parts(Default::default(), None, None);
+        assert_eq!(input, Expression::default());
 
-    fn build_expression_string(&self, alias_list: &mut AliasList) -> anyhow::Result<String> {
-        // Since each exprNode contains a slice of names, values, and children that
-        // correspond to the escaped characters, we an index to traverse the slices
-        let mut index = (0, 0, 0);
+        Ok(())
+    }
 
-        let mut formatted_expression = self.fmt_expression.clone();
+    #[test]
+    fn take_parts() -> anyhow::Result<()> {
+        let mut input = Builder::new()
+            .with_condition(name("foo").equal(value(5)))
+            .with_update(set(name("bar"), value(6)))
+            .build()?;
 
-        let mut idx = 0;
-        while idx < formatted_expression.len() {
-            if formatted_expression.chars().nth(idx).unwrap() != '$' {
-                idx += 1;
-                continue;
-            }
+        assert_eq!(input.take_condition().unwrap(), "#0 = :0");
+        assert_eq!(input.take_condition(), None);
+        assert_eq!(input.take_update().unwrap(), "SET #1 = :1\n");
+        assert_eq!(input.take_filter(), None);
+        assert_eq!(
+            input.take_names().unwrap(),
+            hashmap!(
+                "#0".to_owned() => "foo".to_owned(),
+                "#1".to_owned() => "bar".to_owned()
+            )
+        );
+        assert_eq!(input.names(), &None);
+        assert_eq!(
+            input.into_values().unwrap(),
+            hashmap!(
+                ":0".to_owned() => AttributeValue::N("5".to_owned()),
+                ":1".to_owned() => AttributeValue::N("6".to_owned())
+            )
+        );
 
-            if idx == formatted_expression.len() - 1 {
-                bail!("buildexprNode error: invalid escape character");
-            }
+        Ok(())
+    }
 
-            // if an escaped character is found, substitute it with the proper alias
-            // TODO consider AST instead of string in the future
-            let rune = formatted_expression.chars().nth(idx + 1).unwrap();
-            let alias = match rune {
-                'n' => {
-                    let alias = self.substitute_path(index.0, alias_list)?;
-                    index.0 += 1;
-                    alias
-                }
-                'v' => {
-                    let alias = self.substitute_value(index.1, alias_list)?;
-                    index.1 += 1;
-                    alias
-                }
-                'c' => {
-                    let alias = self.substitute_child(index.2, alias_list)?;
-                    index.2 += 1;
-                    alias
-                }
-                _ => bail!("buildexprNode error: invalid escape rune {}", rune),
-            };
+    #[test]
+    fn shared_clone() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(name("foo").equal(value(5)))
+            .build()?;
 
-            formatted_expression = format!(
-                "{}{}{}",
-                &formatted_expression.as_str()[..idx],
-                alias,
-                &formatted_expression.as_str()[idx + 2..]
-            );
-            idx += alias.len();
-        }
+        let mut cloned = input.clone();
+        assert!(Arc::ptr_eq(&input.expressions, &cloned.expressions));
+        assert!(Arc::ptr_eq(&input.names, &cloned.names));
+        assert!(Arc::ptr_eq(&input.values, &cloned.values));
 
-        Ok(formatted_expression)
-    }
+        // taking parts out of a clone leaves the original unchanged
+        assert_eq!(cloned.take_condition().unwrap(), "#0 = :0");
+        assert_eq!(
+            cloned.take_names().unwrap(),
+            hashmap!("#0".to_owned() => "foo".to_owned())
+        );
+        assert_eq!(
+            cloned,
+            Expression::from_parts(HashMap::new(), None, input.values().clone())
+        );
+        assert_eq!(input.condition(), Some("#0 = :0"));
+        assert_eq!(
+            input.names_map(),
+            hashmap!("#0".to_owned() => "foo".to_owned())
+        );
 
-    fn substitute_path(&self, index: usize, alias_list: &mut AliasList) -> anyhow::Result<String> {
-        if index >= self.names.len() {
-            bail!("substitutePath error: exprNode []names out of range");
-        }
-        Ok(alias_list.alias_path(self.names[index].clone()))
+        Ok(())
     }
 
-    fn substitute_value(&self, index: usize, alias_list: &mut AliasList) -> anyhow::Result<String> {
-        if index >= self.values.len() {
-            bail!("substituteValue error: exprNode []values out of range");
-        }
-        Ok(alias_list.alias_value(self.values[index].clone()))
-    }
+    #[test]
+    fn equivalent_expressions() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(name("foo").equal(value(5)).and(name("bar").equal(value(5))))
+            .with_update(set(name("bar"), value("x")))
+            .build()?;
+
+        let renumbered = Expression::from_parts(
+            hashmap!(
+                ExpressionType::Condition => "(#b = :x) AND (#a = :x)".to_owned(),
+                ExpressionType::Update => "SET #a = :y\n".to_owned()
+            ),
+            Some(hashmap!(
+                "#a".to_owned() => "bar".to_owned(),
+                "#b".to_owned() => "foo".to_owned()
+            )),
+            Some(hashmap!(
+                ":x".to_owned() => AttributeValue::N("5".to_owned()),
+                ":y".to_owned() => AttributeValue::S("x".to_owned())
+            )),
+        );
+        assert!(input.equivalent_to(&renumbered));
+        assert!(renumbered.equivalent_to(&input));
+        assert_expr_eq!(input, renumbered);
+
+        // values are compared by type, and every expression must match
+        let other = Builder::new()
+            .with_condition(
+                name("foo")
+                    .equal(value("5"))
+                    .and(name("bar").equal(value(5))),
+            )
+            .with_update(set(name("bar"), value("x")))
+            .build()?;
+        assert!(!input.equivalent_to(&other));
+        let other = Builder::new()
+            .with_condition(name("foo").equal(value(5)).and(name("bar").equal(value(5))))
+            .build()?;
+        assert!(!input.equivalent_to(&other));
+        assert!(!other.equivalent_to(&input));
+
+        // bare names are compared with the names their aliases stand for
+        let bare = Builder::new()
+            .with_condition(name("foo").equal(value(5)).and(name("bar").equal(value(5))))
+            .with_update(set(name("bar"), value("x")))
+            .with_bare_names(true)
+            .build()?;
+        assert_eq!(bare.condition(), Some("(foo = :0) AND (bar = :1)"));
+        assert!(input.equivalent_to(&bare));
+        assert!(bare.equivalent_to(&renumbered));
+        assert!(!bare.equivalent_to(&other));
+
+        // unknown aliases are compared as text
+        let unknown = Expression::from_parts(
+            hashmap!(ExpressionType::Condition => "#a = :a".to_owned()),
+            None,
+            None,
+        );
+        assert!(unknown.equivalent_to(&unknown.clone()));
+        assert!(!unknown.equivalent_to(&Expression::from_parts(
+            hashmap!(ExpressionType::Condition => "#b = :a".to_owned()),
+            None,
+            None,
+        )));
 
-    fn substitute_child(&self, index: usize, alias_list: &mut AliasList) -> anyhow::Result<String> {
-        if index >= self.children.len() {
-            bail!("substituteChild error: exprNode []children out of range");
-        }
-        self.children[index].build_expression_string(alias_list)
+        Ok(())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use aws_sdk_dynamodb::types::AttributeValue;
 
-    use crate::*;
+    #[test]
+    #[should_panic(expected = "left: condition: foo = 5")]
+    fn assert_expr_eq_fails() {
+        let left = Builder::new()
+            .with_condition(name("foo").equal(value(5)))
+            .build()
+            .unwrap();
+        let right = Builder::new()
+            .with_condition(name("foo").equal(value(6)))
+            .build()
+            .unwrap();
 
-    //https://stackoverflow.com/questions/27582739/how-do-i-create-a-hashmap-literal
-    #[allow(edition_2024_expr_fragment_specifier)]
-    macro_rules! hashmap(
-        { $($key:expr => $value:expr),+ } => {
-            {
-                let mut m = ::std::collections::HashMap::new();
-                $(
-                    m.insert($key, $value);
-                )+
-                m
-            }
-        };
-    );
+        assert_expr_eq!(left, right);
+    }
 
     #[test]
-    fn condition() -> anyhow::Result<()> {
-        let input = Builder::new().with_condition(name("foo").equal(value(5)));
+    fn bare_names() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_projection(names_list(
+                name("a.b[1]"),
+                vec![name("status"), name("x-y")],
+            ))
+            .with_update(set(name("price"), value(5)).remove(name("status")))
+            .with_bare_names(true);
 
+        let expr = input.build()?;
+        assert_eq!(expr.projection(), Some("a.b[1], #0, #1"));
+        assert_eq!(expr.update(), Some("SET price = :0\nREMOVE #0\n"));
         assert_eq!(
-            input.build()?,
-            Expression {
-                expressions: hashmap!(ExpressionType::Condition => "#0 = :0".to_owned()),
-                names: Some(hashmap!("#0".to_owned() => "foo".to_owned())),
-                values: Some(hashmap!(":0".to_owned() => AttributeValue::N("5".to_owned()))),
-            },
+            expr.names(),
+            &Some(hashmap!(
+                "#0".to_owned() => "status".to_owned(),
+                "#1".to_owned() => "x-y".to_owned()
+            ))
         );
 
         Ok(())
     }
 
     #[test]
-    fn projection() -> anyhow::Result<()> {
-        let input =
-            Builder::new().with_projection(names_list(name("foo"), vec![name("bar"), name("baz")]));
+    fn alias_prefix() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(name("foo").equal(value(5)).and(name("bar").is_true()))
+            .with_alias_prefix("n", "v_");
 
         assert_eq!(
             input.build()?,
             Expression {
-                expressions: hashmap!(ExpressionType::Projection => "#0, #1, #2".to_owned()),
-                names: Some(
-                    hashmap!("#0".to_owned() => "foo".to_owned(), "#1".to_owned() => "bar".to_owned(), "#2".to_owned() => "baz".to_owned())
-                ),
+                expressions: hashmap!(ExpressionType::Condition => "(#n0 = :v_0) AND (#n1 = :v_1)".to_owned()).into(),
+                names: Some(hashmap!(
+                    "#n0".to_owned() => "foo".to_owned(),
+                    "#n1".to_owned() => "bar".to_owned()
+                )).into(),
+                values: Some(hashmap!(
+                    ":v_0".to_owned() => AttributeValue::N("5".to_owned()),
+                    ":v_1".to_owned() => AttributeValue::Bool(true)
+                )).into(),
                 ..Default::default()
             },
         );
@@ -642,87 +3232,90 @@ mod tests {
     }
 
     #[test]
-    fn key_condition() -> anyhow::Result<()> {
-        let input = Builder::new().with_key_condition(key("foo").equal(value(5)));
+    fn invalid_alias_prefix() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(name("foo").equal(value(5)))
+            .with_alias_prefix("n", ":v");
 
         assert_eq!(
-            input.build()?,
-            Expression {
-                expressions: hashmap!(ExpressionType::KeyCondition => "#0 = :0".to_owned()),
-                names: Some(hashmap!("#0".to_owned() => "foo".to_owned())),
-                values: Some(hashmap!(":0".to_owned() => AttributeValue::N("5".to_owned()))),
-            },
+            input.build().unwrap_err(),
+            error::ExpressionError::InvalidParameterError(
+                "buildChildTrees".to_owned(),
+                "aliasPrefix".to_owned()
+            )
         );
 
         Ok(())
     }
 
     #[test]
-    fn filter() -> anyhow::Result<()> {
-        let input = Builder::new().with_filter(name("foo").equal(value(5)));
+    fn named_conditions() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_named_condition("item2", name("bar").equal(value(5)))
+            .with_named_condition("item1", name("foo").attribute_exists())
+            .with_condition(name("foo").equal(value(5)));
 
+        let expr = input.build()?;
         assert_eq!(
-            input.build()?,
+            expr,
             Expression {
-                expressions: hashmap!(ExpressionType::Filter => "#0 = :0".to_owned()),
-                names: Some(hashmap!("#0".to_owned() => "foo".to_owned())),
-                values: Some(hashmap!(":0".to_owned() => AttributeValue::N("5".to_owned()))),
+                expressions: hashmap!(
+                    ExpressionType::Condition => "#0 = :0".to_owned(),
+                    ExpressionType::NamedCondition("item1".to_owned()) => "attribute_exists (#0)".to_owned(),
+                    ExpressionType::NamedCondition("item2".to_owned()) => "#1 = :1".to_owned()
+                ).into(),
+                names: Some(hashmap!(
+                    "#0".to_owned() => "foo".to_owned(),
+                    "#1".to_owned() => "bar".to_owned()
+                )).into(),
+                values: Some(hashmap!(
+                    ":0".to_owned() => AttributeValue::N("5".to_owned()),
+                    ":1".to_owned() => AttributeValue::N("5".to_owned())
+                )).into(),
+                ..Default::default()
             },
         );
+        assert_eq!(
+            expr.named_condition("item1").unwrap(),
+            "attribute_exists (#0)"
+        );
+        assert_eq!(expr.named_condition("item3"), None);
+        assert_eq!(expr.named_condition("item2"), Some("#1 = :1"));
+        assert_eq!(expr.condition_tags(), ["item1", "item2"]);
+        assert!(Builder::new()
+            .with_condition(name("foo").equal(value(5)))
+            .build()?
+            .condition_tags()
+            .is_empty());
 
         Ok(())
     }
 
     #[test]
-    fn update() -> anyhow::Result<()> {
-        let input = Builder::new().with_update(set(name("foo"), value(5)));
+    fn invalid_named_condition() -> anyhow::Result<()> {
+        let input = Builder::new().with_named_condition("item1", name("").equal(value(5)));
 
         assert_eq!(
-            input.build()?,
-            Expression {
-                expressions: hashmap!(ExpressionType::Update => "SET #0 = :0\n".to_owned()),
-                names: Some(hashmap!("#0".to_owned() => "foo".to_owned())),
-                values: Some(hashmap!(":0".to_owned() => AttributeValue::N("5".to_owned()))),
-            },
+            input.build().unwrap_err().path().unwrap(),
+            "named_condition[item1] > equal > operand[0]"
         );
 
         Ok(())
     }
 
-    // TODO: not sure if it matters, but this test produces
-    // different results than the Go version, however the
-    // end dynamo outcome is the same for both
     #[test]
-    fn compound() -> anyhow::Result<()> {
+    fn clone_builder() -> anyhow::Result<()> {
+        let common = name("version").equal(value(5));
         let input = Builder::new()
-            .with_condition(name("foo").equal(value(5)))
-            .with_filter(name("bar").less_than(value(6)))
-            .with_projection(names_list(name("foo"), vec![name("bar"), name("baz")]))
-            .with_key_condition(key("foo").equal(value(5)))
-            .with_update(set(name("foo"), value(5)));
+            .with_key_condition(key("foo").equal(value("bar")))
+            .with_filter(common.clone())
+            .with_projection(names_list(name("foo"), vec![name("baz")]))
+            .with_update(set(name("version"), value(6)).add(name("tags"), value(vec!["a", "b"])));
 
+        assert_eq!(input.clone().build()?, input.build()?);
         assert_eq!(
-            input.build()?,
-            Expression {
-                expressions: hashmap!(
-                ExpressionType::Condition => "#0 = :1".to_owned(),
-                ExpressionType::Filter => "#1 < :2".to_owned(),
-                ExpressionType::Projection => "#0, #1, #2".to_owned(),
-                ExpressionType::KeyCondition => "#0 = :0".to_owned(),
-                ExpressionType::Update => "SET #0 = :3\n".to_owned()
-                ),
-                names: Some(hashmap!(
-                "#0".to_owned() => "foo".to_owned(),
-                "#1".to_owned() => "bar".to_owned(),
-                "#2".to_owned() => "baz".to_owned()
-                )),
-                values: Some(hashmap!(
-                    ":0".to_owned() => AttributeValue::N("5".to_owned()),
-                    ":1".to_owned() => AttributeValue::N("5".to_owned()),
-                    ":2".to_owned() => AttributeValue::N("6".to_owned()),
-                    ":3".to_owned() => AttributeValue::N("5".to_owned())
-                )),
-            },
+            Builder::new().with_condition(common.clone()).build()?,
+            Builder::new().with_condition(common).build()?,
         );
 
         Ok(())
@@ -733,15 +3326,45 @@ mod tests {
         let input = Builder::new().with_condition(name("").equal(value(5)));
 
         assert_eq!(
-            input
-                .build()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("equal")
+            .in_path("condition")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_nested_builder() -> anyhow::Result<()> {
+        let unset = name("");
+        let line = line!() - 1;
+        let filter = name("foo").equal(value(5)).and(
+            name("bar")
+                .less_than(value(10))
+                .and(name("baz").between(value(1), unset)),
+        );
+        let input = Builder::new().with_filter(filter);
+
+        let err = input.build().unwrap_err();
+        assert_eq!(
+            err.path().as_deref(),
+            Some("filter > and[1] > and[1] > between > operand[2]")
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "BuildOperand error: unset parameter: NameBuilder (created at {}:{}:21) \
+                 (at filter > and[1] > and[1] > between > operand[2])",
+                file!(),
+                line
+            )
         );
+        assert_eq!(err.location().map(|location| location.line()), Some(line));
 
         Ok(())
     }
@@ -903,14 +3526,12 @@ mod tests {
         let input = Builder::new().with_condition(ConditionBuilder::default());
 
         assert_eq!(
-            input
-                .build()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "buildTree".to_owned(),
                 "ConditionBuilder".to_owned()
             )
+            .in_path("condition")
         );
 
         Ok(())
@@ -1084,21 +3705,6 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn missing_char() -> anyhow::Result<()> {
-        let input = ExpressionNode::from_names(vec!["foo".to_owned()], "$n.$");
-
-        assert_eq!(
-            input
-                .build_expression_string(&mut expression::AliasList::default())
-                .unwrap_err()
-                .to_string(),
-            "buildexprNode error: invalid escape character",
-        );
-
-        Ok(())
-    }
-
     #[test]
     fn names_out_of_range() -> anyhow::Result<()> {
         let input = ExpressionNode::from_names(vec!["foo".to_owned()], "$n.$n");
@@ -1129,24 +3735,6 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn childre_out_of_range() -> anyhow::Result<()> {
-        let input = ExpressionNode {
-            fmt_expression: "$!".to_owned(),
-            ..Default::default()
-        };
-
-        assert_eq!(
-            input
-                .build_expression_string(&mut expression::AliasList::default())
-                .unwrap_err()
-                .to_string(),
-            "buildexprNode error: invalid escape rune !",
-        );
-
-        Ok(())
-    }
-
     #[test]
     fn unset_expression_node() -> anyhow::Result<()> {
         let input = ExpressionNode::default();
@@ -1167,7 +3755,7 @@ mod tests {
 
         assert_eq!(
             input.return_expression(ExpressionType::Projection),
-            Some(&"#0, #1, #2".to_owned()),
+            Some("#0, #1, #2"),
         );
 
         Ok(())
@@ -1225,12 +3813,13 @@ mod tests {
 
     #[test]
     fn duplicate_item() -> anyhow::Result<()> {
-        let mut input = expression::AliasList {
-            names: vec!["foo".to_owned(), "bar".to_owned()],
-            ..Default::default()
-        };
+        let mut input = expression::AliasList::default();
+        input.alias_path("foo");
+        input.alias_path("bar");
 
         assert_eq!(input.alias_path("foo"), "#0".to_owned());
+        assert_eq!(input.alias_path("bar"), "#1".to_owned());
+        assert_eq!(input.alias_path("baz"), "#2".to_owned());
 
         Ok(())
     }
@@ -0,0 +1,194 @@
+//! arbitrary support for fuzzing and property tests
+//!
+//! The Arbitrary implementations of ConditionBuilder, UpdateBuilder and
+//! ProjectionBuilder generate random trees which always build: the names are
+//! valid document paths, the operands of every function have the types it
+//! accepts, the bounds of BETWEEN are in order, and the paths of an update or
+//! a projection never overlap. The trees are bounded in depth and size, so
+//! they stay within the DynamoDB expression limits.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{
+    and, attribute_exists, attribute_not_exists, begins_with, between, contains, equal,
+    greater_than, greater_than_equal, if_not_exists, less_than, less_than_equal, name, names_list,
+    not, not_equal, or, plus, r#in, size, string_set, value, ConditionBuilder, NameBuilder,
+    OperandBuilder, ProjectionBuilder, UpdateBuilder,
+};
+
+// the maximum depth of the generated conditions
+const MAX_CONDITION_DEPTH: usize = 4;
+
+// the maximum number of operations of an update, or of names of a projection
+const MAX_PATHS: usize = 4;
+
+const ATTRIBUTES: [&str; 4] = ["a", "b", "Price", "Tags"];
+
+// returns a path starting with the argument attribute, possibly nested in a
+// map or a list
+fn arbitrary_path(u: &mut Unstructured<'_>, attribute: &str) -> Result<Box<NameBuilder>> {
+    let path = match u.int_in_range(0..=2)? {
+        0 => attribute.to_owned(),
+        1 => format!("{}.{}", attribute, u.choose(&ATTRIBUTES)?),
+        _ => format!("{}[{}]", attribute, u.int_in_range(0..=9)?),
+    };
+
+    Ok(name(path))
+}
+
+fn arbitrary_name(u: &mut Unstructured<'_>) -> Result<Box<NameBuilder>> {
+    let attribute = *u.choose(&ATTRIBUTES)?;
+    arbitrary_path(u, attribute)
+}
+
+fn arbitrary_value(u: &mut Unstructured<'_>) -> Result<Box<dyn OperandBuilder>> {
+    Ok(match u.int_in_range(0..=2)? {
+        0 => value(String::arbitrary(u)?),
+        1 => value(i64::arbitrary(u)?),
+        _ => value(bool::arbitrary(u)?),
+    })
+}
+
+fn arbitrary_comparison(u: &mut Unstructured<'_>) -> Result<ConditionBuilder> {
+    let comparator = *u.choose(&[
+        equal,
+        not_equal,
+        less_than,
+        less_than_equal,
+        greater_than,
+        greater_than_equal,
+    ] as &[fn(_, _) -> _])?;
+
+    let left: Box<dyn OperandBuilder> = if u.ratio(1, 4)? {
+        size(arbitrary_name(u)?)
+    } else {
+        arbitrary_name(u)?
+    };
+
+    Ok(comparator(left, arbitrary_value(u)?))
+}
+
+fn arbitrary_condition(u: &mut Unstructured<'_>, depth: usize) -> Result<ConditionBuilder> {
+    if depth > 1 && u.ratio(1, 2)? {
+        return Ok(match u.int_in_range(0..=2)? {
+            0 => and(
+                arbitrary_condition(u, depth - 1)?,
+                arbitrary_condition(u, depth - 1)?,
+            ),
+            1 => or(
+                arbitrary_condition(u, depth - 1)?,
+                arbitrary_condition(u, depth - 1)?,
+            ),
+            _ => not(arbitrary_condition(u, depth - 1)?),
+        });
+    }
+
+    Ok(match u.int_in_range(0..=5)? {
+        0 => {
+            // the bounds of BETWEEN must be in order
+            let (lower, upper) = (i64::arbitrary(u)?, i64::arbitrary(u)?);
+            between(
+                arbitrary_name(u)?,
+                value(lower.min(upper)),
+                value(lower.max(upper)),
+            )
+        }
+        1 => begins_with(arbitrary_name(u)?, value(String::arbitrary(u)?)),
+        2 => contains(arbitrary_name(u)?, String::arbitrary(u)?),
+        3 => {
+            if bool::arbitrary(u)? {
+                attribute_exists(arbitrary_name(u)?)
+            } else {
+                attribute_not_exists(arbitrary_name(u)?)
+            }
+        }
+        4 => {
+            let right = (0..u.int_in_range(1..=3)?)
+                .map(|_| arbitrary_value(u))
+                .collect::<Result<_>>()?;
+            r#in(arbitrary_name(u)?, right)
+        }
+        _ => arbitrary_comparison(u)?,
+    })
+}
+
+impl<'a> Arbitrary<'a> for ConditionBuilder {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_condition(u, MAX_CONDITION_DEPTH)
+    }
+}
+
+impl<'a> Arbitrary<'a> for UpdateBuilder {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut update = UpdateBuilder::default();
+
+        // every operation has its own top-level attribute, as the paths of
+        // an update can't overlap
+        for idx in 0..u.int_in_range(1..=MAX_PATHS)? {
+            let attribute = format!("u{}", idx);
+            let path = arbitrary_path(u, &attribute)?;
+
+            update = match u.int_in_range(0..=5)? {
+                0 => update.remove(path),
+                1 => update.add(path, value(i64::arbitrary(u)?)),
+                2 => update.delete(path, string_set([String::arbitrary(u)?])),
+                3 => update.set(path, plus(name(attribute), value(i64::arbitrary(u)?))),
+                4 => update.set(path, if_not_exists(name(attribute), arbitrary_value(u)?)),
+                _ => update.set(path, arbitrary_value(u)?),
+            };
+        }
+
+        Ok(update)
+    }
+}
+
+impl<'a> Arbitrary<'a> for ProjectionBuilder {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // the projected paths can't overlap either
+        let names = (1..u.int_in_range(1..=MAX_PATHS)?)
+            .map(|idx| arbitrary_path(u, &format!("p{}", idx)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(names_list(arbitrary_path(u, "p0")?, names))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use crate::*;
+
+    // a xorshift generator, so every run checks the same inputs
+    fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn arbitrary_builders() -> anyhow::Result<()> {
+        for seed in 0..500 {
+            let bytes = random_bytes(seed, 1024);
+            let mut u = Unstructured::new(&bytes);
+
+            let expr = Builder::new()
+                .with_condition(ConditionBuilder::arbitrary(&mut u)?)
+                .with_update(UpdateBuilder::arbitrary(&mut u)?)
+                .with_projection(ProjectionBuilder::arbitrary(&mut u)?)
+                .build_validated()?;
+
+            assert!(expr.condition().is_some());
+            assert!(expr.update().is_some());
+            assert!(expr.projection().is_some());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+//! Updates paired with the conditions guarding them
+//!
+//! Many write patterns are only correct if the update is applied together
+//! with a condition, such as incrementing a version number only if it still
+//! holds the version that was read. A GuardedUpdate keeps both halves
+//! together so that the condition can't be forgotten.
+
+use std::time::SystemTime;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    attribute_not_exists, condition::contains_operand, if_not_exists, list_append, set, ttl, value,
+    ConditionBuilder, LessThanEqualBuilder, NameBuilder, ToAttributeValue, UpdateBuilder,
+};
+
+/// Represents an UpdateBuilder together with the ConditionBuilder that has to
+/// hold for the update to be applied.
+///
+/// A GuardedUpdate is added to a Builder with Builder::with_guarded_update(),
+/// or split with into_parts().
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardedUpdate {
+    condition: ConditionBuilder,
+    update: UpdateBuilder,
+}
+
+impl GuardedUpdate {
+    /// Returns a GuardedUpdate for the argument condition and update.
+    pub fn new(condition: ConditionBuilder, update: UpdateBuilder) -> Self {
+        Self { condition, update }
+    }
+
+    /// Returns the condition guarding the update.
+    pub fn condition(&self) -> &ConditionBuilder {
+        &self.condition
+    }
+
+    /// Returns the guarded update.
+    pub fn update(&self) -> &UpdateBuilder {
+        &self.update
+    }
+
+    /// Returns the condition and the update of the GuardedUpdate.
+    pub fn into_parts(self) -> (ConditionBuilder, UpdateBuilder) {
+        (self.condition, self.update)
+    }
+}
+
+impl From<GuardedUpdate> for (ConditionBuilder, UpdateBuilder) {
+    fn from(guarded: GuardedUpdate) -> Self {
+        guarded.into_parts()
+    }
+}
+
+/// Returns a GuardedUpdate marking an item as deleted, by setting the
+/// argument attribute to the current time if it isn't set yet.
+///
+/// The update is `SET #deleted = :now`, with the time encoded as a number of
+/// seconds since the Unix epoch like Time to Live attributes are, and the
+/// condition is `attribute_not_exists (#deleted)`, so an item that is
+/// already deleted keeps its original deletion time.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let expr = Builder::new()
+///     .with_guarded_update(soft_delete(name("deletedAt")))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(expr.condition().unwrap(), "attribute_not_exists (#0)");
+/// assert_eq!(expr.update().unwrap(), "SET #0 = :0\n");
+/// ```
+pub fn soft_delete(name: Box<NameBuilder>) -> GuardedUpdate {
+    soft_delete_at(name, SystemTime::now())
+}
+
+/// Returns a GuardedUpdate marking an item as deleted at the argument time,
+/// as soft_delete() does.
+pub fn soft_delete_at(name: Box<NameBuilder>, now: SystemTime) -> GuardedUpdate {
+    GuardedUpdate::new(
+        attribute_not_exists(name.clone()),
+        ttl::set_expiry_at(name, now, now),
+    )
+}
+
+/// Returns a ConditionBuilder for the items that aren't marked as deleted by
+/// soft_delete(), with `attribute_not_exists (#deleted)`.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let expr = Builder::new()
+///     .with_filter(not_deleted(name("deletedAt")))
+///     .build()
+///     .unwrap();
+/// assert_eq!(expr.filter().unwrap(), "attribute_not_exists (#0)");
+/// ```
+pub fn not_deleted(name: Box<NameBuilder>) -> ConditionBuilder {
+    attribute_not_exists(name)
+}
+
+/// Returns a GuardedUpdate incrementing the number attribute by the argument
+/// amount, as long as it doesn't go over the argument maximum.
+///
+/// The update is `SET #n = if_not_exists(#n, :0) + :by` and the condition is
+/// `attribute_not_exists (#n) OR #n <= :max - :by`, with `:max - :by`
+/// computed before building since expressions have no arithmetic in
+/// conditions. Concurrent increments of the same item are serialized by
+/// DynamoDB, so the counter never exceeds the maximum, which makes it suited
+/// to rate limits and quotas. The amount is expected to be at most the
+/// maximum, as a missing attribute is always incremented.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // at most 100 requests in the current window
+/// let expr = Builder::new()
+///     .with_guarded_update(bounded_increment(name("requests"), 1, 100))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(
+///     expr.condition().unwrap(),
+///     "(attribute_not_exists (#0)) OR (#0 <= :0)"
+/// );
+/// assert_eq!(expr.update().unwrap(), "SET #0 = if_not_exists(#0, :1) + :2\n");
+/// ```
+pub fn bounded_increment(name: Box<NameBuilder>, by: i64, max: i64) -> GuardedUpdate {
+    GuardedUpdate::new(
+        attribute_not_exists(name.clone())
+            .or(name.clone().less_than_equal(value(max.saturating_sub(by)))),
+        UpdateBuilder::default().increment_with_default(name, by, 0i64),
+    )
+}
+
+/// Returns a GuardedUpdate appending the argument element to the list
+/// attribute, unless the list already contains it.
+///
+/// The update is `SET #l = list_append(if_not_exists(#l, :empty), :list)`,
+/// which creates the list if it doesn't exist yet, and the condition is
+/// `NOT contains (#l, :v)`. A write whose element is already in the list fails
+/// its condition instead of adding a duplicate.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let expr = Builder::new()
+///     .with_guarded_update(append_if_absent(name("followers"), "ace"))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(expr.condition().unwrap(), "NOT (contains (#0, :0))");
+/// assert_eq!(
+///     expr.update().unwrap(),
+///     "SET #0 = list_append(if_not_exists(#0, :1), :2)\n"
+/// );
+/// ```
+pub fn append_if_absent<T: ToAttributeValue>(
+    list_name: Box<NameBuilder>,
+    element: T,
+) -> GuardedUpdate {
+    let element = element.to_attribute_value();
+
+    GuardedUpdate::new(
+        contains_operand(list_name.clone(), value(element.clone())).not(),
+        set(
+            list_name.clone(),
+            list_append(
+                if_not_exists(list_name, value(AttributeValue::L(Vec::new()))),
+                value(AttributeValue::L(vec![element])),
+            ),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    use crate::*;
+
+    #[test]
+    fn guarded_update() -> anyhow::Result<()> {
+        let input = GuardedUpdate::new(name("a").equal(value(1)), set(name("a"), value(2)));
+
+        assert_eq!(input.condition(), &name("a").equal(value(1)));
+        assert_eq!(input.update(), &set(name("a"), value(2)));
+        assert_eq!(
+            <(ConditionBuilder, UpdateBuilder)>::from(input),
+            (name("a").equal(value(1)), set(name("a"), value(2)))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn soft_delete() -> anyhow::Result<()> {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        assert_eq!(
+            soft_delete_at(name("deletedAt"), now).into_parts(),
+            (
+                name("deletedAt").attribute_not_exists(),
+                set(name("deletedAt"), value(1_700_000_000))
+            )
+        );
+        assert_eq!(
+            not_deleted(name("deletedAt")),
+            name("deletedAt").attribute_not_exists()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn bounded_counter() -> anyhow::Result<()> {
+        let input = bounded_increment(name("n"), 5, 100);
+
+        assert_eq!(
+            input.into_parts(),
+            (
+                name("n")
+                    .attribute_not_exists()
+                    .or(name("n").less_than_equal(value(95))),
+                set(name("n"), if_not_exists(name("n"), value(0)).plus(value(5)))
+            )
+        );
+        assert_eq!(
+            bounded_increment(name("n"), 1, i64::MIN).condition(),
+            &name("n")
+                .attribute_not_exists()
+                .or(name("n").less_than_equal(value(i64::MIN)))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn append_unique() -> anyhow::Result<()> {
+        let (condition, update) = append_if_absent(name("l"), 5i64).into_parts();
+
+        assert_eq!(
+            update,
+            set(
+                name("l"),
+                list_append(
+                    if_not_exists(name("l"), value(AttributeValue::L(vec![]))),
+                    value(AttributeValue::L(vec![AttributeValue::N("5".to_owned())]))
+                )
+            )
+        );
+
+        let expr = Builder::new().with_condition(condition).build()?;
+        assert_eq!(expr.condition(), Some("NOT (contains (#0, :0))"));
+        assert_eq!(
+            expr.values().as_ref().map(|values| &values[":0"]),
+            Some(&AttributeValue::N("5".to_owned()))
+        );
+
+        Ok(())
+    }
+}
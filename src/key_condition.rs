@@ -1,19 +1,25 @@
 //! Ported from [key_condition.go](https://github.com/aws/aws-sdk-go/blob/master/service/dynamodb/expression/key_condition.go)
 
-use anyhow::bail;
+use std::collections::HashMap;
+use std::ops::{Bound, RangeBounds};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
 use derivative::*;
 
 use crate::{
-    error::ExpressionError, value, ExpressionNode, KeyBuilder, OperandBuilder, TreeBuilder,
-    ValueBuilderImpl,
+    check_between_bounds, error::ExpressionError, key, numeric_id, record, value, ExpressionNode,
+    FmtExpression, KeyBuilder, KeyConditionSource, KeySchema, NumericId, OperandBuilder, Recorder,
+    TreeBuilder, ValueBuilder, ValueBuilderImpl,
 };
 
-#[derive(Copy, Clone, PartialEq, Debug, Derivative)]
+#[derive(Copy, Clone, PartialEq, Debug, Derivative, strum::Display)]
 #[derivative(Default)]
+#[strum(serialize_all = "snake_case")]
 enum KeyConditionMode {
     #[derivative(Default)]
     Unset,
-    Invalid,
+    Invalid(&'static str),
     Equal,
     LessThan,
     LessThanEqual,
@@ -24,28 +30,47 @@ enum KeyConditionMode {
     BeginsWith,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq)]
 pub struct KeyConditionBuilder {
     operand_list: Vec<Box<dyn OperandBuilder>>,
     key_condition_list: Vec<KeyConditionBuilder>,
     mode: KeyConditionMode,
 }
 
+impl std::fmt::Debug for KeyConditionBuilder {
+    // renders the logical structure, e.g. And(Equal(..), Between(..))
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut tuple = f.debug_tuple(&format!("{:?}", self.mode));
+        for key_condition in self.key_condition_list.iter() {
+            tuple.field(key_condition);
+        }
+        for operand in self.operand_list.iter() {
+            tuple.field(operand);
+        }
+        tuple.finish()
+    }
+}
+
 impl KeyConditionBuilder {
     pub fn and(self, right: KeyConditionBuilder) -> KeyConditionBuilder {
         key_and(self, right)
     }
 
-    fn build_child_nodes(&self) -> anyhow::Result<Vec<ExpressionNode>> {
+    fn build_child_nodes(&self) -> Result<Vec<ExpressionNode>, ExpressionError> {
         let mut child_nodes = Vec::new();
 
-        for key_condition in self.key_condition_list.iter() {
-            let node = key_condition.build_tree()?;
+        for (i, key_condition) in self.key_condition_list.iter().enumerate() {
+            let node = key_condition
+                .build_tree()
+                .map_err(|e| e.in_path(format!("{}[{}]", self.mode, i)))?;
             child_nodes.push(node);
         }
 
-        for ope in self.operand_list.iter() {
-            let operand = ope.build_operand()?;
+        for (i, ope) in self.operand_list.iter().enumerate() {
+            let operand = ope.build_operand().map_err(|e| {
+                e.in_path(format!("operand[{}]", i))
+                    .in_path(self.mode.to_string())
+            })?;
             child_nodes.push(operand.expression_node);
         }
 
@@ -55,58 +80,78 @@ impl KeyConditionBuilder {
     fn compare_build_key_condition(
         mode: KeyConditionMode,
         mut node: ExpressionNode,
-    ) -> anyhow::Result<ExpressionNode> {
-        match mode {
-            KeyConditionMode::Equal => "$c = $c".clone_into(&mut node.fmt_expression),
-            KeyConditionMode::LessThan => "$c < $c".clone_into(&mut node.fmt_expression),
-            KeyConditionMode::LessThanEqual => "$c <= $c".clone_into(&mut node.fmt_expression),
-            KeyConditionMode::GreaterThan => "$c > $c".clone_into(&mut node.fmt_expression),
-            KeyConditionMode::GreaterThanEqual => "$c >= $c".clone_into(&mut node.fmt_expression),
-            _ => bail!(
-                "build compare key condition error: unsupported mode: {:?}",
-                mode
-            ),
-        }
+    ) -> Result<ExpressionNode, ExpressionError> {
+        let operator = match mode {
+            KeyConditionMode::Equal => " = ",
+            KeyConditionMode::LessThan => " < ",
+            KeyConditionMode::LessThanEqual => " <= ",
+            KeyConditionMode::GreaterThan => " > ",
+            KeyConditionMode::GreaterThanEqual => " >= ",
+            _ => {
+                return Err(ExpressionError::UnsupportedMode(
+                    "build compare key condition".to_owned(),
+                    format!("{:?}", mode),
+                ))
+            }
+        };
+        node.fmt_expression = FmtExpression::new().child().literal(operator).child();
+
         Ok(node)
     }
 
     fn and_build_key_condition(
         key_condition_builder: &KeyConditionBuilder,
         mut node: ExpressionNode,
-    ) -> anyhow::Result<ExpressionNode> {
+    ) -> Result<ExpressionNode, ExpressionError> {
         if key_condition_builder.key_condition_list.is_empty()
             && key_condition_builder.operand_list.is_empty()
         {
-            bail!(ExpressionError::InvalidParameterError(
+            return Err(ExpressionError::InvalidParameterError(
                 "andBuildKeyCondition".to_owned(),
                 "KeyConditionBuilder".to_owned(),
             ));
         }
 
-        // create a string with escaped characters to substitute them with proper
-        // aliases during runtime
-        "($c) AND ($c)".clone_into(&mut node.fmt_expression);
+        // create the segments to substitute with proper aliases during runtime
+        node.fmt_expression = KeyConditionBuilder::and_fmt_expression();
 
         Ok(node)
     }
 
-    fn between_build_condition(mut node: ExpressionNode) -> ExpressionNode {
-        // Create a string with special characters that can be substituted later: $c
-        "$c BETWEEN $c AND $c".clone_into(&mut node.fmt_expression);
+    /// Returns the format of the AND of a partition key and a sort key
+    /// condition.
+    pub(crate) fn and_fmt_expression() -> FmtExpression {
+        FmtExpression::new().children(2, ") AND (").wrap("(", ")")
+    }
 
-        node
+    fn between_build_condition(
+        mut node: ExpressionNode,
+    ) -> Result<ExpressionNode, ExpressionError> {
+        check_between_bounds(&node)?;
+
+        // Create the segments that can be substituted later
+        node.fmt_expression = FmtExpression::new()
+            .child()
+            .literal(" BETWEEN ")
+            .child()
+            .literal(" AND ")
+            .child();
+
+        Ok(node)
     }
 
     fn begins_with_build_condition(mut node: ExpressionNode) -> ExpressionNode {
-        // Create a string with special characters that can be substituted later: $c
-        "begins_with ($c, $c)".clone_into(&mut node.fmt_expression);
+        // Create the segments that can be substituted later
+        node.fmt_expression = FmtExpression::new()
+            .children(2, ", ")
+            .wrap("begins_with (", ")");
 
         node
     }
 }
 
 impl TreeBuilder for KeyConditionBuilder {
-    fn build_tree(&self) -> anyhow::Result<ExpressionNode> {
+    fn build_tree(&self) -> Result<ExpressionNode, ExpressionError> {
         let child_nodes = self.build_child_nodes()?;
         let ret = ExpressionNode::from_children(child_nodes);
 
@@ -119,19 +164,64 @@ impl TreeBuilder for KeyConditionBuilder {
                 KeyConditionBuilder::compare_build_key_condition(self.mode, ret)?,
             ),
             KeyConditionMode::And => Ok(KeyConditionBuilder::and_build_key_condition(self, ret)?),
-            KeyConditionMode::Between => Ok(KeyConditionBuilder::between_build_condition(ret)),
+            KeyConditionMode::Between => KeyConditionBuilder::between_build_condition(ret),
             KeyConditionMode::BeginsWith => {
                 Ok(KeyConditionBuilder::begins_with_build_condition(ret))
             }
-            KeyConditionMode::Unset => bail!(ExpressionError::UnsetParameterError(
+            KeyConditionMode::Unset => Err(ExpressionError::UnsetParameterError(
                 "buildTree".to_owned(),
                 "KeyConditionBuilder".to_owned(),
             )),
-            KeyConditionMode::Invalid => {
-                bail!("buildKeyCondition error: invalid key condition constructed")
-            } //_ => bail!("buildKeyCondition error: unsupported mode: {:?}", self.mode),
+            KeyConditionMode::Invalid(reason) => Err(ExpressionError::InvalidKeyCondition {
+                reason: reason.to_owned(),
+            }),
         }
     }
+
+    fn record(&self, recorder: &mut Recorder) -> String {
+        let function = match self.mode {
+            KeyConditionMode::Equal => "key_equal",
+            KeyConditionMode::LessThan => "key_less_than",
+            KeyConditionMode::LessThanEqual => "key_less_than_equal",
+            KeyConditionMode::GreaterThan => "key_greater_than",
+            KeyConditionMode::GreaterThanEqual => "key_greater_than_equal",
+            KeyConditionMode::Between => "key_between",
+            KeyConditionMode::And => {
+                let key_conditions = self
+                    .key_condition_list
+                    .iter()
+                    .map(|key_condition| key_condition.record(recorder))
+                    .collect::<Vec<_>>();
+                return format!("key_and({})", key_conditions.join(", "));
+            }
+            KeyConditionMode::BeginsWith => {
+                let prefix = self
+                    .operand_list
+                    .get(1)
+                    .and_then(|operand| record::string_operand(operand.as_ref()))
+                    .unwrap_or_default();
+                return match self.operand_list.first() {
+                    Some(key) => format!(
+                        "key_begins_with({}, {})",
+                        key.record(recorder),
+                        recorder.redact_string(&prefix)
+                    ),
+                    None => "KeyConditionBuilder::default()".to_owned(),
+                };
+            }
+            KeyConditionMode::Unset => return "KeyConditionBuilder::default()".to_owned(),
+            KeyConditionMode::Invalid(reason) => {
+                return format!("KeyConditionBuilder::default() /* invalid: {} */", reason)
+            }
+        };
+
+        let operands = self
+            .operand_list
+            .iter()
+            .map(|operand| operand.record(recorder))
+            .collect::<Vec<_>>();
+        format!("{}({})", function, operands.join(", "))
+    }
 }
 
 pub fn key_equal(key: Box<KeyBuilder>, value: Box<dyn ValueBuilderImpl>) -> KeyConditionBuilder {
@@ -186,21 +276,55 @@ pub fn key_greater_than_equal(
     }
 }
 
+/// Returns a KeyConditionBuilder selecting the items whose numeric key is
+/// strictly greater than the given ID.
+///
+/// The ID is always encoded as the DynamoDB Number type, which makes this
+/// suitable for cursor pagination over monotonically increasing IDs.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // key_condition represents the items in partition "somePartition" whose
+/// // "Id" sort key comes after the last seen ID
+/// let key_condition = key("Partition")
+///     .equal(value("somePartition"))
+///     .and(key_after(key("Id"), 1541815603606036480u64));
+/// ```
+pub fn key_after(key: Box<KeyBuilder>, id: impl Into<NumericId>) -> KeyConditionBuilder {
+    key_greater_than(key, numeric_id(id))
+}
+
+/// Returns a KeyConditionBuilder selecting the items whose numeric key is
+/// strictly less than the given ID.
+///
+/// The ID is always encoded as the DynamoDB Number type, which makes this
+/// suitable for cursor pagination over monotonically increasing IDs.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // key_condition represents the items in partition "somePartition" whose
+/// // "Id" sort key comes before the last seen ID
+/// let key_condition = key("Partition")
+///     .equal(value("somePartition"))
+///     .and(key_before(key("Id"), 1541815603606036480u64));
+/// ```
+pub fn key_before(key: Box<KeyBuilder>, id: impl Into<NumericId>) -> KeyConditionBuilder {
+    key_less_than(key, numeric_id(id))
+}
+
 pub fn key_and(left: KeyConditionBuilder, right: KeyConditionBuilder) -> KeyConditionBuilder {
     if left.mode != KeyConditionMode::Equal {
-        return KeyConditionBuilder {
-            operand_list: Vec::new(),
-            key_condition_list: Vec::new(),
-            mode: KeyConditionMode::Invalid,
-        };
+        return invalid_key_condition("first key condition must be an equality condition");
     }
 
     if right.mode == KeyConditionMode::And {
-        return KeyConditionBuilder {
-            operand_list: Vec::new(),
-            key_condition_list: Vec::new(),
-            mode: KeyConditionMode::Invalid,
-        };
+        return invalid_key_condition("more than two key conditions are not supported");
     }
 
     KeyConditionBuilder {
@@ -212,20 +336,77 @@ pub fn key_and(left: KeyConditionBuilder, right: KeyConditionBuilder) -> KeyCond
 
 pub fn key_between(
     key: Box<KeyBuilder>,
-    upper: Box<dyn ValueBuilderImpl>,
     lower: Box<dyn ValueBuilderImpl>,
+    upper: Box<dyn ValueBuilderImpl>,
 ) -> KeyConditionBuilder {
     KeyConditionBuilder {
         operand_list: vec![
             key,
-            upper.into_operand_builder(),
             lower.into_operand_builder(),
+            upper.into_operand_builder(),
         ],
         key_condition_list: Vec::new(),
         mode: KeyConditionMode::Between,
     }
 }
 
+/// Returns a KeyConditionBuilder selecting the items whose key falls within
+/// the given range.
+///
+/// An inclusive range `a..=b` maps to `BETWEEN a AND b`, while the half-open
+/// ranges `a..` and `..=b` map to `>= a` and `<= b` respectively, and `..b`
+/// maps to `< b`. A key condition can only hold a single comparison on the
+/// sort key, so a range with an exclusive bound must be half-open and `a..b`
+/// cannot be expressed. Such ranges, fully unbounded ranges and ranges whose
+/// lower bound is greater than their upper bound return the typed error
+/// InvalidKeyCondition when built.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // key_condition represents the items in partition "somePartition" whose
+/// // "Year" sort key is in the nineties
+/// let key_condition = key("Partition")
+///     .equal(value("somePartition"))
+///     .and(key_in_bounds(key("Year"), 1990..=1999));
+/// ```
+pub fn key_in_bounds<T>(key: Box<KeyBuilder>, bounds: impl RangeBounds<T>) -> KeyConditionBuilder
+where
+    T: Clone + PartialOrd + 'static,
+    ValueBuilder<T>: ValueBuilderImpl,
+{
+    match (bounds.start_bound(), bounds.end_bound()) {
+        (Bound::Included(lower), Bound::Included(upper)) => {
+            if lower > upper {
+                return invalid_key_condition("lower bound must not be greater than upper bound");
+            }
+            key_between(key, value(lower.clone()), value(upper.clone()))
+        }
+        (Bound::Included(lower), Bound::Unbounded) => {
+            key_greater_than_equal(key, value(lower.clone()))
+        }
+        (Bound::Excluded(lower), Bound::Unbounded) => key_greater_than(key, value(lower.clone())),
+        (Bound::Unbounded, Bound::Included(upper)) => {
+            key_less_than_equal(key, value(upper.clone()))
+        }
+        (Bound::Unbounded, Bound::Excluded(upper)) => key_less_than(key, value(upper.clone())),
+        (Bound::Unbounded, Bound::Unbounded) => {
+            invalid_key_condition("range must have at least one bound")
+        }
+        _ => invalid_key_condition("range with an exclusive bound must be half-open"),
+    }
+}
+
+fn invalid_key_condition(reason: &'static str) -> KeyConditionBuilder {
+    KeyConditionBuilder {
+        operand_list: Vec::new(),
+        key_condition_list: Vec::new(),
+        mode: KeyConditionMode::Invalid(reason),
+    }
+}
+
 pub fn key_begins_with(key: Box<KeyBuilder>, prefix: impl Into<String>) -> KeyConditionBuilder {
     let v = value(prefix.into());
     KeyConditionBuilder {
@@ -235,6 +416,61 @@ pub fn key_begins_with(key: Box<KeyBuilder>, prefix: impl Into<String>) -> KeyCo
     }
 }
 
+/// Returns a KeyConditionBuilder selecting the items whose string key starts
+/// with the given prefix, with `BETWEEN :prefix AND :upper`.
+///
+/// The upper bound is the prefix followed by the greatest character, which
+/// sorts after every key starting with the prefix since DynamoDB compares
+/// strings by their UTF-8 bytes. A single byte 0xFF can't be used, as it
+/// isn't valid UTF-8. Unlike begins_with, the resulting condition can be
+/// narrowed with further bounds by the caller.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // key_condition represents the items in partition "somePartition" whose
+/// // "Date" sort key is in June 2024
+/// let key_condition = key("Partition")
+///     .equal(value("somePartition"))
+///     .and(key_within_prefix(key("Date"), "2024-06"));
+/// ```
+pub fn key_within_prefix(key: Box<KeyBuilder>, prefix: impl Into<String>) -> KeyConditionBuilder {
+    let prefix = prefix.into();
+    let upper = format!("{}{}", prefix, char::MAX);
+    key_between(key, value(prefix), value(upper))
+}
+
+/// Returns a KeyConditionBuilder selecting the items whose ISO-8601 sort key
+/// falls between the given dates, both included.
+///
+/// The dates can have any precision, such as `2024-06` or `2024-06-30`. Keys
+/// with a finer precision than the end date, such as `2024-06-30T12:00:00Z`
+/// or `2024-06-30#42`, are included as well, so the end date doesn't need to
+/// be turned into an exclusive bound by hand.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let key_condition = key("Partition")
+///     .equal(value("somePartition"))
+///     .and(key_date_between(key("CreatedAt"), "2024-06-01", "2024-06-30"));
+///
+/// let expr = Builder::new().with_key_condition(key_condition).build().unwrap();
+/// assert_eq!(expr.key_condition().unwrap(), "(#0 = :0) AND (#1 BETWEEN :1 AND :2)");
+/// ```
+pub fn key_date_between(
+    key: Box<KeyBuilder>,
+    start: impl Into<String>,
+    end: impl Into<String>,
+) -> KeyConditionBuilder {
+    let upper = format!("{}{}", end.into(), char::MAX);
+    key_between(key, value(start.into()), value(upper))
+}
+
 impl KeyBuilder {
     pub fn equal(self: Box<KeyBuilder>, value: Box<dyn ValueBuilderImpl>) -> KeyConditionBuilder {
         key_equal(self, value)
@@ -270,19 +506,319 @@ impl KeyBuilder {
 
     pub fn between(
         self: Box<KeyBuilder>,
-        upper: Box<dyn ValueBuilderImpl>,
         lower: Box<dyn ValueBuilderImpl>,
+        upper: Box<dyn ValueBuilderImpl>,
     ) -> KeyConditionBuilder {
-        key_between(self, upper, lower)
+        key_between(self, lower, upper)
+    }
+
+    /// Returns a KeyConditionBuilder selecting the items whose key falls
+    /// within the given range.
+    ///
+    /// See key_in_bounds() for how each kind of range is mapped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let key_condition = key("Year").in_bounds(1990..=1999);
+    /// ```
+    pub fn in_bounds<T>(self: Box<KeyBuilder>, bounds: impl RangeBounds<T>) -> KeyConditionBuilder
+    where
+        T: Clone + PartialOrd + 'static,
+        ValueBuilder<T>: ValueBuilderImpl,
+    {
+        key_in_bounds(self, bounds)
     }
 
     pub fn begins_with(self: Box<KeyBuilder>, prefix: impl Into<String>) -> KeyConditionBuilder {
         key_begins_with(self, prefix)
     }
+
+    /// Returns a KeyConditionBuilder selecting the items whose string key
+    /// starts with the given prefix, as key_within_prefix() does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let key_condition = key("Date").within_prefix("2024-06");
+    /// ```
+    pub fn within_prefix(self: Box<KeyBuilder>, prefix: impl Into<String>) -> KeyConditionBuilder {
+        key_within_prefix(self, prefix)
+    }
+
+    /// Returns a KeyConditionBuilder selecting the items whose ISO-8601 key
+    /// falls between the given dates, both included, as key_date_between()
+    /// does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let key_condition = key("CreatedAt").date_between("2024-06-01", "2024-06-30");
+    /// ```
+    pub fn date_between(
+        self: Box<KeyBuilder>,
+        start: impl Into<String>,
+        end: impl Into<String>,
+    ) -> KeyConditionBuilder {
+        key_date_between(self, start, end)
+    }
+
+    /// Returns a KeyConditionBuilder selecting the items whose numeric key is
+    /// strictly greater than the given ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let key_condition = key("Id").after(1541815603606036480u64);
+    /// ```
+    pub fn after(self: Box<KeyBuilder>, id: impl Into<NumericId>) -> KeyConditionBuilder {
+        key_after(self, id)
+    }
+
+    /// Returns a KeyConditionBuilder selecting the items whose numeric key is
+    /// strictly less than the given ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let key_condition = key("Id").before(1541815603606036480u64);
+    /// ```
+    pub fn before(self: Box<KeyBuilder>, id: impl Into<NumericId>) -> KeyConditionBuilder {
+        key_before(self, id)
+    }
+}
+
+/// Represents the partition key of a table or an index in a Key Condition
+/// Expression.
+///
+/// DynamoDB only supports the equality comparison on the partition key, so it
+/// is the only operation PartitionKey exposes, where the untyped key() also
+/// allows comparisons that are rejected when the query is run.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let key_condition = partition_key("pk")
+///     .equal(value("USER#1"))
+///     .and(sort_key("sk").begins_with("ORDER#"));
+///
+/// let expr = Builder::new().with_key_condition(key_condition).build().unwrap();
+/// assert_eq!(expr.key_condition().unwrap(), "(#0 = :0) AND (begins_with (#1, :1))");
+/// ```
+///
+/// ```compile_fail
+/// use dynamodb_expression::*;
+///
+/// let key_condition = partition_key("pk").begins_with("USER#");
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct PartitionKey(Box<KeyBuilder>);
+
+/// Represents the sort key of a table or an index in a Key Condition
+/// Expression.
+///
+/// A condition on the sort key alone isn't a valid Key Condition Expression,
+/// so a SortKeyCondition has to be combined with a PartitionKeyCondition with
+/// PartitionKeyCondition::and().
+///
+/// ```compile_fail
+/// use dynamodb_expression::*;
+///
+/// let builder = Builder::new().with_key_condition(sort_key("sk").equal(value(1)));
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct SortKey(Box<KeyBuilder>);
+
+/// Represents the equality condition on a partition key.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PartitionKeyCondition(KeyConditionBuilder);
+
+/// Represents a condition on a sort key.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SortKeyCondition(KeyConditionBuilder);
+
+/// Creates a PartitionKey for the argument key attribute.
+#[track_caller]
+pub fn partition_key(name: impl Into<String>) -> PartitionKey {
+    PartitionKey(key(name))
+}
+
+/// Creates a SortKey for the argument key attribute.
+#[track_caller]
+pub fn sort_key(name: impl Into<String>) -> SortKey {
+    SortKey(key(name))
+}
+
+impl PartitionKey {
+    pub fn equal(self, value: Box<dyn ValueBuilderImpl>) -> PartitionKeyCondition {
+        PartitionKeyCondition(key_equal(self.0, value))
+    }
+}
+
+impl SortKey {
+    pub fn equal(self, value: Box<dyn ValueBuilderImpl>) -> SortKeyCondition {
+        SortKeyCondition(key_equal(self.0, value))
+    }
+
+    pub fn less_than(self, value: Box<dyn ValueBuilderImpl>) -> SortKeyCondition {
+        SortKeyCondition(key_less_than(self.0, value))
+    }
+
+    pub fn less_than_equal(self, value: Box<dyn ValueBuilderImpl>) -> SortKeyCondition {
+        SortKeyCondition(key_less_than_equal(self.0, value))
+    }
+
+    pub fn greater_than(self, value: Box<dyn ValueBuilderImpl>) -> SortKeyCondition {
+        SortKeyCondition(key_greater_than(self.0, value))
+    }
+
+    pub fn greater_than_equal(self, value: Box<dyn ValueBuilderImpl>) -> SortKeyCondition {
+        SortKeyCondition(key_greater_than_equal(self.0, value))
+    }
+
+    pub fn between(
+        self,
+        lower: Box<dyn ValueBuilderImpl>,
+        upper: Box<dyn ValueBuilderImpl>,
+    ) -> SortKeyCondition {
+        SortKeyCondition(key_between(self.0, lower, upper))
+    }
+
+    /// Returns a SortKeyCondition selecting the items whose sort key falls
+    /// within the given range, as key_in_bounds() does.
+    pub fn in_bounds<T>(self, bounds: impl RangeBounds<T>) -> SortKeyCondition
+    where
+        T: Clone + PartialOrd + 'static,
+        ValueBuilder<T>: ValueBuilderImpl,
+    {
+        SortKeyCondition(key_in_bounds(self.0, bounds))
+    }
+
+    pub fn begins_with(self, prefix: impl Into<String>) -> SortKeyCondition {
+        SortKeyCondition(key_begins_with(self.0, prefix))
+    }
+
+    /// Returns a SortKeyCondition selecting the items whose sort key starts
+    /// with the given prefix, as key_within_prefix() does.
+    pub fn within_prefix(self, prefix: impl Into<String>) -> SortKeyCondition {
+        SortKeyCondition(key_within_prefix(self.0, prefix))
+    }
+
+    /// Returns a SortKeyCondition selecting the items whose ISO-8601 sort key
+    /// falls between the given dates, as key_date_between() does.
+    pub fn date_between(
+        self,
+        start: impl Into<String>,
+        end: impl Into<String>,
+    ) -> SortKeyCondition {
+        SortKeyCondition(key_date_between(self.0, start, end))
+    }
+
+    /// Returns a SortKeyCondition selecting the items whose numeric sort key
+    /// is strictly greater than the given ID, as key_after() does.
+    pub fn after(self, id: impl Into<NumericId>) -> SortKeyCondition {
+        SortKeyCondition(key_after(self.0, id))
+    }
+
+    /// Returns a SortKeyCondition selecting the items whose numeric sort key
+    /// is strictly less than the given ID, as key_before() does.
+    pub fn before(self, id: impl Into<NumericId>) -> SortKeyCondition {
+        SortKeyCondition(key_before(self.0, id))
+    }
+}
+
+impl PartitionKeyCondition {
+    /// Returns the KeyConditionBuilder of the partition key condition and the
+    /// argument sort key condition.
+    pub fn and(self, right: SortKeyCondition) -> KeyConditionBuilder {
+        key_and(self.0, right.0)
+    }
+}
+
+impl From<PartitionKeyCondition> for KeyConditionBuilder {
+    fn from(key_condition: PartitionKeyCondition) -> Self {
+        key_condition.0
+    }
+}
+
+impl KeyConditionSource for PartitionKeyCondition {
+    fn into_key_condition(self) -> KeyConditionBuilder {
+        self.0
+    }
+}
+
+/// Returns a KeyConditionBuilder matching the key of the argument item, along
+/// with the key attributes of the item.
+///
+/// The key attributes named by the KeySchema are copied out of the item, and
+/// each of them is compared for equality in the returned KeyConditionBuilder.
+/// The returned map can be used as the Key of GetItem, UpdateItem and
+/// DeleteItem requests. If the item is missing one of the key attributes,
+/// key_condition_from_item() returns the typed error MissingKeyAttribute.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use dynamodb_expression::*;
+///
+/// let item = HashMap::from([
+///     ("pk".to_owned(), AttributeValue::S("USER#1".to_owned())),
+///     ("sk".to_owned(), AttributeValue::S("PROFILE".to_owned())),
+///     ("email".to_owned(), AttributeValue::S("user@example.com".to_owned())),
+/// ]);
+/// let schema = KeySchema::new("pk").with_sort_key("sk");
+///
+/// let (key_cond, key) = key_condition_from_item(&item, &schema).unwrap();
+/// assert_eq!(key.len(), 2);
+///
+/// let expr = Builder::new().with_key_condition(key_cond).build().unwrap();
+/// assert_eq!(expr.key_condition().unwrap(), "(#0 = :0) AND (#1 = :1)");
+/// ```
+pub fn key_condition_from_item(
+    item: &HashMap<String, AttributeValue>,
+    schema: &KeySchema,
+) -> Result<(KeyConditionBuilder, HashMap<String, AttributeValue>), ExpressionError> {
+    let mut key_condition: Option<KeyConditionBuilder> = None;
+    let mut key_map = HashMap::new();
+
+    for attribute in schema.key_attributes() {
+        let attribute_value = item.get(attribute).ok_or_else(|| {
+            ExpressionError::MissingKeyAttribute(
+                "keyConditionFromItem".to_owned(),
+                attribute.to_owned(),
+            )
+        })?;
+
+        let condition = key(attribute).equal(value(attribute_value.clone()));
+        key_condition = Some(match key_condition {
+            Some(key_condition) => key_condition.and(condition),
+            None => condition,
+        });
+        key_map.insert(attribute.to_owned(), attribute_value.clone());
+    }
+
+    // the partition key is always present, so at least one condition is set
+    Ok((key_condition.unwrap_or_default(), key_map))
 }
 
 #[cfg(test)]
 mod tests {
+    use std::ops::Bound;
+
     use aws_sdk_dynamodb::types::AttributeValue;
 
     use crate::*;
@@ -382,10 +918,7 @@ mod tests {
         let input = KeyConditionBuilder::default();
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "buildTree".to_owned(),
                 "KeyConditionBuilder".to_owned()
@@ -477,8 +1010,10 @@ mod tests {
             .and(key("bar").begins_with("baz"));
 
         assert_eq!(
-            input.build_tree().unwrap_err().to_string(),
-            "buildKeyCondition error: invalid key condition constructed"
+            input.build_tree().unwrap_err(),
+            error::ExpressionError::InvalidKeyCondition {
+                reason: "first key condition must be an equality condition".to_owned()
+            }
         );
 
         Ok(())
@@ -493,8 +1028,84 @@ mod tests {
         );
 
         assert_eq!(
-            input.build_tree().unwrap_err().to_string(),
-            "buildKeyCondition error: invalid key condition constructed"
+            input.build_tree().unwrap_err(),
+            error::ExpressionError::InvalidKeyCondition {
+                reason: "more than two key conditions are not supported".to_owned()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_bounds() -> anyhow::Result<()> {
+        assert_eq!(
+            key("foo").in_bounds(5..=10),
+            key("foo").between(value(5), value(10))
+        );
+        assert_eq!(
+            key("foo").in_bounds(5..),
+            key("foo").greater_than_equal(value(5))
+        );
+        assert_eq!(
+            key("foo").in_bounds(..=10),
+            key("foo").less_than_equal(value(10))
+        );
+        assert_eq!(key("foo").in_bounds(..10), key("foo").less_than(value(10)));
+        assert_eq!(
+            key("foo").in_bounds::<&str>((Bound::Excluded("a"), Bound::Unbounded)),
+            key("foo").greater_than(value("a"))
+        );
+        assert_eq!(
+            key("foo").in_bounds(5..=5).build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                    ExpressionNode::from_values(vec![AttributeValue::N("5".to_owned())], "$v"),
+                    ExpressionNode::from_values(vec![AttributeValue::N("5".to_owned())], "$v"),
+                ],
+                "$c BETWEEN $c AND $c"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn between_bounds_out_of_order() -> anyhow::Result<()> {
+        let input = key("foo").between(value("b"), value("a"));
+
+        assert_eq!(
+            input.build_tree().unwrap_err(),
+            error::ExpressionError::BetweenBoundsOutOfOrder("betweenBuildCondition".to_owned())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_bounds_invalid() -> anyhow::Result<()> {
+        let invalid = |input: KeyConditionBuilder, reason: &str| {
+            assert_eq!(
+                input.build_tree().unwrap_err(),
+                error::ExpressionError::InvalidKeyCondition {
+                    reason: reason.to_owned()
+                }
+            );
+        };
+
+        #[allow(clippy::reversed_empty_ranges)]
+        invalid(
+            key("foo").in_bounds(10..=5),
+            "lower bound must not be greater than upper bound",
+        );
+        invalid(
+            key("foo").in_bounds(5..10),
+            "range with an exclusive bound must be half-open",
+        );
+        invalid(
+            key("foo").in_bounds::<i64>(..),
+            "range must have at least one bound",
         );
 
         Ok(())
@@ -505,14 +1116,13 @@ mod tests {
         let input = key("").equal(value("yikes".to_owned()));
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "KeyBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("equal")
         );
 
         Ok(())
@@ -549,4 +1159,203 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn key_after() -> anyhow::Result<()> {
+        let input = key("foo").after(1541815603606036480u64);
+
+        assert_eq!(
+            input.build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                    ExpressionNode::from_values(
+                        vec![AttributeValue::N("1541815603606036480".to_owned())],
+                        "$v"
+                    ),
+                ],
+                "$c > $c"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_before() -> anyhow::Result<()> {
+        let input = key("foo").before(10u128.pow(38) - 1);
+
+        assert_eq!(
+            input.build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                    ExpressionNode::from_values(
+                        vec![AttributeValue::N(
+                            "99999999999999999999999999999999999999".to_owned()
+                        )],
+                        "$v"
+                    ),
+                ],
+                "$c < $c"
+            )
+        );
+
+        assert_eq!(
+            *key("foo")
+                .before(u128::MAX)
+                .build_tree()
+                .unwrap_err()
+                .root_cause(),
+            error::ExpressionError::InvalidParameterError(
+                "numericId".to_owned(),
+                "NumericId".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn debug_key_condition() -> anyhow::Result<()> {
+        let input = key("foo")
+            .equal(value("bar"))
+            .and(key("baz").begins_with("qux"));
+
+        assert_eq!(
+            format!("{:?}", input),
+            "And(\
+                Equal(KeyBuilder { key: \"foo\" }, ValueBuilder { value: \"bar\" }), \
+                BeginsWith(KeyBuilder { key: \"baz\" }, ValueBuilder { value: \"qux\" })\
+            )"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn eq_key_condition() -> anyhow::Result<()> {
+        let input = key("foo").equal(value("bar"));
+
+        assert_eq!(input, key("foo").equal(value("bar")));
+        assert_ne!(input, key("foo").equal(value("baz")));
+        assert_ne!(input, key("foo").less_than(value("bar")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_condition_from_item() -> anyhow::Result<()> {
+        let item = std::collections::HashMap::from([
+            ("pk".to_owned(), AttributeValue::S("foo".to_owned())),
+            ("sk".to_owned(), AttributeValue::N("5".to_owned())),
+            ("bar".to_owned(), AttributeValue::Bool(true)),
+        ]);
+
+        let (input, key_map) =
+            super::key_condition_from_item(&item, &KeySchema::new("pk").with_sort_key("sk"))?;
+        assert_eq!(
+            input,
+            key("pk")
+                .equal(value(AttributeValue::S("foo".to_owned())))
+                .and(key("sk").equal(value(AttributeValue::N("5".to_owned()))))
+        );
+        assert_eq!(
+            key_map,
+            std::collections::HashMap::from([
+                ("pk".to_owned(), AttributeValue::S("foo".to_owned())),
+                ("sk".to_owned(), AttributeValue::N("5".to_owned())),
+            ])
+        );
+
+        let (input, _) = super::key_condition_from_item(&item, &KeySchema::new("pk"))?;
+        assert_eq!(
+            input,
+            key("pk").equal(value(AttributeValue::S("foo".to_owned())))
+        );
+
+        assert_eq!(
+            super::key_condition_from_item(&item, &KeySchema::new("pk").with_sort_key("baz"))
+                .unwrap_err(),
+            error::ExpressionError::MissingKeyAttribute(
+                "keyConditionFromItem".to_owned(),
+                "baz".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_roles() -> anyhow::Result<()> {
+        assert_eq!(
+            KeyConditionBuilder::from(partition_key("pk").equal(value("a"))),
+            key("pk").equal(value("a"))
+        );
+        assert_eq!(
+            partition_key("pk")
+                .equal(value("a"))
+                .and(sort_key("sk").in_bounds(1i64..=5)),
+            key("pk")
+                .equal(value("a"))
+                .and(key("sk").between(value(1), value(5)))
+        );
+        assert_eq!(
+            partition_key("pk")
+                .equal(value("a"))
+                .and(sort_key("sk").after(7u64)),
+            key("pk").equal(value("a")).and(key("sk").after(7u64))
+        );
+
+        let expr = Builder::new()
+            .with_key_condition(partition_key("pk").equal(value("a")))
+            .build()?;
+        assert_eq!(expr.key_condition(), Some("#0 = :0"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn within_prefix() -> anyhow::Result<()> {
+        assert_eq!(
+            key("sk").within_prefix("2024-06"),
+            key("sk").between(
+                value("2024-06".to_owned()),
+                value("2024-06\u{10FFFF}".to_owned())
+            )
+        );
+        assert_eq!(
+            sort_key("sk").date_between("2024-06-01", "2024-06-30"),
+            sort_key("sk").between(
+                value("2024-06-01".to_owned()),
+                value("2024-06-30\u{10FFFF}".to_owned())
+            )
+        );
+
+        // the upper bound sorts after any key with the prefix, byte-wise
+        let upper = format!("2024-06{}", char::MAX);
+        for sk in [
+            "2024-06",
+            "2024-06-30T23:59:59Z",
+            "2024-06\u{FF}",
+            "2024-06~",
+        ] {
+            assert!(sk.as_bytes() < upper.as_bytes());
+        }
+
+        let err = Builder::new()
+            .with_key_condition(
+                key("pk")
+                    .equal(value("a"))
+                    .and(key("sk").date_between("2024-07", "2024-06")),
+            )
+            .build()
+            .unwrap_err();
+        assert!(matches!(
+            err.root_cause(),
+            error::ExpressionError::BetweenBoundsOutOfOrder(_)
+        ));
+
+        Ok(())
+    }
 }
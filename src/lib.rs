@@ -42,8 +42,8 @@
 //! let scan = client.query()
 //!     .set_expression_attribute_names(expr.names().clone())
 //!     .set_expression_attribute_values(expr.values().clone())
-//!     .filter_expression(expr.filter().cloned().unwrap())
-//!     .projection_expression(expr.projection().cloned().unwrap())
+//!     .filter_expression(expr.filter().unwrap())
+//!     .projection_expression(expr.projection().unwrap())
 //!     .table_name("Music".to_owned());
 //! # })
 //! ```
@@ -57,55 +57,171 @@
 
 //#![deny(missing_docs)]
 #![deny(warnings)]
+#![cfg_attr(
+    not(test),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)
+)]
 
+mod attributes;
+mod backend;
+#[cfg(feature = "compat")]
+pub mod compat;
 mod condition;
+pub mod conditions;
+#[cfg(feature = "cursor")]
+mod cursor;
+mod diff;
+#[cfg(feature = "document")]
+mod document;
 pub mod error;
 mod expression;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+mod guarded;
 mod key_condition;
+mod limits;
+mod normalize;
 mod operand;
+mod path;
 mod projection;
+mod record;
+mod redact;
+mod report;
+pub mod reserved;
+mod schema;
+#[cfg(feature = "serde_dynamo")]
+mod serde_item;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod single_table;
+mod source;
+mod spec;
+mod standalone;
+#[cfg(feature = "tracing")]
+mod trace;
+pub mod ttl;
+mod typed;
 mod update;
+pub mod upsert;
+pub mod versioning;
+mod visit;
 
+pub use attributes::*;
+pub use backend::*;
 pub use condition::*;
+#[cfg(feature = "cursor")]
+pub use cursor::*;
+pub use diff::*;
+#[cfg(feature = "document")]
+pub use document::*;
 pub use expression::*;
+pub use guarded::*;
 pub use key_condition::*;
+pub use limits::*;
 pub use operand::*;
+pub use path::*;
 pub use projection::*;
+pub use record::*;
+pub use redact::*;
+pub use report::*;
+pub use schema::*;
+#[cfg(feature = "serde_dynamo")]
+pub use serde_item::*;
+#[cfg(feature = "serde")]
+pub use serialize::*;
+pub use source::*;
+pub use spec::*;
+pub use standalone::*;
+pub use typed::*;
 pub use update::*;
+pub use visit::*;
 
-macro_rules! impl_value_builder {
-    ($type:ty) => {
-        impl $crate::operand::OperandBuilder for $crate::operand::ValueBuilder<$type> {
-            fn build_operand(&self) -> anyhow::Result<$crate::operand::Operand> {
-                let expr = self.attribute_value();
+/// Returns a NameBuilder for the argument document path, parsed and validated
+/// at compile time.
+///
+/// The path is parsed with the rules of NameParseMode::Strict, so malformed
+/// paths such as `foo..bar` or `foo[a]` and paths nested deeper than
+/// MAX_PATH_DEPTH levels fail to compile instead of failing when the
+/// expression is built. The macro expands to the equivalent Path.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let condition = name!("foo.bar[0].baz").equal(value(5));
+///
+/// let expr = Builder::new().with_condition(condition).build().unwrap();
+/// assert_eq!(expr.condition().unwrap(), "#0.#1[0].#2 = :0");
+/// ```
+///
+/// ```compile_fail
+/// use dynamodb_expression::*;
+///
+/// let condition = name!("foo..bar").equal(value(5));
+/// ```
+#[cfg(feature = "macros")]
+pub use dynamodb_expression_macros::name;
 
-                let node = $crate::expression::ExpressionNode::from_values(vec![expr], "$v");
-                Ok(Operand::new(node))
-            }
-        }
+/// Derives DynamoAttributes for a struct with named fields.
+///
+/// The attribute name of each field is its serde name: `#[serde(rename)]` and
+/// the container `#[serde(rename_all)]` are honored, and fields marked with
+/// `#[serde(skip)]` or `#[serde(skip_serializing)]` are left out. Fields
+/// marked with `#[projection(skip)]` are left out of the projection only.
+/// Flattened fields are not expanded. The derive also adds an UPPER_CASE
+/// attribute name constant to the struct for each of its fields.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// #[derive(DynamoAttributes)]
+/// #[serde(rename_all = "camelCase")]
+/// struct Order {
+///     #[serde(rename = "PK")]
+///     id: String,
+///     created_at: u64,
+///     #[projection(skip)]
+///     line_items: Vec<String>,
+/// }
+///
+/// let condition = name_literal(Order::CREATED_AT).greater_than(value(1000));
+///
+/// let expr = Builder::new()
+///     .with_condition(condition)
+///     .with_projection(Order::projection())
+///     .build()
+///     .unwrap();
+/// assert_eq!(expr.condition().unwrap(), "#1 > :0");
+/// assert_eq!(expr.projection().unwrap(), "#0, #1");
+/// assert_eq!(Order::fields().len(), 3);
+/// ```
+#[cfg(feature = "macros")]
+pub use dynamodb_expression_macros::DynamoAttributes;
 
-        impl $crate::operand::PlusBuilder for $crate::operand::ValueBuilder<$type> {}
-        impl $crate::operand::MinusBuilder for $crate::operand::ValueBuilder<$type> {}
-        impl $crate::operand::ListAppendBuilder for $crate::operand::ValueBuilder<$type> {}
+// allows the macros to refer to the crate from inside of it
+#[cfg(feature = "macros")]
+extern crate self as dynamodb_expression;
 
-        impl $crate::condition::EqualBuilder for $crate::operand::ValueBuilder<$type> {}
-        impl $crate::condition::NotEqualBuilder for $crate::operand::ValueBuilder<$type> {}
-        impl $crate::condition::LessThanBuilder for $crate::operand::ValueBuilder<$type> {}
-        impl $crate::condition::LessThanEqualBuilder for $crate::operand::ValueBuilder<$type> {}
-        impl $crate::condition::GreaterThanBuilder for $crate::operand::ValueBuilder<$type> {}
-        impl $crate::condition::GreaterThanEqualBuilder for $crate::operand::ValueBuilder<$type> {}
-        impl $crate::condition::BetweenBuilder for $crate::operand::ValueBuilder<$type> {}
-        impl $crate::condition::InBuilder for $crate::operand::ValueBuilder<$type> {}
+// every ValueBuilder gets the operations available to values
+macro_rules! impl_value_builder {
+    ($($builder:path),* $(,)?) => {
+        $(impl<T: ToAttributeValue> $builder for ValueBuilder<T> {})*
     };
 }
 
-impl_value_builder!(bool);
-impl_value_builder!(i64);
-impl_value_builder!(f64);
-impl_value_builder!(&'static str);
-impl_value_builder!(Vec<&'static str>);
-impl_value_builder!(String);
-impl_value_builder!(Vec<String>);
-impl_value_builder!(aws_sdk_dynamodb::types::AttributeValue);
-impl_value_builder!(Vec<Box<dyn ValueBuilderImpl>>);
-impl_value_builder!(std::collections::HashMap<String, Box<dyn ValueBuilderImpl>>);
+impl_value_builder!(
+    PlusBuilder,
+    MinusBuilder,
+    ListAppendBuilder,
+    EqualBuilder,
+    NotEqualBuilder,
+    LessThanBuilder,
+    LessThanEqualBuilder,
+    GreaterThanBuilder,
+    GreaterThanEqualBuilder,
+    BetweenBuilder,
+    InBuilder,
+);
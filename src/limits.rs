@@ -0,0 +1,814 @@
+//! DynamoDB service limits for expressions
+//!
+//! [More Information](https://docs.aws.amazon.com/amazondynamodb/latest/developerguide/ServiceQuotas.html#limits-expression-parameters)
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    error::ExpressionError, Expression, ExpressionNode, ExpressionType, KeyConditionBuilder,
+    Segment,
+};
+
+/// The maximum length of any single expression string, in bytes.
+pub const MAX_EXPRESSION_SIZE: usize = 4096;
+
+/// The maximum length of an attribute name referenced by an expression, in bytes.
+pub const MAX_ATTRIBUTE_NAME_LENGTH: usize = 255;
+
+/// The maximum number of operands for the IN comparator.
+pub const MAX_IN_OPERANDS: usize = 100;
+
+/// The maximum number of nested levels in a document path.
+pub const MAX_PATH_DEPTH: usize = 32;
+
+/// The maximum number of operators or functions allowed in an expression.
+pub const MAX_OPERATORS: usize = 300;
+
+/// The maximum size of a partition key value, in bytes.
+pub const MAX_PARTITION_KEY_SIZE: usize = 2048;
+
+/// The maximum size of a sort key value, in bytes.
+pub const MAX_SORT_KEY_SIZE: usize = 1024;
+
+/// The maximum size of an item, in bytes.
+pub const MAX_ITEM_SIZE: usize = 400 * 1024;
+
+/// The maximum aggregate size of the items of a transaction, in bytes.
+pub const MAX_TRANSACTION_SIZE: usize = 4 * 1024 * 1024;
+
+/// Specifies which DynamoDB service limit was exceeded by an expression.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, strum::Display)]
+pub enum ExpressionLimit {
+    /// ExpressionSize represents the maximum expression string size
+    ExpressionSize,
+
+    /// AttributeNameLength represents the maximum attribute name length
+    AttributeNameLength,
+
+    /// InOperands represents the maximum number of IN operands
+    InOperands,
+
+    /// PathDepth represents the maximum document path depth
+    PathDepth,
+
+    /// Operators represents the maximum number of operators and functions
+    Operators,
+
+    /// PartitionKeySize represents the maximum size of a partition key value
+    PartitionKeySize,
+
+    /// SortKeySize represents the maximum size of a sort key value
+    SortKeySize,
+
+    /// ItemSize represents the maximum size of an item, and so of any value
+    ItemSize,
+}
+
+impl ExpressionLimit {
+    /// Returns the maximum value allowed by DynamoDB for this limit.
+    pub fn max(&self) -> usize {
+        match self {
+            ExpressionLimit::ExpressionSize => MAX_EXPRESSION_SIZE,
+            ExpressionLimit::AttributeNameLength => MAX_ATTRIBUTE_NAME_LENGTH,
+            ExpressionLimit::InOperands => MAX_IN_OPERANDS,
+            ExpressionLimit::PathDepth => MAX_PATH_DEPTH,
+            ExpressionLimit::Operators => MAX_OPERATORS,
+            ExpressionLimit::PartitionKeySize => MAX_PARTITION_KEY_SIZE,
+            ExpressionLimit::SortKeySize => MAX_SORT_KEY_SIZE,
+            ExpressionLimit::ItemSize => MAX_ITEM_SIZE,
+        }
+    }
+
+    fn check(self, actual: usize) -> Result<(), ExpressionError> {
+        if actual > self.max() {
+            return Err(ExpressionError::LimitExceededError(
+                "buildValidated".to_owned(),
+                self,
+                actual,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn check_value(self, attribute: &str, value: &AttributeValue) -> Result<(), ExpressionError> {
+        let actual = value_size(value);
+        if actual > self.max() {
+            return Err(ExpressionError::ValueSizeExceeded(
+                "buildValidated".to_owned(),
+                attribute.to_owned(),
+                self,
+                actual,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+const OPERATORS: &[&str] = &[
+    "=", "<>", "<", "<=", ">", ">=", "+", "-", "AND", "OR", "NOT", "IN", "BETWEEN",
+];
+
+const FUNCTIONS: &[&str] = &[
+    "attribute_exists",
+    "attribute_not_exists",
+    "attribute_type",
+    "begins_with",
+    "contains",
+    "size",
+    "list_append",
+    "if_not_exists",
+];
+
+/// Validates the unformatted expression tree of a single expression.
+pub(crate) fn check_node(node: &ExpressionNode) -> Result<(), ExpressionError> {
+    ExpressionLimit::Operators.check(count_operators(node)?)
+}
+
+/// Validates a single formatted expression string.
+pub(crate) fn check_expression(expression: &str) -> Result<(), ExpressionError> {
+    ExpressionLimit::ExpressionSize.check(expression.len())
+}
+
+/// Validates a single aliased attribute name.
+pub(crate) fn check_name(name: &str) -> Result<(), ExpressionError> {
+    ExpressionLimit::AttributeNameLength.check(name.len())
+}
+
+/// Validates the sizes of the values bound by a single expression.
+pub(crate) fn check_values(node: &ExpressionNode) -> Result<(), ExpressionError> {
+    check_node_values(node, "")
+}
+
+fn check_node_values(node: &ExpressionNode, attribute: &str) -> Result<(), ExpressionError> {
+    // the tree is walked with an explicit stack, in the same order as it is
+    // formatted
+    let mut stack = vec![(node, attribute.to_owned())];
+    while let Some((node, attribute)) = stack.pop() {
+        for value in node.values.iter() {
+            ExpressionLimit::ItemSize.check_value(&attribute, value)?;
+        }
+
+        // values are reported against the attribute they are compared to, if any
+        let compared = compared_attribute(node);
+        let attribute = if compared.is_empty() {
+            attribute
+        } else {
+            compared
+        };
+        for child in node.children.iter().rev() {
+            stack.push((child, attribute.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates the sizes of the key values bound by a Key Condition Expression.
+pub(crate) fn check_key_condition(node: &ExpressionNode) -> Result<(), ExpressionError> {
+    // a key condition is either a partition key condition, or the AND of a
+    // partition key condition and a sort key condition
+    let (partition, sort) = match node.children.as_slice() {
+        [partition, sort] if node.fmt_expression == KeyConditionBuilder::and_fmt_expression() => {
+            (partition, Some(sort))
+        }
+        _ => (node, None),
+    };
+
+    check_key_values(partition, ExpressionLimit::PartitionKeySize)?;
+    if let Some(sort) = sort {
+        check_key_values(sort, ExpressionLimit::SortKeySize)?;
+    }
+
+    Ok(())
+}
+
+fn check_key_values(node: &ExpressionNode, limit: ExpressionLimit) -> Result<(), ExpressionError> {
+    let attribute = compared_attribute(node);
+    for child in node.children.iter() {
+        for value in child.values.iter() {
+            limit.check_value(&attribute, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+// returns the path of the first operand of a node referencing an attribute
+fn compared_attribute(node: &ExpressionNode) -> String {
+    node.children
+        .iter()
+        .find(|child| !child.names.is_empty())
+        .map(|child| child.names.join("."))
+        .unwrap_or_default()
+}
+
+// approximates the size DynamoDB accounts for a value
+fn value_size(value: &AttributeValue) -> usize {
+    match value {
+        AttributeValue::S(s) => s.len(),
+        // numbers are stored with two digits per byte plus one byte
+        AttributeValue::N(n) => n.len() / 2 + 1,
+        AttributeValue::B(b) => b.as_ref().len(),
+        AttributeValue::Ss(ss) => ss.iter().map(String::len).sum(),
+        AttributeValue::Ns(ns) => ns.iter().map(|n| n.len() / 2 + 1).sum(),
+        AttributeValue::Bs(bs) => bs.iter().map(|b| b.as_ref().len()).sum(),
+        AttributeValue::L(l) => 3 + l.iter().map(|v| 1 + value_size(v)).sum::<usize>(),
+        AttributeValue::M(m) => {
+            3 + m
+                .iter()
+                .map(|(k, v)| 1 + k.len() + value_size(v))
+                .sum::<usize>()
+        }
+        _ => 1,
+    }
+}
+
+// the size of the JSON encoding of a value, as sent by the SDK
+fn json_value_size(value: &AttributeValue) -> usize {
+    // {"T":...}
+    let typed = |tag: &str, size: usize| 2 + json_string_size(tag) + 1 + size;
+    let base64_size = |len: usize| 2 + len.div_ceil(3) * 4;
+
+    match value {
+        AttributeValue::S(s) => typed("S", json_string_size(s)),
+        AttributeValue::N(n) => typed("N", json_string_size(n)),
+        AttributeValue::B(b) => typed("B", base64_size(b.as_ref().len())),
+        AttributeValue::Bool(b) => typed("BOOL", if *b { 4 } else { 5 }),
+        AttributeValue::Null(_) => typed("NULL", 4),
+        AttributeValue::Ss(ss) => typed(
+            "SS",
+            json_array_size(ss.iter().map(|s| json_string_size(s))),
+        ),
+        AttributeValue::Ns(ns) => typed(
+            "NS",
+            json_array_size(ns.iter().map(|n| json_string_size(n))),
+        ),
+        AttributeValue::Bs(bs) => typed(
+            "BS",
+            json_array_size(bs.iter().map(|b| base64_size(b.as_ref().len()))),
+        ),
+        AttributeValue::L(l) => typed("L", json_array_size(l.iter().map(json_value_size))),
+        AttributeValue::M(m) => typed(
+            "M",
+            json_object_size(m.iter().map(|(k, v)| (k, json_value_size(v)))),
+        ),
+        _ => 2,
+    }
+}
+
+fn json_string_size(s: &str) -> usize {
+    2 + s
+        .chars()
+        .map(|ch| match ch {
+            '"' | '\\' | '\n' | '\r' | '\t' | '\u{8}' | '\u{c}' => 2,
+            ch if ch < ' ' => 6,
+            ch => ch.len_utf8(),
+        })
+        .sum::<usize>()
+}
+
+fn json_array_size(sizes: impl Iterator<Item = usize>) -> usize {
+    // the brackets, and a comma between the elements
+    sizes.fold(1, |total, size| total + size + 1).max(2)
+}
+
+fn json_object_size<'a>(entries: impl Iterator<Item = (&'a String, usize)>) -> usize {
+    json_array_size(entries.map(|(key, size)| json_string_size(key) + 1 + size))
+}
+
+fn count_operators(node: &ExpressionNode) -> Result<usize, ExpressionError> {
+    // the tree is walked with an explicit stack, in the same order as it is
+    // formatted
+    let mut count = 0;
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        count += count_node_operators(node)?;
+        stack.extend(node.children.iter().rev());
+    }
+
+    Ok(count)
+}
+
+fn count_node_operators(node: &ExpressionNode) -> Result<usize, ExpressionError> {
+    // every name, value and child of the node is a single operand token
+    let mut tokens = Vec::new();
+    for segment in node.fmt_expression.segments() {
+        match segment {
+            Segment::Literal(literal) => tokens.extend(
+                literal
+                    .split(|ch: char| ch.is_whitespace() || ch == '(' || ch == ')' || ch == ',')
+                    .filter(|token| !token.is_empty()),
+            ),
+            _ => tokens.push("$"),
+        }
+    }
+
+    let mut count = 0;
+    let mut in_between = false;
+    for (idx, token) in tokens.iter().enumerate() {
+        if *token == "AND" && in_between {
+            // the AND of BETWEEN x AND y is part of the BETWEEN operator
+            in_between = false;
+            continue;
+        }
+
+        if *token == "BETWEEN" {
+            in_between = true;
+        }
+
+        if *token == "IN" {
+            // every remaining operand of the node belongs to the IN list
+            ExpressionLimit::InOperands.check(tokens.len() - idx - 1)?;
+        }
+
+        if OPERATORS.contains(token) || FUNCTIONS.contains(token) {
+            count += 1;
+        }
+    }
+
+    if !node.names.is_empty() {
+        ExpressionLimit::PathDepth.check(path_depth(node))?;
+    }
+
+    Ok(count)
+}
+
+/// Validates the depth of the document path of a name operand.
+pub(crate) fn check_path_depth(node: &ExpressionNode) -> Result<(), ExpressionError> {
+    let depth = path_depth(node);
+    if depth > MAX_PATH_DEPTH {
+        return Err(ExpressionError::LimitExceededError(
+            "BuildOperand".to_owned(),
+            ExpressionLimit::PathDepth,
+            depth,
+        ));
+    }
+
+    Ok(())
+}
+
+fn path_depth(node: &ExpressionNode) -> usize {
+    // every name is one level of the path, as is every list index
+    node.fmt_expression
+        .segments()
+        .iter()
+        .map(|segment| match segment {
+            Segment::Name => 1,
+            Segment::Literal(literal) => literal.matches('[').count(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Represents the sizes of a built Expression, to be compared with the
+/// DynamoDB service limits.
+///
+/// The lengths of the expression strings are compared with
+/// MAX_EXPRESSION_SIZE, and the estimated size of the values with
+/// MAX_ITEM_SIZE. Services can log them, or alert when an Expression gets
+/// close to a limit.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let expr = Builder::new()
+///     .with_condition(name("status").equal(value("open")))
+///     .with_update(set(name("count"), value(1)))
+///     .build()
+///     .unwrap();
+///
+/// let stats = expr.stats();
+/// assert_eq!(stats.name_count(), 2);
+/// assert_eq!(stats.value_count(), 2);
+/// assert_eq!(stats.expression_length(&ExpressionType::Condition), Some(7));
+/// assert!(stats.longest_expression().unwrap().1 < MAX_EXPRESSION_SIZE);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExpressionStats {
+    name_count: usize,
+    value_count: usize,
+    expression_lengths: Vec<(ExpressionType, usize)>,
+    names_size: usize,
+    values_size: usize,
+}
+
+impl ExpressionStats {
+    /// Returns the number of entries of the ExpressionAttributeNames.
+    pub fn name_count(&self) -> usize {
+        self.name_count
+    }
+
+    /// Returns the number of entries of the ExpressionAttributeValues.
+    pub fn value_count(&self) -> usize {
+        self.value_count
+    }
+
+    /// Returns the length in bytes of every expression string, sorted by
+    /// ExpressionType.
+    pub fn expression_lengths(&self) -> &[(ExpressionType, usize)] {
+        &self.expression_lengths
+    }
+
+    /// Returns the length in bytes of the expression string of the argument
+    /// type, or None if the Expression has no such expression.
+    pub fn expression_length(&self, expression_type: &ExpressionType) -> Option<usize> {
+        self.expression_lengths
+            .iter()
+            .find(|(key, _)| key == expression_type)
+            .map(|(_, length)| *length)
+    }
+
+    /// Returns the longest expression string and its length, which is the one
+    /// closest to MAX_EXPRESSION_SIZE.
+    pub fn longest_expression(&self) -> Option<(&ExpressionType, usize)> {
+        self.expression_lengths
+            .iter()
+            .max_by_key(|(_, length)| *length)
+            .map(|(key, length)| (key, *length))
+    }
+
+    /// Returns the size in bytes of the ExpressionAttributeNames, counting
+    /// both the aliases and the names.
+    pub fn names_size(&self) -> usize {
+        self.names_size
+    }
+
+    /// Returns the estimated size in bytes of the ExpressionAttributeValues,
+    /// counting the aliases and the sizes DynamoDB accounts for the values.
+    pub fn values_size(&self) -> usize {
+        self.values_size
+    }
+
+    /// Returns the estimated size in bytes of the whole Expression: the
+    /// expression strings, the names and the values.
+    pub fn estimated_size(&self) -> usize {
+        self.expression_lengths
+            .iter()
+            .map(|(_, length)| length)
+            .sum::<usize>()
+            + self.names_size
+            + self.values_size
+    }
+}
+
+impl Expression {
+    /// Returns the ExpressionStats of the Expression, see ExpressionStats.
+    pub fn stats(&self) -> ExpressionStats {
+        let mut expression_lengths = self
+            .expressions
+            .iter()
+            .map(|(key, expression)| (key.clone(), expression.len()))
+            .collect::<Vec<_>>();
+        expression_lengths.sort();
+
+        let names = self.names().iter().flatten();
+        let values = self.values().iter().flatten();
+
+        ExpressionStats {
+            name_count: names.clone().count(),
+            value_count: values.clone().count(),
+            expression_lengths,
+            names_size: names.map(|(alias, name)| alias.len() + name.len()).sum(),
+            values_size: values
+                .map(|(alias, value)| alias.len() + value_size(value))
+                .sum(),
+        }
+    }
+
+    /// Returns the estimated number of bytes the Expression adds to the JSON
+    /// body of a DynamoDB request: the expression strings, and the
+    /// ExpressionAttributeNames and ExpressionAttributeValues maps, with
+    /// their field names, quoting and escaping. Binary values are counted
+    /// base64 encoded, as they are sent.
+    ///
+    /// Unlike ExpressionStats::estimated_size(), which approximates the sizes
+    /// DynamoDB accounts for, this is the size on the wire, which helps to
+    /// keep a batch of requests, such as the items of a TransactWriteItems,
+    /// within MAX_TRANSACTION_SIZE and MAX_ITEM_SIZE before sending it. A
+    /// named condition is counted as the ConditionExpression it is sent as.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::new()
+    ///     .with_condition(name("status").equal(value("open")))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // "ConditionExpression":"#0 = :0",
+    /// // "ExpressionAttributeNames":{"#0":"status"},
+    /// // "ExpressionAttributeValues":{":0":{"S":"open"}},
+    /// assert_eq!(expr.estimated_wire_size(), 32 + 43 + 48);
+    /// ```
+    pub fn estimated_wire_size(&self) -> usize {
+        // every field is followed by a comma or the end of the object
+        let field_size =
+            |field: &str, value_size: usize| json_string_size(field) + 1 + value_size + 1;
+
+        let expressions_size = self
+            .expressions
+            .iter()
+            .map(|(key, expression)| {
+                let field = match key {
+                    ExpressionType::Projection => "ProjectionExpression",
+                    ExpressionType::KeyCondition => "KeyConditionExpression",
+                    ExpressionType::Condition | ExpressionType::NamedCondition(_) => {
+                        "ConditionExpression"
+                    }
+                    ExpressionType::Filter => "FilterExpression",
+                    ExpressionType::Update => "UpdateExpression",
+                };
+                field_size(field, json_string_size(expression))
+            })
+            .sum::<usize>();
+
+        let names_size = self.names().as_ref().map_or(0, |names| {
+            field_size(
+                "ExpressionAttributeNames",
+                json_object_size(
+                    names
+                        .iter()
+                        .map(|(alias, name)| (alias, json_string_size(name))),
+                ),
+            )
+        });
+
+        let values_size = self.values().as_ref().map_or(0, |values| {
+            field_size(
+                "ExpressionAttributeValues",
+                json_object_size(
+                    values
+                        .iter()
+                        .map(|(alias, value)| (alias, json_value_size(value))),
+                ),
+            )
+        });
+
+        expressions_size + names_size + values_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::primitives::Blob;
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    use crate::*;
+
+    fn limit_error(input: Builder) -> error::ExpressionError {
+        input.build_validated().unwrap_err()
+    }
+
+    #[test]
+    fn within_limits() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(
+                name("foo")
+                    .between(value(1), value(5))
+                    .and(name("bar").r#in(vec![value(1), value(2)])),
+            )
+            .with_update(set(name("foo"), name("foo").plus(value(1))));
+
+        assert_eq!(
+            input.build_validated()?.condition().unwrap(),
+            "(#0 BETWEEN :0 AND :1) AND (#1 IN (:2, :3))"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn expression_size() -> anyhow::Result<()> {
+        let input = Builder::new().with_projection(names_list(
+            name("foo"),
+            (0..1000)
+                .map(|i| name(format!("bar{}", i)))
+                .collect::<Vec<_>>(),
+        ));
+
+        assert_eq!(
+            limit_error(input),
+            error::ExpressionError::LimitExceededError(
+                "buildValidated".to_owned(),
+                ExpressionLimit::ExpressionSize,
+                5895
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn attribute_name_length() -> anyhow::Result<()> {
+        let input = Builder::new().with_condition(name("a".repeat(256)).equal(value(5)));
+
+        assert_eq!(
+            limit_error(input),
+            error::ExpressionError::LimitExceededError(
+                "buildValidated".to_owned(),
+                ExpressionLimit::AttributeNameLength,
+                256
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_operands() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(
+            name("foo").r#in(
+                (0..101i64)
+                    .map(|i| value(i) as Box<dyn OperandBuilder>)
+                    .collect(),
+            ),
+        );
+
+        assert_eq!(
+            limit_error(input),
+            error::ExpressionError::LimitExceededError(
+                "buildValidated".to_owned(),
+                ExpressionLimit::InOperands,
+                101
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn path_depth() -> anyhow::Result<()> {
+        // strict names are checked when they are built, lenient names only here
+        let input = Builder::new().with_condition(
+            name(vec!["foo"; 30].join(".") + "[0][1][2]")
+                .with_parse_mode(NameParseMode::Lenient)
+                .attribute_exists(),
+        );
+
+        assert_eq!(
+            limit_error(input),
+            error::ExpressionError::LimitExceededError(
+                "buildValidated".to_owned(),
+                ExpressionLimit::PathDepth,
+                33
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn operators() -> anyhow::Result<()> {
+        let mut condition = name("foo").equal(value(0));
+        for i in 1..151 {
+            condition = condition.or(name("foo").equal(value(i)));
+        }
+        let input = Builder::new().with_filter(condition);
+
+        assert_eq!(
+            limit_error(input),
+            error::ExpressionError::LimitExceededError(
+                "buildValidated".to_owned(),
+                ExpressionLimit::Operators,
+                301
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn build_ignores_limits() -> anyhow::Result<()> {
+        let input = Builder::new().with_condition(name("a".repeat(256)).equal(value(5)));
+
+        assert!(input.build().is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_size() -> anyhow::Result<()> {
+        let input = Builder::new().with_key_condition(key("pk").equal(value("a".repeat(2049))));
+
+        assert_eq!(
+            limit_error(input),
+            error::ExpressionError::ValueSizeExceeded(
+                "buildValidated".to_owned(),
+                "pk".to_owned(),
+                ExpressionLimit::PartitionKeySize,
+                2049
+            )
+        );
+
+        let input = Builder::new().with_key_condition(
+            key("pk")
+                .equal(value("a".repeat(2048)))
+                .and(key("sk").begins_with("b".repeat(1025))),
+        );
+
+        assert_eq!(
+            limit_error(input),
+            error::ExpressionError::ValueSizeExceeded(
+                "buildValidated".to_owned(),
+                "sk".to_owned(),
+                ExpressionLimit::SortKeySize,
+                1025
+            )
+        );
+
+        let input = Builder::new().with_key_condition(
+            key("pk")
+                .equal(value("a"))
+                .and(key("sk").between(value("b"), value("c".repeat(1024)))),
+        );
+        assert!(input.build_validated().is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn value_size() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(
+            name("foo")
+                .equal(value(1))
+                .and(name("bar.baz").contains("a".repeat(MAX_ITEM_SIZE + 1))),
+        );
+
+        assert_eq!(
+            limit_error(input),
+            error::ExpressionError::ValueSizeExceeded(
+                "buildValidated".to_owned(),
+                "bar.baz".to_owned(),
+                ExpressionLimit::ItemSize,
+                MAX_ITEM_SIZE + 1
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn expression_stats() -> anyhow::Result<()> {
+        let expr = Builder::new()
+            .with_condition(name("foo").equal(value("abcd")))
+            .with_projection(names_list(name("foo"), vec![name("ba")]))
+            .with_update(set(name("ba"), value(12)))
+            .build()?;
+        let stats = expr.stats();
+
+        assert_eq!(stats.name_count(), 2);
+        assert_eq!(stats.value_count(), 2);
+        assert_eq!(
+            stats.expression_lengths(),
+            [
+                (ExpressionType::Projection, 6),
+                (ExpressionType::Condition, 7),
+                (ExpressionType::Update, 12)
+            ]
+        );
+        assert_eq!(stats.expression_length(&ExpressionType::Filter), None);
+        assert_eq!(
+            stats.longest_expression(),
+            Some((&ExpressionType::Update, 12))
+        );
+        assert_eq!(stats.names_size(), 2 + 3 + 2 + 2);
+        assert_eq!(stats.values_size(), 2 + 4 + 2 + 2);
+        assert_eq!(stats.estimated_size(), 25 + 9 + 10);
+
+        let stats = Builder::new()
+            .with_projection(names_list(name("foo"), vec![]))
+            .build()?
+            .stats();
+        assert_eq!(stats.value_count(), 0);
+        assert_eq!(stats.values_size(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn estimated_wire_size() -> anyhow::Result<()> {
+        let expr = Builder::new()
+            .with_update(set(
+                name_literal("q\"x"),
+                value(AttributeValue::L(vec![
+                    AttributeValue::B(Blob::new("abcd")),
+                    AttributeValue::Bool(false),
+                    AttributeValue::M(HashMap::new()),
+                ])),
+            ))
+            .build()?;
+
+        let wire = r##""UpdateExpression":"SET #0 = :0\n","ExpressionAttributeNames":{"#0":"q\"x"},"ExpressionAttributeValues":{":0":{"L":[{"B":"YWJjZA=="},{"BOOL":false},{"M":{}}]}},"##;
+        assert_eq!(expr.estimated_wire_size(), wire.len());
+
+        Ok(())
+    }
+}
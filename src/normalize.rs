@@ -0,0 +1,180 @@
+//! Normalization of condition trees
+//!
+//! Conditions built by different code paths can be logically identical but
+//! structurally different, such as `a AND (b AND c)` and `(c AND a) AND b`.
+//! Normalizing them gives both the same structure, which makes them
+//! comparable with `==` and usually shortens the expression strings.
+
+use crate::{ConditionBuilder, ConditionMode, ConditionRewriter, NameBuilder, OperandBuilder};
+
+impl ConditionBuilder {
+    /// Returns the normalized form of the condition.
+    ///
+    /// Normalization flattens nested AND and OR conditions, removes double
+    /// negations, removes identical conditions combined by the same AND or
+    /// OR, and sorts the conditions they combine. Comparisons of a value with
+    /// an attribute are written with the attribute first, mirroring the
+    /// comparator, so that `:v < #a` becomes `#a > :v`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let a = name("a").equal(value(1));
+    /// let b = not(not(name("b").attribute_exists()));
+    /// let c = less_than(value(5), name("c"));
+    ///
+    /// let condition = a.clone().and(b.clone().and(c.clone())).and(a.clone()).normalize();
+    /// assert_eq!(condition, c.and(b).and(a).normalize());
+    ///
+    /// let expr = Builder::new().with_condition(condition).build().unwrap();
+    /// assert_eq!(
+    ///     expr.condition().unwrap(),
+    ///     "(attribute_exists (#0)) AND (#1 = :0) AND (#2 > :1)"
+    /// );
+    /// ```
+    pub fn normalize(self) -> ConditionBuilder {
+        self.rewrite(&mut Normalize)
+    }
+}
+
+struct Normalize;
+
+impl ConditionRewriter for Normalize {
+    // the conditions combined by the argument condition are already
+    // normalized, as rewrite() goes bottom-up
+    fn rewrite_condition(&mut self, mut condition: ConditionBuilder) -> ConditionBuilder {
+        match condition.kind() {
+            ConditionMode::And | ConditionMode::Or => {
+                let mode = condition.kind();
+                let mut conditions: Vec<ConditionBuilder> = Vec::new();
+                for child in std::mem::take(&mut condition.condition_list) {
+                    let flattened = if child.kind() == mode {
+                        child.condition_list
+                    } else {
+                        vec![child]
+                    };
+                    for child in flattened {
+                        if !conditions.contains(&child) {
+                            conditions.push(child);
+                        }
+                    }
+                }
+                // the Debug output renders the whole structure of a condition
+                conditions.sort_by_cached_key(|child| format!("{:?}", child));
+
+                if conditions.len() == 1 {
+                    return conditions.pop().unwrap_or_default();
+                }
+                condition.condition_list = conditions;
+                condition
+            }
+            ConditionMode::Not => match condition.condition_list.pop() {
+                Some(child) if child.kind() == ConditionMode::Not => {
+                    child.condition_list.into_iter().next().unwrap_or_default()
+                }
+                Some(child) => {
+                    condition.condition_list.push(child);
+                    condition
+                }
+                None => condition,
+            },
+            ConditionMode::Equal
+            | ConditionMode::NotEqual
+            | ConditionMode::LessThan
+            | ConditionMode::LessThanEqual
+            | ConditionMode::GreaterThan
+            | ConditionMode::GreaterThanEqual => {
+                if let [left, right] = condition.operands() {
+                    if !is_name(left.as_ref()) && is_name(right.as_ref()) {
+                        condition.operand_list.swap(0, 1);
+                        condition.mode = mirrored(condition.kind());
+                    }
+                }
+                condition
+            }
+            _ => condition,
+        }
+    }
+}
+
+fn is_name(operand: &dyn OperandBuilder) -> bool {
+    operand.as_any().is::<NameBuilder>()
+}
+
+// returns the comparator giving the same result with swapped operands
+fn mirrored(mode: ConditionMode) -> ConditionMode {
+    match mode {
+        ConditionMode::LessThan => ConditionMode::GreaterThan,
+        ConditionMode::LessThanEqual => ConditionMode::GreaterThanEqual,
+        ConditionMode::GreaterThan => ConditionMode::LessThan,
+        ConditionMode::GreaterThanEqual => ConditionMode::LessThanEqual,
+        mode => mode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn normalize_compound() -> anyhow::Result<()> {
+        let a = name("a").attribute_exists();
+        let b = name("b").attribute_exists();
+        let c = name("c").attribute_exists();
+
+        let input = a.clone().or(b.clone().or(c.clone())).or(b.clone());
+        assert_eq!(
+            input.normalize(),
+            or_all(vec![a.clone(), b.clone(), c.clone()])?
+        );
+
+        // an AND nested in an OR is kept, and normalized
+        let input = c.clone().or(b.clone().and(a.clone().and(b.clone())));
+        assert_eq!(input.normalize(), a.clone().and(b.clone()).or(c.clone()));
+
+        // a compound of identical conditions is the condition itself
+        assert_eq!(a.clone().and(a.clone()).normalize(), a);
+
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_negations() -> anyhow::Result<()> {
+        let a = name("a").attribute_exists();
+
+        assert_eq!(not(not(a.clone())).normalize(), a);
+        assert_eq!(not(not(not(a.clone()))).normalize(), not(a.clone()));
+        assert_eq!(not(not(not(not(a.clone())))).and(a.clone()).normalize(), a);
+
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_operands() -> anyhow::Result<()> {
+        assert_eq!(
+            less_than(value(5), name("a")).normalize(),
+            greater_than(name("a"), value(5))
+        );
+        assert_eq!(
+            greater_than_equal(value(5), name("a")).normalize(),
+            less_than_equal(name("a"), value(5))
+        );
+        assert_eq!(
+            equal(value(5), name("a")).normalize(),
+            equal(name("a"), value(5))
+        );
+        // comparisons of two names or of two values are kept as-is
+        assert_eq!(
+            less_than(name("b"), name("a")).normalize(),
+            less_than(name("b"), name("a"))
+        );
+        assert_eq!(
+            size(name("a")).less_than(value(5)).normalize(),
+            size(name("a")).less_than(value(5))
+        );
+
+        Ok(())
+    }
+}
@@ -1,12 +1,14 @@
 //! Ported from [operand.go](https://github.com/aws/aws-sdk-go/blob/master/service/dynamodb/expression/operand.go)
 
+use std::any::Any;
 use std::collections::HashMap;
+use std::panic::Location;
 
-use anyhow::bail;
+use aws_sdk_dynamodb::primitives::Blob;
 use aws_sdk_dynamodb::types::AttributeValue;
 use derivative::*;
 
-use crate::{error::ExpressionError, ExpressionNode};
+use crate::{error::ExpressionError, ExpressionNode, FmtExpression, Path, PathElement, Recorder};
 
 macro_rules! into_operand_builder {
     () => {
@@ -27,124 +29,263 @@ impl Operand {
     }
 }
 
-pub trait OperandBuilder: Send {
-    fn build_operand(&self) -> anyhow::Result<Operand>;
+pub trait OperandBuilder: OperandBuilderClone + OperandBuilderEq + std::fmt::Debug + Send {
+    fn build_operand(&self) -> Result<Operand, ExpressionError>;
+
+    /// Returns the call constructing the OperandBuilder, see Builder::record().
+    ///
+    /// Values must be recorded through the Recorder so they are redacted.
+    fn record(&self, recorder: &mut Recorder) -> String;
+
+    /// Returns the operands nested in the OperandBuilder, such as the name of
+    /// a size() or the two operands of a plus(), see ExpressionVisitor.
+    fn operands(&self) -> Vec<&dyn OperandBuilder> {
+        Vec::new()
+    }
+
+    /// Calls the argument function with every NameBuilder of the
+    /// OperandBuilder, nested ones included, see ConditionBuilder::rewrite().
+    fn rewrite_names(&mut self, _rewrite: &mut dyn FnMut(&mut NameBuilder)) {}
+}
+
+// helper trait for cloning boxed OperandBuilders
+pub trait OperandBuilderClone {
+    fn clone_box(&self) -> Box<dyn OperandBuilder>;
+}
+
+impl<T> OperandBuilderClone for T
+where
+    T: OperandBuilder + Clone + 'static,
+{
+    fn clone_box(&self) -> Box<dyn OperandBuilder> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn OperandBuilder> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+// helper trait for comparing boxed OperandBuilders
+pub trait OperandBuilderEq {
+    fn as_any(&self) -> &dyn Any;
+
+    fn eq_box(&self, other: &dyn Any) -> bool;
+}
+
+impl<T> OperandBuilderEq for T
+where
+    T: OperandBuilder + PartialEq + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_box(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<T>() == Some(self)
+    }
+}
+
+impl PartialEq for dyn OperandBuilder {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_box(other.as_any())
+    }
 }
 
 // marker trait for working with generic ValueBuilders
-pub trait ValueBuilderImpl: OperandBuilder {
+pub trait ValueBuilderImpl: ValueBuilderImplClone + OperandBuilder {
     fn attribute_value(&self) -> AttributeValue;
 
     fn into_operand_builder(self: Box<Self>) -> Box<dyn OperandBuilder>;
 }
 
-#[derive(Debug, Clone)]
-pub struct ValueBuilder<T> {
-    value: T,
+// helper trait for cloning boxed ValueBuilders
+pub trait ValueBuilderImplClone {
+    fn clone_value_box(&self) -> Box<dyn ValueBuilderImpl>;
 }
 
-impl<T> ValueBuilder<T> {}
+impl<T> ValueBuilderImplClone for T
+where
+    T: ValueBuilderImpl + Clone + 'static,
+{
+    fn clone_value_box(&self) -> Box<dyn ValueBuilderImpl> {
+        Box::new(self.clone())
+    }
+}
 
-impl ValueBuilderImpl for ValueBuilder<bool> {
-    fn attribute_value(&self) -> AttributeValue {
-        AttributeValue::Bool(self.value)
+impl Clone for Box<dyn ValueBuilderImpl> {
+    fn clone(&self) -> Self {
+        self.clone_value_box()
     }
+}
 
-    into_operand_builder!();
+impl PartialEq for dyn ValueBuilderImpl {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_box(other.as_any())
+    }
 }
 
-impl ValueBuilderImpl for ValueBuilder<i64> {
-    fn attribute_value(&self) -> AttributeValue {
-        AttributeValue::N(self.value.to_string())
+/// Represents a type that can be used as a DynamoDB value.
+///
+/// Every ValueBuilder wrapping a type implementing ToAttributeValue can be
+/// used wherever the crate accepts a value, so implementing this trait for
+/// an application type is enough to pass it to value() directly.
+///
+/// # Example
+///
+/// ```
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use dynamodb_expression::*;
+///
+/// #[derive(Clone, PartialEq, Debug)]
+/// struct Cents(u32);
+///
+/// impl ToAttributeValue for Cents {
+///     fn to_attribute_value(&self) -> AttributeValue {
+///         AttributeValue::N(self.0.to_string())
+///     }
+/// }
+///
+/// let condition = name("Price").less_than(value(Cents(500)));
+/// ```
+pub trait ToAttributeValue: Clone + PartialEq + std::fmt::Debug + Send + 'static {
+    fn to_attribute_value(&self) -> AttributeValue;
+
+    /// Checks that the value can be stored by DynamoDB, returning a typed
+    /// error when building the operand if it can't. Every value is valid by
+    /// default.
+    fn validate(&self) -> Result<(), ExpressionError> {
+        Ok(())
     }
+}
 
-    into_operand_builder!();
+impl ToAttributeValue for bool {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::Bool(*self)
+    }
 }
 
-impl ValueBuilderImpl for ValueBuilder<f64> {
-    fn attribute_value(&self) -> AttributeValue {
-        AttributeValue::N(self.value.to_string())
+impl ToAttributeValue for i64 {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::N(self.to_string())
     }
+}
 
-    into_operand_builder!();
+impl ToAttributeValue for f64 {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::N(self.to_string())
+    }
 }
 
-impl ValueBuilderImpl for ValueBuilder<&'static str> {
-    fn attribute_value(&self) -> AttributeValue {
-        AttributeValue::S(self.value.to_owned())
+impl ToAttributeValue for NumericId {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::N(self.0.to_string())
     }
 
-    into_operand_builder!();
+    fn validate(&self) -> Result<(), ExpressionError> {
+        if self.0 > NumericId::MAX.0 {
+            return Err(ExpressionError::InvalidParameterError(
+                "numericId".to_owned(),
+                "NumericId".to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
 }
 
-impl ValueBuilderImpl for ValueBuilder<Vec<&'static str>> {
-    fn attribute_value(&self) -> AttributeValue {
-        if self.value.is_empty() {
+impl ToAttributeValue for &'static str {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::S((*self).to_owned())
+    }
+}
+
+impl ToAttributeValue for Vec<&'static str> {
+    fn to_attribute_value(&self) -> AttributeValue {
+        if self.is_empty() {
             return AttributeValue::Null(true);
         }
 
-        AttributeValue::Ss(self.value.iter().map(|&x| x.to_owned()).collect())
+        AttributeValue::Ss(self.iter().map(|&x| x.to_owned()).collect())
     }
-
-    into_operand_builder!();
 }
 
-impl ValueBuilderImpl for ValueBuilder<String> {
-    fn attribute_value(&self) -> AttributeValue {
-        AttributeValue::S(self.value.clone())
+impl ToAttributeValue for String {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::S(self.clone())
     }
-
-    into_operand_builder!();
 }
 
-impl ValueBuilderImpl for ValueBuilder<Vec<String>> {
-    fn attribute_value(&self) -> AttributeValue {
-        if self.value.is_empty() {
+impl ToAttributeValue for Vec<String> {
+    fn to_attribute_value(&self) -> AttributeValue {
+        if self.is_empty() {
             return AttributeValue::Null(true);
         }
 
-        AttributeValue::Ss(self.value.clone())
+        AttributeValue::Ss(self.clone())
     }
-
-    into_operand_builder!();
 }
 
-impl ValueBuilderImpl for ValueBuilder<AttributeValue> {
-    fn attribute_value(&self) -> AttributeValue {
-        self.value.clone()
+impl ToAttributeValue for AttributeValue {
+    fn to_attribute_value(&self) -> AttributeValue {
+        self.clone()
     }
-
-    into_operand_builder!();
 }
 
-impl ValueBuilderImpl for ValueBuilder<Vec<Box<dyn ValueBuilderImpl>>> {
-    fn attribute_value(&self) -> AttributeValue {
-        if self.value.is_empty() {
+impl ToAttributeValue for Vec<Box<dyn ValueBuilderImpl>> {
+    fn to_attribute_value(&self) -> AttributeValue {
+        if self.is_empty() {
             return AttributeValue::Null(true);
         }
 
-        let value = self.value.iter().map(|x| x.attribute_value()).collect();
+        let value = self.iter().map(|x| x.attribute_value()).collect();
 
         AttributeValue::L(value)
     }
-
-    into_operand_builder!();
 }
 
-impl ValueBuilderImpl for ValueBuilder<HashMap<String, Box<dyn ValueBuilderImpl>>> {
-    fn attribute_value(&self) -> AttributeValue {
-        if self.value.is_empty() {
+impl ToAttributeValue for HashMap<String, Box<dyn ValueBuilderImpl>> {
+    fn to_attribute_value(&self) -> AttributeValue {
+        if self.is_empty() {
             return AttributeValue::Null(true);
         }
 
         let value = self
-            .value
             .iter()
             .map(|(k, v)| (k.clone(), v.attribute_value()))
             .collect();
 
         AttributeValue::M(value)
     }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueBuilder<T> {
+    value: T,
+}
+
+impl<T> ValueBuilder<T> {}
+
+impl<T: ToAttributeValue> OperandBuilder for ValueBuilder<T> {
+    fn build_operand(&self) -> Result<Operand, ExpressionError> {
+        self.value.validate()?;
+        let expr = self.attribute_value();
+
+        let node = ExpressionNode::from_values(vec![expr], FmtExpression::new().value());
+        Ok(Operand::new(node))
+    }
+
+    fn record(&self, recorder: &mut Recorder) -> String {
+        recorder.value(&self.attribute_value())
+    }
+}
+
+impl<T: ToAttributeValue> ValueBuilderImpl for ValueBuilder<T> {
+    fn attribute_value(&self) -> AttributeValue {
+        self.value.to_attribute_value()
+    }
 
     into_operand_builder!();
 }
@@ -153,12 +294,239 @@ pub fn value<T>(value: T) -> Box<ValueBuilder<T>> {
     Box::new(ValueBuilder { value })
 }
 
-#[derive(Default, Debug, Clone)]
+/// Returns a ValueBuilder for a string set of the argument strings.
+///
+/// Duplicate strings are dropped, as DynamoDB sets can't contain them. String
+/// sets can be used with UpdateBuilder::add_to_set() and
+/// UpdateBuilder::delete_from_set().
+///
+/// # Example
+///
+/// ```
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use dynamodb_expression::*;
+///
+/// let tags = string_set(["red", "blue", "red"]);
+/// assert_eq!(
+///     tags.attribute_value(),
+///     AttributeValue::Ss(vec!["red".to_owned(), "blue".to_owned()])
+/// );
+/// ```
+pub fn string_set(
+    values: impl IntoIterator<Item = impl Into<String>>,
+) -> Box<ValueBuilder<AttributeValue>> {
+    value(AttributeValue::Ss(unique(
+        values.into_iter().map(Into::into),
+    )))
+}
+
+/// Returns a ValueBuilder for a number set of the argument numbers.
+///
+/// Duplicate numbers are dropped, as DynamoDB sets can't contain them.
+pub fn number_set(
+    values: impl IntoIterator<Item = impl std::fmt::Display>,
+) -> Box<ValueBuilder<AttributeValue>> {
+    value(AttributeValue::Ns(unique(
+        values.into_iter().map(|value| value.to_string()),
+    )))
+}
+
+/// Returns a ValueBuilder for a binary set of the argument binary values.
+///
+/// Duplicate binary values are dropped, as DynamoDB sets can't contain them.
+pub fn binary_set(
+    values: impl IntoIterator<Item = impl Into<Blob>>,
+) -> Box<ValueBuilder<AttributeValue>> {
+    value(AttributeValue::Bs(unique(
+        values.into_iter().map(Into::into),
+    )))
+}
+
+// drops the duplicates of the argument values, keeping their order
+fn unique<T: PartialEq>(values: impl Iterator<Item = T>) -> Vec<T> {
+    let mut unique = Vec::new();
+    for value in values {
+        if !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+    unique
+}
+
+/// Converts the argument of a function into the OperandBuilder it operates
+/// on, such as the prefix of begins_with().
+///
+/// Strings are converted into ValueBuilders, while OperandBuilders are used
+/// as-is. This allows the functions to take either a plain string or any
+/// operand, like a typed value or the path of another attribute.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let condition = name("Email").begins_with("admin@");
+/// let condition = name("Path").begins_with(name("ParentPath"));
+/// ```
+pub trait IntoOperand {
+    fn into_operand(self) -> Box<dyn OperandBuilder>;
+}
+
+impl<T: OperandBuilder + 'static> IntoOperand for Box<T> {
+    fn into_operand(self) -> Box<dyn OperandBuilder> {
+        self
+    }
+}
+
+impl IntoOperand for Box<dyn OperandBuilder> {
+    fn into_operand(self) -> Box<dyn OperandBuilder> {
+        self
+    }
+}
+
+impl IntoOperand for String {
+    fn into_operand(self) -> Box<dyn OperandBuilder> {
+        value(self)
+    }
+}
+
+impl IntoOperand for &String {
+    fn into_operand(self) -> Box<dyn OperandBuilder> {
+        value(self.clone())
+    }
+}
+
+impl IntoOperand for &str {
+    fn into_operand(self) -> Box<dyn OperandBuilder> {
+        value(self.to_owned())
+    }
+}
+
+/// Specifies how strictly a NameBuilder validates its document path.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Derivative)]
+#[derivative(Default)]
+pub enum NameParseMode {
+    /// Strict validates every path segment against the DynamoDB document path
+    /// grammar: an attribute name optionally followed by one or more list
+    /// indexes (`foo`, `foo[1]`, `foo[1][2]`). Malformed segments such as
+    /// `foo[1]bar` or `foo[a]` are rejected with an InvalidParameterError
+    /// describing the segment, and paths nested deeper than MAX_PATH_DEPTH
+    /// levels with a LimitExceededError.
+    #[derivative(Default)]
+    Strict,
+
+    /// Lenient preserves the original parsing behavior for compatibility.
+    ///
+    /// A segment is only split into a name and an index when it ends with `]`,
+    /// anything else is aliased as a single attribute name as-is.
+    Lenient,
+}
+
+/// Represents a monotonically increasing numeric ID, such as a Snowflake ID.
+///
+/// NumericIds are always encoded as the DynamoDB Number type (N). A DynamoDB
+/// Number holds at most 38 significant digits, so IDs greater than
+/// NumericId::MAX return the typed error InvalidParameterError when built.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct NumericId(pub u128);
+
+impl NumericId {
+    /// The greatest NumericId, the greatest 38 digit number.
+    pub const MAX: NumericId = NumericId(10u128.pow(38) - 1);
+}
+
+impl<T: Into<u128>> From<T> for NumericId {
+    fn from(id: T) -> Self {
+        Self(id.into())
+    }
+}
+
+/// Returns a ValueBuilder for a monotonically increasing numeric ID, such as a
+/// Snowflake ID.
+///
+/// The ID is always encoded as the DynamoDB Number type (N), never as a String,
+/// so comparisons against it are numeric rather than lexical ("10" < "9" when
+/// compared as strings). IDs up to NumericId::MAX, 38 digits, are encoded
+/// without loss of precision, greater ones return the typed error
+/// InvalidParameterError when built.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // condition represents the condition where the item attribute "Id" is
+/// // numerically greater than the given ID
+/// let condition = name("Id").greater_than(numeric_id(1541815603606036480u64));
+/// ```
+pub fn numeric_id(id: impl Into<NumericId>) -> Box<ValueBuilder<NumericId>> {
+    value(id.into())
+}
+
+#[derive(Default, Clone, Derivative)]
+#[derivative(PartialEq)]
 pub struct NameBuilder {
     name: String,
+    mode: NameParseMode,
+    path: Option<Path>,
+    #[derivative(PartialEq = "ignore")]
+    location: Option<&'static Location<'static>>,
+}
+
+impl std::fmt::Debug for NameBuilder {
+    // names built from a Path show the Path instead of the unused name
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => f.debug_struct("NameBuilder").field("path", path).finish(),
+            None => f
+                .debug_struct("NameBuilder")
+                .field("name", &self.name)
+                .field("mode", &self.mode)
+                .finish(),
+        }
+    }
 }
 
 impl NameBuilder {
+    /// Returns the attribute names along the document path of the
+    /// NameBuilder, without the list indexes.
+    ///
+    /// The name is parsed as it is when building an expression, so invalid
+    /// names return the same typed errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// assert_eq!(name("a.b[2].c").attribute_path().unwrap(), ["a", "b", "c"]);
+    /// assert_eq!(name_literal("a.b").attribute_path().unwrap(), ["a.b"]);
+    /// assert!(name("a..b").attribute_path().is_err());
+    /// ```
+    pub fn attribute_path(&self) -> Result<Vec<String>, ExpressionError> {
+        Ok(self.build_name_operand()?.expression_node.names)
+    }
+
+    #[track_caller]
+    pub(crate) fn from_path(path: Path) -> Box<NameBuilder> {
+        Box::new(NameBuilder {
+            path: Some(path),
+            location: Some(Location::caller()),
+            ..Default::default()
+        })
+    }
+
+    // returns a NameBuilder for the element of the list attribute at the
+    // argument index
+    pub(crate) fn element(&self, index: usize) -> Box<NameBuilder> {
+        let mut element = Box::new(self.clone());
+        match element.path.take() {
+            Some(path) => element.path = Some(path.index(index)),
+            None => element.name.push_str(&format!("[{}]", index)),
+        }
+        element
+    }
+
     pub fn size(self: Box<Self>) -> Box<SizeBuilder> {
         Box::new(SizeBuilder { name_builder: self })
     }
@@ -166,12 +534,138 @@ impl NameBuilder {
     pub fn if_not_exists(self: Box<Self>, right: Box<dyn OperandBuilder>) -> Box<SetValueBuilder> {
         if_not_exists(self, right)
     }
+
+    /// Sets the NameParseMode used when the document path is parsed.
+    ///
+    /// Names built from a Path are not parsed, so the mode has no effect on
+    /// them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // "foo[1]bar" is rejected by the default strict mode
+    /// assert!(name("foo[1]bar").build_operand().is_err());
+    ///
+    /// // but is aliased as a single attribute name in lenient mode
+    /// let operand = name("foo[1]bar").with_parse_mode(NameParseMode::Lenient);
+    /// assert!(operand.build_operand().is_ok());
+    /// ```
+    pub fn with_parse_mode(mut self: Box<Self>, mode: NameParseMode) -> Box<NameBuilder> {
+        self.mode = mode;
+        self
+    }
+
+    fn split_word(&self, word: &str) -> Result<(String, String), ExpressionError> {
+        match self.mode {
+            NameParseMode::Strict => NameBuilder::split_word_strict(word),
+            NameParseMode::Lenient => Ok(NameBuilder::split_word_lenient(word)),
+        }
+    }
+
+    fn split_word_lenient(word: &str) -> (String, String) {
+        if word.ends_with(']') {
+            if let Some(j) = word.find('[') {
+                return (word[..j].to_owned(), word[j..].to_owned());
+            }
+        }
+
+        (word.to_owned(), String::new())
+    }
+
+    fn split_word_strict(word: &str) -> Result<(String, String), ExpressionError> {
+        let segment = word;
+        let (word, mut substr) = match word.find('[') {
+            Some(j) => word.split_at(j),
+            None => (word, ""),
+        };
+
+        if word.is_empty() {
+            return Err(ExpressionError::UnsetParameterError(
+                "BuildOperand".to_owned(),
+                "NameBuilder".to_owned(),
+            ));
+        }
+
+        if word.contains(']') {
+            return Err(invalid_segment(segment, "unmatched \"]\""));
+        }
+
+        let indexes = substr;
+        while !substr.is_empty() {
+            // each index must be of the form [digits]
+            if !substr.starts_with('[') {
+                return Err(invalid_segment(
+                    segment,
+                    &format!("unexpected {:?} after list index", substr),
+                ));
+            }
+
+            let end = match substr.find(']') {
+                Some(end) => end,
+                None => return Err(invalid_segment(segment, "unclosed list index")),
+            };
+
+            let index = &substr[1..end];
+            if index.is_empty() || !index.chars().all(|ch| ch.is_ascii_digit()) {
+                return Err(invalid_segment(
+                    segment,
+                    &format!("invalid list index {:?}", index),
+                ));
+            }
+
+            substr = &substr[end + 1..];
+        }
+
+        Ok((word.to_owned(), indexes.to_owned()))
+    }
+}
+
+// the error of a malformed segment of a document path
+fn invalid_segment(segment: &str, reason: &str) -> ExpressionError {
+    ExpressionError::InvalidParameterError(
+        "BuildOperand".to_owned(),
+        format!("NameBuilder ({} in {:?})", reason, segment),
+    )
 }
 
 impl OperandBuilder for NameBuilder {
-    fn build_operand(&self) -> anyhow::Result<Operand> {
+    fn build_operand(&self) -> Result<Operand, ExpressionError> {
+        self.build_name_operand()
+            .map_err(|e| e.created_at(self.location))
+    }
+
+    fn record(&self, recorder: &mut Recorder) -> String {
+        if let Some(path) = &self.path {
+            return match path.elements() {
+                [PathElement::Field(name)] => format!("name_literal({})", recorder.name(name)),
+                _ => format!("{}.into_name()", path.record(recorder)),
+            };
+        }
+
+        match self.mode {
+            NameParseMode::Strict => format!("name({})", recorder.name(&self.name)),
+            NameParseMode::Lenient => format!(
+                "name({}).with_parse_mode(NameParseMode::Lenient)",
+                recorder.name(&self.name)
+            ),
+        }
+    }
+
+    fn rewrite_names(&mut self, rewrite: &mut dyn FnMut(&mut NameBuilder)) {
+        rewrite(self)
+    }
+}
+
+impl NameBuilder {
+    fn build_name_operand(&self) -> Result<Operand, ExpressionError> {
+        if let Some(path) = &self.path {
+            return Ok(Operand::new(path.build_node()?));
+        }
+
         if self.name.is_empty() {
-            bail!(ExpressionError::UnsetParameterError(
+            return Err(ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned(),
             ));
@@ -180,40 +674,39 @@ impl OperandBuilder for NameBuilder {
         let mut node = ExpressionNode::default();
 
         let name_split = self.name.split('.');
-        let mut fmt_names = Vec::new();
 
-        for mut word in name_split {
+        for (idx, word) in name_split.enumerate() {
             if word.is_empty() {
-                bail!(ExpressionError::UnsetParameterError(
+                return Err(ExpressionError::UnsetParameterError(
                     "BuildOperand".to_owned(),
                     "NameBuilder".to_owned(),
                 ));
             }
 
-            let mut substr = "";
-            if word.chars().nth(word.len() - 1).unwrap() == ']' {
-                for (j, ch) in word.chars().enumerate() {
-                    if ch == '[' {
-                        substr = &word[j..];
-                        word = &word[..j];
-                        break;
-                    }
-                }
-            }
+            let (word, substr) = self.split_word(word)?;
 
             if word.is_empty() {
-                bail!(ExpressionError::UnsetParameterError(
+                return Err(ExpressionError::UnsetParameterError(
                     "BuildOperand".to_owned(),
                     "NameBuilder".to_owned(),
                 ));
             }
 
-            // Create a string with special characters that can be substituted later: $p
-            node.names.push(word.to_owned());
-            fmt_names.push(format!("$n{}", substr));
+            // Create the segments that can be substituted later, list indexes
+            // are kept as literals
+            if idx > 0 {
+                node.fmt_expression.push_literal(".");
+            }
+            node.names.push(word);
+            node.fmt_expression = std::mem::take(&mut node.fmt_expression)
+                .name()
+                .literal(substr);
+        }
+
+        if self.mode == NameParseMode::Strict {
+            crate::limits::check_path_depth(&node)?;
         }
 
-        node.fmt_expression = fmt_names.join(".");
         Ok(Operand::new(node))
     }
 }
@@ -222,52 +715,115 @@ impl PlusBuilder for NameBuilder {}
 impl MinusBuilder for NameBuilder {}
 impl ListAppendBuilder for NameBuilder {}
 
+/// Creates a NameBuilder for the argument document path.
+///
+/// The source location of the call is recorded, so that errors caused by an
+/// empty or malformed name point back to the line creating it rather than to
+/// the call to build().
+#[track_caller]
 pub fn name(name: impl Into<String>) -> Box<NameBuilder> {
-    Box::new(NameBuilder { name: name.into() })
+    Box::new(NameBuilder {
+        name: name.into(),
+        mode: NameParseMode::default(),
+        path: None,
+        location: Some(Location::caller()),
+    })
 }
 
-#[derive(Debug, Clone)]
+/// Creates a NameBuilder for the argument attribute name, taken literally.
+///
+/// The name is not parsed as a document path, so dots and brackets are part
+/// of the attribute name and the whole name is aliased once. This is the same
+/// as a Path with a single field.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // condition represents the condition where the item attribute named
+/// // "a.b" exists, rather than the attribute "b" nested in the attribute "a"
+/// let condition = name_literal("a.b").attribute_exists();
+///
+/// let expr = Builder::new().with_condition(condition).build().unwrap();
+/// assert_eq!(expr.condition().unwrap(), "attribute_exists (#0)");
+/// assert_eq!(expr.names().as_ref().unwrap()["#0"], "a.b");
+/// ```
+#[track_caller]
+pub fn name_literal(name: impl Into<String>) -> Box<NameBuilder> {
+    NameBuilder::from_path(crate::path(name))
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct SizeBuilder {
     name_builder: Box<NameBuilder>,
 }
 
 impl OperandBuilder for SizeBuilder {
-    fn build_operand(&self) -> anyhow::Result<Operand> {
+    fn build_operand(&self) -> Result<Operand, ExpressionError> {
         let mut operand = self.name_builder.build_operand()?;
         operand.expression_node.fmt_expression =
-            format!("size ({})", operand.expression_node.fmt_expression);
+            std::mem::take(&mut operand.expression_node.fmt_expression).wrap("size (", ")");
 
         Ok(operand)
     }
+
+    fn record(&self, recorder: &mut Recorder) -> String {
+        format!("size({})", self.name_builder.record(recorder))
+    }
+
+    fn operands(&self) -> Vec<&dyn OperandBuilder> {
+        vec![self.name_builder.as_ref()]
+    }
+
+    fn rewrite_names(&mut self, rewrite: &mut dyn FnMut(&mut NameBuilder)) {
+        rewrite(&mut self.name_builder)
+    }
 }
 
 pub fn size(name_builder: Box<NameBuilder>) -> Box<SizeBuilder> {
     name_builder.size()
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone, Derivative)]
+#[derivative(Debug, PartialEq)]
 pub struct KeyBuilder {
     key: String,
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    location: Option<&'static Location<'static>>,
 }
 
 impl OperandBuilder for KeyBuilder {
-    fn build_operand(&self) -> anyhow::Result<Operand> {
+    fn build_operand(&self) -> Result<Operand, ExpressionError> {
         if self.key.is_empty() {
-            bail!(ExpressionError::UnsetParameterError(
+            return Err(ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "KeyBuilder".to_owned(),
-            ));
+            )
+            .created_at(self.location));
         }
 
         Ok(Operand::new(ExpressionNode::from_names(
             vec![self.key.clone()],
-            "$n",
+            FmtExpression::new().name(),
         )))
     }
+
+    fn record(&self, recorder: &mut Recorder) -> String {
+        format!("key({})", recorder.name(&self.key))
+    }
 }
 
+/// Creates a KeyBuilder for the argument key attribute.
+///
+/// The source location of the call is recorded, so that errors caused by an
+/// empty key point back to the line creating it.
+#[track_caller]
 pub fn key(key: impl Into<String>) -> Box<KeyBuilder> {
-    Box::new(KeyBuilder { key: key.into() })
+    Box::new(KeyBuilder {
+        key: key.into(),
+        location: Some(Location::caller()),
+    })
 }
 
 #[derive(Copy, Clone, PartialEq, Debug, Derivative)]
@@ -281,7 +837,36 @@ enum SetValueMode {
     IfNotExists,
 }
 
-#[derive(Default)]
+/// Represents the result of plus(), minus(), list_append() and
+/// if_not_exists(), the operands that compute the value of a SET operation.
+///
+/// A SetValueBuilder is itself an operand, so the functions can be chained
+/// and nested to any depth, as in
+/// `if_not_exists(name("list"), empty_list).list_append(value(...))`. Note
+/// that DynamoDB accepts a single `+` or `-` per SET operation, the operands
+/// of which can be functions.
+///
+/// # Example
+///
+/// ```
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use dynamodb_expression::*;
+///
+/// // appends "x" to the list, creating it if it doesn't exist
+/// let append = name("list")
+///     .if_not_exists(value(AttributeValue::L(vec![])))
+///     .list_append(value(AttributeValue::L(vec![AttributeValue::S("x".to_owned())])));
+///
+/// let expr = Builder::new()
+///     .with_update(set(name("list"), append))
+///     .build()
+///     .unwrap();
+/// assert_eq!(
+///     expr.update().unwrap(),
+///     "SET #0 = list_append(if_not_exists(#0, :0), :1)\n"
+/// );
+/// ```
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct SetValueBuilder {
     left_operand: Option<Box<dyn OperandBuilder>>,
     right_operand: Option<Box<dyn OperandBuilder>>,
@@ -289,36 +874,92 @@ pub struct SetValueBuilder {
 }
 
 impl OperandBuilder for SetValueBuilder {
-    fn build_operand(&self) -> anyhow::Result<Operand> {
+    fn build_operand(&self) -> Result<Operand, ExpressionError> {
         if self.mode == SetValueMode::Unset {
-            bail!(ExpressionError::UnsetParameterError(
+            return Err(ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "SetValueBuilder".to_owned(),
             ));
         }
 
-        let left = self.left_operand.as_ref().unwrap().build_operand()?;
+        let (left, right) = match (&self.left_operand, &self.right_operand) {
+            (Some(left), Some(right)) => (left.build_operand()?, right.build_operand()?),
+            _ => {
+                return Err(ExpressionError::UnsetParameterError(
+                    "BuildOperand".to_owned(),
+                    "SetValueBuilder".to_owned(),
+                ))
+            }
+        };
         let left_node = left.expression_node;
-
-        let right = self.right_operand.as_ref().unwrap().build_operand()?;
         let right_node = right.expression_node;
 
         let node = ExpressionNode::from_children_expression(
             vec![left_node, right_node],
             match self.mode {
-                SetValueMode::Plus => "$c + $c",
-                SetValueMode::Minus => "$c - $c",
-                SetValueMode::ListAppend => "list_append($c, $c)",
-                SetValueMode::IfNotExists => "if_not_exists($c, $c)",
-                _ => bail!("build operand error: unsupported mode: {:?}", self.mode),
-            }
-            .to_owned(),
+                SetValueMode::Plus => FmtExpression::new().children(2, " + "),
+                SetValueMode::Minus => FmtExpression::new().children(2, " - "),
+                SetValueMode::ListAppend => FmtExpression::new()
+                    .children(2, ", ")
+                    .wrap("list_append(", ")"),
+                SetValueMode::IfNotExists => FmtExpression::new()
+                    .children(2, ", ")
+                    .wrap("if_not_exists(", ")"),
+                _ => {
+                    return Err(ExpressionError::UnsupportedMode(
+                        "build operand".to_owned(),
+                        format!("{:?}", self.mode),
+                    ))
+                }
+            },
         );
 
         Ok(Operand::new(node))
     }
+
+    fn record(&self, recorder: &mut Recorder) -> String {
+        let function = match self.mode {
+            SetValueMode::Plus => "plus",
+            SetValueMode::Minus => "minus",
+            SetValueMode::ListAppend => "list_append",
+            SetValueMode::IfNotExists => "if_not_exists",
+            SetValueMode::Unset => return "Box::new(SetValueBuilder::default())".to_owned(),
+        };
+
+        match (&self.left_operand, &self.right_operand) {
+            (Some(left), Some(right)) => format!(
+                "{}({}, {})",
+                function,
+                left.record(recorder),
+                right.record(recorder)
+            ),
+            _ => "Box::new(SetValueBuilder::default())".to_owned(),
+        }
+    }
+
+    fn operands(&self) -> Vec<&dyn OperandBuilder> {
+        self.left_operand
+            .iter()
+            .chain(self.right_operand.iter())
+            .map(AsRef::as_ref)
+            .collect()
+    }
+
+    fn rewrite_names(&mut self, rewrite: &mut dyn FnMut(&mut NameBuilder)) {
+        for operand in self
+            .left_operand
+            .iter_mut()
+            .chain(self.right_operand.iter_mut())
+        {
+            operand.rewrite_names(rewrite);
+        }
+    }
 }
 
+impl PlusBuilder for SetValueBuilder {}
+impl MinusBuilder for SetValueBuilder {}
+impl ListAppendBuilder for SetValueBuilder {}
+
 pub fn plus(
     left_operand: Box<dyn OperandBuilder>,
     right_operand: Box<dyn OperandBuilder>,
@@ -433,6 +1074,33 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn numeric_id_value() -> anyhow::Result<()> {
+        let input = numeric_id(NumericId::MAX);
+
+        assert_eq!(
+            input.build_operand()?.expression_node,
+            ExpressionNode::from_values(
+                vec![AttributeValue::N(
+                    "99999999999999999999999999999999999999".to_owned()
+                )],
+                "$v"
+            ),
+        );
+
+        for id in [10u128.pow(38), u128::MAX] {
+            assert_eq!(
+                numeric_id(id).build_operand().unwrap_err(),
+                error::ExpressionError::InvalidParameterError(
+                    "numericId".to_owned(),
+                    "NumericId".to_owned()
+                )
+            );
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn attribute_value_as_value() -> anyhow::Result<()> {
         let input = value(AttributeValue::N("5".to_owned()));
@@ -445,6 +1113,32 @@ mod tests {
         Ok(())
     }
 
+    #[derive(Clone, PartialEq, Debug)]
+    struct Cents(u32);
+
+    impl ToAttributeValue for Cents {
+        fn to_attribute_value(&self) -> AttributeValue {
+            AttributeValue::N(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn custom_value() -> anyhow::Result<()> {
+        let input = value(Cents(500));
+
+        assert_eq!(
+            input.build_operand()?.expression_node,
+            ExpressionNode::from_values(vec![AttributeValue::N("500".to_owned())], "$v"),
+        );
+
+        let input = name("price").less_than(value(Cents(500)));
+
+        assert_eq!(input, name("price").less_than(value(Cents(500))));
+        assert!(input.build_tree().is_ok());
+
+        Ok(())
+    }
+
     #[test]
     fn nested_name() -> anyhow::Result<()> {
         let input = name("foo.bar");
@@ -501,10 +1195,7 @@ mod tests {
         let input = key("");
 
         assert_eq!(
-            input
-                .build_operand()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_operand().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "KeyBuilder".to_owned()
@@ -519,10 +1210,7 @@ mod tests {
         let input = name("");
 
         assert_eq!(
-            input
-                .build_operand()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_operand().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
@@ -537,10 +1225,7 @@ mod tests {
         let input = name("foo..bar");
 
         assert_eq!(
-            input
-                .build_operand()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_operand().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
@@ -555,10 +1240,161 @@ mod tests {
         let input = name("[foo]");
 
         assert_eq!(
-            input
-                .build_operand()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_operand().unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "BuildOperand".to_owned(),
+                "NameBuilder".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_name_with_multiple_indexes() -> anyhow::Result<()> {
+        let input = name("foo[1][2].bar");
+
+        assert_eq!(
+            input.build_operand()?.expression_node,
+            ExpressionNode::from_names(vec!["foo".to_owned(), "bar".to_owned()], "$n[1][2].$n"),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn strict_trailing_characters_error() -> anyhow::Result<()> {
+        for (input, expected) in [
+            (
+                "foo[1]bar",
+                r#"NameBuilder (unexpected "bar" after list index in "foo[1]bar")"#,
+            ),
+            (
+                "foo[a]",
+                r#"NameBuilder (invalid list index "a" in "foo[a]")"#,
+            ),
+            (
+                "bar.foo[abc]",
+                r#"NameBuilder (invalid list index "abc" in "foo[abc]")"#,
+            ),
+            ("foo[]", r#"NameBuilder (invalid list index "" in "foo[]")"#),
+            ("foo[1", r#"NameBuilder (unclosed list index in "foo[1")"#),
+            (
+                "foo[1][",
+                r#"NameBuilder (unclosed list index in "foo[1][")"#,
+            ),
+            ("foo]", r#"NameBuilder (unmatched "]" in "foo]")"#),
+            (
+                "foo[1]]",
+                r#"NameBuilder (unexpected "]" after list index in "foo[1]]")"#,
+            ),
+        ] {
+            assert_eq!(
+                name(input).build_operand().unwrap_err(),
+                error::ExpressionError::InvalidParameterError(
+                    "BuildOperand".to_owned(),
+                    expected.to_owned()
+                ),
+                "{}",
+                input
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn strict_path_depth() -> anyhow::Result<()> {
+        let input = name(vec!["foo"; 30].join(".") + "[0][1]");
+        assert!(input.build_operand().is_ok());
+
+        let input = name(vec!["foo"; 30].join(".") + "[0][1][2]");
+        assert_eq!(
+            input.build_operand().unwrap_err(),
+            error::ExpressionError::LimitExceededError(
+                "BuildOperand".to_owned(),
+                ExpressionLimit::PathDepth,
+                33
+            )
+        );
+
+        let input = (0..32).fold(path("foo"), |path, idx| path.index(idx));
+        assert_eq!(
+            input.into_name().build_operand().unwrap_err(),
+            error::ExpressionError::LimitExceededError(
+                "BuildOperand".to_owned(),
+                ExpressionLimit::PathDepth,
+                33
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_trailing_characters() -> anyhow::Result<()> {
+        let input = name("foo[1]bar").with_parse_mode(NameParseMode::Lenient);
+
+        assert_eq!(
+            input.build_operand()?.expression_node,
+            ExpressionNode::from_names(vec!["foo[1]bar".to_owned()], "$n"),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_nested_name_with_index() -> anyhow::Result<()> {
+        let input = name("foo.bar[0].baz").with_parse_mode(NameParseMode::Lenient);
+
+        assert_eq!(
+            input.build_operand()?.expression_node,
+            ExpressionNode::from_names(
+                vec!["foo".to_owned(), "bar".to_owned(), "baz".to_owned()],
+                "$n.$n[0].$n"
+            ),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_index_is_literal() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(
+                name("foo[$v]")
+                    .with_parse_mode(NameParseMode::Lenient)
+                    .equal(value(5)),
+            )
+            .build()?;
+
+        // the index is copied as-is instead of being substituted
+        assert_eq!(input.condition().unwrap(), "#0[$v] = :0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn literal_name() -> anyhow::Result<()> {
+        let input = name_literal("foo.bar[0]");
+
+        assert_eq!(input, path("foo.bar[0]").into_name());
+        assert_eq!(
+            input.build_operand()?.expression_node,
+            ExpressionNode::from_names(vec!["foo.bar[0]".to_owned()], "$n"),
+        );
+
+        let input = Builder::new().with_projection(names_list(name_literal("a.b"), vec![]));
+        assert_eq!(
+            input.record().script(),
+            "use dynamodb_expression::*;\n\
+             \n\
+             let builder = Builder::new()\n    \
+                 .with_projection(names_list(name_literal(\"a.b\"), vec![]));\n"
+        );
+
+        assert_eq!(
+            name_literal("").build_operand().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
@@ -0,0 +1,261 @@
+//! Typed document paths
+//!
+//! A Path spells out the elements of a document path one by one, instead of
+//! parsing them from a string. Attribute names are used as-is, so names
+//! containing dots or brackets can be referenced.
+
+use crate::{
+    error::ExpressionError, ExpressionNode, IntoOperand, NameBuilder, OperandBuilder, Recorder,
+};
+
+/// Represents a single element of a Path.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PathElement {
+    /// Field represents a top-level attribute or a nested map attribute
+    Field(String),
+
+    /// Index represents an element of a list attribute
+    Index(usize),
+}
+
+/// Represents a document path built from explicit elements.
+///
+/// Unlike the document path given to name(), no element of a Path is split on
+/// `.` or `[`, so every field is aliased as a single attribute name. A Path is
+/// converted into a NameBuilder to be used in expressions. name_literal() is a
+/// shorthand for a Path with a single field.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // the attribute "weird.name" of the first element of the list "foo"
+/// let name: Box<NameBuilder> = path("foo").index(0).field("weird.name").into();
+///
+/// let expr = Builder::new()
+///     .with_condition(name.attribute_exists())
+///     .build()
+///     .unwrap();
+/// assert_eq!(expr.condition().unwrap(), "attribute_exists (#0[0].#1)");
+/// assert_eq!(expr.names().as_ref().unwrap()["#1"], "weird.name");
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Path {
+    elements: Vec<PathElement>,
+}
+
+/// Returns a Path starting at the argument top-level attribute.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // condition represents the condition where the nested attribute
+/// // "baz" of the attribute "foo.bar" is 5
+/// let condition = path("foo.bar").field("baz").into_name().equal(value(5));
+/// ```
+pub fn path(field: impl Into<String>) -> Path {
+    Path {
+        elements: vec![PathElement::Field(field.into())],
+    }
+}
+
+impl Path {
+    /// Appends a nested map attribute to the Path.
+    pub fn field(mut self, field: impl Into<String>) -> Self {
+        self.elements.push(PathElement::Field(field.into()));
+        self
+    }
+
+    /// Appends a list index to the Path.
+    pub fn index(mut self, index: usize) -> Self {
+        self.elements.push(PathElement::Index(index));
+        self
+    }
+
+    /// Returns the elements of the Path.
+    pub fn elements(&self) -> &[PathElement] {
+        &self.elements
+    }
+
+    /// Returns a NameBuilder for the Path.
+    ///
+    /// The source location of the call is recorded, as it is by name().
+    #[track_caller]
+    pub fn into_name(self) -> Box<NameBuilder> {
+        NameBuilder::from_path(self)
+    }
+
+    pub(crate) fn build_node(&self) -> Result<ExpressionNode, ExpressionError> {
+        let mut node = ExpressionNode::default();
+
+        for element in self.elements.iter() {
+            match element {
+                PathElement::Field(field) => {
+                    if field.is_empty() {
+                        return Err(ExpressionError::UnsetParameterError(
+                            "BuildOperand".to_owned(),
+                            "NameBuilder".to_owned(),
+                        ));
+                    }
+
+                    if !node.names.is_empty() {
+                        node.fmt_expression.push_literal(".");
+                    }
+                    node.names.push(field.clone());
+                    node.fmt_expression = std::mem::take(&mut node.fmt_expression).name();
+                }
+                PathElement::Index(index) => {
+                    node.fmt_expression.push_literal(format!("[{}]", index));
+                }
+            }
+        }
+
+        crate::limits::check_path_depth(&node)?;
+
+        Ok(node)
+    }
+
+    pub(crate) fn record(&self, recorder: &mut Recorder) -> String {
+        let mut elements = self.elements.iter();
+
+        let mut call = match elements.next() {
+            Some(PathElement::Field(field)) => format!("path({})", recorder.name(field)),
+            _ => "path(\"\")".to_owned(),
+        };
+        for element in elements {
+            match element {
+                PathElement::Field(field) => {
+                    call.push_str(&format!(".field({})", recorder.name(field)))
+                }
+                PathElement::Index(index) => call.push_str(&format!(".index({})", index)),
+            }
+        }
+
+        call
+    }
+}
+
+impl From<Path> for NameBuilder {
+    fn from(path: Path) -> Self {
+        *NameBuilder::from_path(path)
+    }
+}
+
+impl From<Path> for Box<NameBuilder> {
+    #[track_caller]
+    fn from(path: Path) -> Self {
+        NameBuilder::from_path(path)
+    }
+}
+
+impl IntoOperand for Path {
+    #[track_caller]
+    fn into_operand(self) -> Box<dyn OperandBuilder> {
+        NameBuilder::from_path(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn path_elements() -> anyhow::Result<()> {
+        let input = path("foo")
+            .field("bar.baz")
+            .index(2)
+            .index(0)
+            .field("qux[1]");
+
+        assert_eq!(
+            input.elements(),
+            &[
+                PathElement::Field("foo".to_owned()),
+                PathElement::Field("bar.baz".to_owned()),
+                PathElement::Index(2),
+                PathElement::Index(0),
+                PathElement::Field("qux[1]".to_owned()),
+            ]
+        );
+        assert_eq!(
+            input.into_name().build_operand()?.expression_node,
+            ExpressionNode::from_names(
+                vec!["foo".to_owned(), "bar.baz".to_owned(), "qux[1]".to_owned()],
+                "$n.$n[2][0].$n"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn path_matches_name() -> anyhow::Result<()> {
+        let input = path("foo").field("bar").index(0).into_name();
+
+        assert_eq!(
+            input.build_operand()?.expression_node,
+            name("foo.bar[0]").build_operand()?.expression_node
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_field() -> anyhow::Result<()> {
+        let input = path("foo").field("").into_name();
+
+        assert_eq!(
+            input.build_operand().unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "BuildOperand".to_owned(),
+                "NameBuilder".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_path() -> anyhow::Result<()> {
+        let input = Builder::new().with_condition(
+            path("foo")
+                .field("a.b")
+                .index(1)
+                .into_name()
+                .equal(value(5)),
+        );
+
+        assert_eq!(
+            input.record().script(),
+            "use dynamodb_expression::*;\n\
+             \n\
+             let builder = Builder::new()\n    \
+                 .with_condition(equal(path(\"foo\").field(\"a.b\").index(1).into_name(), value(0)));\n"
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn name_macro() -> anyhow::Result<()> {
+        assert_eq!(
+            crate::name!("foo.bar[0][1].baz"),
+            path("foo")
+                .field("bar")
+                .index(0)
+                .index(1)
+                .field("baz")
+                .into_name()
+        );
+        assert_eq!(
+            crate::name!(r"foo").build_operand()?.expression_node,
+            name("foo").build_operand()?.expression_node
+        );
+
+        Ok(())
+    }
+}
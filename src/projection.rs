@@ -1,10 +1,15 @@
 //! Ported from [projection.go](https://github.com/aws/aws-sdk-go/blob/master/service/dynamodb/expression/projection.go)
 
-use anyhow::bail;
+use std::collections::{HashMap, HashSet};
 
-use crate::{error::ExpressionError, ExpressionNode, NameBuilder, OperandBuilder, TreeBuilder};
+use aws_sdk_dynamodb::types::AttributeValue;
 
-#[derive(Default, Debug, Clone)]
+use crate::{
+    error::ExpressionError, name, Builder, Expression, ExpressionNode, FmtExpression, NameBuilder,
+    OperandBuilder, PathElement, Recorder, Segment, TreeBuilder,
+};
+
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct ProjectionBuilder {
     #[allow(clippy::vec_box)]
     names: Vec<Box<NameBuilder>>,
@@ -15,34 +20,271 @@ impl ProjectionBuilder {
         add_names(self, names_list)
     }
 
-    fn build_child_nodes(&self) -> anyhow::Result<Vec<ExpressionNode>> {
+    /// Adds the argument document paths to the projection, see projection().
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let fields = vec!["b".to_owned(), "c[0]".to_owned()];
+    /// let proj = projection(["a"]).extend(fields);
+    ///
+    /// let expr = Builder::new().with_projection(proj).build().unwrap();
+    /// assert_eq!(expr.projection().unwrap(), "#0, #1, #2[0]");
+    /// ```
+    pub fn extend(
+        mut self,
+        names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> ProjectionBuilder {
+        self.names.extend(names.into_iter().map(name));
+        self
+    }
+
+    /// Splits the projection into several Projection Expressions, none of
+    /// which references more than max_names distinct attribute names.
+    ///
+    /// Projecting hundreds of attributes in a single request can run into the
+    /// DynamoDB expression limits. Each returned Expression can be used for a
+    /// separate GetItem or Query request, and the items returned by those
+    /// requests can be combined again with merge_projected_items(). The order
+    /// of the names is kept. If a single name has more than max_names
+    /// distinct path elements, split_expressions() returns the typed error
+    /// InvalidParameterError.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let proj = names_list(name("a"), vec![name("b"), name("c.d"), name("e")]);
+    /// let exprs = proj.split_expressions(2).unwrap();
+    ///
+    /// assert_eq!(exprs.len(), 3);
+    /// assert_eq!(exprs[0].projection().unwrap(), "#0, #1");
+    /// assert_eq!(exprs[1].projection().unwrap(), "#0.#1");
+    /// assert_eq!(exprs[2].projection().unwrap(), "#0");
+    /// ```
+    pub fn split_expressions(self, max_names: usize) -> Result<Vec<Expression>, ExpressionError> {
+        let mut chunks = Vec::new();
+        let mut chunk = Vec::new();
+        let mut chunk_names = HashSet::new();
+
+        for (i, name) in self.names.into_iter().enumerate() {
+            let operand = name
+                .build_operand()
+                .map_err(|e| e.in_path(format!("operand[{}]", i)))?;
+
+            let names = operand
+                .expression_node
+                .names
+                .into_iter()
+                .collect::<HashSet<_>>();
+            if names.len() > max_names {
+                return Err(ExpressionError::InvalidParameterError(
+                    "splitExpressions".to_owned(),
+                    "maxNames".to_owned(),
+                ));
+            }
+
+            if chunk_names.union(&names).count() > max_names {
+                chunks.push(std::mem::take(&mut chunk));
+                chunk_names.clear();
+            }
+
+            chunk.push(name);
+            chunk_names.extend(names);
+        }
+
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+
+        chunks
+            .into_iter()
+            .map(|names| {
+                Builder::new()
+                    .with_projection(ProjectionBuilder { names })
+                    .build()
+            })
+            .collect()
+    }
+
+    /// Removes the names projecting a document path already projected by
+    /// another name, or nested in one, keeping the order of the others.
+    ///
+    /// DynamoDB rejects a Projection Expression with overlapping paths, such
+    /// as `a` and `a.b`, which build() reports with the typed error
+    /// OverlappingPaths. Since projecting `a` returns all of `a.b`, the
+    /// nested path can be dropped without changing the projected items.
+    /// Names that can't be built are kept, for build() to report.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let proj = names_list(
+    ///     name("a.b"),
+    ///     vec![name("c"), name("a"), name("c"), name("d[0]"), name("d[1].e")],
+    /// );
+    ///
+    /// let expr = Builder::new().with_projection(proj.dedup()).build().unwrap();
+    /// assert_eq!(expr.projection().unwrap(), "#0, #1, #2[0], #2[1].#3");
+    /// ```
+    pub fn dedup(mut self) -> ProjectionBuilder {
+        let paths = self
+            .names
+            .iter()
+            .map(|name| {
+                name.build_operand()
+                    .ok()
+                    .map(|operand| node_path(&operand.expression_node))
+            })
+            .collect::<Vec<_>>();
+
+        let mut idx = 0;
+        self.names.retain(|_| {
+            let keep = match &paths[idx] {
+                Some(path) => !paths.iter().enumerate().any(|(other_idx, other)| {
+                    other.as_ref().is_some_and(|other| {
+                        path.starts_with(other) && (other.len() < path.len() || other_idx < idx)
+                    })
+                }),
+                None => true,
+            };
+            idx += 1;
+            keep
+        });
+
+        self
+    }
+
+    fn build_child_nodes(&self) -> Result<Vec<ExpressionNode>, ExpressionError> {
         let mut child_nodes = Vec::new();
-        for name in &self.names {
-            let operand = name.build_operand()?;
+        for (i, name) in self.names.iter().enumerate() {
+            let operand = name
+                .build_operand()
+                .map_err(|e| e.in_path(format!("operand[{}]", i)))?;
             child_nodes.push(operand.expression_node);
         }
         Ok(child_nodes)
     }
 }
 
+/// Collects NameBuilders into a ProjectionBuilder projecting all of them.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let proj: ProjectionBuilder = ["a", "b"].into_iter().map(name).collect();
+/// assert_eq!(proj, names_list(name("a"), vec![name("b")]));
+/// ```
+impl FromIterator<Box<NameBuilder>> for ProjectionBuilder {
+    fn from_iter<T: IntoIterator<Item = Box<NameBuilder>>>(iter: T) -> Self {
+        ProjectionBuilder {
+            names: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Adds NameBuilders to the ProjectionBuilder.
+///
+/// The method ProjectionBuilder::extend() takes precedence in method calls,
+/// so the trait method is called as `Extend::extend(&mut proj, names)`.
+impl Extend<Box<NameBuilder>> for ProjectionBuilder {
+    fn extend<T: IntoIterator<Item = Box<NameBuilder>>>(&mut self, iter: T) {
+        self.names.extend(iter);
+    }
+}
+
 impl TreeBuilder for ProjectionBuilder {
-    fn build_tree(&self) -> anyhow::Result<ExpressionNode> {
+    fn build_tree(&self) -> Result<ExpressionNode, ExpressionError> {
         if self.names.is_empty() {
-            bail!(ExpressionError::UnsetParameterError(
+            return Err(ExpressionError::UnsetParameterError(
                 "buildTree".to_owned(),
                 "ProjectionBuilder".to_owned(),
             ));
         }
 
         let child_nodes = self.build_child_nodes()?;
+        check_overlapping_paths(&child_nodes)?;
 
         let node = ExpressionNode::from_children_expression(
             child_nodes,
-            format!("$c{}", ", $c".repeat(self.names.len() - 1)),
+            FmtExpression::new().children(self.names.len(), ", "),
         );
 
         Ok(node)
     }
+
+    fn record(&self, recorder: &mut Recorder) -> String {
+        let mut names = self.names.iter().map(|name| name.record(recorder));
+        match names.next() {
+            Some(first) => format!(
+                "names_list({}, vec![{}])",
+                first,
+                names.collect::<Vec<_>>().join(", ")
+            ),
+            None => "ProjectionBuilder::default()".to_owned(),
+        }
+    }
+}
+
+// returns the elements of the document path of a built name
+fn node_path(node: &ExpressionNode) -> Vec<PathElement> {
+    let mut names = node.names.iter();
+    let mut elements = Vec::new();
+
+    for segment in node.fmt_expression.segments() {
+        match segment {
+            Segment::Name => elements.extend(names.next().cloned().map(PathElement::Field)),
+            // the list indexes between the names, such as "[0][1]."
+            Segment::Literal(literal) => elements.extend(
+                literal
+                    .split('[')
+                    .skip(1)
+                    .filter_map(|index| index.split(']').next()?.parse().ok())
+                    .map(PathElement::Index),
+            ),
+            _ => (),
+        }
+    }
+
+    elements
+}
+
+fn fmt_path(path: &[PathElement]) -> String {
+    path.iter()
+        .enumerate()
+        .map(|(idx, element)| match element {
+            PathElement::Field(field) if idx == 0 => field.clone(),
+            PathElement::Field(field) => format!(".{}", field),
+            PathElement::Index(index) => format!("[{}]", index),
+        })
+        .collect()
+}
+
+// returns the typed error OverlappingPaths for the first pair of paths of
+// which one is equal to or nested in the other
+fn check_overlapping_paths(nodes: &[ExpressionNode]) -> Result<(), ExpressionError> {
+    let paths = nodes.iter().map(node_path).collect::<Vec<_>>();
+
+    for (idx, path) in paths.iter().enumerate() {
+        for other in &paths[idx + 1..] {
+            if path.starts_with(other) || other.starts_with(path) {
+                return Err(ExpressionError::OverlappingPaths(
+                    "buildTree".to_owned(),
+                    fmt_path(path),
+                    fmt_path(other),
+                ));
+            }
+        }
+    }
+
+    Ok(())
 }
 
 #[allow(clippy::boxed_local)]
@@ -56,6 +298,54 @@ pub fn names_list(
     ProjectionBuilder { names: names_list }
 }
 
+/// Returns a ProjectionBuilder projecting the argument document paths, each
+/// parsed like the argument of name().
+///
+/// Unlike names_list(), projection() takes any iterator of strings, such as a
+/// list of fields known only at runtime. Projecting no path returns an empty
+/// ProjectionBuilder, for which build() returns the typed error
+/// UnsetParameterError. The projection! macro is a shorthand for a fixed list
+/// of paths.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let proj = projection(["a", "b.c", "d[0]"]);
+///
+/// let expr = Builder::new().with_projection(proj).build().unwrap();
+/// assert_eq!(expr.projection().unwrap(), "#0, #1.#2, #3[0]");
+/// assert_eq!(projection(["a", "b.c", "d[0]"]), projection!["a", "b.c", "d[0]"]);
+/// ```
+pub fn projection(names: impl IntoIterator<Item = impl Into<String>>) -> ProjectionBuilder {
+    ProjectionBuilder::default().extend(names)
+}
+
+/// Returns a ProjectionBuilder projecting the argument document paths, see
+/// projection().
+///
+/// The paths can be of different string types.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let field = "c".to_owned();
+/// let proj = projection!["a", "b[1]", field];
+///
+/// let expr = Builder::new().with_projection(proj).build().unwrap();
+/// assert_eq!(expr.projection().unwrap(), "#0, #1[1], #2");
+/// ```
+#[macro_export]
+#[allow(edition_2024_expr_fragment_specifier)]
+macro_rules! projection {
+    ($first:expr $(, $rest:expr)* $(,)?) => {
+        $crate::names_list($crate::name($first), vec![$($crate::name($rest)),*])
+    };
+}
+
 pub fn add_names(
     mut projection_builder: ProjectionBuilder,
     names_list: impl Into<Vec<Box<NameBuilder>>>,
@@ -65,6 +355,55 @@ pub fn add_names(
     projection_builder
 }
 
+/// Merges the items returned by the requests of a projection split with
+/// ProjectionBuilder::split_expressions() back into a single item.
+///
+/// Map attributes that were projected in parts by different requests are
+/// merged recursively. For any other attribute returned by more than one
+/// request, the value of the first request is kept.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use dynamodb_expression::*;
+///
+/// let first = HashMap::from([("a".to_owned(), AttributeValue::N("1".to_owned()))]);
+/// let second = HashMap::from([("b".to_owned(), AttributeValue::N("2".to_owned()))]);
+///
+/// let item = merge_projected_items(vec![first, second]);
+/// assert_eq!(item.len(), 2);
+/// ```
+pub fn merge_projected_items(
+    items: impl IntoIterator<Item = HashMap<String, AttributeValue>>,
+) -> HashMap<String, AttributeValue> {
+    let mut merged = HashMap::new();
+    for item in items {
+        merge_attributes(&mut merged, item);
+    }
+    merged
+}
+
+fn merge_attributes(
+    merged: &mut HashMap<String, AttributeValue>,
+    item: HashMap<String, AttributeValue>,
+) {
+    for (name, value) in item {
+        match merged.get_mut(&name) {
+            Some(AttributeValue::M(existing)) => {
+                if let AttributeValue::M(value) = value {
+                    merge_attributes(existing, value);
+                }
+            }
+            Some(_) => (),
+            None => {
+                merged.insert(name, value);
+            }
+        }
+    }
+}
+
 impl NameBuilder {
     pub fn names_list(
         self: Box<NameBuilder>,
@@ -76,6 +415,10 @@ impl NameBuilder {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::types::AttributeValue;
+
     use crate::*;
 
     #[test]
@@ -150,10 +493,7 @@ mod tests {
         let input = ProjectionBuilder::default();
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "buildTree".to_owned(),
                 "ProjectionBuilder".to_owned()
@@ -184,14 +524,183 @@ mod tests {
         let input = names_list(name(""), vec![]);
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn overlapping_paths() -> anyhow::Result<()> {
+        for (first, second, paths) in [
+            (name("foo"), name("foo"), ("foo", "foo")),
+            (name("foo[1].bar"), name("foo"), ("foo[1].bar", "foo")),
+            (name("foo[1]"), name("foo[1][2]"), ("foo[1]", "foo[1][2]")),
+            (name_literal("a.b"), name_literal("a.b"), ("a.b", "a.b")),
+        ] {
+            assert_eq!(
+                names_list(name("baz"), vec![first, second])
+                    .build_tree()
+                    .unwrap_err(),
+                error::ExpressionError::OverlappingPaths(
+                    "buildTree".to_owned(),
+                    paths.0.to_owned(),
+                    paths.1.to_owned()
+                )
+            );
+        }
+
+        // neither the same names in other lists nor prefixes of names overlap
+        names_list(
+            name("foo[1]"),
+            vec![name("foo[2]"), name("foo[10]"), name("fo"), name("bar.foo")],
+        )
+        .build_tree()?;
+        names_list(name_literal("a.b"), vec![name("a.b")]).build_tree()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn projection_from_strings() -> anyhow::Result<()> {
+        let fields = ["bar".to_owned(), "baz[1]".to_owned()];
+
+        assert_eq!(
+            projection(["foo"]).extend(fields.iter().map(String::as_str)),
+            names_list(name("foo"), vec![name("bar"), name("baz[1]")])
+        );
+        assert_eq!(
+            projection!("foo", fields[0].clone(), "baz[1]",),
+            names_list(name("foo"), vec![name("bar"), name("baz[1]")])
+        );
+        assert_eq!(
+            projection(Vec::<String>::new()).build_tree().unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "buildTree".to_owned(),
+                "ProjectionBuilder".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_projection() -> anyhow::Result<()> {
+        let mut input = ["foo", "bar"]
+            .into_iter()
+            .map(name)
+            .collect::<ProjectionBuilder>();
+        Extend::extend(&mut input, vec![name("baz")]);
+
+        assert_eq!(
+            input,
+            names_list(name("foo"), vec![name("bar"), name("baz")])
+        );
+        assert_eq!(
+            std::iter::empty::<Box<NameBuilder>>().collect::<ProjectionBuilder>(),
+            ProjectionBuilder::default()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn dedup_projection() -> anyhow::Result<()> {
+        let input = names_list(
+            name("foo[1].bar"),
+            vec![
+                name("baz"),
+                name("foo[1]"),
+                name("baz"),
+                name("foo[2].bar"),
+                name(""),
+            ],
+        );
+
+        assert_eq!(
+            input.dedup(),
+            names_list(
+                name("baz"),
+                vec![name("foo[1]"), name("foo[2].bar"), name("")]
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn split_expressions() -> anyhow::Result<()> {
+        let input = names_list(
+            name("foo"),
+            vec![name("bar.foo"), name("baz.qux"), name("bar.baz")],
+        );
+
+        let exprs = input.split_expressions(3)?;
+        assert_eq!(exprs.len(), 2);
+        assert_eq!(exprs[0].projection().unwrap(), "#0, #1.#0");
+        assert_eq!(exprs[1].projection().unwrap(), "#0.#1, #2.#0");
+        assert_eq!(
+            exprs[1].names().clone().unwrap(),
+            HashMap::from([
+                ("#0".to_owned(), "baz".to_owned()),
+                ("#1".to_owned(), "qux".to_owned()),
+                ("#2".to_owned(), "bar".to_owned()),
+            ])
+        );
+
+        assert_eq!(
+            names_list(name("foo.bar"), vec![])
+                .split_expressions(1)
+                .unwrap_err(),
+            error::ExpressionError::InvalidParameterError(
+                "splitExpressions".to_owned(),
+                "maxNames".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_items() -> anyhow::Result<()> {
+        let first = HashMap::from([
+            ("foo".to_owned(), AttributeValue::N("1".to_owned())),
+            (
+                "bar".to_owned(),
+                AttributeValue::M(HashMap::from([(
+                    "foo".to_owned(),
+                    AttributeValue::S("a".to_owned()),
+                )])),
+            ),
+        ]);
+        let second = HashMap::from([
+            ("foo".to_owned(), AttributeValue::N("2".to_owned())),
+            (
+                "bar".to_owned(),
+                AttributeValue::M(HashMap::from([(
+                    "baz".to_owned(),
+                    AttributeValue::S("b".to_owned()),
+                )])),
+            ),
+        ]);
+
+        assert_eq!(
+            merge_projected_items(vec![first, second]),
+            HashMap::from([
+                ("foo".to_owned(), AttributeValue::N("1".to_owned())),
+                (
+                    "bar".to_owned(),
+                    AttributeValue::M(HashMap::from([
+                        ("foo".to_owned(), AttributeValue::S("a".to_owned())),
+                        ("baz".to_owned(), AttributeValue::S("b".to_owned())),
+                    ])),
+                ),
+            ])
         );
 
         Ok(())
@@ -0,0 +1,342 @@
+//! Recording of builders as replayable scripts
+//!
+//! A Recording renders the calls that construct a Builder as Rust source,
+//! with every value replaced by a placeholder of the same type. The script can
+//! be attached to a bug report and replayed to reproduce the expressions,
+//! without sharing the data the expressions were built with.
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{Builder, OperandBuilder};
+
+/// Represents the calls constructing a Builder, rendered as a replayable Rust
+/// script.
+///
+/// Attribute names and the structure of the expressions are kept as-is, while
+/// strings, numbers and binary values are replaced by placeholders. Equal
+/// values are replaced by equal placeholders, so aliasing and deduplication
+/// behave the same when the script is replayed. Booleans and nulls carry no
+/// data and are kept.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let builder = Builder::new()
+///     .with_key_condition(key("customerId").equal(value("c-1234")))
+///     .with_filter(name("total").greater_than(value(100)));
+///
+/// assert_eq!(
+///     builder.record().script(),
+///     "use dynamodb_expression::*;\n\
+///      \n\
+///      let builder = Builder::new()\n    \
+///          .with_key_condition(key_equal(key(\"customerId\"), value(\"s0\")))\n    \
+///          .with_filter(greater_than(name(\"total\"), value(0)));\n"
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Recording {
+    script: String,
+}
+
+impl Recording {
+    /// Returns the recorded script.
+    pub fn script(&self) -> &str {
+        &self.script
+    }
+}
+
+impl std::fmt::Display for Recording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.script)
+    }
+}
+
+/// Records the calls constructing a Builder while redacting its values.
+///
+/// A Recorder is passed to OperandBuilder::record(), implementations of
+/// OperandBuilder outside of this crate can use it to record the values they
+/// contain.
+#[derive(Default, Debug)]
+pub struct Recorder {
+    strings: Vec<String>,
+    numbers: Vec<String>,
+    binaries: Vec<Vec<u8>>,
+    uses_attribute_value: bool,
+}
+
+impl Recorder {
+    /// Returns the call creating a ValueBuilder for the redacted argument
+    /// value.
+    pub fn value(&mut self, value: &AttributeValue) -> String {
+        match value {
+            AttributeValue::S(s) => format!("value({})", self.redact_string(s)),
+            AttributeValue::N(n) => format!("value({})", self.redact_number(n)),
+            AttributeValue::Bool(b) => format!("value({})", b),
+            AttributeValue::Ss(ss) => format!(
+                "value(vec![{}])",
+                ss.iter()
+                    .map(|s| self.redact_string(s))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            _ => format!("value({})", self.attribute_value(value)),
+        }
+    }
+
+    /// Returns the argument name as a string literal. Names are not redacted.
+    pub(crate) fn name(&self, name: &str) -> String {
+        format!("{:?}", name)
+    }
+
+    /// Returns the placeholder string literal for the argument string.
+    pub(crate) fn redact_string(&mut self, s: &str) -> String {
+        let idx = position_or_push(&mut self.strings, s);
+        format!("\"s{}\"", idx)
+    }
+
+    fn redact_number(&mut self, n: &str) -> usize {
+        position_or_push(&mut self.numbers, n)
+    }
+
+    fn redact_binary(&mut self, b: &[u8]) -> String {
+        let idx = position_or_push(&mut self.binaries, b);
+        format!("aws_sdk_dynamodb::primitives::Blob::new(\"b{}\")", idx)
+    }
+
+    fn attribute_value(&mut self, value: &AttributeValue) -> String {
+        self.uses_attribute_value = true;
+
+        match value {
+            AttributeValue::S(s) => {
+                format!("AttributeValue::S({}.to_owned())", self.redact_string(s))
+            }
+            AttributeValue::N(n) => format!(
+                "AttributeValue::N(\"{}\".to_owned())",
+                self.redact_number(n)
+            ),
+            AttributeValue::B(b) => {
+                format!("AttributeValue::B({})", self.redact_binary(b.as_ref()))
+            }
+            AttributeValue::Bool(b) => format!("AttributeValue::Bool({})", b),
+            AttributeValue::Ss(ss) => format!(
+                "AttributeValue::Ss(vec![{}])",
+                ss.iter()
+                    .map(|s| format!("{}.to_owned()", self.redact_string(s)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            AttributeValue::Ns(ns) => format!(
+                "AttributeValue::Ns(vec![{}])",
+                ns.iter()
+                    .map(|n| format!("\"{}\".to_owned()", self.redact_number(n)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            AttributeValue::Bs(bs) => format!(
+                "AttributeValue::Bs(vec![{}])",
+                bs.iter()
+                    .map(|b| self.redact_binary(b.as_ref()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            AttributeValue::L(l) => format!(
+                "AttributeValue::L(vec![{}])",
+                l.iter()
+                    .map(|v| self.attribute_value(v))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            AttributeValue::M(m) => {
+                // map keys are attribute names, sorted to keep the script stable
+                let mut entries = m.iter().collect::<Vec<_>>();
+                entries.sort_by(|x, y| x.0.cmp(y.0));
+
+                format!(
+                    "AttributeValue::M([{}].into_iter().collect())",
+                    entries
+                        .into_iter()
+                        .map(|(k, v)| format!(
+                            "({}.to_owned(), {})",
+                            self.name(k),
+                            self.attribute_value(v)
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+            _ => "AttributeValue::Null(true)".to_owned(),
+        }
+    }
+}
+
+// returns the string of a value operand, such as the prefix of begins_with
+pub(crate) fn string_operand(operand: &dyn OperandBuilder) -> Option<String> {
+    match operand.build_operand().ok()?.expression_node.value()? {
+        AttributeValue::S(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn position_or_push<T, U>(list: &mut Vec<T>, item: &U) -> usize
+where
+    T: PartialEq<U> + std::borrow::Borrow<U>,
+    U: ToOwned<Owned = T> + ?Sized,
+{
+    match list.iter().position(|x| x == item) {
+        Some(idx) => idx,
+        None => {
+            list.push(item.to_owned());
+            list.len() - 1
+        }
+    }
+}
+
+impl Builder {
+    /// Returns a Recording of the calls constructing the argument Builder.
+    ///
+    /// Recording is opt-in and does not change the Builder. The recorded
+    /// script replaces every value by a placeholder, so it can be shared in a
+    /// bug report to reproduce the structure and aliasing of the expressions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let builder = Builder::new().with_update(set(name("email"), value("someone@example.com")));
+    ///
+    /// println!("{}", builder.record());
+    /// assert!(!builder.record().script().contains("someone@example.com"));
+    /// ```
+    pub fn record(&self) -> Recording {
+        let mut recorder = Recorder::default();
+        let calls = self.record_calls(&mut recorder);
+
+        let mut script = String::new();
+        if recorder.uses_attribute_value {
+            script.push_str("use aws_sdk_dynamodb::types::AttributeValue;\n");
+        }
+        script.push_str("use dynamodb_expression::*;\n\nlet builder = Builder::new()");
+        for call in calls {
+            script.push_str("\n    .");
+            script.push_str(&call);
+        }
+        script.push_str(";\n");
+
+        Recording { script }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    use crate::*;
+
+    #[test]
+    fn record_builder() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_key_condition(
+                key("pk")
+                    .equal(value("secret"))
+                    .and(key("sk").begins_with("2024-")),
+            )
+            .with_filter(
+                name("status")
+                    .r#in(vec![value("open"), value("secret")])
+                    .or(attribute_type(name("tags"), DynamoDbAttributeType::StringSet).not()),
+            )
+            .with_projection(names_list(name("pk"), vec![name("items[0].id")]))
+            .with_update(
+                set(name("count"), name("count").plus(value(1)))
+                    .set(
+                        name("meta"),
+                        value(AttributeValue::M(
+                            [("a".to_owned(), AttributeValue::N("1".to_owned()))]
+                                .into_iter()
+                                .collect(),
+                        )),
+                    )
+                    .remove(name("old")),
+            )
+            .with_value_dedup(true);
+
+        assert_eq!(
+            input.record().script(),
+            "use aws_sdk_dynamodb::types::AttributeValue;\n\
+             use dynamodb_expression::*;\n\
+             \n\
+             let builder = Builder::new()\n    \
+                 .with_value_dedup(true)\n    \
+                 .with_projection(names_list(name(\"pk\"), vec![name(\"items[0].id\")]))\n    \
+                 .with_key_condition(key_and(key_equal(key(\"pk\"), value(\"s0\")), key_begins_with(key(\"sk\"), \"s1\")))\n    \
+                 .with_filter(or(r#in(name(\"status\"), vec![value(\"s2\"), value(\"s0\")]), not(attribute_type(name(\"tags\"), DynamoDbAttributeType::StringSet))))\n    \
+                 .with_update(set(name(\"count\"), plus(name(\"count\"), value(0))).set(name(\"meta\"), value(AttributeValue::M([(\"a\".to_owned(), AttributeValue::N(\"0\".to_owned()))].into_iter().collect()))).remove(name(\"old\")));\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_key_schema() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_key_schema(
+                KeySchema::new("pk")
+                    .with_sort_key("sk")
+                    .with_index(SecondaryIndex::new("ByEmail", "email").sparse()),
+            )
+            .with_key_condition(key("pk").equal(value(1)));
+
+        assert_eq!(
+            input.record().script(),
+            "use dynamodb_expression::*;\n\
+             \n\
+             let builder = Builder::new()\n    \
+                 .with_key_schema(KeySchema::new(\"pk\").with_sort_key(\"sk\").with_index(SecondaryIndex::new(\"ByEmail\", \"email\").sparse()))\n    \
+                 .with_key_condition(key_equal(key(\"pk\"), value(0)));\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn replay() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(
+                name("a")
+                    .between(value(5), value(10))
+                    .and(name("b").contains("secret"))
+                    .and(name("c").size().less_than(value(5))),
+            )
+            .with_named_condition("other", attribute_not_exists(name("d")))
+            .with_alias_prefix("n", "v");
+
+        // the recorded script, pasted
+        let replayed = Builder::new()
+            .with_alias_prefix("n", "v")
+            .with_condition(and(
+                and(
+                    between(name("a"), value(0), value(1)),
+                    contains(name("b"), "s0"),
+                ),
+                less_than(size(name("c")), value(0)),
+            ))
+            .with_named_condition("other", attribute_not_exists(name("d")));
+
+        assert_eq!(input.record(), replayed.record());
+
+        let input = input.build()?;
+        let replayed = replayed.build()?;
+        assert_eq!(input.condition(), replayed.condition());
+        assert_eq!(
+            input.named_condition("other"),
+            replayed.named_condition("other")
+        );
+        assert_eq!(input.names(), replayed.names());
+
+        Ok(())
+    }
+}
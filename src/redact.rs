@@ -0,0 +1,235 @@
+//! Redaction of values in logged expressions
+//!
+//! Expression::to_debug_string() substitutes the names and values of an
+//! Expression for its aliases, which makes it readable but also copies any
+//! personal data stored in the values into the logs. A RedactionPolicy
+//! replaces some or all of the values with a mask or a hash instead.
+
+use std::collections::HashSet;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::expression::{ResolvedPart, StableHasher};
+
+/// Represents how a RedactionPolicy replaces a value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Redaction {
+    /// Mask replaces every value with `<redacted>`.
+    #[default]
+    Mask,
+
+    /// Hash replaces every value with a hash of the value and the seed, such
+    /// as `<hash:5f0e3a91c2d4b786>`, so equal values can still be told apart
+    /// from different ones. The seed should be kept secret, as values with
+    /// few possibilities can be guessed from their hashes otherwise.
+    Hash(u64),
+}
+
+/// Represents which values Expression::to_debug_string() redacts, and how.
+///
+/// A value is attributed to the document path written before it in the
+/// expression, such as `Email` in `Email = :0` or in
+/// `begins_with (Email, :0)`, and is redacted when any attribute name of that
+/// path is in the deny-list. Values written before any path of their
+/// condition, such as in `:0 < Price`, are always redacted. Names left unaliased by
+/// with_bare_names() aren't recognized, so with bare names only
+/// RedactionPolicy::all() is reliable.
+///
+/// The tracing feature logs the expressions with to_debug_string(), so it
+/// honors the policy of the Builder as well.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let filt = name("Email")
+///     .equal(value("someone@example.com"))
+///     .and(name("Price").greater_than(value(5)));
+///
+/// let expr = Builder::new()
+///     .with_filter(filt)
+///     .with_redaction_policy(RedactionPolicy::attributes(["Email"]))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(
+///     expr.to_debug_string(),
+///     "filter: (Email = <redacted>) AND (Price > 5)"
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct RedactionPolicy {
+    // None redacts every value
+    attributes: Option<HashSet<String>>,
+    redaction: Redaction,
+}
+
+impl RedactionPolicy {
+    /// Returns a RedactionPolicy redacting every value.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Returns a RedactionPolicy redacting the values of the argument
+    /// attribute names, see RedactionPolicy.
+    pub fn attributes(attributes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            attributes: Some(attributes.into_iter().map(Into::into).collect()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets how the values are replaced, masked by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let expr = Builder::new()
+    ///     .with_filter(name("Email").equal(value("someone@example.com")))
+    ///     .with_redaction_policy(RedactionPolicy::all().with_redaction(Redaction::Hash(7)))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(expr.to_debug_string().starts_with("filter: Email = <hash:"));
+    /// ```
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    // returns whether the value written after the argument path is redacted
+    fn redacts(&self, path: &[&str]) -> bool {
+        match &self.attributes {
+            None => true,
+            Some(_) if path.is_empty() => true,
+            Some(attributes) => path.iter().any(|name| attributes.contains(*name)),
+        }
+    }
+
+    fn redact(&self, value: &AttributeValue) -> String {
+        match self.redaction {
+            Redaction::Mask => "<redacted>".to_owned(),
+            Redaction::Hash(seed) => {
+                let mut hasher = StableHasher::new(seed);
+                hasher.write_value(value);
+                format!("<hash:{:016x}>", hasher.finish())
+            }
+        }
+    }
+}
+
+// follows the parts of an expression, keeping the names of the last document
+// path seen, which the values written after it are attributed to
+#[derive(Default)]
+pub(crate) struct ValueRedactor<'a> {
+    path: Vec<&'a str>,
+    in_path: bool,
+}
+
+impl<'a> ValueRedactor<'a> {
+    // returns the redacted string of the value, if the policy redacts it
+    pub(crate) fn redact(
+        &mut self,
+        policy: Option<&RedactionPolicy>,
+        part: &ResolvedPart<'a>,
+    ) -> Option<String> {
+        match part {
+            ResolvedPart::Name(name) => {
+                if !self.in_path {
+                    self.path.clear();
+                }
+                self.path.push(name);
+                self.in_path = true;
+            }
+            // the separators of a path are dots and list indexes, while an
+            // opening parenthesis starts a new operand or condition, except
+            // for the list of IN
+            ResolvedPart::Text(text) => {
+                if text.contains('(') && !text.ends_with(" IN (") {
+                    self.path.clear();
+                }
+                self.in_path = self.in_path
+                    && !text.is_empty()
+                    && text
+                        .chars()
+                        .all(|ch| matches!(ch, '.' | '[' | ']') || ch.is_ascii_digit());
+            }
+            ResolvedPart::Value(value) => {
+                self.in_path = false;
+                return policy
+                    .filter(|policy| policy.redacts(&self.path))
+                    .map(|policy| policy.redact(value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn redacted_attributes() -> anyhow::Result<()> {
+        let filt = name("Profile.Email")
+            .equal(value("someone@example.com"))
+            .and(name("Tags[0]").equal(value("vip")))
+            .and(value(5).less_than(name("Price")))
+            .and(name("Price").between(value(1), value(10)))
+            .and(in_values(name("Id"), ["a", "b"])?);
+
+        let expr = Builder::new()
+            .with_filter(filt.clone())
+            .with_redaction_policy(RedactionPolicy::attributes(["Profile"]))
+            .build()?;
+        assert_eq!(
+            expr.to_debug_string(),
+            "filter: ((((Profile.Email = <redacted>) AND (Tags[0] = \"vip\")) AND (<redacted> < Price)) AND (Price BETWEEN 1 AND 10)) AND (Id IN (\"a\", \"b\"))"
+        );
+
+        let expr = Builder::new()
+            .with_filter(filt.clone())
+            .with_redaction_policy(RedactionPolicy::attributes(["Tags", "Price", "Id"]))
+            .build()?;
+        assert_eq!(
+            expr.to_debug_string(),
+            "filter: ((((Profile.Email = \"someone@example.com\") AND (Tags[0] = <redacted>)) AND (<redacted> < Price)) AND (Price BETWEEN <redacted> AND <redacted>)) AND (Id IN (<redacted>, <redacted>))"
+        );
+
+        let expr = Builder::new().with_filter(filt).build()?;
+        assert_eq!(
+            expr.with_redaction_policy(RedactionPolicy::all())
+                .to_debug_string(),
+            "filter: ((((Profile.Email = <redacted>) AND (Tags[0] = <redacted>)) AND (<redacted> < Price)) AND (Price BETWEEN <redacted> AND <redacted>)) AND (Id IN (<redacted>, <redacted>))"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn hashed_values() -> anyhow::Result<()> {
+        let hashed = |seed| -> anyhow::Result<String> {
+            Ok(Builder::new()
+                .with_filter(name("a").equal(value("x")).and(name("b").equal(value("x"))))
+                .with_redaction_policy(RedactionPolicy::all().with_redaction(Redaction::Hash(seed)))
+                .build()?
+                .to_debug_string())
+        };
+
+        let debug_string = hashed(1)?;
+        let hashes = debug_string
+            .split("<hash:")
+            .skip(1)
+            .map(|part| &part[..16])
+            .collect::<Vec<_>>();
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(hashes[0], hashes[1]);
+        assert_eq!(hashed(1)?, debug_string);
+        assert_ne!(hashed(2)?, debug_string);
+
+        Ok(())
+    }
+}
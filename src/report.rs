@@ -0,0 +1,329 @@
+//! Detection of changes to the rendered expressions between crate versions
+//!
+//! The crate keeps a corpus of builders together with the output each of them
+//! rendered when the corpus was recorded. Rendering the corpus again shows
+//! which outputs a new version of the crate changed, so downstream code that
+//! stores or compares expression strings can check an upgrade before relying
+//! on it.
+
+use crate::{
+    attribute_exists, key, name, names_list, set, value, BetweenBuilder, Builder, ClauseOrder,
+    EqualBuilder, Expression, LessThanBuilder, PlusBuilder,
+};
+
+/// Represents the result of rendering the compatibility corpus with the
+/// current version of the crate.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let report = render_compat_report();
+/// for change in report.changes() {
+///     println!("{} changed:\n{}\n=>\n{}", change.case(), change.expected(), change.rendered());
+/// }
+/// assert!(report.is_unchanged());
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CompatReport {
+    version: &'static str,
+    cases: usize,
+    changes: Vec<RenderChange>,
+}
+
+impl CompatReport {
+    /// Returns the version of the crate that rendered the corpus.
+    pub fn version(&self) -> &'static str {
+        self.version
+    }
+
+    /// Returns the number of corpus cases that were rendered.
+    pub fn cases(&self) -> usize {
+        self.cases
+    }
+
+    /// Returns the cases whose rendered output differs from the recorded output.
+    pub fn changes(&self) -> &[RenderChange] {
+        &self.changes
+    }
+
+    /// Returns true if every case rendered the recorded output.
+    pub fn is_unchanged(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Represents a corpus case whose rendered output differs from the recorded
+/// output.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RenderChange {
+    case: &'static str,
+    expected: &'static str,
+    rendered: String,
+}
+
+impl RenderChange {
+    /// Returns the name of the corpus case.
+    pub fn case(&self) -> &'static str {
+        self.case
+    }
+
+    /// Returns the recorded output of the case.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// Returns the output rendered by the current version of the crate.
+    pub fn rendered(&self) -> &str {
+        &self.rendered
+    }
+}
+
+struct CorpusCase {
+    name: &'static str,
+    builder: fn() -> Builder,
+    expected: &'static str,
+}
+
+const CORPUS: &[CorpusCase] = &[
+    CorpusCase {
+        name: "condition_equal",
+        builder: || Builder::new().with_condition(name("foo").equal(value(5))),
+        expected: "condition: #0 = :0\n\
+                   name #0: foo\n\
+                   value :0: N(\"5\")",
+    },
+    CorpusCase {
+        name: "condition_nested",
+        builder: || {
+            Builder::new().with_condition(
+                name("foo")
+                    .between(value(1), value(9))
+                    .or(attribute_exists(name("bar.baz[0]")).not()),
+            )
+        },
+        expected: "condition: (#0 BETWEEN :0 AND :1) OR (NOT (attribute_exists (#1.#2[0])))\n\
+                   name #0: foo\n\
+                   name #1: bar\n\
+                   name #2: baz\n\
+                   value :0: N(\"1\")\n\
+                   value :1: N(\"9\")",
+    },
+    CorpusCase {
+        name: "condition_functions",
+        builder: || {
+            Builder::new().with_condition(
+                name("foo")
+                    .begins_with("abc")
+                    .and(name("bar").contains("x"))
+                    .and(name("baz").size().less_than(value(10))),
+            )
+        },
+        expected: "condition: ((begins_with (#0, :0)) AND (contains (#1, :1))) AND (size (#2) < :2)\n\
+                   name #0: foo\n\
+                   name #1: bar\n\
+                   name #2: baz\n\
+                   value :0: S(\"abc\")\n\
+                   value :1: S(\"x\")\n\
+                   value :2: N(\"10\")",
+    },
+    CorpusCase {
+        name: "key_condition",
+        builder: || {
+            Builder::new().with_key_condition(
+                key("pk")
+                    .equal(value("a"))
+                    .and(key("sk").begins_with("b")),
+            )
+        },
+        expected: "key_condition: (#0 = :0) AND (begins_with (#1, :1))\n\
+                   name #0: pk\n\
+                   name #1: sk\n\
+                   value :0: S(\"a\")\n\
+                   value :1: S(\"b\")",
+    },
+    CorpusCase {
+        name: "projection",
+        builder: || {
+            Builder::new().with_projection(names_list(name("foo"), vec![name("bar.baz")]))
+        },
+        expected: "projection: #0, #1.#2\n\
+                   name #0: foo\n\
+                   name #1: bar\n\
+                   name #2: baz",
+    },
+    CorpusCase {
+        name: "update",
+        builder: || {
+            Builder::new().with_update(
+                set(name("foo"), value(5))
+                    .set(name("bar"), name("bar").plus(value(1)))
+                    .remove(name("baz"))
+                    .add(name("qux"), value(vec!["a"])),
+            )
+        },
+        expected: "update: SET #0 = :0, #1 = #1 + :1\nREMOVE #2\nADD #3 :2\n\n\
+                   name #0: foo\n\
+                   name #1: bar\n\
+                   name #2: baz\n\
+                   name #3: qux\n\
+                   value :0: N(\"5\")\n\
+                   value :1: N(\"1\")\n\
+                   value :2: Ss([\"a\"])",
+    },
+    CorpusCase {
+        name: "update_go_sdk_order",
+        builder: || {
+            Builder::new().with_update(
+                set(name("foo"), value(5))
+                    .set(name("bar"), name("bar").plus(value(1)))
+                    .remove(name("baz"))
+                    .add(name("qux"), value(vec!["a"]))
+                    .with_clause_order(ClauseOrder::GoSdk),
+            )
+        },
+        expected: "update: ADD #0 :0\nREMOVE #1\nSET #2 = :1, #3 = #3 + :2\n\n\
+                   name #0: qux\n\
+                   name #1: baz\n\
+                   name #2: foo\n\
+                   name #3: bar\n\
+                   value :0: Ss([\"a\"])\n\
+                   value :1: N(\"5\")\n\
+                   value :2: N(\"1\")",
+    },
+    CorpusCase {
+        name: "multiple_expressions",
+        builder: || {
+            Builder::new()
+                .with_key_condition(key("pk").equal(value("a")))
+                .with_filter(name("foo").less_than(value(5)))
+                .with_projection(names_list(name("pk"), vec![name("foo")]))
+        },
+        expected: "projection: #0, #1\n\
+                   key_condition: #0 = :0\n\
+                   filter: #1 < :1\n\
+                   name #0: pk\n\
+                   name #1: foo\n\
+                   value :0: S(\"a\")\n\
+                   value :1: N(\"5\")",
+    },
+    CorpusCase {
+        name: "unset_name",
+        builder: || Builder::new().with_condition(name("").equal(value(5))),
+        expected: "error: condition > equal > operand[0]: BuildOperand error: unset parameter: NameBuilder",
+    },
+];
+
+/// Renders the compatibility corpus with the current version of the crate
+/// and compares every output to the output recorded for it.
+///
+/// The rendered output of a case lists the expression strings in the order
+/// they are aliased, followed by the sorted ExpressionAttributeNames and
+/// ExpressionAttributeValues, or the error returned by build(). Any change in
+/// alias assignment, formatting or error reporting shows up as a
+/// RenderChange in the returned CompatReport.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let report = render_compat_report();
+/// assert!(report.is_unchanged(), "{:#?}", report.changes());
+/// ```
+pub fn render_compat_report() -> CompatReport {
+    let changes = CORPUS
+        .iter()
+        .filter_map(|case| {
+            let rendered = match (case.builder)().build() {
+                Ok(expr) => render(&expr),
+                Err(err) => format!("error: {}", render_error(&err)),
+            };
+
+            (rendered != case.expected).then_some(RenderChange {
+                case: case.name,
+                expected: case.expected,
+                rendered,
+            })
+        })
+        .collect();
+
+    CompatReport {
+        version: env!("CARGO_PKG_VERSION"),
+        cases: CORPUS.len(),
+        changes,
+    }
+}
+
+fn render_error(err: &crate::error::ExpressionError) -> String {
+    match err.path() {
+        Some(path) => format!("{}: {}", path, err.root_cause()),
+        None => err.root_cause().to_string(),
+    }
+}
+
+fn render(expr: &Expression) -> String {
+    let mut lines = Vec::new();
+
+    for (label, expression) in [
+        ("projection", expr.projection()),
+        ("key_condition", expr.key_condition()),
+        ("condition", expr.condition()),
+        ("filter", expr.filter()),
+        ("update", expr.update()),
+    ] {
+        if let Some(expression) = expression {
+            lines.push(format!("{}: {}", label, expression));
+        }
+    }
+
+    if let Some(names) = expr.names() {
+        let mut names = names.iter().collect::<Vec<_>>();
+        names.sort();
+        for (alias, name) in names {
+            lines.push(format!("name {}: {}", alias, name));
+        }
+    }
+
+    if let Some(values) = expr.values() {
+        let mut values = values.iter().collect::<Vec<_>>();
+        values.sort_by(|x, y| x.0.cmp(y.0));
+        for (alias, value) in values {
+            lines.push(format!("value {}: {:?}", alias, value));
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn corpus_unchanged() -> anyhow::Result<()> {
+        let report = render_compat_report();
+
+        assert_eq!(report.changes(), &[]);
+        assert_eq!(report.cases(), 9);
+        assert_eq!(report.version(), env!("CARGO_PKG_VERSION"));
+        assert!(report.is_unchanged());
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_expression() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_filter(name("foo").equal(value(5)))
+            .build()?;
+
+        assert_eq!(
+            super::render(&input),
+            "filter: #0 = :0\nname #0: foo\nvalue :0: N(\"5\")"
+        );
+
+        Ok(())
+    }
+}
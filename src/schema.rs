@@ -0,0 +1,638 @@
+//! Table schema information used to check expressions against the indexes
+//! they are run on
+
+use crate::{error::ExpressionError, ExpressionNode, FmtExpression, KeyConditionBuilder, Recorder};
+
+/// Represents the primary key of a DynamoDB table, along with its secondary
+/// indexes.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let schema = KeySchema::new("pk")
+///     .with_sort_key("sk")
+///     .with_index(SecondaryIndex::new("ByEmail", "email"));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KeySchema {
+    partition_key: String,
+    sort_key: Option<String>,
+    indexes: Vec<SecondaryIndex>,
+}
+
+impl KeySchema {
+    /// Returns a KeySchema with the given partition key attribute.
+    pub fn new(partition_key: impl Into<String>) -> Self {
+        Self {
+            partition_key: partition_key.into(),
+            sort_key: None,
+            indexes: Vec::new(),
+        }
+    }
+
+    /// Sets the sort key attribute of the table.
+    pub fn with_sort_key(mut self, sort_key: impl Into<String>) -> Self {
+        self.sort_key = Some(sort_key.into());
+        self
+    }
+
+    /// Returns the partition key attribute of the table.
+    pub fn partition_key(&self) -> &str {
+        &self.partition_key
+    }
+
+    /// Returns the sort key attribute of the table, if it has one.
+    pub fn sort_key(&self) -> Option<&str> {
+        self.sort_key.as_deref()
+    }
+
+    /// Adds a secondary index of the table.
+    pub fn with_index(mut self, index: SecondaryIndex) -> Self {
+        self.indexes.push(index);
+        self
+    }
+
+    /// Returns the secondary indexes of the table.
+    pub fn indexes(&self) -> &[SecondaryIndex] {
+        &self.indexes
+    }
+
+    /// Returns the key attributes of the table, partition key first.
+    pub fn key_attributes(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.partition_key.as_str()).chain(self.sort_key.as_deref())
+    }
+
+    // returns the KeySchema constructor calls recreating the KeySchema
+    pub(crate) fn record(&self, recorder: &mut Recorder) -> String {
+        let mut call = format!("KeySchema::new({})", recorder.name(&self.partition_key));
+        if let Some(sort_key) = &self.sort_key {
+            call.push_str(&format!(".with_sort_key({})", recorder.name(sort_key)));
+        }
+        for index in self.indexes.iter() {
+            call.push_str(&format!(".with_index({})", index.record(recorder)));
+        }
+        call
+    }
+}
+
+/// Represents a secondary index of a DynamoDB table.
+///
+/// A sparse index is keyed on an attribute that not every item has. Only the
+/// items that have the attribute are written to the index, so operations on
+/// the base table that rely on the attribute being present should say so
+/// explicitly with attribute_exists().
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let index = SecondaryIndex::new("OpenOrders", "openedAt").sparse();
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SecondaryIndex {
+    name: String,
+    partition_key: String,
+    sort_key: Option<String>,
+    sparse: bool,
+}
+
+impl SecondaryIndex {
+    /// Returns a SecondaryIndex with the given index name and partition key
+    /// attribute.
+    pub fn new(name: impl Into<String>, partition_key: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            partition_key: partition_key.into(),
+            sort_key: None,
+            sparse: false,
+        }
+    }
+
+    /// Sets the sort key attribute of the index.
+    pub fn with_sort_key(mut self, sort_key: impl Into<String>) -> Self {
+        self.sort_key = Some(sort_key.into());
+        self
+    }
+
+    /// Marks the index as sparse, meaning its key attributes are optional on
+    /// the items of the table.
+    pub fn sparse(mut self) -> Self {
+        self.sparse = true;
+        self
+    }
+
+    /// Returns the name of the index.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the partition key attribute of the index.
+    pub fn partition_key(&self) -> &str {
+        &self.partition_key
+    }
+
+    /// Returns the sort key attribute of the index, if it has one.
+    pub fn sort_key(&self) -> Option<&str> {
+        self.sort_key.as_deref()
+    }
+
+    /// Returns true if the index is sparse.
+    pub fn is_sparse(&self) -> bool {
+        self.sparse
+    }
+
+    fn key_attributes(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.partition_key.as_str()).chain(self.sort_key.as_deref())
+    }
+
+    fn record(&self, recorder: &mut Recorder) -> String {
+        let mut call = format!(
+            "SecondaryIndex::new({}, {})",
+            recorder.name(&self.name),
+            recorder.name(&self.partition_key)
+        );
+        if let Some(sort_key) = &self.sort_key {
+            call.push_str(&format!(".with_sort_key({})", recorder.name(sort_key)));
+        }
+        if self.sparse {
+            call.push_str(".sparse()");
+        }
+        call
+    }
+}
+
+/// Represents a problem found when checking an expression against the table
+/// schema. Warnings do not prevent the expression from being built.
+#[derive(thiserror::Error, Clone, PartialEq, Eq, Debug)]
+pub enum SchemaWarning {
+    /// Returned if a filter references a key attribute of a sparse index
+    /// without guarding it with attribute_exists().
+    #[error(
+        "filter assumes {attribute} always exists, but it is the key of sparse index {index}: \
+         add attribute_exists({attribute}) to the filter"
+    )]
+    SparseIndexAttributeAssumed { index: String, attribute: String },
+}
+
+/// Checks the unformatted tree of a filter against the sparse indexes of the
+/// table.
+pub(crate) fn check_sparse_indexes(
+    node: &ExpressionNode,
+    indexes: &[SecondaryIndex],
+) -> Vec<SchemaWarning> {
+    let mut referenced = Vec::new();
+    let mut guarded = Vec::new();
+    collect_attributes(node, true, false, &mut referenced, &mut guarded);
+
+    let mut warnings = Vec::new();
+    for index in indexes.iter().filter(|index| index.is_sparse()) {
+        for attribute in index.key_attributes() {
+            if referenced.contains(&attribute) && !guarded.contains(&attribute) {
+                warnings.push(SchemaWarning::SparseIndexAttributeAssumed {
+                    index: index.name().to_owned(),
+                    attribute: attribute.to_owned(),
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Checks the unformatted tree of a key condition against the key of the
+/// table and of its indexes, returning the key attributes of the one it
+/// queries, the table first.
+///
+/// The partition key must be compared for equality, and the sort key, if any,
+/// must be the sort key of the same table or index. If an index name is
+/// given, the key condition is only checked against the key of that index.
+pub(crate) fn check_key_condition(
+    schema: &KeySchema,
+    index_name: Option<&str>,
+    node: &ExpressionNode,
+) -> Result<Vec<String>, ExpressionError> {
+    let (partition, sort) = match node.children.as_slice() {
+        [partition, sort] if node.fmt_expression == KeyConditionBuilder::and_fmt_expression() => {
+            (partition, Some(sort))
+        }
+        _ => (node, None),
+    };
+
+    let equality = FmtExpression::new().child().literal(" = ").child();
+    let partition_attribute = compared_attribute(partition);
+    if partition.fmt_expression != equality {
+        return Err(key_schema_violation(format!(
+            "key condition must compare the partition key for equality, not {}",
+            partition_attribute
+        )));
+    }
+    let sort_attribute = sort.map(compared_attribute);
+
+    let keys = match index_name {
+        Some(index_name) => {
+            let index = check_index(schema, index_name)?;
+            vec![(index.partition_key(), index.sort_key())]
+        }
+        None => std::iter::once((schema.partition_key(), schema.sort_key()))
+            .chain(
+                schema
+                    .indexes()
+                    .iter()
+                    .map(|index| (index.partition_key(), index.sort_key())),
+            )
+            .collect(),
+    };
+    for (partition_key, sort_key) in keys {
+        if partition_key != partition_attribute {
+            continue;
+        }
+
+        if sort_attribute.is_none() || sort_key == sort_attribute {
+            return Ok(std::iter::once(partition_key)
+                .chain(sort_key)
+                .map(str::to_owned)
+                .collect());
+        }
+    }
+
+    let queried = match index_name {
+        Some(index_name) => format!("index {}", index_name),
+        None => "the table or of one of its indexes".to_owned(),
+    };
+    Err(key_schema_violation(match sort_attribute {
+        Some(sort_attribute) => format!(
+            "{} and {} aren't the key of {}",
+            partition_attribute, sort_attribute, queried
+        ),
+        None => format!(
+            "{} isn't the partition key of {}",
+            partition_attribute, queried
+        ),
+    }))
+}
+
+/// Returns the index of the table with the argument name.
+pub(crate) fn check_index<'a>(
+    schema: &'a KeySchema,
+    index_name: &str,
+) -> Result<&'a SecondaryIndex, ExpressionError> {
+    schema
+        .indexes()
+        .iter()
+        .find(|index| index.name() == index_name)
+        .ok_or_else(|| key_schema_violation(format!("unknown index {}", index_name)))
+}
+
+/// Checks that the unformatted tree of a filter doesn't reference the
+/// argument key attributes, which DynamoDB rejects in the Filter Expression
+/// of a Query.
+pub(crate) fn check_filter(keys: &[String], node: &ExpressionNode) -> Result<(), ExpressionError> {
+    let mut referenced = Vec::new();
+    let mut guarded = Vec::new();
+    collect_attributes(node, true, false, &mut referenced, &mut guarded);
+
+    match referenced
+        .into_iter()
+        .chain(guarded)
+        .find(|attribute| keys.iter().any(|key| key == attribute))
+    {
+        Some(attribute) => Err(key_schema_violation(format!(
+            "filter references the key attribute {}",
+            attribute
+        ))),
+        None => Ok(()),
+    }
+}
+
+// returns the key attribute compared by a key condition node
+fn compared_attribute(node: &ExpressionNode) -> &str {
+    node.children
+        .iter()
+        .find_map(|child| child.names.first())
+        .map(String::as_str)
+        .unwrap_or_default()
+}
+
+fn key_schema_violation(reason: String) -> ExpressionError {
+    ExpressionError::KeySchemaViolation("checkKeySchema".to_owned(), reason)
+}
+
+// only attribute_exists() conditions AND-ed at the top level of the filter
+// guard their attribute, not the ones under NOT or in an OR branch
+fn collect_attributes<'a>(
+    node: &'a ExpressionNode,
+    top_level: bool,
+    in_exists: bool,
+    referenced: &mut Vec<&'a str>,
+    guarded: &mut Vec<&'a str>,
+) {
+    // only the top level attribute of a path can be an index key
+    if let Some(attribute) = node.names.first() {
+        if in_exists {
+            guarded.push(attribute);
+        } else {
+            referenced.push(attribute);
+        }
+    }
+
+    let in_exists = top_level && node.fmt_expression.starts_with("attribute_exists (");
+    let top_level = top_level && is_and(node);
+    for child in node.children.iter() {
+        collect_attributes(child, top_level, in_exists, referenced, guarded);
+    }
+}
+
+fn is_and(node: &ExpressionNode) -> bool {
+    node.fmt_expression
+        == FmtExpression::new()
+            .children(node.children.len(), ") AND (")
+            .wrap("(", ")")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn sparse_index() -> SecondaryIndex {
+        SecondaryIndex::new("OpenOrders", "openedAt")
+            .with_sort_key("priority")
+            .sparse()
+    }
+
+    #[test]
+    fn unguarded_sparse_key() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(name("openedAt").less_than(value(5)));
+
+        assert_eq!(
+            input.check_sparse_indexes(&[sparse_index()])?,
+            vec![SchemaWarning::SparseIndexAttributeAssumed {
+                index: "OpenOrders".to_owned(),
+                attribute: "openedAt".to_owned(),
+            }],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn guarded_sparse_key() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(
+            name("openedAt")
+                .attribute_exists()
+                .and(name("openedAt").less_than(value(5))),
+        );
+
+        assert_eq!(input.check_sparse_indexes(&[sparse_index()])?, vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn negated_sparse_guard() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(name("openedAt").attribute_exists().not());
+
+        assert_eq!(
+            input.check_sparse_indexes(&[sparse_index()])?,
+            vec![SchemaWarning::SparseIndexAttributeAssumed {
+                index: "OpenOrders".to_owned(),
+                attribute: "openedAt".to_owned(),
+            }],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn alternative_sparse_guard() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(
+            name("openedAt")
+                .attribute_exists()
+                .or(name("openedAt").less_than(value(5))),
+        );
+
+        assert_eq!(
+            input.check_sparse_indexes(&[sparse_index()])?,
+            vec![SchemaWarning::SparseIndexAttributeAssumed {
+                index: "OpenOrders".to_owned(),
+                attribute: "openedAt".to_owned(),
+            }],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_and_sparse_guard() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(
+            name("status").equal(value("open")).and(
+                name("openedAt")
+                    .attribute_exists()
+                    .and(name("openedAt").less_than(value(5))),
+            ),
+        );
+
+        assert_eq!(input.check_sparse_indexes(&[sparse_index()])?, vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_sparse_key() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(
+            name("status")
+                .equal(value("open"))
+                .and(name("priority.level").size().greater_than(value(1))),
+        );
+
+        assert_eq!(
+            input.check_sparse_indexes(&[sparse_index()])?,
+            vec![SchemaWarning::SparseIndexAttributeAssumed {
+                index: "OpenOrders".to_owned(),
+                attribute: "priority".to_owned(),
+            }],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn dense_index() -> anyhow::Result<()> {
+        let input = Builder::new().with_filter(name("openedAt").less_than(value(5)));
+
+        assert_eq!(
+            input.check_sparse_indexes(&[SecondaryIndex::new("OpenOrders", "openedAt")])?,
+            vec![],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn no_filter() -> anyhow::Result<()> {
+        let input = Builder::new().with_condition(name("openedAt").less_than(value(5)));
+
+        assert_eq!(input.check_sparse_indexes(&[sparse_index()])?, vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn warning_message() -> anyhow::Result<()> {
+        let input = SchemaWarning::SparseIndexAttributeAssumed {
+            index: "OpenOrders".to_owned(),
+            attribute: "openedAt".to_owned(),
+        };
+
+        assert_eq!(
+            input.to_string(),
+            "filter assumes openedAt always exists, but it is the key of sparse index OpenOrders: \
+             add attribute_exists(openedAt) to the filter"
+        );
+
+        Ok(())
+    }
+
+    fn key_schema() -> KeySchema {
+        KeySchema::new("pk")
+            .with_sort_key("sk")
+            .with_index(SecondaryIndex::new("ByEmail", "email").with_sort_key("createdAt"))
+    }
+
+    fn violation(reason: &str) -> error::ExpressionError {
+        error::ExpressionError::InPath {
+            path: vec!["key_condition".to_owned()],
+            source: Box::new(error::ExpressionError::KeySchemaViolation(
+                "checkKeySchema".to_owned(),
+                reason.to_owned(),
+            )),
+        }
+    }
+
+    #[test]
+    fn key_schema_key_condition() -> anyhow::Result<()> {
+        for key_condition in [
+            key("pk").equal(value("a")),
+            key("pk").equal(value("a")).and(key("sk").begins_with("b")),
+            key("email").equal(value("a")),
+            key("email")
+                .equal(value("a"))
+                .and(key("createdAt").greater_than(value(5))),
+        ] {
+            let input = Builder::new()
+                .with_key_schema(key_schema())
+                .with_key_condition(key_condition);
+            assert!(input.build().is_ok());
+        }
+
+        let input = Builder::new()
+            .with_key_schema(key_schema())
+            .with_key_condition(key("sk").begins_with("b"));
+        assert_eq!(
+            input.build().unwrap_err(),
+            violation("key condition must compare the partition key for equality, not sk")
+        );
+
+        let input = Builder::new()
+            .with_key_schema(key_schema())
+            .with_key_condition(key("other").equal(value("a")));
+        assert_eq!(
+            input.build().unwrap_err(),
+            violation("other isn't the partition key of the table or of one of its indexes")
+        );
+
+        let input = Builder::new()
+            .with_key_schema(key_schema())
+            .with_key_condition(
+                key("pk")
+                    .equal(value("a"))
+                    .and(key("createdAt").equal(value(1))),
+            );
+        assert_eq!(
+            input.build().unwrap_err(),
+            violation("pk and createdAt aren't the key of the table or of one of its indexes")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_schema_filter() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_key_schema(key_schema())
+            .with_key_condition(key("pk").equal(value("a")))
+            .with_filter(
+                name("status")
+                    .equal(value("open"))
+                    .and(name("sk").attribute_exists()),
+            );
+        assert_eq!(
+            input.build().unwrap_err(),
+            error::ExpressionError::InPath {
+                path: vec!["filter".to_owned()],
+                source: Box::new(error::ExpressionError::KeySchemaViolation(
+                    "checkKeySchema".to_owned(),
+                    "filter references the key attribute sk".to_owned(),
+                )),
+            }
+        );
+
+        // the table keys can be filtered on when querying an index
+        let input = Builder::new()
+            .with_key_schema(key_schema())
+            .with_key_condition(key("email").equal(value("a")))
+            .with_filter(name("sk").begins_with("b"));
+        assert!(input.build().is_ok());
+
+        // scans can filter on any attribute
+        let input = Builder::new()
+            .with_key_schema(key_schema())
+            .with_filter(name("pk").equal(value("a")));
+        assert!(input.build().is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_schema_index() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_key_schema(key_schema())
+            .for_index("ByEmail")
+            .with_key_condition(key("email").equal(value("a")))
+            .with_filter(name("pk").equal(value("b")))
+            .build()?;
+        assert_eq!(input.index_name(), Some("ByEmail"));
+
+        let input = Builder::new()
+            .with_key_schema(key_schema())
+            .for_index("ByEmail")
+            .with_key_condition(key("pk").equal(value("a")));
+        assert_eq!(
+            input.build().unwrap_err(),
+            violation("pk isn't the partition key of index ByEmail")
+        );
+
+        let input = Builder::new()
+            .with_key_schema(key_schema())
+            .for_index("ByName")
+            .with_filter(name("pk").equal(value("a")));
+        assert_eq!(
+            input.build().unwrap_err(),
+            error::ExpressionError::KeySchemaViolation(
+                "checkKeySchema".to_owned(),
+                "unknown index ByName".to_owned()
+            )
+        );
+
+        // without a KeySchema, the index name is passed through
+        let input = Builder::new()
+            .for_index("ByName")
+            .with_key_condition(key("pk").equal(value("a")))
+            .build()?;
+        assert_eq!(input.index_name(), Some("ByName"));
+
+        Ok(())
+    }
+}
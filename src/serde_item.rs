@@ -0,0 +1,268 @@
+//! Values, conditions and updates from serde_dynamo items
+//!
+//! Projects serializing their items with serde_dynamo hold them as a
+//! serde_dynamo Item, a map of serde_dynamo AttributeValues. The conversions
+//! of this module turn those into DynamoValues, so that they can be compared
+//! or stored with an operand, and whole items into conditions and updates.
+
+use serde_dynamo::{AttributeValue, Item};
+
+use crate::{
+    backend::invalid_attribute_value, equal, error::ExpressionError, name_literal, value,
+    AttributeValueLike, ConditionBuilder, DynamoValue, UpdateBuilder,
+};
+
+impl AttributeValueLike for AttributeValue {
+    // DynamoDB only accepts Null(true)
+    fn into_dynamo_value(self) -> Result<DynamoValue, ExpressionError> {
+        Ok(match self {
+            AttributeValue::S(s) => DynamoValue::S(s),
+            AttributeValue::N(n) => DynamoValue::N(n),
+            AttributeValue::B(b) => DynamoValue::B(b),
+            AttributeValue::Bool(b) => DynamoValue::Bool(b),
+            AttributeValue::Null(true) => DynamoValue::Null,
+            AttributeValue::Null(false) => {
+                return Err(invalid_attribute_value(
+                    "serde_dynamo::AttributeValue::Null(false)",
+                ))
+            }
+            AttributeValue::Ss(ss) => DynamoValue::Ss(ss),
+            AttributeValue::Ns(ns) => DynamoValue::Ns(ns),
+            AttributeValue::Bs(bs) => DynamoValue::Bs(bs),
+            AttributeValue::L(l) => DynamoValue::L(
+                l.into_iter()
+                    .map(Self::into_dynamo_value)
+                    .collect::<Result<_, _>>()?,
+            ),
+            AttributeValue::M(m) => DynamoValue::M(
+                m.into_iter()
+                    .map(|(k, v)| Ok((k, v.into_dynamo_value()?)))
+                    .collect::<Result<_, ExpressionError>>()?,
+            ),
+        })
+    }
+
+    fn from_dynamo_value(value: DynamoValue) -> Self {
+        match value {
+            DynamoValue::S(s) => AttributeValue::S(s),
+            DynamoValue::N(n) => AttributeValue::N(n),
+            DynamoValue::B(b) => AttributeValue::B(b),
+            DynamoValue::Bool(b) => AttributeValue::Bool(b),
+            DynamoValue::Null => AttributeValue::Null(true),
+            DynamoValue::Ss(ss) => AttributeValue::Ss(ss),
+            DynamoValue::Ns(ns) => AttributeValue::Ns(ns),
+            DynamoValue::Bs(bs) => AttributeValue::Bs(bs),
+            DynamoValue::L(l) => {
+                AttributeValue::L(l.into_iter().map(Self::from_dynamo_value).collect())
+            }
+            DynamoValue::M(m) => AttributeValue::M(
+                m.into_iter()
+                    .map(|(k, v)| (k, Self::from_dynamo_value(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl TryFrom<AttributeValue> for DynamoValue {
+    type Error = ExpressionError;
+
+    /// Converts the serde_dynamo AttributeValue into a DynamoValue, see
+    /// AttributeValueLike::into_dynamo_value().
+    fn try_from(value: AttributeValue) -> Result<Self, Self::Error> {
+        value.into_dynamo_value()
+    }
+}
+
+impl From<DynamoValue> for AttributeValue {
+    fn from(value: DynamoValue) -> Self {
+        AttributeValue::from_dynamo_value(value)
+    }
+}
+
+impl TryFrom<Item> for DynamoValue {
+    type Error = ExpressionError;
+
+    /// Converts the serde_dynamo Item into a DynamoValue map.
+    fn try_from(item: Item) -> Result<Self, Self::Error> {
+        AttributeValue::M(item.into_inner()).into_dynamo_value()
+    }
+}
+
+/// Returns an UpdateBuilder that SETs every attribute of the argument
+/// serde_dynamo Item, in the order of their names.
+///
+/// Each attribute is a top-level attribute named by its key. Items with a
+/// value that has no DynamoValue, such as Null(false), return the typed error
+/// InvalidParameterError.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use dynamodb_expression::*;
+///
+/// let item = serde_dynamo::Item::from(HashMap::from([
+///     ("nickname".to_owned(), serde_dynamo::AttributeValue::S("ace".to_owned())),
+///     ("age".to_owned(), serde_dynamo::AttributeValue::N("30".to_owned())),
+/// ]));
+///
+/// let expr = Builder::new().with_update(item_update(item).unwrap()).build().unwrap();
+/// assert_eq!(expr.update().unwrap(), "SET #0 = :0, #1 = :1\n");
+/// assert_eq!(expr.names().as_ref().unwrap()["#0"], "age");
+/// ```
+pub fn item_update(item: Item) -> Result<UpdateBuilder, ExpressionError> {
+    Ok(sorted_attributes(item)?.into_iter().fold(
+        UpdateBuilder::default(),
+        |update, (attribute, attribute_value)| {
+            update.set(name_literal(attribute), value(attribute_value))
+        },
+    ))
+}
+
+/// Returns a ConditionBuilder representing every attribute of the argument
+/// serde_dynamo Item being equal to its value in the item, in the order of
+/// their names.
+///
+/// Each attribute is a top-level attribute named by its key. Items with a
+/// value that has no DynamoValue, such as Null(false), return the typed error
+/// InvalidParameterError.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use dynamodb_expression::*;
+///
+/// let item = serde_dynamo::Item::from(HashMap::from([
+///     ("status".to_owned(), serde_dynamo::AttributeValue::S("open".to_owned())),
+///     ("version".to_owned(), serde_dynamo::AttributeValue::N("3".to_owned())),
+/// ]));
+///
+/// let expr = Builder::new()
+///     .with_condition(item_condition(item).unwrap())
+///     .build()
+///     .unwrap();
+/// assert_eq!(expr.condition().unwrap(), "(#0 = :0) AND (#1 = :1)");
+/// ```
+pub fn item_condition(item: Item) -> Result<ConditionBuilder, ExpressionError> {
+    Ok(sorted_attributes(item)?
+        .into_iter()
+        .map(|(attribute, attribute_value)| equal(name_literal(attribute), value(attribute_value)))
+        .collect())
+}
+
+fn sorted_attributes(item: Item) -> Result<Vec<(String, DynamoValue)>, ExpressionError> {
+    match DynamoValue::try_from(item)? {
+        DynamoValue::M(attributes) => Ok(attributes.into_iter().collect()),
+        _ => unreachable!("an Item converts to a map"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde_dynamo::{AttributeValue, Item};
+
+    use crate::{error::ExpressionError, *};
+
+    #[test]
+    fn serde_dynamo_round_trip() -> anyhow::Result<()> {
+        let input = AttributeValue::M(HashMap::from([
+            ("s".to_owned(), AttributeValue::S("a".to_owned())),
+            ("n".to_owned(), AttributeValue::N("1".to_owned())),
+            ("b".to_owned(), AttributeValue::B(b"b".to_vec())),
+            ("bool".to_owned(), AttributeValue::Bool(true)),
+            ("null".to_owned(), AttributeValue::Null(true)),
+            ("ss".to_owned(), AttributeValue::Ss(vec!["a".to_owned()])),
+            ("ns".to_owned(), AttributeValue::Ns(vec!["1".to_owned()])),
+            ("bs".to_owned(), AttributeValue::Bs(vec![b"b".to_vec()])),
+            (
+                "l".to_owned(),
+                AttributeValue::L(vec![AttributeValue::S("a".to_owned())]),
+            ),
+        ]));
+
+        let dynamo_value = DynamoValue::try_from(input.clone())?;
+        assert_eq!(
+            dynamo_value,
+            DynamoValue::M(BTreeMap::from([
+                ("s".to_owned(), DynamoValue::S("a".to_owned())),
+                ("n".to_owned(), DynamoValue::N("1".to_owned())),
+                ("b".to_owned(), DynamoValue::B(b"b".to_vec())),
+                ("bool".to_owned(), DynamoValue::Bool(true)),
+                ("null".to_owned(), DynamoValue::Null),
+                ("ss".to_owned(), DynamoValue::Ss(vec!["a".to_owned()])),
+                ("ns".to_owned(), DynamoValue::Ns(vec!["1".to_owned()])),
+                ("bs".to_owned(), DynamoValue::Bs(vec![b"b".to_vec()])),
+                (
+                    "l".to_owned(),
+                    DynamoValue::L(vec![DynamoValue::S("a".to_owned())])
+                ),
+            ]))
+        );
+        assert_eq!(AttributeValue::from(dynamo_value.clone()), input);
+
+        let AttributeValue::M(attributes) = input else {
+            unreachable!();
+        };
+        assert_eq!(DynamoValue::try_from(Item::from(attributes))?, dynamo_value);
+
+        assert_eq!(
+            DynamoValue::try_from(AttributeValue::L(vec![AttributeValue::Null(false)])),
+            Err(ExpressionError::InvalidParameterError(
+                "intoDynamoValue".to_owned(),
+                "serde_dynamo::AttributeValue::Null(false)".to_owned()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn serde_dynamo_operands() -> anyhow::Result<()> {
+        let item = || {
+            Item::from(HashMap::from([
+                ("b".to_owned(), AttributeValue::N("5".to_owned())),
+                ("a".to_owned(), AttributeValue::S("x".to_owned())),
+            ]))
+        };
+
+        let expr = Builder::new()
+            .with_condition(item_condition(item())?)
+            .with_update(
+                item_update(item())?.set(name("c"), value_like(AttributeValue::Bool(true))?),
+            )
+            .build()?;
+
+        assert_eq!(expr.condition(), Some("(#0 = :0) AND (#1 = :1)"));
+        assert_eq!(expr.update(), Some("SET #0 = :2, #1 = :3, #2 = :4\n"));
+        assert_eq!(
+            expr.values_as::<AttributeValue>()?.unwrap(),
+            HashMap::from([
+                (":0".to_owned(), AttributeValue::S("x".to_owned())),
+                (":1".to_owned(), AttributeValue::N("5".to_owned())),
+                (":2".to_owned(), AttributeValue::S("x".to_owned())),
+                (":3".to_owned(), AttributeValue::N("5".to_owned())),
+                (":4".to_owned(), AttributeValue::Bool(true)),
+            ])
+        );
+
+        assert_eq!(
+            item_update(Item::from(HashMap::from([(
+                "a".to_owned(),
+                AttributeValue::Null(false)
+            )])))
+            .unwrap_err(),
+            ExpressionError::InvalidParameterError(
+                "intoDynamoValue".to_owned(),
+                "serde_dynamo::AttributeValue::Null(false)".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+//! UpdateBuilders generated from serializable items
+
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{error::ExpressionError, name_literal, value, UpdateBuilder};
+
+/// Represents the options of update_from_with().
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // REMOVE the fields that are None, and leave out the key attribute
+/// let options = UpdateFromOptions::default()
+///     .with_remove_none(true)
+///     .with_skipped(["pk"]);
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct UpdateFromOptions {
+    remove_none: bool,
+    skipped: Vec<String>,
+}
+
+impl UpdateFromOptions {
+    /// Sets whether fields that serialize to null, such as None, are removed
+    /// from the item with a REMOVE operation. They are left untouched by
+    /// default.
+    pub fn with_remove_none(mut self, remove_none: bool) -> Self {
+        self.remove_none = remove_none;
+        self
+    }
+
+    /// Adds attributes that are left out of the update, such as the key
+    /// attributes, which can't be updated.
+    pub fn with_skipped(mut self, attributes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.skipped.extend(attributes.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// Returns an UpdateBuilder that SETs every field of the argument item that
+/// isn't None.
+///
+/// The item must serialize to a map, each entry of which becomes a top-level
+/// attribute named by its serialized name. Fields that serialize to null are
+/// left untouched. The values are converted the way serde_json sees them:
+/// numbers become N, strings S, sequences L and maps M, so sets and binary
+/// attributes can't be produced this way. If the item doesn't serialize to a
+/// map, update_from() returns the typed error InvalidParameterError.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// #[derive(serde::Serialize)]
+/// struct Profile {
+///     nickname: Option<String>,
+///     age: Option<u32>,
+/// }
+///
+/// let update = update_from(&Profile {
+///     nickname: Some("ace".to_owned()),
+///     age: None,
+/// })
+/// .unwrap();
+///
+/// let expr = Builder::new().with_update(update).build().unwrap();
+/// assert_eq!(expr.update().unwrap(), "SET #0 = :0\n");
+/// ```
+pub fn update_from<T: Serialize + ?Sized>(item: &T) -> Result<UpdateBuilder, ExpressionError> {
+    update_from_with(item, UpdateFromOptions::default())
+}
+
+/// Returns an UpdateBuilder for the fields of the argument item, as
+/// update_from() does, with the argument options.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// #[derive(serde::Serialize)]
+/// struct Profile {
+///     pk: String,
+///     nickname: Option<String>,
+///     age: Option<u32>,
+/// }
+///
+/// let update = update_from_with(
+///     &Profile {
+///         pk: "user#1".to_owned(),
+///         nickname: Some("ace".to_owned()),
+///         age: None,
+///     },
+///     UpdateFromOptions::default()
+///         .with_remove_none(true)
+///         .with_skipped(["pk"]),
+/// )
+/// .unwrap();
+///
+/// let expr = Builder::new().with_update(update).build().unwrap();
+/// assert_eq!(expr.update().unwrap(), "SET #0 = :0\nREMOVE #1\n");
+/// ```
+pub fn update_from_with<T: Serialize + ?Sized>(
+    item: &T,
+    options: UpdateFromOptions,
+) -> Result<UpdateBuilder, ExpressionError> {
+    let fields = match serde_json::to_value(item) {
+        Ok(Value::Object(fields)) => fields,
+        _ => {
+            return Err(ExpressionError::InvalidParameterError(
+                "updateFrom".to_owned(),
+                "item".to_owned(),
+            ))
+        }
+    };
+
+    let mut update = UpdateBuilder::default();
+    for (field, field_value) in fields {
+        if options.skipped.contains(&field) {
+            continue;
+        }
+
+        update = match field_value {
+            Value::Null if options.remove_none => update.remove(name_literal(field)),
+            Value::Null => update,
+            field_value => update.set(name_literal(field), value(to_attribute_value(field_value))),
+        };
+    }
+
+    Ok(update)
+}
+
+fn to_attribute_value(value: Value) -> AttributeValue {
+    match value {
+        Value::Null => AttributeValue::Null(true),
+        Value::Bool(b) => AttributeValue::Bool(b),
+        Value::Number(n) => AttributeValue::N(n.to_string()),
+        Value::String(s) => AttributeValue::S(s),
+        Value::Array(array) => {
+            AttributeValue::L(array.into_iter().map(to_attribute_value).collect())
+        }
+        Value::Object(object) => AttributeValue::M(
+            object
+                .into_iter()
+                .map(|(name, value)| (name, to_attribute_value(value)))
+                .collect::<HashMap<_, _>>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::types::AttributeValue;
+    use serde::Serialize;
+
+    use crate::*;
+
+    #[derive(Serialize)]
+    struct Item {
+        pk: String,
+        count: Option<i64>,
+        note: Option<String>,
+        tags: Vec<&'static str>,
+        #[serde(rename = "meta.data")]
+        meta: HashMap<String, Option<bool>>,
+    }
+
+    fn item() -> Item {
+        Item {
+            pk: "a".to_owned(),
+            count: Some(3),
+            note: None,
+            tags: vec!["x"],
+            meta: HashMap::from([("ok".to_owned(), None)]),
+        }
+    }
+
+    #[test]
+    fn update_from_item() -> anyhow::Result<()> {
+        let input = update_from(&item())?;
+
+        let expected = UpdateBuilder::default()
+            .set(
+                name_literal("count"),
+                value(AttributeValue::N("3".to_owned())),
+            )
+            .set(
+                name_literal("meta.data"),
+                value(AttributeValue::M(HashMap::from([(
+                    "ok".to_owned(),
+                    AttributeValue::Null(true),
+                )]))),
+            )
+            .set(name_literal("pk"), value(AttributeValue::S("a".to_owned())))
+            .set(
+                name_literal("tags"),
+                value(AttributeValue::L(vec![AttributeValue::S("x".to_owned())])),
+            );
+        assert_eq!(input, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_from_options() -> anyhow::Result<()> {
+        let input = update_from_with(
+            &item(),
+            UpdateFromOptions::default()
+                .with_remove_none(true)
+                .with_skipped(["pk", "meta.data", "tags"]),
+        )?;
+
+        let expected = UpdateBuilder::default()
+            .set(
+                name_literal("count"),
+                value(AttributeValue::N("3".to_owned())),
+            )
+            .remove(name_literal("note"));
+        assert_eq!(input, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_from_invalid() -> anyhow::Result<()> {
+        assert_eq!(
+            update_from(&5).unwrap_err(),
+            error::ExpressionError::InvalidParameterError(
+                "updateFrom".to_owned(),
+                "item".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+}
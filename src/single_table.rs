@@ -0,0 +1,215 @@
+//! Prefixed composite keys of single-table designs
+//!
+//! Single-table designs store several entity types in one table, and tell
+//! them apart by prefixing their keys with the entity type, such as
+//! `USER#123` or `ORDER#2024-06-01#42`. The helpers of this module build such
+//! keys and the key conditions selecting one entity type.
+
+use std::fmt;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    key_begins_with, value, KeyBuilder, KeyConditionBuilder, SortKey, SortKeyCondition,
+    ToAttributeValue, ValueBuilder,
+};
+
+/// The delimiter between the parts of a CompositeKey, unless set otherwise.
+pub const DEFAULT_DELIMITER: &str = "#";
+
+/// Represents a key made of an entity type followed by parts, joined by a
+/// delimiter, such as `ORDER#2024-06-01#42`.
+///
+/// A CompositeKey can be used as a value, and its prefix() with
+/// begins_with() to select the keys starting with the same parts.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+/// use dynamodb_expression::single_table::CompositeKey;
+///
+/// let order = CompositeKey::new("ORDER").with_part("2024-06-01").with_part(42);
+/// assert_eq!(order.to_string(), "ORDER#2024-06-01#42");
+///
+/// let key_condition = key("pk")
+///     .equal(value(CompositeKey::new("USER").with_part(123)))
+///     .and(key("sk").begins_with(CompositeKey::new("ORDER").with_part("2024-06").prefix()));
+///
+/// let expr = Builder::new().with_key_condition(key_condition).build().unwrap();
+/// assert_eq!(expr.key_condition().unwrap(), "(#0 = :0) AND (begins_with (#1, :1))");
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CompositeKey {
+    delimiter: String,
+    parts: Vec<String>,
+}
+
+impl CompositeKey {
+    /// Returns a CompositeKey for the argument entity type, joined with the
+    /// DEFAULT_DELIMITER.
+    pub fn new(entity: impl Into<String>) -> Self {
+        Self {
+            delimiter: DEFAULT_DELIMITER.to_owned(),
+            parts: vec![entity.into()],
+        }
+    }
+
+    /// Sets the delimiter the parts of the key are joined with.
+    pub fn with_delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = delimiter.into();
+        self
+    }
+
+    /// Appends a part to the key.
+    pub fn with_part(mut self, part: impl fmt::Display) -> Self {
+        self.parts.push(part.to_string());
+        self
+    }
+
+    /// Returns the entity type of the key.
+    pub fn entity(&self) -> &str {
+        self.parts.first().map(String::as_str).unwrap_or_default()
+    }
+
+    /// Returns the parts of the key after the entity type.
+    pub fn parts(&self) -> &[String] {
+        self.parts.get(1..).unwrap_or_default()
+    }
+
+    /// Returns the key followed by the delimiter, which begins every key
+    /// having more parts than this one.
+    pub fn prefix(&self) -> String {
+        format!("{}{}", self, self.delimiter)
+    }
+}
+
+impl fmt::Display for CompositeKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.parts.join(&self.delimiter))
+    }
+}
+
+impl ToAttributeValue for CompositeKey {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::S(self.to_string())
+    }
+}
+
+/// Returns the value of the key of an entity, such as `USER#123` for
+/// entity_key("USER", 123).
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let key_condition = key("pk").equal(single_table::entity_key("USER", 123));
+/// ```
+pub fn entity_key(entity: impl Into<String>, id: impl fmt::Display) -> Box<ValueBuilder<String>> {
+    value(CompositeKey::new(entity).with_part(id).to_string())
+}
+
+/// Returns the prefix of the keys of an entity type, which is the argument
+/// followed by the DEFAULT_DELIMITER unless it already ends with it.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// assert_eq!(single_table::sk_prefix("ORDER"), "ORDER#");
+/// assert_eq!(single_table::sk_prefix("ORDER#"), "ORDER#");
+///
+/// let key_condition = key("sk").begins_with(single_table::sk_prefix("ORDER"));
+/// ```
+pub fn sk_prefix(prefix: impl Into<String>) -> String {
+    let mut prefix = prefix.into();
+    if !prefix.ends_with(DEFAULT_DELIMITER) {
+        prefix.push_str(DEFAULT_DELIMITER);
+    }
+    prefix
+}
+
+impl KeyBuilder {
+    /// Returns a KeyConditionBuilder selecting the items whose key belongs to
+    /// the argument entity type, with `begins_with (#sk, :prefix)` and the
+    /// prefix of sk_prefix().
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let key_condition = key("pk")
+    ///     .equal(single_table::entity_key("USER", 123))
+    ///     .and(key("sk").begins_with_entity("ORDER"));
+    ///
+    /// let expr = Builder::new().with_key_condition(key_condition).build().unwrap();
+    /// assert_eq!(
+    ///     expr.values().as_ref().unwrap()[":1"],
+    ///     aws_sdk_dynamodb::types::AttributeValue::S("ORDER#".to_owned())
+    /// );
+    /// ```
+    pub fn begins_with_entity(
+        self: Box<KeyBuilder>,
+        entity: impl Into<String>,
+    ) -> KeyConditionBuilder {
+        key_begins_with(self, sk_prefix(entity))
+    }
+}
+
+impl SortKey {
+    /// Returns a SortKeyCondition selecting the items whose sort key belongs
+    /// to the argument entity type, as KeyBuilder::begins_with_entity() does.
+    pub fn begins_with_entity(self, entity: impl Into<String>) -> SortKeyCondition {
+        self.begins_with(sk_prefix(entity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    use crate::single_table::*;
+    use crate::*;
+
+    #[test]
+    fn composite_key() -> anyhow::Result<()> {
+        let input = CompositeKey::new("ORDER")
+            .with_delimiter("|")
+            .with_part("2024")
+            .with_part(7);
+
+        assert_eq!(input.to_string(), "ORDER|2024|7");
+        assert_eq!(input.prefix(), "ORDER|2024|7|");
+        assert_eq!(input.entity(), "ORDER");
+        assert_eq!(input.parts(), ["2024".to_owned(), "7".to_owned()]);
+        assert_eq!(
+            input.to_attribute_value(),
+            AttributeValue::S("ORDER|2024|7".to_owned())
+        );
+        assert_eq!(CompositeKey::new("USER").parts(), [] as [String; 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn entity_keys() -> anyhow::Result<()> {
+        assert_eq!(entity_key("USER", 123), value("USER#123".to_owned()));
+        assert_eq!(
+            key("sk").begins_with_entity("ORDER"),
+            key("sk").begins_with("ORDER#")
+        );
+        assert_eq!(
+            partition_key("pk")
+                .equal(entity_key("USER", 1))
+                .and(sort_key("sk").begins_with_entity("ORDER#")),
+            key("pk")
+                .equal(value("USER#1".to_owned()))
+                .and(key("sk").begins_with("ORDER#"))
+        );
+
+        Ok(())
+    }
+}
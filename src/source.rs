@@ -0,0 +1,138 @@
+//! Traits allowing other types to be added to a Builder
+//!
+//! Frameworks with their own higher level query types can implement these
+//! traits to pass their types directly to the with_*() methods of the Builder.
+//! The types are moved into the Builder, so nothing is copied.
+
+use crate::{ConditionBuilder, KeyConditionBuilder, ProjectionBuilder, UpdateBuilder};
+
+/// Represents a type that can be used as a Filter Expression or a Condition
+/// Expression.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// struct ArtistFilter {
+///     artist: String,
+/// }
+///
+/// impl FilterSource for ArtistFilter {
+///     fn into_filter(self) -> ConditionBuilder {
+///         name("Artist").equal(value(self.artist))
+///     }
+/// }
+///
+/// let filt = ArtistFilter {
+///     artist: "No One You Know".to_owned(),
+/// };
+/// let expr = Builder::new().with_filter(filt).build().unwrap();
+/// ```
+pub trait FilterSource {
+    /// Returns the ConditionBuilder representing the type.
+    fn into_filter(self) -> ConditionBuilder;
+}
+
+impl FilterSource for ConditionBuilder {
+    fn into_filter(self) -> ConditionBuilder {
+        self
+    }
+}
+
+/// Represents a type that can be used as a Key Condition Expression.
+pub trait KeyConditionSource {
+    /// Returns the KeyConditionBuilder representing the type.
+    fn into_key_condition(self) -> KeyConditionBuilder;
+}
+
+impl KeyConditionSource for KeyConditionBuilder {
+    fn into_key_condition(self) -> KeyConditionBuilder {
+        self
+    }
+}
+
+/// Represents a type that can be used as a Projection Expression.
+pub trait ProjectionSource {
+    /// Returns the ProjectionBuilder representing the type.
+    fn into_projection(self) -> ProjectionBuilder;
+}
+
+impl ProjectionSource for ProjectionBuilder {
+    fn into_projection(self) -> ProjectionBuilder {
+        self
+    }
+}
+
+/// Represents a type that can be used as an Update Expression.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// struct Rename {
+///     new_name: String,
+/// }
+///
+/// impl UpdateSource for Rename {
+///     fn into_update(self) -> UpdateBuilder {
+///         set(name("Name"), value(self.new_name)).add(name("Revision"), value(1))
+///     }
+/// }
+///
+/// let update = Rename {
+///     new_name: "someName".to_owned(),
+/// };
+/// let expr = Builder::new().with_update(update).build().unwrap();
+/// ```
+pub trait UpdateSource {
+    /// Returns the UpdateBuilder representing the type.
+    fn into_update(self) -> UpdateBuilder;
+}
+
+impl UpdateSource for UpdateBuilder {
+    fn into_update(self) -> UpdateBuilder {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    struct Active;
+
+    impl FilterSource for Active {
+        fn into_filter(self) -> ConditionBuilder {
+            name("active").is_true()
+        }
+    }
+
+    struct Touch(i64);
+
+    impl UpdateSource for Touch {
+        fn into_update(self) -> UpdateBuilder {
+            set(name("updatedAt"), value(self.0))
+        }
+    }
+
+    #[test]
+    fn custom_sources() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_filter(Active)
+            .and_condition(Active)
+            .with_update(Touch(5));
+
+        assert_eq!(
+            input.build()?,
+            Builder::new()
+                .with_filter(name("active").is_true())
+                .with_condition(name("active").is_true())
+                .with_update(set(name("updatedAt"), value(5)))
+                .build()?
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,694 @@
+//! Request specifications bundling the expressions of an operation with its
+//! other parameters
+
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::operation::query::builders::{QueryFluentBuilder, QueryInputBuilder};
+use aws_sdk_dynamodb::operation::query::QueryInput;
+use aws_sdk_dynamodb::operation::scan::builders::{ScanFluentBuilder, ScanInputBuilder};
+use aws_sdk_dynamodb::operation::scan::ScanInput;
+use aws_sdk_dynamodb::operation::update_item::builders::{
+    UpdateItemFluentBuilder, UpdateItemInputBuilder,
+};
+use aws_sdk_dynamodb::operation::update_item::UpdateItemInput;
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    error::ExpressionError, Builder, Expression, FilterSource, GuardedUpdate, KeyConditionSource,
+    KeySchema, ProjectionSource, UpdateSource,
+};
+
+/// Represents a type whose parts can be set on a DynamoDB request builder,
+/// such as the fluent builder returned by Client::query() or the matching
+/// input builder.
+pub trait ApplyExpression<B> {
+    /// Returns the argument request builder with the expressions, the
+    /// ExpressionAttributeNames, the ExpressionAttributeValues and the other
+    /// parameters set.
+    ///
+    /// Parameters that aren't set are reset to None on the request builder.
+    fn apply(self, builder: B) -> Result<B, ExpressionError>;
+}
+
+/// Represents the parameters of a Query: its key condition, filter,
+/// projection, index, scan direction and limit.
+///
+/// A QuerySpec is either applied to a request builder with apply(), or built
+/// into its Expression and parameters with build().
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// # tokio_test::block_on(async {
+/// let shared_config = aws_config::from_env().load().await;
+/// let client = aws_sdk_dynamodb::Client::new(&shared_config);
+///
+/// let query = QuerySpec::new("SomeTable")
+///     .for_index("ByEmail")
+///     .with_key_condition(key("email").equal(value("ace@example.com")))
+///     .with_filter(name("status").equal(value("active")))
+///     .with_scan_index_forward(false)
+///     .with_limit(10)
+///     .apply(client.query())
+///     .unwrap();
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuerySpec {
+    table_name: String,
+    builder: Builder,
+    scan_index_forward: Option<bool>,
+    limit: Option<i32>,
+}
+
+impl QuerySpec {
+    /// Returns a QuerySpec querying the argument table.
+    pub fn new(table_name: impl Into<String>) -> Self {
+        Self {
+            table_name: table_name.into(),
+            builder: Builder::new(),
+            scan_index_forward: None,
+            limit: None,
+        }
+    }
+
+    /// Sets the Key Condition Expression of the Query.
+    pub fn with_key_condition(mut self, key_condition: impl KeyConditionSource) -> Self {
+        self.builder = self.builder.with_key_condition(key_condition);
+        self
+    }
+
+    /// Sets the Filter Expression of the Query.
+    pub fn with_filter(mut self, filter: impl FilterSource) -> Self {
+        self.builder = self.builder.with_filter(filter);
+        self
+    }
+
+    /// Sets the Projection Expression of the Query.
+    pub fn with_projection(mut self, projection: impl ProjectionSource) -> Self {
+        self.builder = self.builder.with_projection(projection);
+        self
+    }
+
+    /// Sets the secondary index the Query is run on, see Builder::for_index().
+    pub fn for_index(mut self, index_name: impl Into<String>) -> Self {
+        self.builder = self.builder.for_index(index_name);
+        self
+    }
+
+    /// Sets the KeySchema the expressions are checked against, see
+    /// Builder::with_key_schema().
+    pub fn with_key_schema(mut self, key_schema: KeySchema) -> Self {
+        self.builder = self.builder.with_key_schema(key_schema);
+        self
+    }
+
+    /// Sets whether the items are returned in ascending sort key order, which
+    /// is the default, or in descending order.
+    pub fn with_scan_index_forward(mut self, scan_index_forward: bool) -> Self {
+        self.scan_index_forward = Some(scan_index_forward);
+        self
+    }
+
+    /// Sets the maximum number of items evaluated by the Query.
+    pub fn with_limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Builds the expressions of the QuerySpec, returning them along with the
+    /// other parameters of the Query.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let query = QuerySpec::new("SomeTable")
+    ///     .with_key_condition(key("pk").equal(value("USER#1")))
+    ///     .with_limit(10)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(query.table_name(), "SomeTable");
+    /// assert_eq!(query.expression().key_condition().unwrap(), "#0 = :0");
+    /// assert_eq!(query.limit(), Some(10));
+    /// ```
+    pub fn build(self) -> Result<BuiltQuery, ExpressionError> {
+        Ok(BuiltQuery {
+            table_name: self.table_name,
+            expression: self.builder.build()?,
+            scan_index_forward: self.scan_index_forward,
+            limit: self.limit,
+        })
+    }
+
+    /// Returns a QueryInputBuilder with the parts of the QuerySpec set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let input = QuerySpec::new("SomeTable")
+    ///     .with_key_condition(key("pk").equal(value("USER#1")))
+    ///     .into_input()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(input.get_key_condition_expression().as_deref(), Some("#0 = :0"));
+    /// ```
+    pub fn into_input(self) -> Result<QueryInputBuilder, ExpressionError> {
+        self.apply(QueryInput::builder())
+    }
+}
+
+/// Represents the built Expression of a QuerySpec along with the other
+/// parameters of the Query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltQuery {
+    table_name: String,
+    expression: Expression,
+    scan_index_forward: Option<bool>,
+    limit: Option<i32>,
+}
+
+impl BuiltQuery {
+    /// Returns the name of the queried table.
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Returns the Expression of the Query. Its index_name() is the secondary
+    /// index the Query is run on, if any.
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    /// Returns the scan direction of the Query, if set.
+    pub fn scan_index_forward(&self) -> Option<bool> {
+        self.scan_index_forward
+    }
+
+    /// Returns the limit of the Query, if set.
+    pub fn limit(&self) -> Option<i32> {
+        self.limit
+    }
+
+    /// Returns the Expression of the Query.
+    pub fn into_expression(self) -> Expression {
+        self.expression
+    }
+}
+
+// the fluent and the input builders of an operation share their setters
+macro_rules! apply_query {
+    ($builder:ty) => {
+        impl ApplyExpression<$builder> for QuerySpec {
+            fn apply(self, builder: $builder) -> Result<$builder, ExpressionError> {
+                let BuiltQuery {
+                    table_name,
+                    mut expression,
+                    scan_index_forward,
+                    limit,
+                } = self.build()?;
+
+                Ok(builder
+                    .table_name(table_name)
+                    .set_index_name(expression.index_name().map(str::to_owned))
+                    .set_key_condition_expression(expression.take_key_condition())
+                    .set_filter_expression(expression.take_filter())
+                    .set_projection_expression(expression.take_projection())
+                    .set_expression_attribute_names(expression.take_names())
+                    .set_expression_attribute_values(expression.take_values())
+                    .set_scan_index_forward(scan_index_forward)
+                    .set_limit(limit))
+            }
+        }
+    };
+}
+
+apply_query!(QueryFluentBuilder);
+apply_query!(QueryInputBuilder);
+
+/// Represents the parameters of a Scan: its filter, projection, index,
+/// segment and limit.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // the first of four segments of a parallel scan
+/// let input = ScanSpec::new("SomeTable")
+///     .with_filter(name("status").equal(value("active")))
+///     .with_segment(0, 4)
+///     .into_input()
+///     .unwrap();
+///
+/// assert_eq!(input.get_filter_expression().as_deref(), Some("#0 = :0"));
+/// assert_eq!(input.get_total_segments(), &Some(4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScanSpec {
+    table_name: String,
+    builder: Builder,
+    segment: Option<(i32, i32)>,
+    limit: Option<i32>,
+}
+
+impl ScanSpec {
+    /// Returns a ScanSpec scanning the argument table.
+    pub fn new(table_name: impl Into<String>) -> Self {
+        Self {
+            table_name: table_name.into(),
+            builder: Builder::new(),
+            segment: None,
+            limit: None,
+        }
+    }
+
+    /// Sets the Filter Expression of the Scan.
+    pub fn with_filter(mut self, filter: impl FilterSource) -> Self {
+        self.builder = self.builder.with_filter(filter);
+        self
+    }
+
+    /// Sets the Projection Expression of the Scan.
+    pub fn with_projection(mut self, projection: impl ProjectionSource) -> Self {
+        self.builder = self.builder.with_projection(projection);
+        self
+    }
+
+    /// Sets the secondary index the Scan is run on, see Builder::for_index().
+    pub fn for_index(mut self, index_name: impl Into<String>) -> Self {
+        self.builder = self.builder.for_index(index_name);
+        self
+    }
+
+    /// Sets the segment of a parallel Scan, out of the argument total number
+    /// of segments.
+    ///
+    /// The segment must be less than the total number of segments, otherwise
+    /// building the ScanSpec returns the typed error InvalidParameterError.
+    pub fn with_segment(mut self, segment: i32, total_segments: i32) -> Self {
+        self.segment = Some((segment, total_segments));
+        self
+    }
+
+    /// Sets the maximum number of items evaluated by the Scan.
+    pub fn with_limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Builds the expressions of the ScanSpec, returning them along with the
+    /// other parameters of the Scan.
+    pub fn build(self) -> Result<BuiltScan, ExpressionError> {
+        if let Some((segment, total_segments)) = self.segment {
+            if segment < 0 || segment >= total_segments {
+                return Err(ExpressionError::InvalidParameterError(
+                    "scanSpec".to_owned(),
+                    "segment".to_owned(),
+                ));
+            }
+        }
+
+        Ok(BuiltScan {
+            table_name: self.table_name,
+            expression: self.builder.build()?,
+            segment: self.segment,
+            limit: self.limit,
+        })
+    }
+
+    /// Returns a ScanInputBuilder with the parts of the ScanSpec set.
+    pub fn into_input(self) -> Result<ScanInputBuilder, ExpressionError> {
+        self.apply(ScanInput::builder())
+    }
+}
+
+/// Represents the built Expression of a ScanSpec along with the other
+/// parameters of the Scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltScan {
+    table_name: String,
+    expression: Expression,
+    segment: Option<(i32, i32)>,
+    limit: Option<i32>,
+}
+
+impl BuiltScan {
+    /// Returns the name of the scanned table.
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Returns the Expression of the Scan. Its index_name() is the secondary
+    /// index the Scan is run on, if any.
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    /// Returns the segment of the Scan and the total number of segments, if
+    /// set.
+    pub fn segment(&self) -> Option<(i32, i32)> {
+        self.segment
+    }
+
+    /// Returns the limit of the Scan, if set.
+    pub fn limit(&self) -> Option<i32> {
+        self.limit
+    }
+
+    /// Returns the Expression of the Scan.
+    pub fn into_expression(self) -> Expression {
+        self.expression
+    }
+}
+
+macro_rules! apply_scan {
+    ($builder:ty) => {
+        impl ApplyExpression<$builder> for ScanSpec {
+            fn apply(self, builder: $builder) -> Result<$builder, ExpressionError> {
+                let BuiltScan {
+                    table_name,
+                    mut expression,
+                    segment,
+                    limit,
+                } = self.build()?;
+
+                Ok(builder
+                    .table_name(table_name)
+                    .set_index_name(expression.index_name().map(str::to_owned))
+                    .set_filter_expression(expression.take_filter())
+                    .set_projection_expression(expression.take_projection())
+                    .set_expression_attribute_names(expression.take_names())
+                    .set_expression_attribute_values(expression.take_values())
+                    .set_segment(segment.map(|(segment, _)| segment))
+                    .set_total_segments(segment.map(|(_, total_segments)| total_segments))
+                    .set_limit(limit))
+            }
+        }
+    };
+}
+
+apply_scan!(ScanFluentBuilder);
+apply_scan!(ScanInputBuilder);
+
+/// Represents the parameters of an UpdateItem: the key of the item, its
+/// update and its condition.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use aws_sdk_dynamodb::types::AttributeValue;
+/// use dynamodb_expression::*;
+///
+/// let input = UpdateSpec::new(
+///     "SomeTable",
+///     HashMap::from([("pk".to_owned(), AttributeValue::S("USER#1".to_owned()))]),
+/// )
+/// .with_update(set(name("status"), value("active")))
+/// .with_guarded_update(versioning::optimistic_lock(name("version"), 3))
+/// .into_input()
+/// .unwrap();
+///
+/// assert_eq!(input.get_condition_expression().as_deref(), Some("#0 = :0"));
+/// assert_eq!(
+///     input.get_update_expression().as_deref(),
+///     Some("SET #1 = :1, #0 = #0 + :2\n")
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct UpdateSpec {
+    table_name: String,
+    key: HashMap<String, AttributeValue>,
+    builder: Builder,
+}
+
+impl UpdateSpec {
+    /// Returns an UpdateSpec updating the item of the argument table with the
+    /// argument key.
+    pub fn new(table_name: impl Into<String>, key: HashMap<String, AttributeValue>) -> Self {
+        Self {
+            table_name: table_name.into(),
+            key,
+            builder: Builder::new(),
+        }
+    }
+
+    /// Sets the Update Expression of the UpdateItem.
+    pub fn with_update(mut self, update: impl UpdateSource) -> Self {
+        self.builder = self.builder.with_update(update);
+        self
+    }
+
+    /// Sets the Condition Expression of the UpdateItem.
+    pub fn with_condition(mut self, condition: impl FilterSource) -> Self {
+        self.builder = self.builder.with_condition(condition);
+        self
+    }
+
+    /// Adds the argument GuardedUpdate to the UpdateItem, see
+    /// Builder::with_guarded_update().
+    pub fn with_guarded_update(mut self, guarded: GuardedUpdate) -> Self {
+        self.builder = self.builder.with_guarded_update(guarded);
+        self
+    }
+
+    /// Builds the expressions of the UpdateSpec, returning them along with
+    /// the other parameters of the UpdateItem.
+    pub fn build(self) -> Result<BuiltUpdate, ExpressionError> {
+        Ok(BuiltUpdate {
+            table_name: self.table_name,
+            key: self.key,
+            expression: self.builder.build()?,
+        })
+    }
+
+    /// Returns an UpdateItemInputBuilder with the parts of the UpdateSpec set.
+    pub fn into_input(self) -> Result<UpdateItemInputBuilder, ExpressionError> {
+        self.apply(UpdateItemInput::builder())
+    }
+}
+
+/// Represents the built Expression of an UpdateSpec along with the other
+/// parameters of the UpdateItem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltUpdate {
+    table_name: String,
+    key: HashMap<String, AttributeValue>,
+    expression: Expression,
+}
+
+impl BuiltUpdate {
+    /// Returns the name of the updated table.
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Returns the key of the updated item.
+    pub fn key(&self) -> &HashMap<String, AttributeValue> {
+        &self.key
+    }
+
+    /// Returns the Expression of the UpdateItem.
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    /// Returns the Expression of the UpdateItem.
+    pub fn into_expression(self) -> Expression {
+        self.expression
+    }
+}
+
+macro_rules! apply_update {
+    ($builder:ty) => {
+        impl ApplyExpression<$builder> for UpdateSpec {
+            fn apply(self, builder: $builder) -> Result<$builder, ExpressionError> {
+                let BuiltUpdate {
+                    table_name,
+                    key,
+                    mut expression,
+                } = self.build()?;
+
+                Ok(builder
+                    .table_name(table_name)
+                    .set_key(Some(key))
+                    .set_update_expression(expression.take_update())
+                    .set_condition_expression(expression.take_condition())
+                    .set_expression_attribute_names(expression.take_names())
+                    .set_expression_attribute_values(expression.take_values()))
+            }
+        }
+    };
+}
+
+apply_update!(UpdateItemFluentBuilder);
+apply_update!(UpdateItemInputBuilder);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::config::BehaviorVersion;
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    use crate::*;
+
+    fn client() -> aws_sdk_dynamodb::Client {
+        aws_sdk_dynamodb::Client::from_conf(
+            aws_sdk_dynamodb::Config::builder()
+                .behavior_version(BehaviorVersion::latest())
+                .build(),
+        )
+    }
+
+    fn query_spec() -> QuerySpec {
+        QuerySpec::new("Table")
+            .with_key_schema(
+                KeySchema::new("pk").with_index(SecondaryIndex::new("ByEmail", "email")),
+            )
+            .for_index("ByEmail")
+            .with_key_condition(key("email").equal(value("a")))
+            .with_filter(name("status").equal(value("open")))
+            .with_projection(names_list(name("pk"), vec![]))
+            .with_scan_index_forward(false)
+            .with_limit(5)
+    }
+
+    #[test]
+    fn query_input() -> anyhow::Result<()> {
+        let input = query_spec().into_input()?.build()?;
+
+        assert_eq!(input.table_name(), Some("Table"));
+        assert_eq!(input.index_name(), Some("ByEmail"));
+        assert_eq!(input.projection_expression(), Some("#0"));
+        assert_eq!(input.key_condition_expression(), Some("#1 = :0"));
+        assert_eq!(input.filter_expression(), Some("#2 = :1"));
+        assert_eq!(
+            input.expression_attribute_names().map(|names| names.len()),
+            Some(3)
+        );
+        assert_eq!(
+            input
+                .expression_attribute_values()
+                .and_then(|values| values.get(":1")),
+            Some(&AttributeValue::S("open".to_owned()))
+        );
+        assert_eq!(input.scan_index_forward(), Some(false));
+        assert_eq!(input.limit(), Some(5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_fluent() -> anyhow::Result<()> {
+        let input = query_spec().apply(client().query())?;
+
+        assert_eq!(input.as_input().get_table_name().as_deref(), Some("Table"));
+        assert_eq!(
+            input.as_input().get_key_condition_expression().as_deref(),
+            Some("#1 = :0")
+        );
+
+        let err = QuerySpec::new("Table")
+            .with_key_schema(KeySchema::new("pk"))
+            .with_key_condition(key("sk").equal(value(1)))
+            .apply(client().query())
+            .unwrap_err();
+        assert!(matches!(
+            err.root_cause(),
+            error::ExpressionError::KeySchemaViolation(..)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_build() -> anyhow::Result<()> {
+        let input = QuerySpec::new("Table")
+            .with_key_condition(key("pk").equal(value("a")))
+            .build()?;
+
+        assert_eq!(input.table_name(), "Table");
+        assert_eq!(input.scan_index_forward(), None);
+        assert_eq!(input.limit(), None);
+        assert_eq!(
+            input.into_expression(),
+            Builder::new()
+                .with_key_condition(key("pk").equal(value("a")))
+                .build()?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn scan_spec() -> anyhow::Result<()> {
+        let input = ScanSpec::new("Table")
+            .for_index("ByEmail")
+            .with_filter(name("status").equal(value("open")))
+            .with_projection(names_list(name("pk"), vec![]))
+            .with_segment(1, 4)
+            .with_limit(5)
+            .apply(client().scan())?;
+        let input = input.as_input();
+
+        assert_eq!(input.get_table_name().as_deref(), Some("Table"));
+        assert_eq!(input.get_index_name().as_deref(), Some("ByEmail"));
+        assert_eq!(input.get_projection_expression().as_deref(), Some("#0"));
+        assert_eq!(input.get_filter_expression().as_deref(), Some("#1 = :0"));
+        assert_eq!(input.get_segment(), &Some(1));
+        assert_eq!(input.get_total_segments(), &Some(4));
+        assert_eq!(input.get_limit(), &Some(5));
+
+        for (segment, total_segments) in [(4, 4), (-1, 4), (0, 0)] {
+            assert_eq!(
+                ScanSpec::new("Table")
+                    .with_segment(segment, total_segments)
+                    .build()
+                    .unwrap_err(),
+                error::ExpressionError::InvalidParameterError(
+                    "scanSpec".to_owned(),
+                    "segment".to_owned()
+                )
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_spec() -> anyhow::Result<()> {
+        let key = HashMap::from([("pk".to_owned(), AttributeValue::S("a".to_owned()))]);
+
+        let input = UpdateSpec::new("Table", key.clone())
+            .with_condition(name("status").equal(value("open")))
+            .with_update(set(name("status"), value("closed")))
+            .apply(client().update_item())?;
+        let input = input.as_input();
+
+        assert_eq!(input.get_table_name().as_deref(), Some("Table"));
+        assert_eq!(input.get_key(), &Some(key.clone()));
+        assert_eq!(input.get_condition_expression().as_deref(), Some("#0 = :0"));
+        assert_eq!(
+            input.get_update_expression().as_deref(),
+            Some("SET #0 = :1\n")
+        );
+
+        let input = UpdateSpec::new("Table", key)
+            .with_update(set(name("a"), value(1)))
+            .build()?;
+        assert_eq!(input.table_name(), "Table");
+        assert_eq!(input.key().len(), 1);
+        assert_eq!(input.expression().update(), Some("SET #0 = :0\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+//! Building a single expression without a Builder
+//!
+//! An API that takes a single expression, such as a ConditionExpression,
+//! only needs that expression and its names and values. The build_standalone()
+//! method of each builder returns them in a struct of its own, without
+//! constructing a Builder and looking the expression up in an Expression.
+
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    error::ExpressionError, Builder, ConditionBuilder, ExpressionType, KeyConditionBuilder,
+    ProjectionBuilder, UpdateBuilder,
+};
+
+macro_rules! built_expression {
+    (
+        $(#[$meta:meta])*
+        $built:ident, $builder:ty, $with:ident, $expression_type:ident
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, PartialEq, Debug)]
+        pub struct $built {
+            expression: String,
+            names: Option<HashMap<String, String>>,
+            values: Option<HashMap<String, AttributeValue>>,
+        }
+
+        impl $built {
+            /// Returns the expression string.
+            pub fn expression(&self) -> &str {
+                &self.expression
+            }
+
+            /// Returns the ExpressionAttributeNames of the expression.
+            pub fn names(&self) -> &Option<HashMap<String, String>> {
+                &self.names
+            }
+
+            /// Returns the ExpressionAttributeValues of the expression.
+            pub fn values(&self) -> &Option<HashMap<String, AttributeValue>> {
+                &self.values
+            }
+
+            /// Deconstructs the result into the expression string, the
+            /// ExpressionAttributeNames and the ExpressionAttributeValues.
+            #[allow(clippy::type_complexity)]
+            pub fn into_parts(
+                self,
+            ) -> (
+                String,
+                Option<HashMap<String, String>>,
+                Option<HashMap<String, AttributeValue>>,
+            ) {
+                (self.expression, self.names, self.values)
+            }
+        }
+
+        impl $builder {
+            #[doc = concat!(
+                "Builds the expression alone, into a ",
+                stringify!($built),
+                ", as Builder::new().",
+                stringify!($with),
+                "() would."
+            )]
+            pub fn build_standalone(self) -> Result<$built, ExpressionError> {
+                let (mut expressions, names, values) =
+                    Builder::new().$with(self).build()?.into_parts();
+
+                let expression = expressions
+                    .remove(&ExpressionType::$expression_type)
+                    .unwrap_or_default();
+
+                Ok($built {
+                    expression,
+                    names,
+                    values,
+                })
+            }
+        }
+    };
+}
+
+built_expression!(
+    /// Represents a Condition Expression built by
+    /// ConditionBuilder::build_standalone(), which can be used as a Filter
+    /// Expression as well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let built = name("status").equal(value("open")).build_standalone().unwrap();
+    ///
+    /// assert_eq!(built.expression(), "#0 = :0");
+    /// assert_eq!(built.names().as_ref().unwrap()["#0"], "status");
+    /// ```
+    BuiltCondition,
+    ConditionBuilder,
+    with_condition,
+    Condition
+);
+
+built_expression!(
+    /// Represents an Update Expression built by
+    /// UpdateBuilder::build_standalone().
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let built = set(name("count"), value(1)).build_standalone().unwrap();
+    /// assert_eq!(built.expression(), "SET #0 = :0\n");
+    /// ```
+    BuiltUpdateExpression,
+    UpdateBuilder,
+    with_update,
+    Update
+);
+
+built_expression!(
+    /// Represents a Projection Expression built by
+    /// ProjectionBuilder::build_standalone(). A projection has no values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let built = projection(["a", "b"]).build_standalone().unwrap();
+    /// assert_eq!(built.expression(), "#0, #1");
+    /// assert_eq!(built.values(), &None);
+    /// ```
+    BuiltProjection,
+    ProjectionBuilder,
+    with_projection,
+    Projection
+);
+
+built_expression!(
+    /// Represents a Key Condition Expression built by
+    /// KeyConditionBuilder::build_standalone().
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let built = key("pk").equal(value("a")).build_standalone().unwrap();
+    /// assert_eq!(built.expression(), "#0 = :0");
+    /// ```
+    BuiltKeyCondition,
+    KeyConditionBuilder,
+    with_key_condition,
+    KeyCondition
+);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::types::AttributeValue;
+
+    use crate::*;
+
+    #[test]
+    fn build_standalone() -> anyhow::Result<()> {
+        let (expression, names, values) = name("foo")
+            .equal(value(5))
+            .and(name("bar").attribute_exists())
+            .build_standalone()?
+            .into_parts();
+        assert_eq!(expression, "(#0 = :0) AND (attribute_exists (#1))");
+        assert_eq!(
+            names,
+            Some(HashMap::from([
+                ("#0".to_owned(), "foo".to_owned()),
+                ("#1".to_owned(), "bar".to_owned())
+            ]))
+        );
+        assert_eq!(
+            values,
+            Some(HashMap::from([(
+                ":0".to_owned(),
+                AttributeValue::N("5".to_owned())
+            )]))
+        );
+
+        let built = remove(name("foo")).build_standalone()?;
+        assert_eq!(built.expression(), "REMOVE #0\n");
+        assert_eq!(built.values(), &None);
+
+        assert_eq!(
+            UpdateBuilder::default().build_standalone().unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "buildTree".to_owned(),
+                "UpdateBuilder".to_owned()
+            )
+            .in_path("update")
+        );
+
+        Ok(())
+    }
+}
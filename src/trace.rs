@@ -0,0 +1,141 @@
+//! tracing instrumentation of Builder::build()
+//!
+//! Every build runs in a debug-level `build_expression` span. A successful
+//! build emits a debug event for each expression, with its type, its length
+//! and the aliased expression string, which never contains the names and
+//! values themselves, followed by an event with the alias counts and sizes.
+//! The expressions with the names and values substituted, as returned by
+//! Expression::to_debug_string(), are only emitted at the trace level, with
+//! the values redacted by the RedactionPolicy of the Builder. A failed build
+//! emits a debug event with the error.
+
+use crate::{error::ExpressionError, Expression};
+
+pub(crate) fn record_build(result: &Result<Expression, ExpressionError>) {
+    let expression = match result {
+        Ok(expression) => expression,
+        Err(error) => {
+            tracing::debug!(%error, "expression build failed");
+            return;
+        }
+    };
+
+    let mut keys = expression.expressions.keys().collect::<Vec<_>>();
+    keys.sort();
+
+    for key in keys {
+        let formatted_expression = &expression.expressions[key];
+        tracing::debug!(
+            expression_type = %key,
+            length = formatted_expression.len(),
+            expression = %formatted_expression.trim_end(),
+            "built expression"
+        );
+    }
+
+    let stats = expression.stats();
+    tracing::debug!(
+        name_count = stats.name_count(),
+        value_count = stats.value_count(),
+        names_size = stats.names_size(),
+        values_size = stats.values_size(),
+        estimated_size = stats.estimated_size(),
+        "built expression attributes"
+    );
+
+    tracing::trace!(
+        expression = %expression.to_debug_string(),
+        "built expression values"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    use crate::*;
+
+    // records the level and the fields of every event
+    #[derive(Default, Clone)]
+    struct Recorder(Arc<Mutex<Vec<(Level, String)>>>);
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.push_str(&format!("{}={:?} ", field.name(), value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(String::new());
+            event.record(&mut fields);
+            self.0
+                .lock()
+                .unwrap()
+                .push((*event.metadata().level(), fields.0.trim_end().to_owned()));
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn traced_build() -> anyhow::Result<()> {
+        let recorder = Recorder::default();
+
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let _ = Builder::new()
+                .with_filter(name("Artist").equal(value("No One You Know")))
+                .build();
+            let _ = Builder::new().with_update(UpdateBuilder::default()).build();
+        });
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                (
+                    Level::DEBUG,
+                    "message=built expression expression_type=filter length=7 expression=#0 = :0"
+                        .to_owned()
+                ),
+                (
+                    Level::DEBUG,
+                    "message=built expression attributes name_count=1 value_count=1 names_size=8 values_size=17 estimated_size=32"
+                        .to_owned()
+                ),
+                (
+                    Level::TRACE,
+                    "message=built expression values expression=filter: Artist = \"No One You Know\""
+                        .to_owned()
+                ),
+                (
+                    Level::DEBUG,
+                    "message=expression build failed error=buildTree error: unset parameter: UpdateBuilder (at update)"
+                        .to_owned()
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+}
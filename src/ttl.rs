@@ -0,0 +1,153 @@
+//! Time to Live attributes
+//!
+//! DynamoDB deletes items whose Time to Live attribute holds a time in the
+//! past, encoded as a number of seconds since the Unix epoch. Deleting expired
+//! items can take a while, so reads usually filter them out as well. The
+//! functions of this module encode the times the same way everywhere.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{set, value, ConditionBuilder, GreaterThanBuilder, NameBuilder, UpdateBuilder};
+
+/// Represents the time an item expires at.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Expiry {
+    /// At is an absolute expiration time
+    At(SystemTime),
+
+    /// In is an expiration time relative to the time it is encoded at
+    In(Duration),
+}
+
+impl Expiry {
+    /// Returns the expiration time as a number of seconds since the Unix
+    /// epoch, relative to the argument current time.
+    ///
+    /// Times before the epoch are encoded as 0.
+    pub fn epoch_seconds(&self, now: SystemTime) -> i64 {
+        let time = match self {
+            Expiry::At(time) => *time,
+            Expiry::In(duration) => now + *duration,
+        };
+
+        epoch_seconds(time)
+    }
+}
+
+impl From<SystemTime> for Expiry {
+    fn from(time: SystemTime) -> Self {
+        Expiry::At(time)
+    }
+}
+
+impl From<Duration> for Expiry {
+    fn from(duration: Duration) -> Self {
+        Expiry::In(duration)
+    }
+}
+
+/// Returns an UpdateBuilder setting the Time to Live attribute to the
+/// argument expiration time, with `SET #exp = :epoch_seconds`.
+///
+/// The expiration time is either a SystemTime, or a Duration from now.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use dynamodb_expression::*;
+///
+/// // the session expires in an hour
+/// let update = ttl::set_expiry(name("expiresAt"), Duration::from_secs(3600));
+///
+/// let expr = Builder::new().with_update(update).build().unwrap();
+/// assert_eq!(expr.update().unwrap(), "SET #0 = :0\n");
+/// ```
+pub fn set_expiry(name: Box<NameBuilder>, expiry: impl Into<Expiry>) -> UpdateBuilder {
+    set_expiry_at(name, expiry, SystemTime::now())
+}
+
+/// Returns an UpdateBuilder setting the Time to Live attribute to the
+/// argument expiration time, as set_expiry() does, relative to the argument
+/// current time.
+pub fn set_expiry_at(
+    name: Box<NameBuilder>,
+    expiry: impl Into<Expiry>,
+    now: SystemTime,
+) -> UpdateBuilder {
+    set(name, value(expiry.into().epoch_seconds(now)))
+}
+
+/// Returns a ConditionBuilder for the items that haven't expired yet, with
+/// `#exp > :now`.
+///
+/// Items without the Time to Live attribute, which never expire, don't
+/// satisfy the condition.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let expr = Builder::new()
+///     .with_filter(ttl::not_expired(name("expiresAt")))
+///     .build()
+///     .unwrap();
+/// assert_eq!(expr.filter().unwrap(), "#0 > :0");
+/// ```
+pub fn not_expired(name: Box<NameBuilder>) -> ConditionBuilder {
+    not_expired_at(name, SystemTime::now())
+}
+
+/// Returns a ConditionBuilder for the items that haven't expired at the
+/// argument time, as not_expired() does.
+pub fn not_expired_at(name: Box<NameBuilder>, now: SystemTime) -> ConditionBuilder {
+    name.greater_than(value(epoch_seconds(now)))
+}
+
+fn epoch_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|elapsed| i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use crate::*;
+
+    #[test]
+    fn set_expiry() -> anyhow::Result<()> {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        assert_eq!(
+            ttl::set_expiry_at(name("exp"), Duration::from_secs(60), now),
+            set(name("exp"), value(1_700_000_060))
+        );
+        assert_eq!(
+            ttl::set_expiry_at(name("exp"), UNIX_EPOCH + Duration::from_millis(5_999), now),
+            set(name("exp"), value(5))
+        );
+        assert_eq!(
+            ttl::set_expiry_at(name("exp"), UNIX_EPOCH - Duration::from_secs(1), now),
+            set(name("exp"), value(0))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn not_expired() -> anyhow::Result<()> {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        assert_eq!(
+            ttl::not_expired_at(name("exp"), now),
+            name("exp").greater_than(value(1_700_000_000))
+        );
+        assert!(ttl::Expiry::from(SystemTime::now()).epoch_seconds(now) > 1_700_000_000);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+//! Typed attribute names
+//!
+//! A NameBuilder can be compared with a value of any type, so comparing a
+//! number attribute with a string compiles, and only fails to match items at
+//! runtime. A TypedNameBuilder carries the type of its attribute, and its
+//! comparisons only accept values convertible into that type. Every method
+//! lowers to the untyped builders, so the conditions are the same as the ones
+//! written with name() and value().
+
+use std::marker::PhantomData;
+
+use derivative::Derivative;
+
+use crate::{
+    attribute_exists, attribute_not_exists, begins_with, between, contains, equal,
+    error::ExpressionError, greater_than, greater_than_equal, in_values, less_than,
+    less_than_equal, name, not_equal, set, value, ConditionBuilder, NameBuilder, ToAttributeValue,
+    UpdateBuilder,
+};
+
+/// Represents a document path whose attribute holds values of type T.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let age = typed_name::<i64>("Age");
+/// let condition = age.clone().greater_than(18).and(age.less_than(65));
+///
+/// let expr = Builder::new().with_condition(condition).build().unwrap();
+/// assert_eq!(expr.condition().unwrap(), "(#0 > :0) AND (#0 < :1)");
+/// ```
+///
+/// Comparing it with a value of another type doesn't compile:
+///
+/// ```compile_fail
+/// use dynamodb_expression::*;
+///
+/// let condition = typed_name::<i64>("Age").equal("forty");
+/// ```
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), PartialEq(bound = ""))]
+pub struct TypedNameBuilder<T> {
+    name: Box<NameBuilder>,
+    #[derivative(Debug = "ignore")]
+    value_type: PhantomData<fn() -> T>,
+}
+
+/// Creates a TypedNameBuilder for the argument document path, whose
+/// attribute holds values of type T, see TypedNameBuilder.
+#[track_caller]
+pub fn typed_name<T: ToAttributeValue>(path: impl Into<String>) -> TypedNameBuilder<T> {
+    TypedNameBuilder {
+        name: name(path),
+        value_type: PhantomData,
+    }
+}
+
+impl<T: ToAttributeValue> TypedNameBuilder<T> {
+    /// Returns the untyped NameBuilder of the document path.
+    pub fn name(self) -> Box<NameBuilder> {
+        self.name
+    }
+
+    /// Returns a ConditionBuilder comparing the attribute with the argument
+    /// value for equality, see equal().
+    pub fn equal(self, right: impl Into<T>) -> ConditionBuilder {
+        equal(self.name, value(right.into()))
+    }
+
+    /// Returns a ConditionBuilder comparing the attribute with the argument
+    /// value for inequality, see not_equal().
+    pub fn not_equal(self, right: impl Into<T>) -> ConditionBuilder {
+        not_equal(self.name, value(right.into()))
+    }
+
+    /// Returns a ConditionBuilder checking that the attribute is less than
+    /// the argument value, see less_than().
+    pub fn less_than(self, right: impl Into<T>) -> ConditionBuilder {
+        less_than(self.name, value(right.into()))
+    }
+
+    /// Returns a ConditionBuilder checking that the attribute is less than or
+    /// equal to the argument value, see less_than_equal().
+    pub fn less_than_equal(self, right: impl Into<T>) -> ConditionBuilder {
+        less_than_equal(self.name, value(right.into()))
+    }
+
+    /// Returns a ConditionBuilder checking that the attribute is greater than
+    /// the argument value, see greater_than().
+    pub fn greater_than(self, right: impl Into<T>) -> ConditionBuilder {
+        greater_than(self.name, value(right.into()))
+    }
+
+    /// Returns a ConditionBuilder checking that the attribute is greater than
+    /// or equal to the argument value, see greater_than_equal().
+    pub fn greater_than_equal(self, right: impl Into<T>) -> ConditionBuilder {
+        greater_than_equal(self.name, value(right.into()))
+    }
+
+    /// Returns a ConditionBuilder checking that the attribute is between the
+    /// argument values, see between().
+    pub fn between(self, lower: impl Into<T>, upper: impl Into<T>) -> ConditionBuilder {
+        between(self.name, value(lower.into()), value(upper.into()))
+    }
+
+    /// Returns a ConditionBuilder checking that the attribute is one of the
+    /// argument values, see in_values().
+    pub fn in_values(
+        self,
+        values: impl IntoIterator<Item = impl Into<T>>,
+    ) -> Result<ConditionBuilder, ExpressionError> {
+        in_values(self.name, values.into_iter().map(Into::into))
+    }
+
+    /// Returns a ConditionBuilder checking that the attribute exists, see
+    /// attribute_exists().
+    pub fn attribute_exists(self) -> ConditionBuilder {
+        attribute_exists(self.name)
+    }
+
+    /// Returns a ConditionBuilder checking that the attribute doesn't exist,
+    /// see attribute_not_exists().
+    pub fn attribute_not_exists(self) -> ConditionBuilder {
+        attribute_not_exists(self.name)
+    }
+
+    /// Returns an UpdateBuilder setting the attribute to the argument value,
+    /// see set().
+    pub fn set(self, right: impl Into<T>) -> UpdateBuilder {
+        set(self.name, value(right.into()))
+    }
+}
+
+impl TypedNameBuilder<String> {
+    /// Returns a ConditionBuilder checking that the string attribute begins
+    /// with the argument prefix, see begins_with().
+    pub fn begins_with(self, prefix: impl Into<String>) -> ConditionBuilder {
+        begins_with(self.name, value(prefix.into()))
+    }
+
+    /// Returns a ConditionBuilder checking that the string attribute contains
+    /// the argument substring, see contains().
+    pub fn contains(self, substr: impl Into<String>) -> ConditionBuilder {
+        contains(self.name, substr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn typed_comparisons() -> anyhow::Result<()> {
+        let age = || typed_name::<i64>("Age");
+        let title = || typed_name::<String>("Title");
+
+        for (typed, untyped) in [
+            (age().equal(5), name("Age").equal(value(5i64))),
+            (age().not_equal(5i32), name("Age").not_equal(value(5i64))),
+            (age().less_than(5u8), name("Age").less_than(value(5i64))),
+            (
+                age().between(1, 10),
+                name("Age").between(value(1i64), value(10i64)),
+            ),
+            (age().in_values([1, 2])?, in_values(name("Age"), [1i64, 2])?),
+            (
+                title().greater_than_equal("a"),
+                name("Title").greater_than_equal(value("a".to_owned())),
+            ),
+            (
+                title().begins_with("a"),
+                name("Title").begins_with(value("a".to_owned())),
+            ),
+            (title().contains("a"), name("Title").contains("a")),
+            (title().attribute_exists(), name("Title").attribute_exists()),
+        ] {
+            assert_eq!(typed.build_tree()?, untyped.build_tree()?);
+        }
+
+        assert_eq!(
+            age().set(5).build_tree()?,
+            set(name("Age"), value(5i64)).build_tree()?
+        );
+        assert_eq!(age().name(), name("Age"));
+
+        Ok(())
+    }
+}
@@ -1,47 +1,54 @@
 //! Ported from [update.go](https://github.com/aws/aws-sdk-go/blob/master/service/dynamodb/expression/update.go)
 
 use std::collections::HashMap;
-use std::fmt::Write;
 
-use anyhow::bail;
+use aws_sdk_dynamodb::types::AttributeValue;
 use derivative::*;
 
 use crate::{
-    error::ExpressionError, ExpressionNode, NameBuilder, OperandBuilder, TreeBuilder,
-    ValueBuilderImpl,
+    error::ExpressionError, if_not_exists, minus, plus, value, ExpressionNode, FmtExpression,
+    NameBuilder, OperandBuilder, Recorder, ToAttributeValue, TreeBuilder, ValueBuilderImpl,
 };
 
+/// Specifies the clause of an operation of an Update Expression.
 #[derive(Copy, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Debug, Derivative, strum::AsRefStr)]
 #[derivative(Default)]
-pub(crate) enum OperationMode {
+pub enum OperationMode {
+    /// Set represents a SET operation
     #[derivative(Default)]
     #[strum(serialize = "SET")]
     Set,
 
+    /// Remove represents a REMOVE operation
     #[strum(serialize = "REMOVE")]
     Remove,
 
+    /// Add represents an ADD operation
     #[strum(serialize = "ADD")]
     Add,
 
+    /// Delete represents a DELETE operation
     #[strum(serialize = "DELETE")]
     Delete,
 }
 
-#[derive(Default)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub(crate) struct OperationBuilder {
-    name: Box<NameBuilder>,
-    value: Option<Box<dyn OperandBuilder>>,
+    pub(crate) name: Box<NameBuilder>,
+    pub(crate) value: Option<Box<dyn OperandBuilder>>,
     mode: OperationMode,
 }
 
 impl OperationBuilder {
-    fn build_operation(&self) -> anyhow::Result<ExpressionNode> {
-        let path_child = self.name.build_operand()?;
+    fn build_operation(&self) -> Result<ExpressionNode, ExpressionError> {
+        let path_child = self
+            .name
+            .build_operand()
+            .map_err(|e| e.in_path("operand[0]"))?;
 
         let mut node = ExpressionNode::from_children_expression(
             vec![path_child.expression_node],
-            "$c".to_owned(),
+            FmtExpression::new().child(),
         );
 
         if self.mode == OperationMode::Remove {
@@ -49,37 +56,41 @@ impl OperationBuilder {
         }
 
         if let Some(value) = &self.value {
-            let value_child = value.build_operand()?;
+            let value_child = value.build_operand().map_err(|e| e.in_path("operand[1]"))?;
             node.children.push(value_child.expression_node);
         }
 
-        node.fmt_expression.push_str(match self.mode {
-            OperationMode::Set => " = $c",
-            OperationMode::Add | OperationMode::Delete => " $c",
-            _ => bail!(
-                "build update error: build operation error: unsupported mode: {:?}",
-                self.mode
-            ),
-        });
+        let separator = match self.mode {
+            OperationMode::Set => " = ",
+            OperationMode::Add | OperationMode::Delete => " ",
+            _ => {
+                return Err(ExpressionError::UnsupportedMode(
+                    "build update error: build operation".to_owned(),
+                    format!("{:?}", self.mode),
+                ))
+            }
+        };
+        node.fmt_expression = FmtExpression::new().children(2, separator);
 
         Ok(node)
     }
 
     fn build_child_nodes(
         operation_builder_list: impl AsRef<[OperationBuilder]>,
-    ) -> anyhow::Result<ExpressionNode> {
+    ) -> Result<ExpressionNode, ExpressionError> {
         if operation_builder_list.as_ref().is_empty() {
-            bail!("buildChildNodes error: operationBuilder list is empty");
+            return Err(ExpressionError::EmptyOperationList);
         }
 
-        let mut node = ExpressionNode::default();
-        node.fmt_expression = format!(
-            "$c{}",
-            ", $c".repeat(operation_builder_list.as_ref().len() - 1)
+        let mut node = ExpressionNode::from_children_expression(
+            Vec::with_capacity(operation_builder_list.as_ref().len()),
+            FmtExpression::new().children(operation_builder_list.as_ref().len(), ", "),
         );
 
-        for val in operation_builder_list.as_ref() {
-            let val_node = val.build_operation()?;
+        for (i, val) in operation_builder_list.as_ref().iter().enumerate() {
+            let val_node = val
+                .build_operation()
+                .map_err(|e| e.in_path(format!("{}[{}]", val.mode.as_ref().to_lowercase(), i)))?;
             node.children.push(val_node);
         }
 
@@ -87,37 +98,71 @@ impl OperationBuilder {
     }
 }
 
+/// Represents how increment() and decrement() change a number attribute.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
+pub enum CounterMode {
+    /// Set assigns the result of the arithmetic, `SET #n = #n + :v`. The
+    /// attribute must already exist.
+    #[default]
+    Set,
+
+    /// Add adds to the attribute, `ADD #n :v`, treating a missing attribute as
+    /// 0. Decrementing adds the negated number.
+    Add,
+}
+
 pub fn delete(name: Box<NameBuilder>, value: Box<dyn ValueBuilderImpl>) -> UpdateBuilder {
-    let empty_update_builder = UpdateBuilder {
-        operations: HashMap::new(),
-    };
+    let empty_update_builder = UpdateBuilder::default();
     empty_update_builder.delete(name, value)
 }
 
 pub fn add(name: Box<NameBuilder>, value: Box<dyn ValueBuilderImpl>) -> UpdateBuilder {
-    let empty_update_builder = UpdateBuilder {
-        operations: HashMap::new(),
-    };
+    let empty_update_builder = UpdateBuilder::default();
     empty_update_builder.add(name, value)
 }
 
 pub fn remove(name: Box<NameBuilder>) -> UpdateBuilder {
-    let empty_update_builder = UpdateBuilder {
-        operations: HashMap::new(),
-    };
+    let empty_update_builder = UpdateBuilder::default();
     empty_update_builder.remove(name)
 }
 
 pub fn set(name: Box<NameBuilder>, operand_builder: Box<dyn OperandBuilder>) -> UpdateBuilder {
-    let empty_update_builder = UpdateBuilder {
-        operations: HashMap::new(),
-    };
+    let empty_update_builder = UpdateBuilder::default();
     empty_update_builder.set(name, operand_builder)
 }
 
-#[derive(Default)]
+/// Represents the order of the clauses of an Update Expression.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
+pub enum ClauseOrder {
+    /// Standard orders the clauses SET, REMOVE, ADD, DELETE, the order they
+    /// are documented in by DynamoDB.
+    #[default]
+    Standard,
+
+    /// GoSdk orders the clauses alphabetically, ADD, DELETE, REMOVE, SET, as
+    /// the Go SDK does, so the Update Expressions match its output byte for
+    /// byte.
+    GoSdk,
+}
+
+#[derive(Default, Clone, PartialEq)]
 pub struct UpdateBuilder {
     operations: HashMap<OperationMode, Vec<OperationBuilder>>,
+    clause_order: ClauseOrder,
+}
+
+impl std::fmt::Debug for UpdateBuilder {
+    // renders the clauses in the same order build_tree() does
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct("UpdateBuilder");
+        for (mode, operation_list) in self.ordered_operations() {
+            s.field(mode.as_ref(), operation_list);
+        }
+        if self.clause_order != ClauseOrder::default() {
+            s.field("clause_order", &self.clause_order);
+        }
+        s.finish()
+    }
 }
 
 impl UpdateBuilder {
@@ -184,34 +229,502 @@ impl UpdateBuilder {
 
         self
     }
+
+    /// Sets the order of the clauses of the Update Expression, by default
+    /// ClauseOrder::Standard.
+    ///
+    /// The order of the operations within a clause is the order they were
+    /// added in.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let update = set(name("a"), value(1)).add(name("b"), value(2));
+    ///
+    /// let expr = Builder::new().with_update(update.clone()).build().unwrap();
+    /// assert_eq!(expr.update().unwrap(), "SET #0 = :0\nADD #1 :1\n");
+    ///
+    /// let expr = Builder::new()
+    ///     .with_update(update.with_clause_order(ClauseOrder::GoSdk))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(expr.update().unwrap(), "ADD #0 :0\nSET #1 = :1\n");
+    /// ```
+    pub fn with_clause_order(mut self, clause_order: ClauseOrder) -> UpdateBuilder {
+        self.clause_order = clause_order;
+        self
+    }
+
+    /// Returns the number of operations of the UpdateBuilder, over all of its
+    /// clauses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let changes: Vec<(&str, i64)> = Vec::new();
+    ///
+    /// let update = changes.into_iter().fold(UpdateBuilder::default(), |update, (field, v)| {
+    ///     update.set(name(field), value(v))
+    /// });
+    ///
+    /// // nothing changed, so there's no UpdateItem to send
+    /// assert!(update.is_empty());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.operations.values().map(Vec::len).sum()
+    }
+
+    /// Returns true if the UpdateBuilder has no operation, for which build()
+    /// returns the typed error UnsetParameterError.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of operations of the SET clause.
+    pub fn set_count(&self) -> usize {
+        self.count(OperationMode::Set)
+    }
+
+    /// Returns the number of operations of the REMOVE clause.
+    pub fn remove_count(&self) -> usize {
+        self.count(OperationMode::Remove)
+    }
+
+    /// Returns the number of operations of the ADD clause.
+    pub fn add_count(&self) -> usize {
+        self.count(OperationMode::Add)
+    }
+
+    /// Returns the number of operations of the DELETE clause.
+    pub fn delete_count(&self) -> usize {
+        self.count(OperationMode::Delete)
+    }
+
+    fn count(&self, mode: OperationMode) -> usize {
+        self.operations.get(&mode).map_or(0, Vec::len)
+    }
+
+    // adds the operations of the argument UpdateBuilder after the operations
+    // of this one
+    pub(crate) fn merge(mut self, other: UpdateBuilder) -> UpdateBuilder {
+        for (mode, operation_list) in other.operations {
+            self.operations
+                .entry(mode)
+                .or_default()
+                .extend(operation_list);
+        }
+        self
+    }
+
+    // returns the clauses in the order they are built in
+    pub(crate) fn ordered_operations(&self) -> Vec<(&OperationMode, &Vec<OperationBuilder>)> {
+        let mut operations = self.operations.iter().collect::<Vec<_>>();
+        match self.clause_order {
+            ClauseOrder::Standard => operations.sort_unstable_by_key(|(mode, _)| **mode),
+            ClauseOrder::GoSdk => {
+                operations.sort_unstable_by(|x, y| x.0.as_ref().cmp(y.0.as_ref()))
+            }
+        }
+        operations
+    }
+
+    /// Adds a SET operation for each of the argument names and operands.
+    ///
+    /// This is the same as calling set() for each of them, in order, without
+    /// growing the operation list one at a time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use dynamodb_expression::*;
+    ///
+    /// let fields = BTreeMap::from([("a", 1), ("b", 2)]);
+    ///
+    /// let update = UpdateBuilder::default().set_many(fields.into_iter().map(
+    ///     |(field, field_value)| -> (Box<NameBuilder>, Box<dyn OperandBuilder>) {
+    ///         (name(field), value(field_value))
+    ///     },
+    /// ));
+    ///
+    /// let expr = Builder::new().with_update(update).build().unwrap();
+    /// assert_eq!(expr.update().unwrap(), "SET #0 = :0, #1 = :1\n");
+    /// ```
+    pub fn set_many(
+        self,
+        operations: impl IntoIterator<Item = (Box<NameBuilder>, Box<dyn OperandBuilder>)>,
+    ) -> UpdateBuilder {
+        self.push_operations(
+            OperationMode::Set,
+            operations
+                .into_iter()
+                .map(|(name, operand_builder)| (name, Some(operand_builder))),
+        )
+    }
+
+    /// Adds a REMOVE operation for each of the argument names.
+    ///
+    /// This is the same as calling remove() for each of them, in order.
+    pub fn remove_many(self, names: impl IntoIterator<Item = Box<NameBuilder>>) -> UpdateBuilder {
+        self.push_operations(
+            OperationMode::Remove,
+            names.into_iter().map(|name| (name, None)),
+        )
+    }
+
+    fn push_operations(
+        mut self,
+        mode: OperationMode,
+        operations: impl Iterator<Item = (Box<NameBuilder>, Option<Box<dyn OperandBuilder>>)>,
+    ) -> UpdateBuilder {
+        let mut operations = operations.peekable();
+
+        // an empty operation list fails to build, so none is added
+        if operations.peek().is_none() {
+            return self;
+        }
+
+        let list = self.operations.entry(mode).or_default();
+        list.reserve(operations.size_hint().0);
+        list.extend(operations.map(|(name, value)| OperationBuilder { name, value, mode }));
+
+        self
+    }
+
+    /// Increments the number attribute by the argument amount, with
+    /// `SET #n = #n + :v`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let update = UpdateBuilder::default().increment(name("views"), 1);
+    ///
+    /// let expr = Builder::new().with_update(update).build().unwrap();
+    /// assert_eq!(expr.update().unwrap(), "SET #0 = #0 + :0\n");
+    /// ```
+    pub fn increment<T: ToAttributeValue>(self, name: Box<NameBuilder>, by: T) -> UpdateBuilder {
+        self.increment_with(name, by, CounterMode::Set)
+    }
+
+    /// Decrements the number attribute by the argument amount, with
+    /// `SET #n = #n - :v`.
+    pub fn decrement<T: ToAttributeValue>(self, name: Box<NameBuilder>, by: T) -> UpdateBuilder {
+        self.decrement_with(name, by, CounterMode::Set)
+    }
+
+    /// Increments the number attribute by the argument amount, with the
+    /// argument CounterMode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// // a missing attribute is created with the value 1
+    /// let update = UpdateBuilder::default().increment_with(name("views"), 1, CounterMode::Add);
+    ///
+    /// let expr = Builder::new().with_update(update).build().unwrap();
+    /// assert_eq!(expr.update().unwrap(), "ADD #0 :0\n");
+    /// ```
+    pub fn increment_with<T: ToAttributeValue>(
+        self,
+        name: Box<NameBuilder>,
+        by: T,
+        mode: CounterMode,
+    ) -> UpdateBuilder {
+        match mode {
+            CounterMode::Set => self.set(name.clone(), plus(name, value(by))),
+            CounterMode::Add => self.add(name, value(by)),
+        }
+    }
+
+    /// Decrements the number attribute by the argument amount, with the
+    /// argument CounterMode.
+    ///
+    /// With CounterMode::Add, the amount must be a number, it is negated and
+    /// added to the attribute.
+    pub fn decrement_with<T: ToAttributeValue>(
+        self,
+        name: Box<NameBuilder>,
+        by: T,
+        mode: CounterMode,
+    ) -> UpdateBuilder {
+        match mode {
+            CounterMode::Set => self.set(name.clone(), minus(name, value(by))),
+            CounterMode::Add => self.add(name, value(negate(by.to_attribute_value()))),
+        }
+    }
+
+    /// Increments the number attribute by the argument amount, starting from
+    /// the argument value if the attribute doesn't exist, with
+    /// `SET #n = if_not_exists(#n, :start) + :v`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let update = UpdateBuilder::default().increment_with_default(name("views"), 1, 100);
+    ///
+    /// let expr = Builder::new().with_update(update).build().unwrap();
+    /// assert_eq!(expr.update().unwrap(), "SET #0 = if_not_exists(#0, :0) + :1\n");
+    /// ```
+    pub fn increment_with_default<T: ToAttributeValue, S: ToAttributeValue>(
+        self,
+        name: Box<NameBuilder>,
+        by: T,
+        start: S,
+    ) -> UpdateBuilder {
+        self.set(
+            name.clone(),
+            plus(if_not_exists(name, value(start)), value(by)),
+        )
+    }
+
+    /// Sets the attribute to the argument value only if it doesn't exist yet,
+    /// with `SET #n = if_not_exists(#n, :v)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let update = UpdateBuilder::default().set_default(name("createdAt"), value(1700000000));
+    ///
+    /// let expr = Builder::new().with_update(update).build().unwrap();
+    /// assert_eq!(expr.update().unwrap(), "SET #0 = if_not_exists(#0, :0)\n");
+    /// ```
+    pub fn set_default(
+        self,
+        name: Box<NameBuilder>,
+        value: Box<dyn OperandBuilder>,
+    ) -> UpdateBuilder {
+        self.set(name.clone(), if_not_exists(name, value))
+    }
+
+    /// Sets the attribute, if it doesn't exist yet, to the first of the
+    /// fallback attributes that exists, or else to the argument value.
+    ///
+    /// The if_not_exists() calls are nested, so
+    /// `coalesce(name("a"), [name("b"), name("c")], value(0))` expands to
+    /// `SET #a = if_not_exists(#a, if_not_exists(#b, if_not_exists(#c, :0)))`.
+    /// Without fallback attributes, coalesce() is the same as set_default().
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let update = UpdateBuilder::default().coalesce(
+    ///     name("displayName"),
+    ///     [name("nickname"), name("fullName")],
+    ///     value("anonymous"),
+    /// );
+    ///
+    /// let expr = Builder::new().with_update(update).build().unwrap();
+    /// assert_eq!(
+    ///     expr.update().unwrap(),
+    ///     "SET #0 = if_not_exists(#0, if_not_exists(#1, if_not_exists(#2, :0)))\n"
+    /// );
+    /// ```
+    pub fn coalesce(
+        self,
+        name: Box<NameBuilder>,
+        fallbacks: impl IntoIterator<Item = Box<NameBuilder>>,
+        value: Box<dyn OperandBuilder>,
+    ) -> UpdateBuilder {
+        let fallbacks = fallbacks.into_iter().collect::<Vec<_>>();
+
+        // nested from the innermost fallback outwards
+        let value = fallbacks
+            .into_iter()
+            .rev()
+            .fold(value, |value, fallback| if_not_exists(fallback, value));
+
+        self.set_default(name, value)
+    }
+
+    /// Adds the elements of the argument set to the set attribute, with
+    /// `ADD #n :v`.
+    ///
+    /// Unlike add(), the value must be a non-empty string, number or binary
+    /// set, such as the ones returned by string_set(), otherwise
+    /// add_to_set() returns the typed error InvalidParameterError. A missing
+    /// attribute is created with the elements of the set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let update = UpdateBuilder::default()
+    ///     .add_to_set(name("tags"), string_set(["red", "blue"]))
+    ///     .unwrap();
+    ///
+    /// let expr = Builder::new().with_update(update).build().unwrap();
+    /// assert_eq!(expr.update().unwrap(), "ADD #0 :0\n");
+    ///
+    /// assert!(UpdateBuilder::default()
+    ///     .add_to_set(name("tags"), value("red"))
+    ///     .is_err());
+    /// ```
+    pub fn add_to_set(
+        self,
+        name: Box<NameBuilder>,
+        set: Box<dyn ValueBuilderImpl>,
+    ) -> Result<UpdateBuilder, ExpressionError> {
+        check_set("addToSet", set.as_ref())?;
+        Ok(self.add(name, set))
+    }
+
+    /// Deletes the elements of the argument set from the set attribute, with
+    /// `DELETE #n :v`.
+    ///
+    /// The value must be a non-empty string, number or binary set, otherwise
+    /// delete_from_set() returns the typed error InvalidParameterError.
+    pub fn delete_from_set(
+        self,
+        name: Box<NameBuilder>,
+        set: Box<dyn ValueBuilderImpl>,
+    ) -> Result<UpdateBuilder, ExpressionError> {
+        check_set("deleteFromSet", set.as_ref())?;
+        Ok(self.delete(name, set))
+    }
+
+    /// Removes the elements of the list attribute at the argument indices.
+    ///
+    /// The indices are sorted in descending order and duplicates are dropped,
+    /// so the removal of one element can't shift the index of another.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let update = UpdateBuilder::default().remove_list_elements(name("list"), [3, 9, 1]);
+    ///
+    /// let expr = Builder::new().with_update(update).build().unwrap();
+    /// assert_eq!(expr.update().unwrap(), "REMOVE #0[9], #0[3], #0[1]\n");
+    /// ```
+    // takes a boxed name like the other operations do
+    #[allow(clippy::boxed_local)]
+    pub fn remove_list_elements(
+        mut self,
+        name: Box<NameBuilder>,
+        indices: impl IntoIterator<Item = usize>,
+    ) -> UpdateBuilder {
+        let mut indices = indices.into_iter().collect::<Vec<_>>();
+        indices.sort_unstable_by(|x, y| y.cmp(x));
+        indices.dedup();
+
+        for index in indices {
+            self = self.remove(name.element(index));
+        }
+
+        self
+    }
+}
+
+// checks that the argument value is a set that can be used with ADD and DELETE
+fn check_set(function_name: &str, set: &dyn ValueBuilderImpl) -> Result<(), ExpressionError> {
+    let is_set = match set.attribute_value() {
+        AttributeValue::Ss(values) | AttributeValue::Ns(values) => !values.is_empty(),
+        AttributeValue::Bs(values) => !values.is_empty(),
+        _ => false,
+    };
+
+    if !is_set {
+        return Err(ExpressionError::InvalidParameterError(
+            function_name.to_owned(),
+            "set".to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+// negates a number attribute value, other attribute values are left as-is
+fn negate(value: AttributeValue) -> AttributeValue {
+    match value {
+        AttributeValue::N(n) => match n.strip_prefix('-') {
+            Some(positive) => AttributeValue::N(positive.to_owned()),
+            None => AttributeValue::N(format!("-{}", n)),
+        },
+        value => value,
+    }
 }
 
 impl TreeBuilder for UpdateBuilder {
-    fn build_tree(&self) -> anyhow::Result<ExpressionNode> {
+    fn build_tree(&self) -> Result<ExpressionNode, ExpressionError> {
         if self.operations.is_empty() {
-            bail!(ExpressionError::UnsetParameterError(
+            return Err(ExpressionError::UnsetParameterError(
                 "buildTree".to_owned(),
                 "UpdateBuilder".to_owned(),
             ));
         }
 
-        let mut ret = ExpressionNode::default();
+        let operations = self.ordered_operations();
+
+        let mut children = Vec::with_capacity(operations.len());
+        let mut fmt_expression = FmtExpression::new();
+        for (key, operation_list) in operations {
+            fmt_expression = fmt_expression
+                .literal(key.as_ref().to_owned())
+                .literal(" ")
+                .child()
+                .literal("\n");
+
+            let child_node = OperationBuilder::build_child_nodes(operation_list)?;
+            children.push(child_node);
+        }
+
+        Ok(ExpressionNode::from_children_expression(
+            children,
+            fmt_expression,
+        ))
+    }
+
+    fn into_update(self: Box<Self>) -> Option<UpdateBuilder> {
+        Some(*self)
+    }
 
-        let mut modes = Vec::new();
-        for mode in self.operations.keys() {
-            modes.push(mode);
+    fn record(&self, recorder: &mut Recorder) -> String {
+        let operations = self.ordered_operations();
+
+        let mut calls = Vec::new();
+        for (mode, operation_list) in operations {
+            for operation in operation_list {
+                let name = operation.name.record(recorder);
+                calls.push(match &operation.value {
+                    Some(value) if *mode != OperationMode::Remove => format!(
+                        "{}({}, {})",
+                        mode.as_ref().to_lowercase(),
+                        name,
+                        value.record(recorder)
+                    ),
+                    _ => format!("{}({})", mode.as_ref().to_lowercase(), name),
+                });
+            }
         }
-        modes.sort_unstable_by(|x, y| x.as_ref().partial_cmp(y.as_ref()).unwrap());
 
-        for key in modes {
-            writeln!(ret.fmt_expression, "{} $c", key.as_ref())?;
+        let clause_order = match self.clause_order {
+            ClauseOrder::Standard => "",
+            ClauseOrder::GoSdk => ".with_clause_order(ClauseOrder::GoSdk)",
+        };
 
-            let child_node =
-                OperationBuilder::build_child_nodes(self.operations.get(key).unwrap())?;
-            ret.children.push(child_node);
+        if calls.is_empty() {
+            return format!("UpdateBuilder::default(){}", clause_order);
         }
 
-        Ok(ret)
+        // the first operation uses the function, the others chain the methods
+        calls.join(".") + clause_order
     }
 }
 
@@ -243,6 +756,35 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn operation_counts() -> anyhow::Result<()> {
+        let input = set(name("foo"), value(5))
+            .set(name("bar"), value(6))
+            .remove(name("baz"))
+            .add(name("qux"), value(1))
+            .remove_many(vec![]);
+
+        assert_eq!(input.len(), 4);
+        assert!(!input.is_empty());
+        assert_eq!(input.set_count(), 2);
+        assert_eq!(input.remove_count(), 1);
+        assert_eq!(input.add_count(), 1);
+        assert_eq!(input.delete_count(), 0);
+
+        let input = UpdateBuilder::default().set_many(vec![]);
+        assert_eq!(input.len(), 0);
+        assert!(input.is_empty());
+        assert_eq!(
+            input.build_tree().unwrap_err(),
+            error::ExpressionError::UnsetParameterError(
+                "buildTree".to_owned(),
+                "UpdateBuilder".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn add_operation() -> anyhow::Result<()> {
         let input = OperationBuilder {
@@ -293,14 +835,12 @@ mod tests {
         };
 
         assert_eq!(
-            input
-                .build_operation()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_operation().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
         );
 
         Ok(())
@@ -463,56 +1003,47 @@ mod tests {
             .delete(name("foo"), value(5))
             .remove(name("foo"));
 
+        let operation = |fmt_expression: &str| {
+            ExpressionNode::from_children_expression(
+                vec![ExpressionNode::from_children_expression(
+                    vec![
+                        ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
+                        ExpressionNode::from_values(vec![AttributeValue::N("5".to_owned())], "$v"),
+                    ],
+                    fmt_expression,
+                )],
+                "$c",
+            )
+        };
+        let remove_operation = ExpressionNode::from_children_expression(
+            vec![ExpressionNode::from_children_expression(
+                vec![ExpressionNode::from_names(vec!["foo".to_owned()], "$n")],
+                "$c",
+            )],
+            "$c",
+        );
+
         assert_eq!(
             input.build_tree()?,
             ExpressionNode::from_children_expression(
                 vec![
-                    ExpressionNode::from_children_expression(
-                        vec![ExpressionNode::from_children_expression(
-                            vec![
-                                ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
-                                ExpressionNode::from_values(
-                                    vec![AttributeValue::N("5".to_owned())],
-                                    "$v"
-                                ),
-                            ],
-                            "$c $c"
-                        )],
-                        "$c"
-                    ),
-                    ExpressionNode::from_children_expression(
-                        vec![ExpressionNode::from_children_expression(
-                            vec![
-                                ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
-                                ExpressionNode::from_values(
-                                    vec![AttributeValue::N("5".to_owned())],
-                                    "$v"
-                                ),
-                            ],
-                            "$c $c"
-                        )],
-                        "$c"
-                    ),
-                    ExpressionNode::from_children_expression(
-                        vec![ExpressionNode::from_children_expression(
-                            vec![ExpressionNode::from_names(vec!["foo".to_owned()], "$n")],
-                            "$c"
-                        )],
-                        "$c"
-                    ),
-                    ExpressionNode::from_children_expression(
-                        vec![ExpressionNode::from_children_expression(
-                            vec![
-                                ExpressionNode::from_names(vec!["foo".to_owned()], "$n"),
-                                ExpressionNode::from_values(
-                                    vec![AttributeValue::N("5".to_owned())],
-                                    "$v"
-                                ),
-                            ],
-                            "$c = $c"
-                        )],
-                        "$c"
-                    )
+                    operation("$c = $c"),
+                    remove_operation.clone(),
+                    operation("$c $c"),
+                    operation("$c $c"),
+                ],
+                "SET $c\nREMOVE $c\nADD $c\nDELETE $c\n"
+            )
+        );
+
+        assert_eq!(
+            input.with_clause_order(ClauseOrder::GoSdk).build_tree()?,
+            ExpressionNode::from_children_expression(
+                vec![
+                    operation("$c $c"),
+                    operation("$c $c"),
+                    remove_operation,
+                    operation("$c = $c"),
                 ],
                 "ADD $c\nDELETE $c\nREMOVE $c\nSET $c\n"
             )
@@ -526,10 +1057,7 @@ mod tests {
         let input = UpdateBuilder::default();
 
         assert_eq!(
-            input
-                .build_tree()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_tree().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "buildTree".to_owned(),
                 "UpdateBuilder".to_owned()
@@ -687,10 +1215,7 @@ mod tests {
         let input = SetValueBuilder::default();
 
         assert_eq!(
-            input
-                .build_operand()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_operand().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "SetValueBuilder".to_owned()
@@ -705,10 +1230,7 @@ mod tests {
         let input = name("").plus(name("foo"));
 
         assert_eq!(
-            input
-                .build_operand()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_operand().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
@@ -723,10 +1245,7 @@ mod tests {
         let input = name("foo").plus(name(""));
 
         assert_eq!(
-            input
-                .build_operand()
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            input.build_operand().unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
@@ -818,13 +1337,231 @@ mod tests {
         let input = vec![OperationBuilder::default()];
 
         assert_eq!(
-            OperationBuilder::build_child_nodes(input)
-                .map_err(|e| e.downcast::<error::ExpressionError>().unwrap())
-                .unwrap_err(),
+            OperationBuilder::build_child_nodes(input).unwrap_err(),
             error::ExpressionError::UnsetParameterError(
                 "BuildOperand".to_owned(),
                 "NameBuilder".to_owned()
             )
+            .in_path("operand[0]")
+            .in_path("set[0]")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn debug_update() -> anyhow::Result<()> {
+        let input = set(name("foo"), value(5)).remove(name("bar"));
+
+        assert_eq!(
+            format!("{:?}", input),
+            "UpdateBuilder { \
+                SET: [OperationBuilder { name: NameBuilder { name: \"foo\", mode: Strict }, value: Some(ValueBuilder { value: 5 }), mode: Set }], \
+                REMOVE: [OperationBuilder { name: NameBuilder { name: \"bar\", mode: Strict }, value: None, mode: Remove }] \
+            }"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn eq_update() -> anyhow::Result<()> {
+        let input = set(name("foo"), value(5)).remove(name("bar"));
+
+        assert_eq!(input, remove(name("bar")).set(name("foo"), value(5)));
+        assert_ne!(input, set(name("foo"), value(6)).remove(name("bar")));
+        assert_ne!(input, set(name("foo"), value(5)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn counters() -> anyhow::Result<()> {
+        let input = UpdateBuilder::default()
+            .increment(name("a"), 1)
+            .decrement(name("b"), 2.5);
+        assert_eq!(
+            input,
+            set(name("a"), name("a").plus(value(1))).set(name("b"), name("b").minus(value(2.5)))
+        );
+
+        let input = UpdateBuilder::default()
+            .increment_with(name("a"), 1, CounterMode::Add)
+            .decrement_with(name("b"), 2, CounterMode::Add)
+            .decrement_with(name("c"), -3, CounterMode::Add);
+        assert_eq!(
+            input,
+            add(name("a"), value(1))
+                .add(name("b"), value(AttributeValue::N("-2".to_owned())))
+                .add(name("c"), value(AttributeValue::N("3".to_owned())))
+        );
+
+        let input = UpdateBuilder::default().increment_with_default(name("a"), 1, 10);
+        let expr = Builder::new().with_update(input).build()?;
+        assert_eq!(expr.update(), Some("SET #0 = if_not_exists(#0, :0) + :1\n"));
+        assert_eq!(
+            expr.values().as_ref().map(|values| values[":0"].clone()),
+            Some(AttributeValue::N("10".to_owned()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn defaults() -> anyhow::Result<()> {
+        let input = UpdateBuilder::default().set_default(name("a"), value(1));
+        assert_eq!(input, set(name("a"), if_not_exists(name("a"), value(1))));
+
+        let input = UpdateBuilder::default().coalesce(name("a"), [], value(1));
+        assert_eq!(input, set(name("a"), if_not_exists(name("a"), value(1))));
+
+        let input = UpdateBuilder::default().coalesce(name("a"), [name("b"), name("c")], value(1));
+        assert_eq!(
+            input,
+            set(
+                name("a"),
+                if_not_exists(
+                    name("a"),
+                    if_not_exists(name("b"), if_not_exists(name("c"), value(1)))
+                )
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sets() -> anyhow::Result<()> {
+        let input = UpdateBuilder::default()
+            .add_to_set(name("a"), string_set(["x", "y", "x"]))?
+            .delete_from_set(name("b"), number_set([1, 2]))?;
+        assert_eq!(
+            input,
+            add(
+                name("a"),
+                value(AttributeValue::Ss(vec!["x".to_owned(), "y".to_owned()]))
+            )
+            .delete(
+                name("b"),
+                value(AttributeValue::Ns(vec!["1".to_owned(), "2".to_owned()]))
+            )
+        );
+
+        assert_eq!(
+            UpdateBuilder::default()
+                .add_to_set(name("a"), value("x"))
+                .unwrap_err(),
+            error::ExpressionError::InvalidParameterError("addToSet".to_owned(), "set".to_owned())
+        );
+        assert_eq!(
+            UpdateBuilder::default()
+                .delete_from_set(name("a"), binary_set(Vec::<Vec<u8>>::new()))
+                .unwrap_err(),
+            error::ExpressionError::InvalidParameterError(
+                "deleteFromSet".to_owned(),
+                "set".to_owned()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn remove_list_elements() -> anyhow::Result<()> {
+        let input = UpdateBuilder::default().remove_list_elements(name("a.b"), [1, 9, 3, 9]);
+        assert_eq!(
+            input,
+            remove(name("a.b[9]"))
+                .remove(name("a.b[3]"))
+                .remove(name("a.b[1]"))
+        );
+
+        let input = UpdateBuilder::default().remove_list_elements(path("a.b").into_name(), [0, 2]);
+        let expr = Builder::new().with_update(input).build()?;
+        assert_eq!(expr.update(), Some("REMOVE #0[2], #0[0]\n"));
+        assert_eq!(
+            expr.names().as_ref().map(|names| names["#0"].as_str()),
+            Some("a.b")
+        );
+
+        assert_eq!(
+            UpdateBuilder::default().remove_list_elements(name("a"), []),
+            UpdateBuilder::default()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn many_operations() -> anyhow::Result<()> {
+        let input = UpdateBuilder::default()
+            .set(name("a"), value(1))
+            .set_many(vec![
+                (name("b"), value(2i64) as Box<dyn OperandBuilder>),
+                (name("c"), name("d").plus(value(3))),
+            ])
+            .remove_many([name("e"), name("f")]);
+        assert_eq!(
+            input,
+            set(name("a"), value(1))
+                .set(name("b"), value(2))
+                .set(name("c"), name("d").plus(value(3)))
+                .remove(name("e"))
+                .remove(name("f"))
+        );
+
+        let input = UpdateBuilder::default()
+            .set_many(Vec::new())
+            .remove_many([]);
+        assert_eq!(input, UpdateBuilder::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn set_value_chaining() -> anyhow::Result<()> {
+        let list = || value(AttributeValue::L(vec![AttributeValue::S("a".to_owned())]));
+
+        let input = name("foo")
+            .if_not_exists(value(0))
+            .plus(value(1))
+            .minus(name("bar"));
+        assert_eq!(
+            input,
+            minus(
+                plus(if_not_exists(name("foo"), value(0)), value(1)),
+                name("bar")
+            )
+        );
+
+        let input = name("foo")
+            .if_not_exists(value(AttributeValue::L(vec![])))
+            .list_append(list());
+        let expr = Builder::new()
+            .with_update(set(name("foo"), input))
+            .build()?;
+        assert_eq!(
+            expr.update(),
+            Some("SET #0 = list_append(if_not_exists(#0, :0), :1)\n")
+        );
+
+        // nesting to any depth
+        let mut input = name("foo").list_append(list());
+        for _ in 0..100 {
+            input = input.list_append(list());
+        }
+        let operand = input.build_operand()?;
+        assert_eq!(
+            operand.expression_node.fmt_expression,
+            "list_append($c, $c)"
+        );
+        assert_eq!(
+            Builder::new()
+                .with_update(set(name("foo"), input))
+                .build()?
+                .update()
+                .map(|update| update.matches("list_append(").count()),
+            Some(101)
         );
 
         Ok(())
@@ -0,0 +1,156 @@
+//! Writes that create an item if it is missing and update it otherwise
+//!
+//! A put-style write creates the item only if no item with the same key
+//! exists, guarded by `attribute_not_exists` on the key attributes. An
+//! update-style write always succeeds, and fills in attributes such as a
+//! creation time only when the item is created, with `if_not_exists`.
+
+use crate::{
+    attribute_not_exists, error::ExpressionError, ConditionBuilder, GuardedUpdate, NameBuilder,
+    OperandBuilder, UpdateBuilder,
+};
+
+/// Returns a ConditionBuilder for a write that only succeeds if no item with
+/// the same key exists, with `attribute_not_exists (#k)` for each of the
+/// argument key attributes.
+///
+/// It is typically used as the condition of a PutItem. If no key attribute is
+/// given, if_missing() returns the typed error UnsetParameterError.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let condition = upsert::if_missing([name("pk"), name("sk")]).unwrap();
+///
+/// let expr = Builder::new().with_condition(condition).build().unwrap();
+/// assert_eq!(
+///     expr.condition().unwrap(),
+///     "(attribute_not_exists (#0)) AND (attribute_not_exists (#1))"
+/// );
+/// ```
+pub fn if_missing(
+    key: impl IntoIterator<Item = Box<NameBuilder>>,
+) -> Result<ConditionBuilder, ExpressionError> {
+    key.into_iter()
+        .map(attribute_not_exists)
+        .reduce(ConditionBuilder::and)
+        .ok_or_else(|| {
+            ExpressionError::UnsetParameterError("ifMissing".to_owned(), "key".to_owned())
+        })
+}
+
+/// Returns a GuardedUpdate creating an item with the argument attributes, if
+/// no item with the same key exists.
+///
+/// The condition is the one of if_missing(), and the update SETs every
+/// attribute. It lets an UpdateItem behave as a put that never overwrites.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let create = upsert::create(
+///     [name("pk")],
+///     [
+///         (name("name"), value("ace") as Box<dyn OperandBuilder>),
+///         (name("level"), value(1i64)),
+///     ],
+/// )
+/// .unwrap();
+///
+/// let expr = Builder::new().with_guarded_update(create).build().unwrap();
+/// assert_eq!(expr.condition().unwrap(), "attribute_not_exists (#0)");
+/// assert_eq!(expr.update().unwrap(), "SET #1 = :0, #2 = :1\n");
+/// ```
+pub fn create(
+    key: impl IntoIterator<Item = Box<NameBuilder>>,
+    attributes: impl IntoIterator<Item = (Box<NameBuilder>, Box<dyn OperandBuilder>)>,
+) -> Result<GuardedUpdate, ExpressionError> {
+    Ok(GuardedUpdate::new(
+        if_missing(key)?,
+        UpdateBuilder::default().set_many(attributes),
+    ))
+}
+
+/// Returns an UpdateBuilder creating or updating an item, which SETs the
+/// argument values and SETs the argument defaults only if they don't exist
+/// yet.
+///
+/// The values are set with `SET #v = :v` and the defaults with
+/// `SET #d = if_not_exists(#d, :d)`, so the defaults keep the values of the
+/// first write.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let update = upsert::merge(
+///     [(name("updatedAt"), value(1_700_000_100i64) as Box<dyn OperandBuilder>)],
+///     [(name("createdAt"), value(1_700_000_100i64) as Box<dyn OperandBuilder>)],
+/// );
+///
+/// let expr = Builder::new().with_update(update).build().unwrap();
+/// assert_eq!(expr.update().unwrap(), "SET #0 = :0, #1 = if_not_exists(#1, :1)\n");
+/// ```
+pub fn merge(
+    values: impl IntoIterator<Item = (Box<NameBuilder>, Box<dyn OperandBuilder>)>,
+    defaults: impl IntoIterator<Item = (Box<NameBuilder>, Box<dyn OperandBuilder>)>,
+) -> UpdateBuilder {
+    defaults.into_iter().fold(
+        UpdateBuilder::default().set_many(values),
+        |update, (name, default)| update.set_default(name, default),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn if_missing() -> anyhow::Result<()> {
+        assert_eq!(
+            upsert::if_missing([name("pk"), name("sk")])?,
+            name("pk")
+                .attribute_not_exists()
+                .and(name("sk").attribute_not_exists())
+        );
+        assert_eq!(
+            upsert::if_missing([name("pk")])?,
+            name("pk").attribute_not_exists()
+        );
+        assert_eq!(
+            upsert::if_missing(Vec::new()).unwrap_err(),
+            error::ExpressionError::UnsetParameterError("ifMissing".to_owned(), "key".to_owned())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_and_merge() -> anyhow::Result<()> {
+        let input = upsert::create(
+            [name("pk")],
+            [(name("a"), value(1i64) as Box<dyn OperandBuilder>)],
+        )?;
+        assert_eq!(
+            input.into_parts(),
+            (name("pk").attribute_not_exists(), set(name("a"), value(1)))
+        );
+        assert!(upsert::create(Vec::new(), Vec::new()).is_err());
+
+        let input = upsert::merge(
+            [(name("a"), value(1i64) as Box<dyn OperandBuilder>)],
+            [(name("b"), value(2i64) as Box<dyn OperandBuilder>)],
+        );
+        assert_eq!(
+            input,
+            set(name("a"), value(1)).set(name("b"), if_not_exists(name("b"), value(2)))
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! Optimistic locking with a version number attribute
+//!
+//! Each write of an item checks that the version attribute still holds the
+//! version that was read, and increments it. A concurrent write in between
+//! makes the condition fail, instead of being silently overwritten.
+
+use crate::{
+    attribute_not_exists, set, value, EqualBuilder, GuardedUpdate, NameBuilder, UpdateBuilder,
+};
+
+/// Returns the GuardedUpdate of a write to an item whose version attribute
+/// holds the argument current version.
+///
+/// If there is a current version, the condition is `#v = :current` and the
+/// update is `SET #v = #v + :1`. If there is none, because the item is written
+/// for the first time, the condition is `attribute_not_exists (#v)` and the
+/// update is `SET #v = :1`.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// let expr = Builder::new()
+///     .with_update(set(name("status"), value("shipped")))
+///     .with_guarded_update(versioning::optimistic_lock(name("version"), 3))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(expr.condition().unwrap(), "#0 = :0");
+/// assert_eq!(expr.update().unwrap(), "SET #1 = :1, #0 = #0 + :2\n");
+///
+/// // the first write of the item
+/// let (condition, update) = versioning::optimistic_lock(name("version"), None).into_parts();
+/// let expr = Builder::new()
+///     .with_condition(condition)
+///     .with_update(update)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(expr.condition().unwrap(), "attribute_not_exists (#0)");
+/// assert_eq!(expr.update().unwrap(), "SET #0 = :0\n");
+/// ```
+pub fn optimistic_lock(name: Box<NameBuilder>, current: impl Into<Option<i64>>) -> GuardedUpdate {
+    match current.into() {
+        Some(current) => GuardedUpdate::new(
+            name.clone().equal(value(current)),
+            UpdateBuilder::default().increment(name, 1),
+        ),
+        None => GuardedUpdate::new(attribute_not_exists(name.clone()), set(name, value(1))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn optimistic_lock() -> anyhow::Result<()> {
+        let input = versioning::optimistic_lock(name("version"), 7);
+        assert_eq!(
+            input.clone().into_parts(),
+            (
+                name("version").equal(value(7)),
+                set(name("version"), name("version").plus(value(1)))
+            )
+        );
+
+        let input = versioning::optimistic_lock(name("version"), None);
+        assert_eq!(input.condition(), &name("version").attribute_not_exists());
+        assert_eq!(input.update(), &set(name("version"), value(1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn builder_guarded_update() -> anyhow::Result<()> {
+        let input = Builder::new()
+            .with_condition(name("status").equal(value("open")))
+            .with_update(set(name("status"), value("closed")))
+            .with_guarded_update(versioning::optimistic_lock(name("version"), 2))
+            .build()?;
+
+        assert_eq!(input.condition(), Some("(#0 = :0) AND (#1 = :1)"));
+        assert_eq!(input.update(), Some("SET #0 = :2, #1 = #1 + :3\n"));
+
+        let input = Builder::new()
+            .with_guarded_update(versioning::optimistic_lock(name("version"), None))
+            .build()?;
+
+        assert_eq!(input.condition(), Some("attribute_not_exists (#0)"));
+        assert_eq!(input.update(), Some("SET #0 = :0\n"));
+
+        Ok(())
+    }
+}
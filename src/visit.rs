@@ -0,0 +1,452 @@
+//! Traversal of the Condition and Update Expression trees
+//!
+//! An ExpressionVisitor receives a callback for every node of a
+//! ConditionBuilder or UpdateBuilder tree, in the order the tree is
+//! formatted. Analyzers, such as the ones collecting the attributes an
+//! expression reads or estimating its cost, can be written outside the crate
+//! without parsing the expression strings.
+//!
+//! A ConditionRewriter goes further and returns a new ConditionBuilder, with
+//! renamed attributes or replaced conditions.
+
+use crate::{
+    error::ExpressionError, ConditionBuilder, NameBuilder, OperandBuilder, OperationMode,
+    UpdateBuilder,
+};
+
+/// Represents a set of callbacks called while walking an expression tree with
+/// ConditionBuilder::visit() or UpdateBuilder::visit().
+///
+/// Every callback does nothing by default, so a visitor only implements the
+/// ones it needs.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // collects the attributes referenced by an expression
+/// #[derive(Default)]
+/// struct Attributes(Vec<String>);
+///
+/// impl ExpressionVisitor for Attributes {
+///     fn visit_name(&mut self, name: &NameBuilder) {
+///         if let Ok(path) = name.attribute_path() {
+///             self.0.push(path.join("."));
+///         }
+///     }
+/// }
+///
+/// let mut attributes = Attributes::default();
+/// name("a")
+///     .equal(value(1))
+///     .and(size(name("b.c")).greater_than(value(2)))
+///     .visit(&mut attributes);
+/// set(name("d"), name("d").plus(value(1))).visit(&mut attributes);
+///
+/// assert_eq!(attributes.0, ["a", "b.c", "d", "d"]);
+/// ```
+pub trait ExpressionVisitor {
+    /// Called for every condition of the tree, before its operands and the
+    /// conditions it combines.
+    fn enter_condition(&mut self, _condition: &ConditionBuilder) {}
+
+    /// Called for every condition of the tree, after its operands and the
+    /// conditions it combines.
+    fn leave_condition(&mut self, _condition: &ConditionBuilder) {}
+
+    /// Called for every operation of an Update Expression, before its name
+    /// and its value.
+    fn visit_operation(
+        &mut self,
+        _mode: OperationMode,
+        _name: &NameBuilder,
+        _value: Option<&dyn OperandBuilder>,
+    ) {
+    }
+
+    /// Called for every operand of the tree, including the nested ones,
+    /// before its own operands.
+    fn visit_operand(&mut self, _operand: &dyn OperandBuilder) {}
+
+    /// Called for every operand of the tree that is a NameBuilder, after
+    /// visit_operand().
+    fn visit_name(&mut self, _name: &NameBuilder) {}
+}
+
+enum Step<'a> {
+    Enter(&'a ConditionBuilder),
+    Leave(&'a ConditionBuilder),
+}
+
+impl ConditionBuilder {
+    /// Walks the condition tree, calling the callbacks of the argument
+    /// ExpressionVisitor for each of its nodes, see ExpressionVisitor.
+    pub fn visit(&self, visitor: &mut impl ExpressionVisitor) {
+        // the tree is walked with an explicit stack, as long chains of AND
+        // conditions are deep
+        let mut stack = vec![Step::Enter(self)];
+
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Enter(condition) => {
+                    visitor.enter_condition(condition);
+                    for operand in condition.operands() {
+                        visit_operand(operand.as_ref(), visitor);
+                    }
+                    stack.push(Step::Leave(condition));
+                    stack.extend(condition.children().iter().rev().map(Step::Enter));
+                }
+                Step::Leave(condition) => visitor.leave_condition(condition),
+            }
+        }
+    }
+}
+
+impl UpdateBuilder {
+    /// Walks the operations of the Update Expression in the order they are
+    /// formatted, calling the callbacks of the argument ExpressionVisitor for
+    /// each of them and of their operands, see ExpressionVisitor.
+    pub fn visit(&self, visitor: &mut impl ExpressionVisitor) {
+        for (mode, operation_list) in self.ordered_operations() {
+            for operation in operation_list {
+                let value = operation.value.as_deref();
+                visitor.visit_operation(*mode, &operation.name, value);
+                visit_operand(operation.name.as_ref(), visitor);
+                if let Some(value) = value {
+                    visit_operand(value, visitor);
+                }
+            }
+        }
+    }
+}
+
+fn visit_operand(operand: &dyn OperandBuilder, visitor: &mut impl ExpressionVisitor) {
+    visitor.visit_operand(operand);
+    if let Some(name) = operand.as_any().downcast_ref::<NameBuilder>() {
+        visitor.visit_name(name);
+    }
+    for nested in operand.operands() {
+        visit_operand(nested, visitor);
+    }
+}
+
+/// Represents a set of callbacks rewriting a condition tree with
+/// ConditionBuilder::rewrite().
+///
+/// By default, the callbacks leave the tree unchanged, so a rewriter only
+/// implements the ones it needs.
+///
+/// # Example
+///
+/// ```
+/// use dynamodb_expression::*;
+///
+/// // replaces the conditions of a user-provided filter on tenant_id, which
+/// // the user can't choose, with a condition that always fails
+/// struct TenantScope;
+///
+/// impl ConditionRewriter for TenantScope {
+///     fn rewrite_condition(&mut self, condition: ConditionBuilder) -> ConditionBuilder {
+///         let on_tenant = condition.operands().iter().any(|operand| {
+///             operand
+///                 .as_any()
+///                 .downcast_ref::<NameBuilder>()
+///                 .and_then(|name| name.attribute_path().ok())
+///                 .is_some_and(|path| path == ["tenant_id"])
+///         });
+///         if on_tenant {
+///             name("tenant_id").attribute_not_exists()
+///         } else {
+///             condition
+///         }
+///     }
+/// }
+///
+/// let user_filter = name("status").equal(value("open")).or(name("tenant_id").equal(value("other")));
+/// let filter = name("tenant_id")
+///     .equal(value("acme"))
+///     .and(user_filter.rewrite(&mut TenantScope));
+///
+/// let expr = Builder::new().with_filter(filter).build().unwrap();
+/// assert_eq!(
+///     expr.filter().unwrap(),
+///     "(#0 = :0) AND ((#1 = :1) OR (attribute_not_exists (#0)))"
+/// );
+/// ```
+pub trait ConditionRewriter {
+    /// Called for every NameBuilder of the tree, nested ones included, which
+    /// can be modified or replaced in place.
+    fn rewrite_name(&mut self, _name: &mut NameBuilder) {}
+
+    /// Called for every condition of the tree, after the conditions it
+    /// combines were rewritten, and returns the condition replacing it.
+    fn rewrite_condition(&mut self, condition: ConditionBuilder) -> ConditionBuilder {
+        condition
+    }
+}
+
+enum RewriteStep {
+    Expand(ConditionBuilder),
+    // the condition, with its children rewritten and moved to the results
+    Rebuild(ConditionBuilder, usize),
+}
+
+impl ConditionBuilder {
+    /// Returns the condition tree rewritten by the argument ConditionRewriter,
+    /// see ConditionRewriter.
+    ///
+    /// The names of a condition are rewritten before the conditions it
+    /// combines, and the conditions are rewritten bottom-up.
+    pub fn rewrite(self, rewriter: &mut impl ConditionRewriter) -> ConditionBuilder {
+        // the tree is rewritten with an explicit stack, as for visit()
+        let mut stack = vec![RewriteStep::Expand(self)];
+        let mut results: Vec<ConditionBuilder> = Vec::new();
+
+        loop {
+            let Some(step) = stack.pop() else {
+                break;
+            };
+            match step {
+                RewriteStep::Expand(mut condition) => {
+                    for operand in condition.operand_list.iter_mut() {
+                        operand.rewrite_names(&mut |name| rewriter.rewrite_name(name));
+                    }
+                    let children = std::mem::take(&mut condition.condition_list);
+                    stack.push(RewriteStep::Rebuild(condition, children.len()));
+                    stack.extend(children.into_iter().rev().map(RewriteStep::Expand));
+                }
+                RewriteStep::Rebuild(mut condition, children) => {
+                    condition.condition_list = results.split_off(results.len() - children);
+                    results.push(rewriter.rewrite_condition(condition));
+                }
+            }
+        }
+
+        results.pop().unwrap_or_default()
+    }
+
+    /// Returns the condition with every reference to the attribute of the
+    /// argument from NameBuilder replaced by the argument to NameBuilder.
+    ///
+    /// Attributes are compared by their attribute_path(), so nested
+    /// attributes of from are left unchanged. If from is not a valid name,
+    /// rename_attribute() returns its typed error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dynamodb_expression::*;
+    ///
+    /// let condition = name("owner")
+    ///     .equal(value("ace"))
+    ///     .and(size(name("owner")).greater_than(value(2)))
+    ///     .rename_attribute(name("owner"), name("meta.owner"))
+    ///     .unwrap();
+    ///
+    /// let expr = Builder::new().with_condition(condition).build().unwrap();
+    /// assert_eq!(expr.condition().unwrap(), "(#0.#1 = :0) AND (size (#0.#1) > :1)");
+    /// ```
+    #[allow(clippy::boxed_local)]
+    pub fn rename_attribute(
+        self,
+        from: Box<NameBuilder>,
+        to: Box<NameBuilder>,
+    ) -> Result<ConditionBuilder, ExpressionError> {
+        Ok(self.rewrite(&mut Rename {
+            from: from.attribute_path()?,
+            to,
+        }))
+    }
+}
+
+struct Rename {
+    from: Vec<String>,
+    to: Box<NameBuilder>,
+}
+
+impl ConditionRewriter for Rename {
+    fn rewrite_name(&mut self, name: &mut NameBuilder) {
+        if name.attribute_path().is_ok_and(|path| path == self.from) {
+            *name = (*self.to).clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl ExpressionVisitor for Trace {
+        fn enter_condition(&mut self, condition: &ConditionBuilder) {
+            self.0.push(format!("enter {}", condition.kind()));
+        }
+
+        fn leave_condition(&mut self, condition: &ConditionBuilder) {
+            self.0.push(format!("leave {}", condition.kind()));
+        }
+
+        fn visit_operation(
+            &mut self,
+            mode: OperationMode,
+            _name: &NameBuilder,
+            value: Option<&dyn OperandBuilder>,
+        ) {
+            self.0
+                .push(format!("{} {}", mode.as_ref(), value.map_or(0, |_| 1)));
+        }
+
+        fn visit_operand(&mut self, operand: &dyn OperandBuilder) {
+            self.0.push(format!("operand {}", operand.operands().len()));
+        }
+
+        fn visit_name(&mut self, name: &NameBuilder) {
+            self.0.push(format!("name {:?}", name.attribute_path()));
+        }
+    }
+
+    #[test]
+    fn visit_condition() -> anyhow::Result<()> {
+        let mut trace = Trace::default();
+        name("a")
+            .equal(value(1))
+            .or(not(size(name("b")).less_than(value(2))))
+            .visit(&mut trace);
+
+        assert_eq!(
+            trace.0,
+            [
+                "enter or",
+                "enter equal",
+                "operand 0",
+                "name Ok([\"a\"])",
+                "operand 0",
+                "leave equal",
+                "enter not",
+                "enter less_than",
+                "operand 1",
+                "operand 0",
+                "name Ok([\"b\"])",
+                "operand 0",
+                "leave less_than",
+                "leave not",
+                "leave or",
+            ]
+        );
+
+        // long AND chains don't overflow the stack
+        let mut trace = Trace::default();
+        (0..10_000)
+            .map(|i| name(format!("a{}", i)).attribute_exists())
+            .reduce(ConditionBuilder::and)
+            .unwrap_or_default()
+            .visit(&mut trace);
+        assert_eq!(trace.0.len(), 10_000 * 4 + 9_999 * 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn visit_update() -> anyhow::Result<()> {
+        let mut trace = Trace::default();
+        set(name("a"), name("a").plus(value(1)))
+            .remove(name("b"))
+            .visit(&mut trace);
+
+        assert_eq!(
+            trace.0,
+            [
+                "SET 1",
+                "operand 0",
+                "name Ok([\"a\"])",
+                "operand 2",
+                "operand 0",
+                "name Ok([\"a\"])",
+                "operand 0",
+                "REMOVE 0",
+                "operand 0",
+                "name Ok([\"b\"])",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rewrite_condition() -> anyhow::Result<()> {
+        // swaps the operands of every OR and renames "a" to "z"
+        struct Swap;
+
+        impl ConditionRewriter for Swap {
+            fn rewrite_name(&mut self, name: &mut NameBuilder) {
+                if name.attribute_path().is_ok_and(|path| path == ["a"]) {
+                    *name = *crate::name("z");
+                }
+            }
+
+            fn rewrite_condition(&mut self, condition: ConditionBuilder) -> ConditionBuilder {
+                match condition.kind() {
+                    ConditionMode::Or => {
+                        let mut children = condition.children().to_vec();
+                        children.reverse();
+                        children
+                            .into_iter()
+                            .reduce(ConditionBuilder::or)
+                            .unwrap_or_default()
+                    }
+                    _ => condition,
+                }
+            }
+        }
+
+        let input = name("a")
+            .equal(value(1))
+            .or(not(size(name("a")).less_than(value(2))))
+            .and(name("b").attribute_exists());
+
+        assert_eq!(
+            input.rewrite(&mut Swap),
+            not(size(name("z")).less_than(value(2)))
+                .or(name("z").equal(value(1)))
+                .and(name("b").attribute_exists())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rename_attribute() -> anyhow::Result<()> {
+        let input = name("a").equal(name("a.b")).and(name("a").begins_with("x"));
+
+        assert_eq!(
+            input.clone().rename_attribute(name("a"), name("c"))?,
+            name("c").equal(name("a.b")).and(name("c").begins_with("x"))
+        );
+        assert_eq!(
+            input
+                .clone()
+                .rename_attribute(name("a.b"), name_literal("c"))?,
+            name("a")
+                .equal(name_literal("c"))
+                .and(name("a").begins_with("x"))
+        );
+        assert!(input.rename_attribute(name(""), name("c")).is_err());
+
+        // long AND chains don't overflow the stack
+        let input = (0..10_000)
+            .map(|i| name(format!("a{}", i)).attribute_exists())
+            .reduce(ConditionBuilder::and)
+            .unwrap_or_default();
+        let mut trace = Trace::default();
+        input
+            .rename_attribute(name("a0"), name("b"))?
+            .visit(&mut trace);
+        assert_eq!(trace.0[9_999 + 2], "name Ok([\"b\"])");
+        assert_eq!(trace.0.len(), 10_000 * 4 + 9_999 * 2);
+
+        Ok(())
+    }
+}